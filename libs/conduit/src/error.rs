@@ -35,6 +35,8 @@ pub enum Error {
     ConnectionClosed,
     #[error("non utf8 path")]
     NonUtf8Path,
+    #[error("metadata is missing a {0}")]
+    IncompleteMetadata(&'static str),
 }
 
 impl From<try_buf::ErrorKind> for Error {
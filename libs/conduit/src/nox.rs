@@ -35,6 +35,7 @@ impl PrimitiveTy {
             PrimitiveTy::Bool => ElementType::U8,
             PrimitiveTy::F32 => ElementType::F32,
             PrimitiveTy::F64 => ElementType::F64,
+            PrimitiveTy::F16 => ElementType::F16,
         }
     }
 }
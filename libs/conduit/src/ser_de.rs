@@ -211,6 +211,7 @@ impl ComponentType {
             PrimitiveTy::Bool => cow_array(buf, shape).map(ComponentValue::Bool),
             PrimitiveTy::F32 => cow_array(buf, shape).map(ComponentValue::F32),
             PrimitiveTy::F64 => cow_array(buf, shape).map(ComponentValue::F64),
+            PrimitiveTy::F16 => cow_array(buf, shape).map(ComponentValue::F16),
         }?;
         Ok((size, value))
     }
@@ -25,6 +25,21 @@ pub struct EntityId(pub u64);
 
 impl EntityId {
     pub const RESOURCE: EntityId = EntityId(224);
+
+    /// Packs `index` into the low 32 bits of the id and `generation` into the high 32 bits
+    pub const fn from_parts(index: u32, generation: u32) -> Self {
+        EntityId((generation as u64) << 32 | index as u64)
+    }
+
+    /// The slot the entity occupies, which is shared by every entity that reuses the id
+    pub const fn index(self) -> u32 {
+        self.0 as u32
+    }
+
+    /// The number of times the index had been reused when this id was handed out
+    pub const fn generation(self) -> u32 {
+        (self.0 >> 32) as u32
+    }
 }
 
 impl From<u64> for EntityId {
@@ -78,9 +93,12 @@ pub enum PrimitiveTy {
     I16,
     I32,
     I64,
+    /// Stored as one byte per value, `0` for false and `1` for true, rather than bit-packed the
+    /// way arrow stores boolean arrays.
     Bool,
     F32,
     F64,
+    F16,
 }
 
 impl PrimitiveTy {
@@ -97,6 +115,7 @@ impl PrimitiveTy {
             PrimitiveTy::Bool => size_of::<bool>(),
             PrimitiveTy::F32 => size_of::<f32>(),
             PrimitiveTy::F64 => size_of::<f64>(),
+            PrimitiveTy::F16 => size_of::<half::f16>(),
         }
     }
 }
@@ -131,6 +150,7 @@ pub enum ComponentValue<'a> {
     Bool(ndarray::CowArray<'a, bool, IxDyn>),
     F32(ndarray::CowArray<'a, f32, IxDyn>),
     F64(ndarray::CowArray<'a, f64, IxDyn>),
+    F16(ndarray::CowArray<'a, half::f16, IxDyn>),
 }
 
 impl<'a> ComponentValue<'a> {
@@ -147,6 +167,7 @@ impl<'a> ComponentValue<'a> {
             ComponentValue::Bool(_) => PrimitiveTy::Bool,
             ComponentValue::F32(_) => PrimitiveTy::F32,
             ComponentValue::F64(_) => PrimitiveTy::F64,
+            ComponentValue::F16(_) => PrimitiveTy::F16,
         }
     }
 
@@ -163,6 +184,7 @@ impl<'a> ComponentValue<'a> {
             ComponentValue::Bool(a) => a.shape().into(),
             ComponentValue::F32(a) => a.shape().into(),
             ComponentValue::F64(a) => a.shape().into(),
+            ComponentValue::F16(a) => a.shape().into(),
         }
     }
 
@@ -186,6 +208,7 @@ impl<'a> ComponentValue<'a> {
             ComponentValue::Bool(a) => ComponentValue::Bool(CowArray::from(a.into_owned())),
             ComponentValue::F32(a) => ComponentValue::F32(CowArray::from(a.into_owned())),
             ComponentValue::F64(a) => ComponentValue::F64(CowArray::from(a.into_owned())),
+            ComponentValue::F16(a) => ComponentValue::F16(CowArray::from(a.into_owned())),
         }
     }
 
@@ -202,6 +225,7 @@ impl<'a> ComponentValue<'a> {
             ComponentValue::Bool(b) => b.as_slice().map(bytemuck::cast_slice),
             ComponentValue::F32(b) => b.as_slice().map(bytemuck::cast_slice),
             ComponentValue::F64(b) => b.as_slice().map(bytemuck::cast_slice),
+            ComponentValue::F16(b) => b.as_slice().map(bytemuck::cast_slice),
         }
     }
 }
@@ -283,9 +307,154 @@ pub enum MetadataQuery {
 pub struct Metadata {
     pub component_id: ComponentId,
     pub component_type: ComponentType,
+    #[serde(serialize_with = "serialize_sorted")]
     pub tags: HashMap<String, TagValue>,
 }
 
+/// Serializes a map with its keys in sorted order, so that serialized metadata is stable across runs.
+fn serialize_sorted<S, K, V>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+    K: Serialize + Ord,
+    V: Serialize,
+{
+    serializer.collect_map(map.iter().collect::<std::collections::BTreeMap<_, _>>())
+}
+
+impl Metadata {
+    pub fn builder() -> MetadataBuilder {
+        MetadataBuilder::default()
+    }
+
+    /// The physical unit of the component's values, such as `"m/s"`, stored in the `unit` tag so
+    /// that the wire format of `Metadata` stays the same.
+    pub fn unit(&self) -> Option<&str> {
+        self.tags.get(UNIT_TAG).and_then(TagValue::as_str)
+    }
+
+    pub fn set_unit(&mut self, unit: &str) {
+        self.tags
+            .insert(UNIT_TAG.to_string(), TagValue::String(unit.to_string()));
+    }
+
+    /// A human readable description of the component, stored in the `description` tag
+    pub fn description(&self) -> Option<&str> {
+        self.tags.get(DESCRIPTION_TAG).and_then(TagValue::as_str)
+    }
+
+    pub fn set_description(&mut self, description: &str) {
+        self.tags.insert(
+            DESCRIPTION_TAG.to_string(),
+            TagValue::String(description.to_string()),
+        );
+    }
+
+    /// The precision and scale of a decimal component, whose `I64` values hold the unscaled
+    /// decimals, stored in the `decimal_precision` and `decimal_scale` tags.
+    pub fn decimal(&self) -> Option<(Option<usize>, usize)> {
+        let scale = self.tags.get(DECIMAL_SCALE_TAG)?.as_str()?.parse().ok()?;
+        let precision = self
+            .tags
+            .get(DECIMAL_PRECISION_TAG)
+            .and_then(TagValue::as_str)
+            .and_then(|p| p.parse().ok());
+        Some((precision, scale))
+    }
+
+    pub fn set_decimal(&mut self, precision: Option<usize>, scale: usize) {
+        match precision {
+            Some(precision) => self.tags.insert(
+                DECIMAL_PRECISION_TAG.to_string(),
+                TagValue::String(precision.to_string()),
+            ),
+            None => self.tags.remove(DECIMAL_PRECISION_TAG),
+        };
+        self.tags.insert(
+            DECIMAL_SCALE_TAG.to_string(),
+            TagValue::String(scale.to_string()),
+        );
+    }
+}
+
+const UNIT_TAG: &str = "unit";
+const DESCRIPTION_TAG: &str = "description";
+const DECIMAL_PRECISION_TAG: &str = "decimal_precision";
+const DECIMAL_SCALE_TAG: &str = "decimal_scale";
+
+#[derive(Clone, Debug, Default)]
+pub struct MetadataBuilder {
+    name: Option<String>,
+    component_type: Option<ComponentType>,
+    asset: bool,
+    unit: Option<String>,
+    description: Option<String>,
+}
+
+impl MetadataBuilder {
+    /// Sets the component's name, which is also hashed to produce its [`ComponentId`].
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn scalar(self, primitive_ty: PrimitiveTy) -> Self {
+        self.tensor(primitive_ty, [])
+    }
+
+    pub fn tensor(
+        mut self,
+        primitive_ty: PrimitiveTy,
+        shape: impl IntoIterator<Item = usize>,
+    ) -> Self {
+        self.component_type = Some(ComponentType {
+            primitive_ty,
+            shape: shape.into_iter().collect(),
+        });
+        self
+    }
+
+    pub fn asset(mut self, asset: bool) -> Self {
+        self.asset = asset;
+        self
+    }
+
+    pub fn unit(mut self, unit: &str) -> Self {
+        self.unit = Some(unit.to_string());
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    pub fn build(self) -> Result<Metadata, crate::Error> {
+        let name = self.name.ok_or(crate::Error::IncompleteMetadata("name"))?;
+        let component_type = self
+            .component_type
+            .ok_or(crate::Error::IncompleteMetadata("component type"))?;
+        if component_type.shape.len() >= u8::MAX as usize {
+            return Err(crate::Error::TooManyDims);
+        }
+        let mut tags = HashMap::from([("name".to_string(), TagValue::String(name.clone()))]);
+        if self.asset {
+            tags.insert("asset".to_string(), TagValue::Bool(true));
+        }
+        let mut metadata = Metadata {
+            component_id: ComponentId::new(&name),
+            component_type,
+            tags,
+        };
+        if let Some(unit) = &self.unit {
+            metadata.set_unit(unit);
+        }
+        if let Some(description) = &self.description {
+            metadata.set_description(description);
+        }
+        Ok(metadata)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
 pub enum TagValue {
     Unit,
@@ -317,3 +486,42 @@ pub trait Asset: Serialize {
     const ASSET_ID: AssetId;
     fn asset_id(&self) -> AssetId;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_builder() {
+        let metadata = Metadata::builder()
+            .name("velocity")
+            .tensor(PrimitiveTy::F64, [3])
+            .build()
+            .unwrap();
+        assert_eq!(metadata.component_id, ComponentId::new("velocity"));
+        assert_eq!(metadata.component_type.primitive_ty, PrimitiveTy::F64);
+        assert_eq!(metadata.component_type.shape.as_slice(), &[3]);
+        assert_eq!(
+            metadata.tags.get("name"),
+            Some(&TagValue::String("velocity".to_string()))
+        );
+        assert!(!metadata.tags.contains_key("asset"));
+
+        let err = Metadata::builder().scalar(PrimitiveTy::F64).build();
+        assert!(matches!(err, Err(crate::Error::IncompleteMetadata("name"))));
+    }
+
+    #[test]
+    fn test_metadata_unit() {
+        let metadata = Metadata::builder()
+            .name("velocity")
+            .tensor(PrimitiveTy::F64, [3])
+            .unit("m/s")
+            .build()
+            .unwrap();
+        assert_eq!(metadata.unit(), Some("m/s"));
+        assert_eq!(metadata.description(), None);
+        let bytes = postcard::to_allocvec(&metadata).unwrap();
+        assert_eq!(postcard::from_bytes::<Metadata>(&bytes).unwrap(), metadata);
+    }
+}
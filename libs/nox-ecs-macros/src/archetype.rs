@@ -45,6 +45,11 @@ pub fn archetype(input: TokenStream) -> TokenStream {
                 vec![#( <#tys>::component_type(), )*]
             }
 
+            fn component_names() -> Vec<Option<&'static str>> {
+                use #crate_name::Component;
+                vec![#( <#tys>::name(), )*]
+            }
+
             fn insert_into_table(self, table: &mut #crate_name::Table<#crate_name::HostStore>) {
                 #(
                    self.#idents.insert_into_table(table);
@@ -60,6 +60,10 @@ pub fn component(input: TokenStream) -> TokenStream {
             fn component_type() -> #crate_name::conduit::ComponentType {
                 <#ty as #crate_name::Component>::component_type()
             }
+
+            fn name() -> Option<&'static str> {
+                Some(#id_string)
+            }
         }
     }
     .into()
@@ -3,6 +3,7 @@ use conduit::{Asset, AssetId, ComponentId, ComponentValue};
 use nox::{FromBuilder, IntoOp, Noxpr};
 use serde::{Deserialize, Serialize};
 
+use std::collections::HashMap;
 use std::marker::PhantomData;
 
 #[derive(Debug)]
@@ -101,6 +102,39 @@ impl AssetStore {
         }
     }
 
+    /// Returns the id each of `other`'s assets gets when merged into `self` by [`AssetStore::merge`],
+    /// without modifying either store
+    pub fn merged_ids(&self, other: &AssetStore) -> Vec<u64> {
+        let mut ids = self
+            .data
+            .iter()
+            .enumerate()
+            .map(|(id, item)| ((item.asset_id, &item.inner), id as u64))
+            .collect::<HashMap<_, _>>();
+        let mut next = self.data.len() as u64;
+        other
+            .data
+            .iter()
+            .map(|item| {
+                *ids.entry((item.asset_id, &item.inner)).or_insert_with(|| {
+                    next += 1;
+                    next - 1
+                })
+            })
+            .collect()
+    }
+
+    /// Appends the assets from `other` that aren't already present, returning the new id of each of `other`'s assets
+    pub fn merge(&mut self, other: AssetStore) -> Vec<u64> {
+        let ids = self.merged_ids(&other);
+        for (item, id) in other.data.into_iter().zip(&ids) {
+            if *id as usize == self.data.len() {
+                self.data.push(item);
+            }
+        }
+        ids
+    }
+
     pub fn value<C>(&self, handle: Handle<C>) -> Option<&AssetItem> {
         let val = self.data.get(handle.id as usize)?;
         Some(val)
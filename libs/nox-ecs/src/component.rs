@@ -14,6 +14,10 @@ pub trait Component: IntoOp + for<'a> nox::FromBuilder<Item<'a> = Self> {
     fn is_asset() -> bool {
         false
     }
+    /// The human readable name stored in the column's `name` tag, if the component has one.
+    fn name() -> Option<&'static str> {
+        None
+    }
 }
 
 macro_rules! impl_scalar_primitive {
@@ -69,6 +73,10 @@ macro_rules! impl_spatial_ty {
                     shape: $shape,
                 }
             }
+
+            fn name() -> Option<&'static str> {
+                Some($name)
+            }
         }
     };
 }
@@ -94,6 +102,13 @@ impl_spatial_ty!(
     "spatial_inertia_f64"
 );
 
+impl_spatial_ty!(
+    nox::Vector::<f64, 3>,
+    PrimitiveTy::F64,
+    smallvec![3],
+    "vector_f64_3"
+);
+
 impl_spatial_ty!(
     nox::SpatialForce::<f64>,
     PrimitiveTy::F64,
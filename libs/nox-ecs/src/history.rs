@@ -1,8 +1,16 @@
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
 
+use conduit::ComponentId;
+use polars::prelude::{DataType, NamedFrom};
 use polars::{frame::DataFrame, series::Series};
+use serde::{Deserialize, Serialize};
 
-use crate::{polars::PolarsWorld, Error, World};
+use crate::{
+    polars::{check_same_columns, Metadata, PolarsWorld, ENTITY_ID_COMPONENT},
+    Error, World,
+};
 
 #[derive(Default, Debug, Clone)]
 pub struct History {
@@ -19,13 +27,35 @@ impl History {
         }
         for (time, world) in self.worlds.iter().enumerate().skip(1) {
             let mut world = world.clone();
-            for (tick_df, final_df) in world
-                .archetypes
-                .values_mut()
-                .zip(final_world.archetypes.values_mut())
-            {
+            for (archetype_id, tick_df) in &mut world.archetypes {
+                let tick_metadata = world
+                    .metadata
+                    .archetypes
+                    .get(archetype_id)
+                    .ok_or(Error::SchemaMismatch)?;
                 add_time(tick_df, time)?;
-                final_df.vstack(tick_df)?;
+                let Some(final_df) = final_world.archetypes.get_mut(archetype_id) else {
+                    // archetypes first spawned after the first tick start at their first tick
+                    final_world
+                        .archetypes
+                        .insert(*archetype_id, tick_df.clone());
+                    final_world
+                        .metadata
+                        .archetypes
+                        .insert(*archetype_id, tick_metadata.clone());
+                    for (component_id, id) in &world.metadata.component_map {
+                        if id == archetype_id {
+                            final_world
+                                .metadata
+                                .component_map
+                                .insert(*component_id, *id);
+                        }
+                    }
+                    continue;
+                };
+                let final_metadata = &final_world.metadata.archetypes[archetype_id];
+                check_same_columns(&final_metadata.columns, &tick_metadata.columns)?;
+                final_df.vstack_mut(tick_df)?;
             }
         }
         Ok(Some(final_world))
@@ -56,3 +86,420 @@ fn add_time(df: &mut DataFrame, time: usize) -> Result<(), Error> {
     df.with_column(series.with_name("time"))?;
     Ok(())
 }
+
+/// Reads a directory of worlds written by [`PolarsWorld::write_to_dir`], one sub-directory per tick.
+pub struct ReplayReader {
+    dir: PathBuf,
+}
+
+/// A summary of every tick in a replay directory, cached as `index.json`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ReplayIndex {
+    pub ticks: Vec<ReplayTick>,
+    /// The state of the replay directory when the index was written, missing from indexes that
+    /// predate it
+    #[serde(default)]
+    pub dir: Option<DirStamp>,
+}
+
+/// The modification time and entry count of a replay directory, which change whenever a tick
+/// directory is added or removed.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirStamp {
+    /// Nanoseconds since the Unix epoch, if the platform reports modification times
+    pub modified: Option<u64>,
+    pub entries: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileStamp {
+    pub size: u64,
+    /// Nanoseconds since the Unix epoch, if the platform reports modification times
+    pub modified: Option<u64>,
+}
+
+impl FileStamp {
+    fn of(metadata: &std::fs::Metadata) -> Self {
+        FileStamp {
+            size: metadata.len(),
+            modified: modified_nanos(metadata),
+        }
+    }
+}
+
+fn modified_nanos(metadata: &std::fs::Metadata) -> Option<u64> {
+    let since_epoch = metadata
+        .modified()
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?;
+    u64::try_from(since_epoch.as_nanos()).ok()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReplayTick {
+    /// The tick's directory, relative to the replay directory
+    pub path: PathBuf,
+    pub tick: u64,
+    pub entity_count: usize,
+    /// The combined size in bytes of the tick's files
+    pub size: u64,
+    /// The size and modification time of each of the tick's files, by file name
+    #[serde(default)]
+    pub files: BTreeMap<String, FileStamp>,
+}
+
+impl ReplayReader {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Returns the cached index, rebuilding it if `index.json` does not exist yet or is stale.
+    ///
+    /// The check only looks at the replay directory itself and each tick's `metadata.json`,
+    /// which [`PolarsWorld::write_to_dir`] rewrites along with the rest of the tick, rather than
+    /// every file. A tick whose other files were changed by some other means, or rewritten
+    /// within the filesystem's timestamp granularity, is only picked up by
+    /// [`ReplayReader::build_index`].
+    pub fn index(&self) -> Result<ReplayIndex, Error> {
+        match self.cached_index()? {
+            Some(index) if self.is_fresh(&index)? => Ok(index),
+            _ => self.build_index(),
+        }
+    }
+
+    fn is_fresh(&self, index: &ReplayIndex) -> Result<bool, Error> {
+        if index.dir != Some(self.dir_stamp()?) {
+            return Ok(false);
+        }
+        for tick in &index.ticks {
+            let path = self.dir.join(&tick.path).join("metadata.json");
+            let current = match std::fs::metadata(path) {
+                Ok(metadata) => FileStamp::of(&metadata),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+                Err(err) => return Err(err.into()),
+            };
+            if tick.files.get("metadata.json") != Some(&current) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Scans the replay directory for ticks and writes the result to `index.json`.
+    ///
+    /// Ticks already present in an existing index whose files all have the same size and
+    /// modification time are reused rather than read again.
+    pub fn build_index(&self) -> Result<ReplayIndex, Error> {
+        let cached = self.cached_index()?.unwrap_or_default().ticks;
+        let mut ticks = vec![];
+        for (relative, files) in self.tick_dirs()? {
+            if let Some(tick) = cached
+                .iter()
+                .find(|t| t.path == relative && t.files == files)
+            {
+                ticks.push(tick.clone());
+                continue;
+            }
+            let metadata: Metadata = serde_json::from_reader(File::open(
+                self.dir.join(&relative).join("metadata.json"),
+            )?)?;
+            ticks.push(ReplayTick {
+                path: relative,
+                tick: metadata.tick,
+                entity_count: metadata
+                    .archetypes
+                    .values()
+                    .map(|a| a.entity_map.len())
+                    .sum(),
+                size: files.values().map(|f| f.size).sum(),
+                files,
+            });
+        }
+        ticks.sort_by_key(|t| t.tick);
+        // create `index.json` before stamping the directory, since adding it changes the stamp
+        let file = File::create(self.index_path())?;
+        let index = ReplayIndex {
+            ticks,
+            dir: Some(self.dir_stamp()?),
+        };
+        serde_json::to_writer(file, &index)?;
+        Ok(index)
+    }
+
+    fn cached_index(&self) -> Result<Option<ReplayIndex>, Error> {
+        match File::open(self.index_path()) {
+            Ok(file) => Ok(Some(serde_json::from_reader(file)?)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn dir_stamp(&self) -> Result<DirStamp, Error> {
+        let metadata = std::fs::metadata(&self.dir)?;
+        Ok(DirStamp {
+            modified: modified_nanos(&metadata),
+            entries: std::fs::read_dir(&self.dir)?.count(),
+        })
+    }
+
+    /// Lists every tick directory, relative to the replay directory, with the size and
+    /// modification time of each of its files.
+    fn tick_dirs(&self) -> Result<Vec<(PathBuf, BTreeMap<String, FileStamp>)>, Error> {
+        let mut dirs = vec![];
+        for entry in std::fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if !path.join("metadata.json").exists() {
+                continue;
+            }
+            let mut files = BTreeMap::new();
+            for file in std::fs::read_dir(&path)? {
+                let file = file?;
+                let name = file.file_name().to_string_lossy().into_owned();
+                files.insert(name, FileStamp::of(&file.metadata()?));
+            }
+            dirs.push((PathBuf::from(entry.file_name()), files));
+        }
+        Ok(dirs)
+    }
+
+    /// Numerically differentiates `component` across the replay, where `dt` is the time between
+    /// consecutive ticks.
+    ///
+    /// The returned frame has a row per tick and entity, holding the derivative of each of the
+    /// component's elements. Interior ticks use a central difference, while an entity's first
+    /// and last tick fall back to a one-sided difference.
+    ///
+    /// Returns [`Error::InvalidTimeStep`] unless `dt` is positive and finite.
+    pub fn derivative(&self, component: ComponentId, dt: f64) -> Result<DataFrame, Error> {
+        if !(dt.is_finite() && dt > 0.0) {
+            return Err(Error::InvalidTimeStep(dt));
+        }
+        let mut scalar = true;
+        let mut samples = vec![];
+        for tick in self.index()?.ticks {
+            let world = PolarsWorld::read_from_dir(self.dir.join(&tick.path))?;
+            let mut values = BTreeMap::new();
+            if let Some(archetype_id) = world.metadata.component_map.get(&component) {
+                let metadata = world
+                    .metadata
+                    .archetypes
+                    .get(archetype_id)
+                    .ok_or(Error::ComponentNotFound)?;
+                if let Some(column) = metadata
+                    .columns
+                    .iter()
+                    .find(|c| c.metadata.component_id == component)
+                {
+                    scalar = column.metadata.component_type.shape.is_empty();
+                }
+                for entity in metadata.entity_map.keys() {
+                    let series = world
+                        .entity_row(*entity)?
+                        .remove(&component)
+                        .ok_or(Error::ComponentNotFound)?
+                        .explode()?
+                        .cast(&DataType::Float64)?;
+                    let value: Vec<f64> = series.f64()?.into_no_null_iter().collect();
+                    values.insert(*entity, value);
+                }
+            }
+            samples.push((tick.tick, values));
+        }
+
+        let (mut ticks, mut entities, mut derivatives) = (vec![], vec![], vec![]);
+        for (i, (tick, values)) in samples.iter().enumerate() {
+            for (entity, value) in values {
+                let sample = |j: usize| {
+                    let (tick, values) = samples.get(j)?;
+                    Some((*tick, values.get(entity)?))
+                };
+                let current = (*tick, value);
+                let prev = i.checked_sub(1).and_then(sample);
+                let next = sample(i + 1);
+                let ((start_tick, start), (end_tick, end)) = match (prev, next) {
+                    (Some(prev), Some(next)) => (prev, next),
+                    (Some(prev), None) => (prev, current),
+                    (None, Some(next)) => (current, next),
+                    (None, None) => continue,
+                };
+                let span = (end_tick - start_tick) as f64 * dt;
+                let derivative: Vec<f64> = start
+                    .iter()
+                    .zip(end)
+                    .map(|(start, end)| (end - start) / span)
+                    .collect();
+                ticks.push(*tick);
+                entities.push(entity.0);
+                derivatives.push(Series::new("", derivative));
+            }
+        }
+        let mut derivative = Series::new(&component.0.to_string(), derivatives);
+        if scalar {
+            derivative = derivative.explode()?;
+        }
+        let df = DataFrame::new(vec![
+            Series::new("tick", ticks),
+            Series::new(&ENTITY_ID_COMPONENT.0.to_string(), entities),
+            derivative,
+        ])?;
+        Ok(df)
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.dir.join("index.json")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArchetypeId, Component};
+
+    #[test]
+    fn test_compact_new_archetype() {
+        #[derive(crate::Component)]
+        struct X(nox::Scalar<f64>);
+        #[derive(crate::Component)]
+        struct Y(nox::Scalar<f64>);
+
+        let mut world = World::default();
+        world.spawn(X(nox::Scalar::host(1.0)));
+        let mut history = History::default();
+        history.push_world(&world).unwrap();
+        world.spawn(Y(nox::Scalar::host(2.0)));
+        history.push_world(&world).unwrap();
+
+        let compacted = history.compact_to_world().unwrap().unwrap();
+        let x = ArchetypeId::of::<X>();
+        let y = ArchetypeId::of::<Y>();
+        assert_eq!(compacted.archetypes[&x].height(), 2);
+        assert_eq!(compacted.archetypes[&y].height(), 1);
+        assert_eq!(compacted.metadata.component_map[&Y::component_id()], y);
+        let time = compacted.archetypes[&y].column("time").unwrap();
+        assert_eq!(time.u64().unwrap().get(0), Some(1));
+    }
+
+    #[test]
+    fn test_replay_index() {
+        #[derive(crate::Component)]
+        struct X(nox::Scalar<f64>);
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut world = World::default();
+        for tick in 0..3 {
+            world.spawn(X(nox::Scalar::host(tick as f64)));
+            world.tick = tick;
+            world
+                .to_polars()
+                .unwrap()
+                .write_to_dir(dir.path().join(format!("tick_{}", tick)))
+                .unwrap();
+        }
+
+        let reader = ReplayReader::new(dir.path());
+        let index = reader.build_index().unwrap();
+        assert_eq!(
+            index
+                .ticks
+                .iter()
+                .map(|t| (t.tick, t.entity_count))
+                .collect::<Vec<_>>(),
+            &[(0, 1), (1, 2), (2, 3)]
+        );
+        assert!(index.ticks.iter().all(|t| t.size > 0));
+        assert!(dir.path().join("index.json").exists());
+        assert_eq!(reader.index().unwrap(), index);
+
+        // a tick written after the index was cached is picked up on the next read
+        world.spawn(X(nox::Scalar::host(3.0)));
+        world.tick = 3;
+        world
+            .to_polars()
+            .unwrap()
+            .write_to_dir(dir.path().join("tick_3"))
+            .unwrap();
+        let index = reader.index().unwrap();
+        assert_eq!(index.ticks.len(), 4);
+        assert_eq!(index.ticks[3].entity_count, 4);
+
+        // as is a tick that was rewritten in place, once the filesystem's timestamp
+        // granularity has passed
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        world.spawn(X(nox::Scalar::host(4.0)));
+        world
+            .to_polars()
+            .unwrap()
+            .write_to_dir(dir.path().join("tick_3"))
+            .unwrap();
+        assert_eq!(reader.index().unwrap().ticks[3].entity_count, 5);
+
+        // even when the rewrite leaves every file the same size
+        let before = reader.index().unwrap().ticks[3].files.clone();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        world
+            .to_polars()
+            .unwrap()
+            .write_to_dir(dir.path().join("tick_3"))
+            .unwrap();
+        let after = reader.index().unwrap().ticks[3].files.clone();
+        assert_eq!(
+            before.values().map(|f| f.size).collect::<Vec<_>>(),
+            after.values().map(|f| f.size).collect::<Vec<_>>()
+        );
+        assert_ne!(before, after);
+
+        // and a removed one is dropped
+        std::fs::remove_dir_all(dir.path().join("tick_0")).unwrap();
+        let index = reader.index().unwrap();
+        assert_eq!(index.ticks.first().map(|t| t.tick), Some(1));
+    }
+
+    #[test]
+    fn test_replay_derivative() {
+        use crate::Component;
+
+        #[derive(crate::Component)]
+        struct X(nox::Scalar<f64>);
+
+        let dir = tempfile::tempdir().unwrap();
+        for tick in 0..4 {
+            let mut world = World::default();
+            world.spawn(X(nox::Scalar::host(2.0 * tick as f64)));
+            world.spawn(X(nox::Scalar::host(5.0 - tick as f64)));
+            world.tick = tick;
+            world
+                .to_polars()
+                .unwrap()
+                .write_to_dir(dir.path().join(format!("tick_{}", tick)))
+                .unwrap();
+        }
+
+        let reader = ReplayReader::new(dir.path());
+        let df = reader.derivative(X::component_id(), 0.5).unwrap();
+        assert_eq!(df.height(), 8);
+        let entities = df.column("entity_id").unwrap().u64().unwrap();
+        let derivative = df
+            .column(&X::component_id().0.to_string())
+            .unwrap()
+            .f64()
+            .unwrap();
+        for (entity, derivative) in entities
+            .into_no_null_iter()
+            .zip(derivative.into_no_null_iter())
+        {
+            let expected = if entity == 0 { 4.0 } else { -2.0 };
+            assert!((derivative - expected).abs() < 1e-9);
+        }
+
+        for dt in [0.0, -0.5, f64::NAN] {
+            assert!(matches!(
+                reader.derivative(X::component_id(), dt),
+                Err(Error::InvalidTimeStep(_))
+            ));
+        }
+    }
+}
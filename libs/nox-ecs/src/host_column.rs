@@ -1,7 +1,7 @@
 use std::ops::Deref;
 
 use bytemuck::Pod;
-use conduit::{ComponentId, ComponentType, ComponentValue};
+use conduit::{ComponentId, ComponentType, ComponentValue, PrimitiveTy};
 use nox::{
     xla::{ArrayElement, PjRtBuffer},
     Client, NoxprNode,
@@ -31,6 +31,33 @@ impl HostColumn {
         }
     }
 
+    /// Builds a column from an array shaped `(len, shape...)`, taking the component shape
+    /// from every axis after the first
+    pub fn from_array<T: ArrayElement + Pod>(
+        component_id: ComponentId,
+        array: ndarray::ArrayViewD<'_, T>,
+    ) -> Result<Self, Error> {
+        let primitive_ty = primitive_ty(T::TY).ok_or(Error::Nox(nox::Error::IncompatibleDType))?;
+        let (&len, shape) = array.shape().split_first().ok_or(Error::ShapeMismatch {
+            expected: vec![0],
+            found: vec![],
+        })?;
+        let buf = match array.as_slice() {
+            Some(slice) => bytemuck::cast_slice(slice).to_vec(),
+            None => bytemuck::cast_slice(&array.iter().copied().collect::<Vec<T>>()).to_vec(),
+        };
+        Ok(HostColumn {
+            buf,
+            len,
+            component_type: ComponentType {
+                primitive_ty,
+                shape: shape.iter().copied().collect(),
+            },
+            component_id,
+            asset: false,
+        })
+    }
+
     pub fn push<T: Component + 'static>(&mut self, val: T) {
         assert_eq!(self.component_type, T::component_type());
         let op = val.into_op();
@@ -46,6 +73,61 @@ impl HostColumn {
         self.len += 1;
     }
 
+    /// Appends a single row holding `value`, which must have one element per entry of the
+    /// component's shape
+    pub fn push_row<T: ArrayElement + Pod>(&mut self, value: &[T]) -> Result<(), Error> {
+        let element_type = self.component_type.primitive_ty.element_type();
+        if element_type != T::TY {
+            return Err(Error::ElementTypeMismatch {
+                expected: element_type,
+                found: T::TY,
+            });
+        }
+        self.append_rows(bytemuck::cast_slice(value), 1)
+    }
+
+    /// Appends `count` rows of raw, already encoded component values to the column
+    pub fn append_rows(&mut self, bytes: &[u8], count: usize) -> Result<(), Error> {
+        let expected_len = count
+            .checked_mul(self.component_type.size())
+            .ok_or(Error::ValueSizeMismatch)?;
+        if bytes.len() != expected_len {
+            return Err(Error::ValueSizeMismatch);
+        }
+        self.buf.extend_from_slice(bytes);
+        self.len += count;
+        Ok(())
+    }
+
+    /// Keeps only the given rows, in the order provided, and releases any unused capacity
+    pub fn retain_rows(&mut self, rows: &[usize]) {
+        let size = self.component_type.size();
+        let mut buf = Vec::with_capacity(rows.len() * size);
+        for &row in rows {
+            buf.extend_from_slice(&self.buf[row * size..(row + 1) * size]);
+        }
+        self.buf = buf;
+        self.len = rows.len();
+    }
+
+    /// Reserves capacity for at least `additional` more rows
+    pub fn reserve(&mut self, additional: usize) {
+        self.buf.reserve(additional * self.component_type.size());
+    }
+
+    /// Removes every row, keeping the buffer's capacity
+    pub fn clear(&mut self) {
+        self.buf.clear();
+        self.len = 0;
+    }
+
+    /// Removes a single row, shifting every row after it down by one
+    pub fn remove_row(&mut self, row: usize) {
+        let size = self.component_type.size();
+        self.buf.drain(row * size..(row + 1) * size);
+        self.len -= 1;
+    }
+
     pub fn len(&self) -> usize {
         self.len
     }
@@ -100,6 +182,71 @@ impl HostColumn {
         bytemuck::try_cast_slice(self.buf.as_slice()).ok()
     }
 
+    /// Returns the value stored in `row`
+    pub fn value(&self, row: usize) -> Option<ComponentValue<'_>> {
+        let size = self.component_type.size();
+        let buf = self.buf.get(row * size..(row + 1) * size)?;
+        let (_, value) = self.component_type.parse_value(buf).ok()?;
+        Some(value)
+    }
+
+    /// Returns the values stored in `row`, or `None` if the row is out of bounds or `T`
+    /// doesn't match the column's element type
+    pub fn get<T: ArrayElement + Pod>(&self, row: usize) -> Option<&[T]> {
+        let stride = self.component_type.shape.iter().product::<usize>();
+        self.typed_buf::<T>()?.get(row * stride..(row + 1) * stride)
+    }
+
+    /// Iterates over the values of each row, as slices holding one element per entry of the
+    /// component's shape
+    pub fn rows<T: ArrayElement + Pod>(&self) -> Result<impl Iterator<Item = &[T]> + '_, Error> {
+        let element_type = self.component_type.primitive_ty.element_type();
+        if element_type != T::TY {
+            return Err(Error::ElementTypeMismatch {
+                expected: element_type,
+                found: T::TY,
+            });
+        }
+        let buf = self.typed_buf::<T>().ok_or(Error::ValueSizeMismatch)?;
+        let stride = self.component_type.shape.iter().product::<usize>();
+        Ok((0..self.len).map(move |row| &buf[row * stride..(row + 1) * stride]))
+    }
+
+    /// Overwrites the values stored in `row` with `value`
+    pub fn set<T: ArrayElement + Pod>(&mut self, row: usize, value: &[T]) -> Result<(), Error> {
+        let element_type = self.component_type.primitive_ty.element_type();
+        if element_type != T::TY {
+            return Err(Error::ElementTypeMismatch {
+                expected: element_type,
+                found: T::TY,
+            });
+        }
+        if row >= self.len {
+            return Err(Error::RowOutOfBounds(row));
+        }
+        let size = self.component_type.size();
+        let value: &[u8] = bytemuck::cast_slice(value);
+        if value.len() != size {
+            return Err(Error::ValueSizeMismatch);
+        }
+        self.buf[row * size..(row + 1) * size].copy_from_slice(value);
+        Ok(())
+    }
+
+    /// Views the column as an array shaped `(len, shape...)` without copying
+    pub fn as_array_view<T: ArrayElement + Pod>(
+        &self,
+    ) -> Result<ndarray::ArrayViewD<'_, T>, Error> {
+        let element_type = self.component_type.primitive_ty.element_type();
+        if element_type != T::TY {
+            return Err(Error::ElementTypeMismatch {
+                expected: element_type,
+                found: T::TY,
+            });
+        }
+        self.ndarray().ok_or(Error::ValueSizeMismatch)
+    }
+
     pub fn ndarray<T: ArrayElement + Pod>(&self) -> Option<ndarray::ArrayViewD<'_, T>> {
         let shape: SmallVec<[usize; 4]> = std::iter::once(self.len)
             .chain(self.component_type.shape.iter().copied())
@@ -169,3 +316,110 @@ impl HostColumn {
         }
     }
 }
+
+fn primitive_ty(element_type: nox::xla::ElementType) -> Option<PrimitiveTy> {
+    use nox::xla::ElementType;
+    match element_type {
+        ElementType::U8 => Some(PrimitiveTy::U8),
+        ElementType::U16 => Some(PrimitiveTy::U16),
+        ElementType::U32 => Some(PrimitiveTy::U32),
+        ElementType::U64 => Some(PrimitiveTy::U64),
+        ElementType::S8 => Some(PrimitiveTy::I8),
+        ElementType::S16 => Some(PrimitiveTy::I16),
+        ElementType::S32 => Some(PrimitiveTy::I32),
+        ElementType::S64 => Some(PrimitiveTy::I64),
+        ElementType::F32 => Some(PrimitiveTy::F32),
+        ElementType::F64 => Some(PrimitiveTy::F64),
+        ElementType::F16 => Some(PrimitiveTy::F16),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_rows() {
+        let component_type = ComponentType {
+            primitive_ty: conduit::PrimitiveTy::F64,
+            shape: smallvec::smallvec![2],
+        };
+        let mut column = HostColumn::new(component_type, ComponentId::new("foo"));
+        column.push_raw(bytemuck::cast_slice(&[1.0f64, 2.0]));
+
+        let rows = [3.0f64, 4.0, 5.0, 6.0];
+        column.append_rows(bytemuck::cast_slice(&rows), 2).unwrap();
+        assert_eq!(column.len(), 3);
+        assert_eq!(
+            column.typed_buf::<f64>().unwrap(),
+            &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]
+        );
+
+        let res = column.append_rows(bytemuck::cast_slice(&rows), 1);
+        assert!(matches!(res, Err(Error::ValueSizeMismatch)));
+        assert_eq!(column.len(), 3);
+    }
+
+    #[test]
+    fn test_push_row() {
+        let component_type = ComponentType {
+            primitive_ty: conduit::PrimitiveTy::F64,
+            shape: smallvec::smallvec![3],
+        };
+        let mut column = HostColumn::new(component_type, ComponentId::new("foo"));
+        for i in 0..3 {
+            let i = i as f64;
+            column.push_row(&[i, i + 0.5, -i]).unwrap();
+        }
+        assert_eq!(column.len(), 3);
+        let expected = [0.0f64, 0.5, 0.0, 1.0, 1.5, -1.0, 2.0, 2.5, -2.0];
+        assert_eq!(column.raw_buf(), bytemuck::cast_slice::<f64, u8>(&expected));
+
+        let res = column.push_row(&[1.0f64, 2.0]);
+        assert!(matches!(res, Err(Error::ValueSizeMismatch)));
+        let res = column.push_row(&[1.0f32, 2.0, 3.0]);
+        assert!(matches!(res, Err(Error::ElementTypeMismatch { .. })));
+        assert_eq!(column.len(), 3);
+    }
+
+    #[test]
+    fn test_rows() {
+        let array = ndarray::Array::from_shape_fn((4, 3), |(i, j)| (i * 10 + j) as f64).into_dyn();
+        let column = HostColumn::from_array(ComponentId::new("pos"), array.view()).unwrap();
+        let rows = column.rows::<f64>().unwrap().collect::<Vec<_>>();
+        assert_eq!(rows.len(), 4);
+        for (i, row) in rows.into_iter().enumerate() {
+            let i = i as f64 * 10.0;
+            assert_eq!(row, &[i, i + 1.0, i + 2.0]);
+        }
+        assert!(matches!(
+            column.rows::<f32>(),
+            Err(Error::ElementTypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_array_round_trip() {
+        let array =
+            ndarray::Array::from_shape_fn((5, 2, 3), |(i, j, k)| (i * 100 + j * 10 + k) as f64)
+                .into_dyn();
+        let column = HostColumn::from_array(ComponentId::new("foo"), array.view()).unwrap();
+        assert_eq!(column.len(), 5);
+        assert_eq!(&column.component_type.shape[..], &[2, 3]);
+        assert_eq!(
+            column.component_type.primitive_ty,
+            conduit::PrimitiveTy::F64
+        );
+
+        let view = column.as_array_view::<f64>().unwrap();
+        assert_eq!(view, array.view());
+        assert!(matches!(
+            column.as_array_view::<f32>(),
+            Err(Error::ElementTypeMismatch { .. })
+        ));
+
+        let round_trip = HostColumn::from_array(ComponentId::new("foo"), view).unwrap();
+        assert_eq!(round_trip, column);
+    }
+}
@@ -1,19 +1,46 @@
-use crate::{ComponentGroup, Error, Query};
-use crate::{IntoSystem, System, SystemParam};
-use nox::IntoOp;
+use crate::polars::check_precision;
+use crate::{ComponentGroup, Error, Exec, Query, SharedWorld, World};
+use crate::{IntoSystem, System, SystemExt, SystemParam};
+use conduit::PrimitiveTy;
+use nox::{Client, IntoOp};
+use std::collections::HashMap;
 use std::ops::Add;
 use std::sync::Arc;
 use std::{marker::PhantomData, ops::Mul};
 
-pub struct Rk4<U, DU, Pipe> {
+/// The numerical scheme used to advance state `U` by its derivative `DU`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Integrator {
+    /// Classic fourth-order Runge-Kutta, evaluating the pipeline four times per step.
+    #[default]
+    Rk4,
+    /// Explicit first-order Euler, evaluating the pipeline once per step.
+    Euler,
+}
+
+pub struct Integrate<U, DU, Pipe> {
+    integrator: Integrator,
     dt: f64,
     pipe: Arc<Pipe>,
     phantom_data: PhantomData<(U, DU)>,
 }
 
-impl<Pipe, U, DU> Rk4<U, DU, Pipe> {
+/// The previous name of [`Integrate`], from when RK4 was the only integrator.
+#[deprecated(note = "renamed to `Integrate`")]
+pub type Rk4<U, DU, Pipe> = Integrate<U, DU, Pipe>;
+
+/// The previous name of [`IntegratorExt`].
+pub use IntegratorExt as Rk4Ext;
+
+impl<Pipe, U, DU> Integrate<U, DU, Pipe> {
+    /// Integrates `pipe` with [`Integrator::Rk4`].
     pub fn new(pipe: Pipe, dt: f64) -> Self {
+        Self::with_integrator(pipe, Integrator::Rk4, dt)
+    }
+
+    pub fn with_integrator(pipe: Pipe, integrator: Integrator, dt: f64) -> Self {
         Self {
+            integrator,
             dt,
             pipe: Arc::new(pipe),
             phantom_data: PhantomData,
@@ -21,35 +48,45 @@ impl<Pipe, U, DU> Rk4<U, DU, Pipe> {
     }
 }
 
-pub trait Rk4Ext {
-    fn rk4<U, DU>(self) -> Rk4<U, DU, Self>
+pub trait IntegratorExt {
+    fn rk4<U, DU>(self) -> Integrate<U, DU, Self>
+    where
+        Self: Sized;
+    fn rk4_with_dt<U, DU>(self, dt: f64) -> Integrate<U, DU, Self>
     where
         Self: Sized;
-    fn rk4_with_dt<U, DU>(self, dt: f64) -> Rk4<U, DU, Self>
+    fn integrate_with_dt<U, DU>(self, integrator: Integrator, dt: f64) -> Integrate<U, DU, Self>
     where
         Self: Sized;
 }
 
-impl<Sys> Rk4Ext for Sys
+impl<Sys> IntegratorExt for Sys
 where
     Sys: System,
 {
-    fn rk4<U, DU>(self) -> Rk4<U, DU, Self>
+    fn rk4<U, DU>(self) -> Integrate<U, DU, Self>
     where
         Self: Sized,
     {
-        Rk4::new(self, 1.0 / 60.0)
+        Integrate::new(self, 1.0 / 60.0)
     }
 
-    fn rk4_with_dt<U, DU>(self, dt: f64) -> Rk4<U, DU, Self>
+    fn rk4_with_dt<U, DU>(self, dt: f64) -> Integrate<U, DU, Self>
     where
         Self: Sized,
     {
-        Rk4::new(self, dt)
+        Integrate::new(self, dt)
+    }
+
+    fn integrate_with_dt<U, DU>(self, integrator: Integrator, dt: f64) -> Integrate<U, DU, Self>
+    where
+        Self: Sized,
+    {
+        Integrate::with_integrator(self, integrator, dt)
     }
 }
 
-impl<Pipe, U, DU> System for Rk4<U, DU, Pipe>
+impl<Pipe, U, DU> System for Integrate<U, DU, Pipe>
 where
     Query<U>: SystemParam<Item = Query<U>> + Clone,
     Query<DU>: SystemParam<Item = Query<DU>> + Clone,
@@ -84,21 +121,173 @@ where
             f(dt).pipe(self.pipe.clone()).add_to_builder(builder)?;
             Ok(Query::<DU>::from_builder(builder))
         };
-        let k1 = step(0.0)?;
-        let k2 = step(dt / 2.0)?;
-        let k3 = step(dt / 2.0)?;
-        let k4 = step(dt)?;
-        let u = init_u
-            .join_query(k1)
-            .join_query(k2)
-            .join_query(k3)
-            .join_query(k4)
-            .map(|(((du, k1), k2), k3), k4| du + (dt / 6.0) * (k1 + 2.0 * k2 + 2.0 * k3 + k4))?;
+        let u = match self.integrator {
+            Integrator::Rk4 => {
+                let k1 = step(0.0)?;
+                let k2 = step(dt / 2.0)?;
+                let k3 = step(dt / 2.0)?;
+                let k4 = step(dt)?;
+                init_u
+                    .join_query(k1)
+                    .join_query(k2)
+                    .join_query(k3)
+                    .join_query(k4)
+                    .map(|(((u, k1), k2), k3), k4| {
+                        u + (dt / 6.0) * (k1 + 2.0 * k2 + 2.0 * k3 + k4)
+                    })?
+            }
+            Integrator::Euler => {
+                let k1 = step(0.0)?;
+                init_u.join_query(k1).map(|u, k1| u + dt * k1)?
+            }
+        };
         u.insert_into_builder(builder);
         Ok(())
     }
 }
 
+/// Step size bounds and error tolerance for [`AdaptiveExec`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AdaptiveConfig {
+    /// The smallest step taken, even when its error estimate is still above `tol`.
+    pub min_dt: f64,
+    /// The largest step taken, used whenever the error estimate allows it.
+    pub max_dt: f64,
+    /// The largest accepted difference between a full step and two half steps, in any element
+    /// of the integrated state.
+    pub tol: f64,
+}
+
+/// Advances a world with RK4, sizing each step by step doubling.
+///
+/// Every step is taken both whole and as two half steps, and the step is halved and retried
+/// until the two agree to within [`AdaptiveConfig::tol`]. Step sizes are `max_dt / 2^k`, so
+/// each size is compiled once and reused.
+pub struct AdaptiveExec<U, DU, Pipe> {
+    pub world: SharedWorld,
+    config: AdaptiveConfig,
+    pipe: Arc<Pipe>,
+    execs: HashMap<u32, Exec>,
+    level: u32,
+    elapsed: f64,
+    phantom_data: PhantomData<(U, DU)>,
+}
+
+impl<Pipe, U, DU> AdaptiveExec<U, DU, Pipe>
+where
+    Integrate<U, DU, Pipe>: System,
+    U: ComponentGroup,
+{
+    pub fn new(world: World, pipe: Pipe, config: AdaptiveConfig) -> Result<Self, Error> {
+        let AdaptiveConfig {
+            min_dt,
+            max_dt,
+            tol,
+        } = config;
+        if !(min_dt > 0.0 && min_dt <= max_dt && max_dt.is_finite() && tol > 0.0) {
+            return Err(Error::InvalidAdaptiveConfig(config));
+        }
+        Ok(Self {
+            world: SharedWorld::from_host(world),
+            config,
+            pipe: Arc::new(pipe),
+            execs: HashMap::new(),
+            level: 0,
+            elapsed: 0.0,
+            phantom_data: PhantomData,
+        })
+    }
+
+    /// The size of the next step to be attempted.
+    pub fn dt(&self) -> f64 {
+        self.config.max_dt / 2f64.powi(self.level as i32)
+    }
+
+    /// The simulated time covered by every step taken so far.
+    pub fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+
+    /// Takes a single step, shrinking it until the error estimate is within tolerance, and
+    /// returns the size of the step taken. A non-finite error estimate always rejects the step,
+    /// and fails with [`Error::NonFiniteStep`] once the step can't shrink any further.
+    pub fn step(&mut self, client: &Client) -> Result<f64, Error> {
+        self.world.load_all_columns()?;
+        loop {
+            let level = self.level;
+            let dt = self.dt();
+            let mut full = self.world.fork();
+            self.exec(level)?.run(&mut full, client)?;
+            let mut halves = self.world.fork();
+            let half = self.exec(level + 1)?;
+            half.run(&mut halves, client)?;
+            half.run(&mut halves, client)?;
+            let error = Self::state_error(&mut full, &mut halves)?;
+            let can_shrink = dt / 2.0 >= self.config.min_dt;
+            if !error.is_finite() {
+                if !can_shrink {
+                    return Err(Error::NonFiniteStep(dt));
+                }
+                self.level += 1;
+                continue;
+            }
+            if error > self.config.tol && can_shrink {
+                self.level += 1;
+                continue;
+            }
+            // RK4's local error scales with dt^5, so doubling the step multiplies it by 32
+            if error * 32.0 < self.config.tol && level > 0 {
+                self.level -= 1;
+            }
+            halves.host.tick += 1;
+            self.world = halves;
+            self.elapsed += dt;
+            return Ok(dt);
+        }
+    }
+
+    fn exec(&mut self, level: u32) -> Result<&Exec, Error> {
+        if !self.execs.contains_key(&level) {
+            let dt = self.config.max_dt / 2f64.powi(level as i32);
+            let integrate = Integrate::<U, DU, Pipe> {
+                integrator: Integrator::Rk4,
+                dt,
+                pipe: self.pipe.clone(),
+                phantom_data: PhantomData,
+            };
+            let exec = integrate.build(&mut self.world.host)?;
+            self.execs.insert(level, exec);
+        }
+        Ok(&self.execs[&level])
+    }
+
+    /// The largest absolute difference between any element of `U` in the two worlds, or NaN if
+    /// any difference is NaN.
+    fn state_error(a: &mut SharedWorld, b: &mut SharedWorld) -> Result<f64, Error> {
+        a.load_all_columns()?;
+        b.load_all_columns()?;
+        let mut error = 0.0f64;
+        for id in U::component_ids() {
+            let a = a.host.column_by_id(id).ok_or(Error::ComponentNotFound)?;
+            let b = b.host.column_by_id(id).ok_or(Error::ComponentNotFound)?;
+            check_precision(
+                PrimitiveTy::F64,
+                a.column.buffer.component_type.primitive_ty,
+            )?;
+            let a = a.typed_buf::<f64>().ok_or(Error::ValueSizeMismatch)?;
+            let b = b.typed_buf::<f64>().ok_or(Error::ValueSizeMismatch)?;
+            for (a, b) in a.iter().zip(b) {
+                let diff = (a - b).abs();
+                if diff.is_nan() {
+                    return Ok(f64::NAN);
+                }
+                error = error.max(diff);
+            }
+        }
+        Ok(error)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -156,7 +345,20 @@ mod tests {
         let mut exec = builder.build().unwrap();
         exec.run(&client).unwrap();
         let col = exec.column(X::component_id()).unwrap();
-        assert_eq!(col.typed_buf::<f64>().unwrap(), &[0.16666666666666669])
+        assert_eq!(col.typed_buf::<f64>().unwrap(), &[0.16666666666666669]);
+
+        // the names from before the rename still build the same pipeline
+        let mut world = World::default();
+        world.spawn(Body {
+            x: X::host(0.0),
+            v: V::host(10.0),
+        });
+        #[allow(deprecated)]
+        let rk4: Rk4<X, V, ()> = Rk4::new((), 1.0 / 60.0);
+        let mut exec = world.builder().tick_pipeline(rk4).build().unwrap();
+        exec.run(&client).unwrap();
+        let col = exec.column(X::component_id()).unwrap();
+        assert_eq!(col.typed_buf::<f64>().unwrap(), &[0.16666666666666669]);
     }
 
     #[test]
@@ -242,4 +444,160 @@ mod tests {
             &[1.0f64, 0.0, 0.0, 0.0, 0.016666666666666666, 0.0, 0.0]
         )
     }
+
+    #[test]
+    fn test_adaptive_step() {
+        #[derive(Clone, Component)]
+        struct X(Scalar<f64>);
+        #[derive(Clone, Component)]
+        struct V(Scalar<f64>);
+        #[derive(Clone, Component)]
+        struct A(Scalar<f64>);
+
+        #[derive(FromBuilder, ComponentGroup, IntoOp)]
+        struct U {
+            x: X,
+            v: V,
+        }
+
+        #[derive(FromBuilder, ComponentGroup, IntoOp)]
+        struct DU {
+            v: V,
+            a: A,
+        }
+
+        impl Add<DU> for U {
+            type Output = U;
+
+            fn add(self, du: DU) -> Self::Output {
+                U {
+                    x: X(self.x.0 + du.v.0),
+                    v: V(self.v.0 + du.a.0),
+                }
+            }
+        }
+
+        impl Add for DU {
+            type Output = DU;
+
+            fn add(self, du: DU) -> Self::Output {
+                DU {
+                    v: V(self.v.0 + du.v.0),
+                    a: A(self.a.0 + du.a.0),
+                }
+            }
+        }
+
+        impl Mul<DU> for f64 {
+            type Output = DU;
+
+            fn mul(self, rhs: DU) -> Self::Output {
+                DU {
+                    v: V(self * rhs.v.0),
+                    a: A(self * rhs.a.0),
+                }
+            }
+        }
+
+        #[derive(Archetype)]
+        struct Body {
+            x: X,
+            v: V,
+            a: A,
+        }
+
+        // a stiff spring for x > 0, so the body coasts in, bounces off it, and coasts back out
+        fn spring(q: Query<X>) -> Query<A> {
+            q.map(|x: X| {
+                let zero = x.0.clone() * 0.0;
+                A(Scalar::select(&x.0.greater(&zero), x.0 * -10000.0, zero))
+            })
+            .unwrap()
+        }
+
+        let mut world = World::default();
+        world.spawn(Body {
+            x: X::host(-1.0),
+            v: V::host(1.0),
+            a: A::host(0.0),
+        });
+        let config = AdaptiveConfig {
+            min_dt: 1e-4,
+            max_dt: 0.1,
+            tol: 1e-6,
+        };
+        let mut exec = AdaptiveExec::<U, DU, _>::new(world, spring.into_system(), config).unwrap();
+        let client = nox::Client::cpu().unwrap();
+        let mut steps = vec![];
+        while exec.elapsed() < 2.5 {
+            let x = exec
+                .world
+                .host
+                .column::<X>()
+                .unwrap()
+                .typed_buf::<f64>()
+                .unwrap()[0];
+            steps.push((x, exec.step(&client).unwrap()));
+        }
+        let coasting = |x: f64| x < -0.2;
+        assert!(steps
+            .iter()
+            .filter(|(x, _)| coasting(*x))
+            .any(|(_, dt)| *dt == config.max_dt));
+        assert!(steps[..5].iter().all(|(_, dt)| *dt == config.max_dt));
+        let smallest = steps.iter().map(|(_, dt)| *dt).fold(f64::MAX, f64::min);
+        assert!(smallest < config.max_dt / 16.0, "smallest step {smallest}");
+        assert!(steps.len() < 2000);
+        let (x, _) = steps.last().unwrap();
+        assert!(*x < -0.1, "body should have bounced back, x = {x}");
+
+        let body = || {
+            let mut world = World::default();
+            world.spawn(Body {
+                x: X::host(-1.0),
+                v: V::host(1.0),
+                a: A::host(0.0),
+            });
+            world
+        };
+        for bad in [
+            AdaptiveConfig {
+                min_dt: 0.0,
+                ..config
+            },
+            AdaptiveConfig {
+                min_dt: 0.2,
+                ..config
+            },
+            AdaptiveConfig {
+                max_dt: f64::INFINITY,
+                ..config
+            },
+            AdaptiveConfig { tol: 0.0, ..config },
+            AdaptiveConfig {
+                tol: f64::NAN,
+                ..config
+            },
+        ] {
+            assert!(matches!(
+                AdaptiveExec::<U, DU, _>::new(body(), spring.into_system(), bad),
+                Err(Error::InvalidAdaptiveConfig(_))
+            ));
+        }
+
+        // an infinite acceleration makes both estimates infinite, so their difference is NaN
+        fn blow_up(q: Query<X>) -> Query<A> {
+            q.map(|x: X| A(x.0.clone() / (x.0 * 0.0))).unwrap()
+        }
+        let config = AdaptiveConfig {
+            min_dt: 0.025,
+            ..config
+        };
+        let mut exec =
+            AdaptiveExec::<U, DU, _>::new(body(), blow_up.into_system(), config).unwrap();
+        assert!(matches!(
+            exec.step(&client),
+            Err(Error::NonFiniteStep(dt)) if dt == 0.025
+        ));
+    }
 }
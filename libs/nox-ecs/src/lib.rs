@@ -2,7 +2,7 @@ extern crate self as nox_ecs;
 
 use bytemuck::{AnyBitPattern, Pod};
 use conduit::well_known::EntityMetadata;
-use conduit::{Asset, ComponentId, ComponentType, ComponentValue, EntityId, Metadata};
+use conduit::{Asset, ComponentId, ComponentType, ComponentValue, EntityId, Metadata, TagValue};
 use history::History;
 use nox::xla::{ArrayElement, BufferArgsRef, HloModuleProto, PjRtBuffer, PjRtLoadedExecutable};
 use nox::{ArrayTy, Client, CompFn, Noxpr, NoxprFn};
@@ -41,6 +41,7 @@ pub use conduit_exec::*;
 pub use dyn_array::*;
 pub use host_column::*;
 pub use integrator::*;
+pub use polars::{StreamingPolarsWorld, WorldReplay};
 pub use query::*;
 
 pub use nox_ecs_macros::{Archetype, Component};
@@ -64,6 +65,63 @@ impl Clone for Table<HostStore> {
     }
 }
 
+impl Table<HostStore> {
+    /// Drops every row that is no longer referenced by `entity_map`, packing the
+    /// remaining rows densely and shrinking each buffer down to its contents.
+    pub fn compact(&mut self) {
+        let mut live: Vec<(usize, EntityId)> = self
+            .entity_map
+            .iter()
+            .map(|(entity, row)| (*row, *entity))
+            .collect();
+        live.sort_unstable();
+        let rows: Vec<usize> = live.iter().map(|(row, _)| *row).collect();
+        for column in self.columns.values_mut() {
+            column.buffer.retain_rows(&rows);
+        }
+        self.entity_buffer.retain_rows(&rows);
+        self.entity_map = live
+            .into_iter()
+            .enumerate()
+            .map(|(new_row, (_, entity))| (entity, new_row))
+            .collect();
+    }
+
+    /// Removes every row while keeping the table's columns, so the archetype can be spawned into
+    /// again
+    pub fn clear(&mut self) {
+        for column in self.columns.values_mut() {
+            column.buffer.clear();
+        }
+        self.entity_buffer.clear();
+        self.entity_map.clear();
+    }
+
+    /// Removes the row belonging to `entity`, returning false if the table doesn't contain it
+    pub fn remove_entity(&mut self, entity: EntityId) -> bool {
+        let Some(row) = self.entity_map.remove(&entity) else {
+            return false;
+        };
+        for column in self.columns.values_mut() {
+            column.buffer.remove_row(row);
+        }
+        self.entity_buffer.remove_row(row);
+        for other_row in self.entity_map.values_mut() {
+            if *other_row > row {
+                *other_row -= 1;
+            }
+        }
+        true
+    }
+}
+
+impl<S: WorldStore> Table<S> {
+    /// Returns the row `entity` is stored at in this table's columns
+    pub fn row_of(&self, entity: EntityId) -> Option<usize> {
+        self.entity_map.get(&entity).copied()
+    }
+}
+
 impl<S: WorldStore> std::fmt::Debug for Table<S>
 where
     S::EntityBuffer: std::fmt::Debug,
@@ -109,6 +167,10 @@ pub struct World<S: WorldStore = HostStore> {
     pub assets: AssetStore,
     pub tick: u64,
     pub entity_len: u64,
+    pub entity_allocator: EntityAllocator,
+    /// Components registered with [`World::register_component`], which may not be stored in any
+    /// archetype yet. Not carried through [`PolarsWorld`].
+    pub registered_components: HashMap<ComponentId, Metadata>,
 }
 
 impl Clone for World {
@@ -119,6 +181,8 @@ impl Clone for World {
             assets: self.assets.clone(),
             tick: 0,
             entity_len: self.entity_len,
+            entity_allocator: self.entity_allocator.clone(),
+            registered_components: self.registered_components.clone(),
         }
     }
 }
@@ -131,10 +195,57 @@ impl<S: WorldStore> Default for World<S> {
             assets: Default::default(),
             tick: 0,
             entity_len: 0,
+            entity_allocator: Default::default(),
+            registered_components: Default::default(),
         }
     }
 }
 
+/// Hands out entity ids, reusing the ids of despawned entities before growing `entity_len`.
+///
+/// A reused id keeps its index but has its generation bumped, so handles to the despawned
+/// entity can be told apart from the entity that replaced it, see [`Error::StaleEntity`].
+/// The free list isn't persisted, so a world read back from disk only hands out fresh ids.
+#[derive(Clone, Debug, Default)]
+pub struct EntityAllocator {
+    free: Vec<EntityId>,
+    generations: HashMap<u32, u32>,
+}
+
+impl EntityAllocator {
+    /// Returns the most recently freed id with its generation bumped, or `EntityId(entity_len)`
+    /// if there are none
+    pub fn alloc(&mut self, entity_len: u64) -> EntityId {
+        let Some(freed) = self.free.pop() else {
+            return EntityId(entity_len);
+        };
+        let generation = freed.generation().wrapping_add(1);
+        self.generations.insert(freed.index(), generation);
+        EntityId::from_parts(freed.index(), generation)
+    }
+
+    /// Makes the index of a despawned entity available for reuse
+    pub fn free(&mut self, entity: EntityId) {
+        self.free.push(entity);
+    }
+
+    /// Marks an id picked by the caller as live, so its index isn't handed out again by
+    /// [`EntityAllocator::alloc`] while the entity exists
+    pub fn claim(&mut self, entity: EntityId) {
+        self.free.retain(|freed| freed.index() != entity.index());
+        if entity.generation() != 0 || self.generations.contains_key(&entity.index()) {
+            self.generations.insert(entity.index(), entity.generation());
+        }
+    }
+
+    /// Whether `entity`'s index has since been handed out again with a newer generation
+    pub fn is_stale(&self, entity: EntityId) -> bool {
+        self.generations
+            .get(&entity.index())
+            .is_some_and(|generation| *generation != entity.generation())
+    }
+}
+
 impl<S: WorldStore> World<S> {
     pub fn column_mut<C: Component + 'static>(&mut self) -> Option<ColumnRefMut<'_, S>> {
         let Some(id) = self.component_map.get(&C::component_id()) else {
@@ -164,6 +275,33 @@ impl<S: WorldStore> World<S> {
         })
     }
 
+    /// Returns the metadata of the column storing `id`, if any archetype contains it.
+    pub fn component_metadata(&self, id: ComponentId) -> Option<&Metadata> {
+        let table_id = self.component_map.get(&id)?;
+        let column = self.archetypes.get(table_id)?.columns.get(&id)?;
+        Some(&column.metadata)
+    }
+
+    /// Checks that `id` isn't already registered to a component with a `name` tag other than
+    /// `name`, which would mean two component names hash to the same id.
+    pub fn check_component_name(&self, id: ComponentId, name: &str) -> Result<(), Error> {
+        let Some(existing) = self
+            .component_metadata(id)
+            .and_then(|metadata| metadata.tags.get("name"))
+            .and_then(TagValue::as_str)
+        else {
+            return Ok(());
+        };
+        if existing != name {
+            return Err(Error::ComponentIdCollision {
+                id,
+                existing: existing.to_string(),
+                name: name.to_string(),
+            });
+        }
+        Ok(())
+    }
+
     pub fn column_by_id_mut(&mut self, id: ComponentId) -> Option<ColumnRefMut<'_, S>> {
         let Some(table_id) = self.component_map.get(&id) else {
             return None;
@@ -215,14 +353,44 @@ impl From<Entity<'_>> for EntityId {
 }
 
 impl World<HostStore> {
-    pub fn get_or_insert_archetype<A: Archetype + 'static>(&mut self) -> &mut Table<HostStore> {
+    /// Returns the table storing `A`, creating it the first time `A` is seen.
+    ///
+    /// Creating the table registers the names of `A`'s components, failing with
+    /// [`Error::ComponentIdCollision`] if one of them hashes to the id of a differently named
+    /// component.
+    pub fn get_or_insert_archetype<A: Archetype + 'static>(
+        &mut self,
+    ) -> Result<&mut Table<HostStore>, Error> {
         let archetype_id = ArchetypeId::type_id(TypeId::of::<A>());
-        self.archetypes.entry(archetype_id).or_insert_with(|| {
+        if !self.archetypes.contains_key(&archetype_id) {
+            let mut names = HashMap::new();
+            for (id, name) in A::component_ids().into_iter().zip(A::component_names()) {
+                let Some(name) = name else {
+                    continue;
+                };
+                self.check_component_name(id, name)?;
+                if let Some(existing) = names.insert(id, name) {
+                    if existing != name {
+                        return Err(Error::ComponentIdCollision {
+                            id,
+                            existing: existing.to_string(),
+                            name: name.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+        Ok(self.archetypes.entry(archetype_id).or_insert_with(|| {
             let component_ids = A::component_ids();
             let columns = component_ids
                 .iter()
                 .zip(A::component_tys().iter())
-                .map(|(id, ty)| {
+                .zip(A::component_names())
+                .map(|((id, ty), name)| {
+                    let tags = name
+                        .map(|name| ("name".to_string(), TagValue::String(name.to_string())))
+                        .into_iter()
+                        .collect();
                     (
                         *id,
                         Column {
@@ -230,7 +398,7 @@ impl World<HostStore> {
                             metadata: Metadata {
                                 component_id: *id,
                                 component_type: ty.clone(),
-                                tags: HashMap::new(),
+                                tags,
                             },
                         },
                     )
@@ -244,27 +412,308 @@ impl World<HostStore> {
                 entity_buffer: HostColumn::new(ComponentType::u64(), ComponentId::new("entity_id")),
                 entity_map: BTreeMap::default(),
             }
-        })
+        }))
     }
 
+    /// # Panics
+    ///
+    /// Panics if one of the archetype's component ids collides with a differently named
+    /// component, see [`World::try_spawn`].
     pub fn spawn(&mut self, archetype: impl Archetype + 'static) -> Entity<'_> {
-        let entity_id = EntityId(self.entity_len);
-        self.spawn_with_id(archetype, entity_id);
-        Entity {
+        self.try_spawn(archetype)
+            .expect("failed to spawn archetype")
+    }
+
+    pub fn try_spawn(&mut self, archetype: impl Archetype + 'static) -> Result<Entity<'_>, Error> {
+        let entity_id = self.entity_allocator.alloc(self.entity_len);
+        if let Err(err) = self.try_spawn_with_id(archetype, entity_id) {
+            self.entity_allocator.free(entity_id);
+            return Err(err);
+        }
+        Ok(Entity {
             id: entity_id,
             world: self,
-        }
+        })
     }
 
+    /// # Panics
+    ///
+    /// Panics if one of the archetype's component ids collides with a differently named
+    /// component, see [`World::try_spawn_with_id`].
     pub fn spawn_with_id<A: Archetype + 'static>(&mut self, archetype: A, entity_id: EntityId) {
+        self.try_spawn_with_id(archetype, entity_id)
+            .expect("failed to spawn archetype")
+    }
+
+    pub fn try_spawn_with_id<A: Archetype + 'static>(
+        &mut self,
+        archetype: A,
+        entity_id: EntityId,
+    ) -> Result<(), Error> {
         use nox::ScalarExt;
-        let table = self.get_or_insert_archetype::<A>();
+        let table = self.get_or_insert_archetype::<A>()?;
         table
             .entity_map
             .insert(entity_id, table.entity_buffer.len());
         table.entity_buffer.push(entity_id.0.constant());
         archetype.insert_into_table(table);
-        self.entity_len += 1;
+        self.entity_allocator.claim(entity_id);
+        self.entity_len = self.entity_len.max(entity_id.index() as u64 + 1);
+        Ok(())
+    }
+
+    /// Registers a component whose schema is only known at runtime, so it can be passed to
+    /// [`World::spawn_dynamic`]. Registering an id again replaces its metadata.
+    ///
+    /// Registrations aren't persisted on their own: converting the world to polars only keeps
+    /// the metadata of components stored in an archetype, so a component that was registered
+    /// but never spawned must be registered again after the world is read back.
+    pub fn register_component(&mut self, metadata: Metadata) -> ComponentId {
+        let id = metadata.component_id;
+        self.registered_components.insert(id, metadata);
+        id
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the components can't be spawned together, see [`World::try_spawn_dynamic`].
+    pub fn spawn_dynamic(&mut self, components: &[(ComponentId, &[u8])]) -> EntityId {
+        self.try_spawn_dynamic(components)
+            .expect("failed to spawn dynamic components")
+    }
+
+    /// Spawns an entity from one row of raw bytes per component, without a static [`Archetype`].
+    ///
+    /// Each component must either be registered with [`World::register_component`] or already be
+    /// stored in the world, in which case the stored metadata wins, and its bytes must be exactly
+    /// one value of its `ComponentType`.
+    /// Entities spawned with the same set of components share an archetype, in whatever order
+    /// the components are given, including an archetype created by [`World::spawn`] with exactly
+    /// those components. A component already stored in an archetype with a different set of
+    /// components fails with [`Error::InconsistentArchetype`], and an empty set of components
+    /// with [`Error::NoComponents`].
+    pub fn try_spawn_dynamic(
+        &mut self,
+        components: &[(ComponentId, &[u8])],
+    ) -> Result<EntityId, Error> {
+        let mut values = BTreeMap::new();
+        for (id, data) in components {
+            if values.insert(*id, *data).is_some() {
+                return Err(Error::DuplicateComponent(*id));
+            }
+        }
+        if values.is_empty() {
+            return Err(Error::NoComponents);
+        }
+        let archetype_id = match values.keys().find_map(|id| self.component_map.get(id)) {
+            Some(existing) => {
+                let same_columns = self
+                    .archetypes
+                    .get(existing)
+                    .is_some_and(|table| table.columns.keys().eq(values.keys()));
+                if !same_columns {
+                    return Err(Error::InconsistentArchetype(*existing));
+                }
+                *existing
+            }
+            None => {
+                let id = dynamic_archetype_id(values.keys());
+                if self.archetypes.contains_key(&id) {
+                    return Err(Error::InconsistentArchetype(id));
+                }
+                id
+            }
+        };
+        let mut columns = BTreeMap::new();
+        for (id, data) in &values {
+            let metadata = self
+                .component_metadata(*id)
+                .or_else(|| self.registered_components.get(id))
+                .ok_or(Error::ComponentNotFound)?;
+            if let Some(name) = metadata.tags.get("name").and_then(TagValue::as_str) {
+                self.check_component_name(*id, name)?;
+            }
+            if data.len() != metadata.component_type.size() {
+                return Err(Error::ValueSizeMismatch);
+            }
+            match self.component_map.get(id) {
+                Some(existing) if *existing != archetype_id => {
+                    return Err(Error::InconsistentArchetype(*existing))
+                }
+                _ => {}
+            }
+            let mut buffer = HostColumn::new(metadata.component_type.clone(), *id);
+            buffer.asset = matches!(metadata.tags.get("asset"), Some(TagValue::Bool(true)));
+            let metadata = metadata.clone();
+            columns.insert(*id, Column { buffer, metadata });
+        }
+        let entity_id = self.entity_allocator.alloc(self.entity_len);
+        self.entity_len = self.entity_len.max(entity_id.index() as u64 + 1);
+        for id in values.keys() {
+            self.component_map.insert(*id, archetype_id);
+        }
+        let table = self
+            .archetypes
+            .entry(archetype_id)
+            .or_insert_with(|| Table {
+                columns,
+                entity_buffer: HostColumn::new(ComponentType::u64(), ComponentId::new("entity_id")),
+                entity_map: BTreeMap::default(),
+            });
+        for (id, data) in values {
+            let column = table.columns.get_mut(&id).ok_or(Error::ComponentNotFound)?;
+            column.buffer.append_rows(data, 1)?;
+        }
+        table
+            .entity_map
+            .insert(entity_id, table.entity_buffer.len());
+        table.entity_buffer.push_raw(&entity_id.0.to_ne_bytes());
+        Ok(entity_id)
+    }
+
+    /// Joins the columns storing `ids` by entity, yielding the values of each entity that
+    /// has every requested component, in the same order as `ids`
+    pub fn query(
+        &self,
+        ids: &[ComponentId],
+    ) -> impl Iterator<Item = (EntityId, Vec<ComponentValue<'_>>)> + '_ {
+        let columns = ids
+            .iter()
+            .map(|id| {
+                let table = self.archetypes.get(self.component_map.get(id)?)?;
+                Some((table, &table.columns.get(id)?.buffer))
+            })
+            .collect::<Option<Vec<_>>>()
+            .unwrap_or_default();
+        let entities = columns
+            .first()
+            .map(|(table, _)| *table)
+            .into_iter()
+            .flat_map(|table| table.entity_map.keys());
+        entities.filter_map(move |entity| {
+            let values = columns
+                .iter()
+                .map(|(table, column)| column.value(table.row_of(*entity)?))
+                .collect::<Option<Vec<_>>>()?;
+            Some((*entity, values))
+        })
+    }
+
+    /// Spawns every archetype in `archetypes`, reserving room in each column up front
+    /// rather than growing it row by row
+    pub fn spawn_batch<A: Archetype + 'static>(
+        &mut self,
+        archetypes: impl IntoIterator<Item = A>,
+    ) -> Result<Vec<EntityId>, Error> {
+        use nox::ScalarExt;
+        let archetypes = archetypes.into_iter();
+        let (additional, _) = archetypes.size_hint();
+        self.get_or_insert_archetype::<A>()?;
+        let table = self
+            .archetypes
+            .get_mut(&ArchetypeId::of::<A>())
+            .expect("archetype was just inserted");
+        for column in table.columns.values_mut() {
+            column.buffer.reserve(additional);
+        }
+        table.entity_buffer.reserve(additional);
+        let mut ids = Vec::with_capacity(additional);
+        for archetype in archetypes {
+            let entity_id = self.entity_allocator.alloc(self.entity_len);
+            self.entity_len = self.entity_len.max(entity_id.index() as u64 + 1);
+            table
+                .entity_map
+                .insert(entity_id, table.entity_buffer.len());
+            table.entity_buffer.push(entity_id.0.constant());
+            archetype.insert_into_table(table);
+            ids.push(entity_id);
+        }
+        Ok(ids)
+    }
+
+    /// Removes `entity` from every archetype it was spawned into, freeing its id for reuse
+    pub fn despawn(&mut self, entity: EntityId) -> Result<(), Error> {
+        self.check_entity(entity)?;
+        for table in self.archetypes.values_mut() {
+            table.remove_entity(entity);
+        }
+        self.entity_allocator.free(entity);
+        // freed ids are handed out before fresh ones, so `entity_len` can shrink past the
+        // most recently spawned entity without a fresh id colliding with its index
+        if entity.index() as u64 + 1 == self.entity_len {
+            self.entity_len -= 1;
+        }
+        Ok(())
+    }
+
+    /// Despawns every entity and rewinds `tick`, keeping the archetypes, the `component_map` and
+    /// the assets, so a world can be refilled for another run without re-deriving its schema.
+    ///
+    /// Entity ids start again from zero, so handles to the cleared entities are not reported as
+    /// stale if their ids are handed out again.
+    pub fn clear(&mut self) {
+        for table in self.archetypes.values_mut() {
+            table.clear();
+        }
+        self.entity_len = 0;
+        self.tick = 0;
+        self.entity_allocator = EntityAllocator::default();
+    }
+
+    /// Attaches `value` to an entity that has already been spawned.
+    ///
+    /// This does not migrate the entity into an archetype with `T` added. Each component is
+    /// stored in exactly one table, recorded in [`World::component_map`], and queries join tables
+    /// by entity, so an expanded archetype would hold a second copy of the entity's existing
+    /// components. Instead the entity's rows stay where they are, and it gains a row in the table
+    /// holding `T` alone, the same table [`Entity::insert`] adds to.
+    ///
+    /// Fails with [`Error::InconsistentArchetype`] if `T` is already stored as part of a larger
+    /// archetype, since the entity would be missing that table's other columns, and with
+    /// [`Error::DuplicateEntity`] if the entity already has a `T`.
+    pub fn add_component<T: Component + 'static>(
+        &mut self,
+        entity: EntityId,
+        value: T,
+    ) -> Result<(), Error> {
+        use nox::ScalarExt;
+        self.check_entity(entity)?;
+        let archetype_id = ArchetypeId::of::<T>();
+        match self.component_map.get(&T::component_id()) {
+            Some(id) if *id != archetype_id => return Err(Error::InconsistentArchetype(*id)),
+            _ => {}
+        }
+        let table = self.get_or_insert_archetype::<T>()?;
+        if table.entity_map.contains_key(&entity) {
+            return Err(Error::DuplicateEntity(entity));
+        }
+        table.entity_map.insert(entity, table.entity_buffer.len());
+        table.entity_buffer.push(entity.0.constant());
+        value.insert_into_table(table);
+        Ok(())
+    }
+
+    /// Checks that `entity` is live, distinguishing a handle whose id has since been reused from
+    /// one that was never spawned
+    fn check_entity(&self, entity: EntityId) -> Result<(), Error> {
+        if self.entity_allocator.is_stale(entity) {
+            return Err(Error::StaleEntity(entity));
+        }
+        if !self
+            .archetypes
+            .values()
+            .any(|table| table.entity_map.contains_key(&entity))
+        {
+            return Err(Error::EntityNotFound);
+        }
+        Ok(())
+    }
+
+    /// Compacts every archetype table, see [`Table::compact`]
+    pub fn compact_all(&mut self) {
+        for table in self.archetypes.values_mut() {
+            table.compact();
+        }
     }
 
     pub fn copy_to_client(&self, client: &Client) -> Result<World<ClientStore>, Error> {
@@ -299,6 +748,8 @@ impl World<HostStore> {
             assets: AssetStore::default(),
             tick: self.tick,
             entity_len: self.entity_len,
+            entity_allocator: self.entity_allocator.clone(),
+            registered_components: self.registered_components.clone(),
         })
     }
 
@@ -325,6 +776,165 @@ impl World<HostStore> {
     }
 }
 
+impl World<ClientStore> {
+    /// Copies every column back from the device, producing the host-side equivalent of this world.
+    ///
+    /// Assets are only ever stored on the host, so the returned world's [`AssetStore`] is empty.
+    pub fn to_host(&self) -> Result<World<HostStore>, Error> {
+        let archetypes = self
+            .archetypes
+            .iter()
+            .map(|(id, table)| {
+                let entities = table.entity_buffer.to_literal_sync()?;
+                let len = entities.raw_buf().len() / std::mem::size_of::<u64>();
+                let columns = table
+                    .columns
+                    .iter()
+                    .map(|(id, column)| {
+                        let literal = column.buffer.to_literal_sync()?;
+                        let mut buffer =
+                            HostColumn::new(column.metadata.component_type.clone(), *id);
+                        buffer.asset = matches!(
+                            column.metadata.tags.get("asset"),
+                            Some(TagValue::Bool(true))
+                        );
+                        buffer.append_rows(literal.raw_buf(), len)?;
+                        Ok((
+                            *id,
+                            Column {
+                                buffer,
+                                metadata: column.metadata.clone(),
+                            },
+                        ))
+                    })
+                    .collect::<Result<BTreeMap<_, _>, Error>>()?;
+                let mut entity_buffer =
+                    HostColumn::new(ComponentType::u64(), ComponentId::new("entity_id"));
+                entity_buffer.append_rows(entities.raw_buf(), len)?;
+                let table = Table {
+                    columns,
+                    entity_buffer,
+                    entity_map: table.entity_map.clone(),
+                };
+                Ok((*id, table))
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(World {
+            archetypes,
+            component_map: self.component_map.clone(),
+            assets: AssetStore::default(),
+            tick: self.tick,
+            entity_len: self.entity_len,
+            entity_allocator: self.entity_allocator.clone(),
+            registered_components: self.registered_components.clone(),
+        })
+    }
+
+    /// Transfers the world to the host with [`World::to_host`], then converts it to polars.
+    pub fn to_polars(&self) -> Result<PolarsWorld, Error> {
+        self.to_host()?.to_polars()
+    }
+}
+
+/// Builds a [`World`] directly from raw component bytes, without going through [`Archetype`]
+/// bundles.
+///
+/// Every component must be registered with [`RawWorldBuilder::register_component`] before the
+/// world is built, and each entity supplies one row of data per component.
+#[derive(Default)]
+pub struct RawWorldBuilder {
+    metadata: HashMap<ComponentId, Metadata>,
+    archetypes: BTreeMap<ArchetypeId, Vec<(EntityId, HashMap<ComponentId, Vec<u8>>)>>,
+}
+
+impl RawWorldBuilder {
+    pub fn register_component(&mut self, metadata: Metadata) {
+        self.metadata.insert(metadata.component_id, metadata);
+    }
+
+    pub fn add_entity(
+        &mut self,
+        archetype: ArchetypeId,
+        id: EntityId,
+        components: HashMap<ComponentId, Vec<u8>>,
+    ) {
+        self.archetypes
+            .entry(archetype)
+            .or_default()
+            .push((id, components));
+    }
+
+    /// Constructs a table per archetype, checking that every entity in an archetype has the same
+    /// set of components, that each value is exactly one row of its component type, and that no
+    /// component is stored in more than one archetype.
+    pub fn build(self) -> Result<World, Error> {
+        let mut world = World::default();
+        for (archetype_id, entities) in self.archetypes {
+            let Some((_, first)) = entities.first() else {
+                continue;
+            };
+            let mut columns = first
+                .keys()
+                .map(|id| {
+                    let metadata = self
+                        .metadata
+                        .get(id)
+                        .ok_or(Error::ComponentNotFound)?
+                        .clone();
+                    let buffer = HostColumn::new(metadata.component_type.clone(), *id);
+                    Ok((*id, Column { buffer, metadata }))
+                })
+                .collect::<Result<BTreeMap<_, _>, Error>>()?;
+            let mut entity_buffer =
+                HostColumn::new(ComponentType::u64(), ComponentId::new("entity_id"));
+            let mut entity_map = BTreeMap::default();
+            for (entity_id, mut components) in entities {
+                if components.len() != columns.len() {
+                    return Err(Error::InconsistentArchetype(archetype_id));
+                }
+                for (id, column) in &mut columns {
+                    let data = components
+                        .remove(id)
+                        .ok_or(Error::InconsistentArchetype(archetype_id))?;
+                    column.buffer.append_rows(&data, 1)?;
+                }
+                if entity_map.insert(entity_id, entity_buffer.len()).is_some() {
+                    return Err(Error::DuplicateEntity(entity_id));
+                }
+                entity_buffer.push_raw(&entity_id.0.to_ne_bytes());
+                world.entity_len = world.entity_len.max(entity_id.index() as u64 + 1);
+            }
+            for id in columns.keys() {
+                if let Some(existing) = world.component_map.insert(*id, archetype_id) {
+                    return Err(Error::InconsistentArchetype(existing));
+                }
+            }
+            world.archetypes.insert(
+                archetype_id,
+                Table {
+                    columns,
+                    entity_buffer,
+                    entity_map,
+                },
+            );
+        }
+        Ok(world)
+    }
+}
+
+/// Derives the archetype of an entity spawned with [`World::spawn_dynamic`] from its sorted
+/// component ids, hashing them with 128-bit FNV-1a.
+fn dynamic_archetype_id<'a>(ids: impl Iterator<Item = &'a ComponentId>) -> ArchetypeId {
+    const OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+    let hash = ids.fold(OFFSET, |hash, id| {
+        id.0.to_le_bytes().iter().fold(hash, |hash, byte| {
+            (hash ^ *byte as u128).wrapping_mul(PRIME)
+        })
+    });
+    ArchetypeId::new(hash)
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct ArchetypeId(u128);
 
@@ -431,6 +1041,9 @@ impl ColumnRefMut<'_, HostStore> {
 pub trait Archetype {
     fn component_ids() -> Vec<ComponentId>;
     fn component_tys() -> Vec<ComponentType>;
+    fn component_names() -> Vec<Option<&'static str>> {
+        Self::component_ids().iter().map(|_| None).collect()
+    }
     fn insert_into_table(self, table: &mut Table<HostStore>);
 }
 
@@ -447,6 +1060,10 @@ impl<T: Component + 'static> Archetype for T {
     fn component_tys() -> Vec<ComponentType> {
         vec![T::component_type()]
     }
+
+    fn component_names() -> Vec<Option<&'static str>> {
+        vec![T::name()]
+    }
 }
 
 impl<S: WorldStore> Column<S> {
@@ -834,6 +1451,24 @@ impl<A: System, B: System> System for Pipe<A, B> {
     }
 }
 
+/// Pipes a list of systems into a single fused system.
+///
+/// `systems![a, b, c]` is equivalent to `a.pipe(b).pipe(c)`: every system is traced into the
+/// same pipeline, so the whole chain is compiled into one computation and runs in a single
+/// dispatch per tick, while each system still observes the outputs of the ones before it.
+#[macro_export]
+macro_rules! systems {
+    (@pipe $acc:expr) => {
+        $acc
+    };
+    (@pipe $acc:expr, $next:expr $(, $rest:expr)*) => {
+        $crate::systems!(@pipe $crate::IntoSystem::pipe($acc, $next) $(, $rest)*)
+    };
+    ($first:expr $(, $rest:expr)* $(,)?) => {
+        $crate::systems!(@pipe $crate::IntoSystem::into_system($first) $(, $rest)*)
+    };
+}
+
 #[derive(Default)]
 pub struct WorldBuilder<Sys = (), StartupSys = ()> {
     world: World<HostStore>,
@@ -1064,6 +1699,22 @@ impl SharedWorld {
         Ok(())
     }
 
+    /// Copies back every column that isn't already current on the host.
+    fn load_all_columns(&mut self) -> Result<(), Error> {
+        let Some(client_world) = self.client.get() else {
+            return Ok(());
+        };
+        let ids = self.host.component_map.keys().copied().collect::<Vec<_>>();
+        for id in ids {
+            if self.loaded_components.contains(&id) || self.dirty_components.contains(&id) {
+                continue;
+            }
+            self.host.load_column_from_client(id, client_world)?;
+            self.loaded_components.insert(id);
+        }
+        Ok(())
+    }
+
     fn copy_all_columns(&mut self) -> Result<(), Error> {
         let Some(client_world) = self.client.get_mut() else {
             return Ok(());
@@ -1106,6 +1757,25 @@ impl WorldExec {
         Ok(())
     }
 
+    /// Steps the world like [`WorldExec::run`], but leaves the columns in device memory.
+    ///
+    /// Nothing is copied back to the host and no history is recorded, so columns are only
+    /// transferred when read through [`WorldExec::column`] or [`WorldExec::to_polars`].
+    pub fn run_resident(&mut self, client: &Client) -> Result<(), Error> {
+        if let Some(startup_exec) = self.startup_exec.take() {
+            startup_exec.run(&mut self.world, client)?;
+        }
+        self.tick_exec.run(&mut self.world, client)?;
+        self.world.host.tick += 1;
+        Ok(())
+    }
+
+    /// Pulls any columns still on the device back to the host, and converts the world to polars.
+    pub fn to_polars(&mut self) -> Result<PolarsWorld, Error> {
+        self.world.load_all_columns()?;
+        self.world.host.to_polars()
+    }
+
     pub fn time_step(&self) -> Duration {
         self.tick_exec
             .metadata
@@ -1295,6 +1965,13 @@ pub trait ColumnStore {
     fn column(&self, id: ComponentId) -> Result<Self::Column<'_>, Error>;
     fn assets(&self) -> Option<&AssetStore>;
     fn tick(&self) -> u64;
+    /// The ids of every component in the store's component map
+    fn component_ids(&self) -> impl Iterator<Item = ComponentId> + '_;
+    /// Iterates over every component in the store, skipping any whose column can't be read
+    fn components(&self) -> impl Iterator<Item = (ComponentId, Self::Column<'_>)> {
+        self.component_ids()
+            .filter_map(move |id| Some((id, self.column(id).ok()?)))
+    }
 }
 
 impl ColumnStore for WorldExec {
@@ -1316,6 +1993,10 @@ impl ColumnStore for WorldExec {
     fn tick(&self) -> u64 {
         self.world.host.tick
     }
+
+    fn component_ids(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        self.world.host.component_map.keys().copied()
+    }
 }
 
 pub trait ColumnRef {
@@ -1364,12 +2045,16 @@ pub enum Error {
     InvalidQuery,
     #[error("entity not found")]
     EntityNotFound,
+    #[error("entity {0:?} was despawned and its id reused")]
+    StaleEntity(EntityId),
     #[error("io {0}")]
     Io(#[from] std::io::Error),
     #[error("polars {0}")]
     Polars(#[from] ::polars::error::PolarsError),
     #[error("arrow {0}")]
     Arrow(#[from] arrow::error::ArrowError),
+    #[error("parquet {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
     #[error("invalid component id")]
     InvalidComponentId,
     #[error("serde_json {0}")]
@@ -1378,6 +2063,83 @@ pub enum Error {
     Postcard(#[from] postcard::Error),
     #[error("world not found")]
     WorldNotFound,
+    #[error("precision mismatch: expected {expected:?}, found {found:?}")]
+    PrecisionMismatch {
+        expected: conduit::PrimitiveTy,
+        found: conduit::PrimitiveTy,
+    },
+    #[error("element type mismatch: expected {expected:?}, found {found:?}")]
+    ElementTypeMismatch {
+        expected: nox::xla::ElementType,
+        found: nox::xla::ElementType,
+    },
+    #[error("row {0} out of bounds")]
+    RowOutOfBounds(usize),
+    #[error("slice of {len} rows at row {start} is out of bounds for {height} rows")]
+    SliceOutOfBounds {
+        start: usize,
+        len: usize,
+        height: usize,
+    },
+    #[error("expected a host value, found a traced expression")]
+    NonConstant,
+    #[error("unsupported metadata version {0}")]
+    UnsupportedVersion(u32),
+    #[error("unknown assets.bin codec {0:?}")]
+    UnknownAssetsCodec(Option<u8>),
+    #[error("malformed world bytes")]
+    MalformedWorldBytes,
+    #[error("tick index {0} out of bounds")]
+    TickOutOfBounds(usize),
+    #[error("shape too large")]
+    ShapeTooLarge,
+    #[error("shape mismatch: expected {expected:?}, found {found:?}")]
+    ShapeMismatch {
+        expected: Vec<usize>,
+        found: Vec<usize>,
+    },
+    #[error("duplicate entity {0:?}")]
+    DuplicateEntity(EntityId),
+    #[error("component {0:?} was given more than once")]
+    DuplicateComponent(ComponentId),
+    #[error("an entity must have at least one component")]
+    NoComponents,
+    #[error("archetype schema mismatch")]
+    SchemaMismatch,
+    #[error("component {0:?} belongs to an archetype that was not loaded")]
+    DanglingComponent(ComponentId),
+    #[error("checkpoint was written by nox-ecs {found}, which is newer than {current}")]
+    NewerCrateVersion { found: String, current: String },
+    #[error("entity map of archetype {0:?} does not match its entity_id column")]
+    EntityMapMismatch(ArchetypeId),
+    #[error("entities in archetype {0:?} have different component sets")]
+    InconsistentArchetype(ArchetypeId),
+    #[error("component id {id:?} is shared by {existing:?} and {name:?}")]
+    ComponentIdCollision {
+        id: ComponentId,
+        existing: String,
+        name: String,
+    },
+    #[error("component {0:?} has nulls inside a list or array")]
+    NullsUnsupported(ComponentId),
+    #[error(
+        "decimal component {0:?} must be a scalar i64 column of non-null values that fit in an i64"
+    )]
+    InvalidDecimal(ComponentId),
+    #[error("adaptive step bounds must satisfy 0 < min_dt <= max_dt and tol > 0, found {0:?}")]
+    InvalidAdaptiveConfig(AdaptiveConfig),
+    #[error("adaptive step error estimate is not finite at the minimum step of {0}")]
+    NonFiniteStep(f64),
+    #[error("time step must be positive and finite, found {0}")]
+    InvalidTimeStep(f64),
+    #[error("delta was written against tick {expected}, but the world is at tick {found}")]
+    DeltaBaselineMismatch { expected: u64, found: u64 },
+    #[error("failed to write archetype {failed:?} after writing {written:?}: {source}")]
+    PartialWrite {
+        written: Vec<ArchetypeId>,
+        failed: ArchetypeId,
+        source: Box<Error>,
+    },
 }
 
 impl From<nox::xla::Error> for Error {
@@ -1457,6 +2219,685 @@ mod tests {
         world.spawn(body);
     }
 
+    #[test]
+    fn test_raw_world_builder() {
+        #[derive(Component)]
+        struct A(Scalar<f64>);
+
+        #[derive(Component)]
+        struct B(Scalar<f64>);
+
+        #[derive(Archetype)]
+        struct Body {
+            a: A,
+            b: B,
+        }
+
+        let mut world = World::default();
+        world.spawn(Body {
+            a: A(Scalar::host(1.0)),
+            b: B(Scalar::host(2.0)),
+        });
+        world.spawn(Body {
+            a: A(Scalar::host(3.0)),
+            b: B(Scalar::host(4.0)),
+        });
+
+        let archetype_id = ArchetypeId::of::<Body>();
+        let mut builder = RawWorldBuilder::default();
+        for id in [A::component_id(), B::component_id()] {
+            builder.register_component(world.component_metadata(id).unwrap().clone());
+        }
+        for (id, a, b) in [(0, 1.0f64, 2.0f64), (1, 3.0, 4.0)] {
+            let components = HashMap::from([
+                (A::component_id(), a.to_ne_bytes().to_vec()),
+                (B::component_id(), b.to_ne_bytes().to_vec()),
+            ]);
+            builder.add_entity(archetype_id, EntityId(id), components);
+        }
+        let raw = builder.build().unwrap();
+
+        assert_eq!(raw.entity_len, world.entity_len);
+        assert_eq!(raw.component_map, world.component_map);
+        let (raw_table, table) = (
+            &raw.archetypes[&archetype_id],
+            &world.archetypes[&archetype_id],
+        );
+        assert_eq!(raw_table.entity_map, table.entity_map);
+        assert_eq!(raw_table.entity_buffer, table.entity_buffer);
+        for (id, column) in &table.columns {
+            assert_eq!(raw_table.columns[id].buffer, column.buffer);
+        }
+
+        let mut builder = RawWorldBuilder::default();
+        builder.register_component(world.component_metadata(A::component_id()).unwrap().clone());
+        builder.add_entity(
+            archetype_id,
+            EntityId(0),
+            HashMap::from([(A::component_id(), 1.0f64.to_ne_bytes().to_vec())]),
+        );
+        builder.add_entity(archetype_id, EntityId(1), HashMap::new());
+        assert!(matches!(
+            builder.build(),
+            Err(Error::InconsistentArchetype(id)) if id == archetype_id
+        ));
+
+        // the same component can't be stored in two archetypes
+        let mut builder = RawWorldBuilder::default();
+        builder.register_component(world.component_metadata(A::component_id()).unwrap().clone());
+        for (archetype, entity) in [(1, 0), (2, 1)] {
+            builder.add_entity(
+                ArchetypeId::new(archetype),
+                EntityId(entity),
+                HashMap::from([(A::component_id(), 1.0f64.to_ne_bytes().to_vec())]),
+            );
+        }
+        assert!(matches!(
+            builder.build(),
+            Err(Error::InconsistentArchetype(id)) if id == ArchetypeId::new(1)
+        ));
+    }
+
+    #[test]
+    fn test_spawn_dynamic() {
+        use ::polars::prelude::DataType;
+        use conduit::PrimitiveTy;
+
+        let mut world = World::default();
+        let accel = world.register_component(
+            Metadata::builder()
+                .name("accel")
+                .tensor(PrimitiveTy::F64, [3])
+                .build()
+                .unwrap(),
+        );
+        let mode = world.register_component(
+            Metadata::builder()
+                .name("mode")
+                .scalar(PrimitiveTy::U32)
+                .build()
+                .unwrap(),
+        );
+        let a = [1.0f64, 2.0, 3.0];
+        let b = [4.0f64, 5.0, 6.0];
+        let first = world.spawn_dynamic(&[
+            (accel, bytemuck::cast_slice(&a)),
+            (mode, &7u32.to_ne_bytes()),
+        ]);
+        let second = world.spawn_dynamic(&[
+            (mode, &8u32.to_ne_bytes()),
+            (accel, bytemuck::cast_slice(&b)),
+        ]);
+        assert_ne!(first, second);
+        assert_eq!(world.component_map[&accel], world.component_map[&mode]);
+
+        let polars = world.to_polars().unwrap();
+        let df = &polars.archetypes[&world.component_map[&accel]];
+        assert_eq!(df.height(), 2);
+        let accel_series = df.column(&accel.0.to_string()).unwrap();
+        assert_eq!(
+            accel_series.dtype(),
+            &DataType::Array(Box::new(DataType::Float64), 3)
+        );
+        let mode_series = df.column(&mode.0.to_string()).unwrap();
+        assert_eq!(mode_series.dtype(), &DataType::UInt32);
+        let column = world.column_by_id(accel).unwrap();
+        assert_eq!(
+            column.typed_buf::<f64>().unwrap(),
+            &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]
+        );
+
+        assert!(matches!(
+            world.try_spawn_dynamic(&[(accel, bytemuck::cast_slice(&a))]),
+            Err(Error::InconsistentArchetype(_))
+        ));
+        assert!(matches!(
+            world.try_spawn_dynamic(&[(accel, &[0u8; 4]), (mode, &7u32.to_ne_bytes())]),
+            Err(Error::ValueSizeMismatch)
+        ));
+        assert!(matches!(
+            world.try_spawn_dynamic(&[(mode, &[0u8; 4]), (mode, &[0u8; 4])]),
+            Err(Error::DuplicateComponent(id)) if id == mode
+        ));
+        assert!(matches!(
+            world.try_spawn_dynamic(&[]),
+            Err(Error::NoComponents)
+        ));
+    }
+
+    #[test]
+    fn test_spawn_dynamic_into_bundle_archetype() {
+        #[derive(Component)]
+        struct A(Scalar<f64>);
+
+        let mut world = World::default();
+        world.spawn(A(Scalar::host(1.0)));
+        let archetype_id = world.component_map[&A::component_id()];
+        let entity = world
+            .try_spawn_dynamic(&[(A::component_id(), &2.0f64.to_ne_bytes())])
+            .unwrap();
+        assert_eq!(world.archetypes.len(), 1);
+        let table = &world.archetypes[&archetype_id];
+        assert_eq!(table.entity_map[&entity], 1);
+        assert_eq!(
+            table.columns[&A::component_id()]
+                .buffer
+                .typed_buf::<f64>()
+                .unwrap(),
+            &[1.0, 2.0]
+        );
+    }
+
+    #[test]
+    fn test_compact() {
+        #[derive(Component)]
+        struct A(Scalar<f64>);
+
+        let mut world = World::default();
+        for i in 0..10 {
+            world.spawn(A(Scalar::host(i as f64)));
+        }
+        let archetype_id = world.component_map[&A::component_id()];
+        let table = world.archetypes.get_mut(&archetype_id).unwrap();
+        // remove odd entities from the map, leaving their rows behind
+        for i in (1..10).step_by(2) {
+            table.entity_map.remove(&EntityId(i));
+        }
+        let capacity = table.columns[&A::component_id()].buffer.buf.capacity();
+
+        world.compact_all();
+        let table = &world.archetypes[&archetype_id];
+        let column = &table.columns[&A::component_id()].buffer;
+        assert_eq!(column.len(), 5);
+        assert!(column.buf.capacity() < capacity);
+        // shrinking only promises a capacity of at least the length
+        assert_eq!(column.buf.len(), 5 * 8);
+        assert!(column.buf.capacity() >= column.buf.len());
+        assert_eq!(table.entity_buffer.len(), 5);
+        assert_eq!(
+            table.entity_buffer.typed_buf::<u64>().unwrap(),
+            &[0, 2, 4, 6, 8]
+        );
+        for i in (0..10).step_by(2) {
+            let row = table.entity_map[&EntityId(i)];
+            assert_eq!(row, i as usize / 2);
+            assert_eq!(column.typed_buf::<f64>().unwrap()[row], i as f64);
+        }
+    }
+
+    #[test]
+    fn test_despawn() {
+        #[derive(Component)]
+        struct A(Scalar<f64>);
+
+        #[derive(Component)]
+        struct B(Scalar<f64>);
+
+        #[derive(Archetype)]
+        struct Body {
+            a: A,
+            b: B,
+        }
+
+        let mut world = World::default();
+        let ids: Vec<EntityId> = (0..3)
+            .map(|i| {
+                world
+                    .spawn(Body {
+                        a: A::host(i as f64),
+                        b: B::host(10.0 * i as f64),
+                    })
+                    .id()
+            })
+            .collect();
+
+        world.despawn(ids[1]).unwrap();
+        assert!(matches!(world.despawn(ids[1]), Err(Error::EntityNotFound)));
+        assert_eq!(world.entity_len, 3);
+
+        let table = &world.archetypes[&ArchetypeId::of::<Body>()];
+        assert_eq!(table.entity_map, BTreeMap::from([(ids[0], 0), (ids[2], 1)]));
+        assert_eq!(table.entity_buffer.typed_buf::<u64>().unwrap(), &[0, 2]);
+        let a = &table.columns[&A::component_id()].buffer;
+        let b = &table.columns[&B::component_id()].buffer;
+        assert_eq!(a.len(), 2);
+        assert_eq!(a.typed_buf::<f64>().unwrap(), &[0.0, 2.0]);
+        assert_eq!(b.typed_buf::<f64>().unwrap(), &[0.0, 20.0]);
+
+        world.despawn(ids[2]).unwrap();
+        assert_eq!(world.entity_len, 2);
+    }
+
+    #[test]
+    fn test_clear() {
+        #[derive(Component)]
+        struct A(Scalar<f64>);
+
+        #[derive(Component)]
+        struct B(Scalar<f64>);
+
+        #[derive(Archetype)]
+        struct Body {
+            a: A,
+            b: B,
+        }
+
+        let mut world = World::default();
+        let pbr = world.insert_asset(Pbr::Url("body".to_string()));
+        for i in 0..3 {
+            world.spawn(Body {
+                a: A::host(i as f64),
+                b: B::host(10.0 * i as f64),
+            });
+        }
+        world.tick = 5;
+        world.clear();
+        assert_eq!(world.entity_len, 0);
+        assert_eq!(world.tick, 0);
+        assert_eq!(world.archetypes.len(), 1);
+        assert_eq!(
+            world.component_map[&A::component_id()],
+            ArchetypeId::of::<Body>()
+        );
+        assert!(world.assets.value(pbr).is_some());
+        let table = &world.archetypes[&ArchetypeId::of::<Body>()];
+        assert!(table.entity_map.is_empty() && table.entity_buffer.is_empty());
+        assert!(table.columns.values().all(|c| c.buffer.is_empty()));
+
+        let id = world
+            .spawn(Body {
+                a: A::host(7.0),
+                b: B::host(70.0),
+            })
+            .id();
+        assert_eq!(id, EntityId(0));
+        assert_eq!(world.archetypes.len(), 1);
+        let table = &world.archetypes[&ArchetypeId::of::<Body>()];
+        assert_eq!(table.entity_map, BTreeMap::from([(id, 0)]));
+        let a = &table.columns[&A::component_id()].buffer;
+        assert_eq!(a.typed_buf::<f64>().unwrap(), &[7.0]);
+    }
+
+    #[test]
+    fn test_spawn_batch() {
+        #[derive(Component)]
+        struct A(Scalar<f64>);
+
+        #[derive(Component)]
+        struct B(Scalar<f64>);
+
+        #[derive(Component)]
+        struct C(Scalar<f64>);
+
+        #[derive(Archetype)]
+        struct Body {
+            a: A,
+            b: B,
+        }
+
+        let body = |i: usize| Body {
+            a: A::host(i as f64),
+            b: B::host(-(i as f64)),
+        };
+
+        let mut looped = World::default();
+        looped.spawn(C::host(-1.0));
+        let looped_ids: Vec<EntityId> = (0..1000).map(|i| looped.spawn(body(i)).id()).collect();
+
+        let mut batched = World::default();
+        batched.spawn(C::host(-1.0));
+        let batched_ids = batched.spawn_batch((0..1000).map(body)).unwrap();
+
+        assert_eq!(batched_ids, looped_ids);
+        assert_eq!(batched.entity_len, looped.entity_len);
+        let archetype_id = ArchetypeId::of::<Body>();
+        let (batched, looped) = (
+            &batched.archetypes[&archetype_id],
+            &looped.archetypes[&archetype_id],
+        );
+        assert_eq!(batched.entity_map, looped.entity_map);
+        assert_eq!(batched.entity_buffer, looped.entity_buffer);
+        for (id, column) in &looped.columns {
+            assert_eq!(batched.columns[id].buffer.len(), 1000);
+            assert_eq!(batched.columns[id].buffer, column.buffer);
+        }
+    }
+
+    #[test]
+    fn test_component_id_collision() {
+        #[derive(Component)]
+        struct A(Scalar<f64>);
+
+        // reports `A`'s id under another name, as if "impostor" hashed to the same value
+        struct Impostor(Scalar<f64>);
+
+        impl Archetype for Impostor {
+            fn component_ids() -> Vec<ComponentId> {
+                vec![A::component_id()]
+            }
+
+            fn component_tys() -> Vec<ComponentType> {
+                vec![A::component_type()]
+            }
+
+            fn component_names() -> Vec<Option<&'static str>> {
+                vec![Some("impostor")]
+            }
+
+            fn insert_into_table(self, table: &mut Table<HostStore>) {
+                A(self.0).insert_into_table(table);
+            }
+        }
+
+        let mut world = World::default();
+        world.spawn(A::host(1.0));
+        let err = world.try_spawn(Impostor(A::host(2.0).0)).err().unwrap();
+        assert!(matches!(
+            err,
+            Error::ComponentIdCollision { id, ref existing, ref name }
+                if id == A::component_id() && existing == "a" && name == "impostor"
+        ));
+        assert_eq!(world.entity_len, 1);
+        assert_eq!(world.archetypes.len(), 1);
+        assert_eq!(world.column::<A>().unwrap().column.buffer.len(), 1);
+        world.spawn(A::host(3.0));
+        assert_eq!(world.column::<A>().unwrap().column.buffer.len(), 2);
+    }
+
+    #[test]
+    fn test_column_get_set() {
+        #[derive(Archetype)]
+        struct Body {
+            pos: WorldPos,
+        }
+
+        let mut world = World::default();
+        let ids: Vec<EntityId> = (0..2)
+            .map(|i| {
+                let inner = nox::nalgebra::SVector::<f64, 7>::repeat(i as f64).into();
+                world
+                    .spawn(Body {
+                        pos: WorldPos(nox::SpatialTransform { inner }),
+                    })
+                    .id()
+            })
+            .collect();
+
+        let table = world
+            .archetypes
+            .get_mut(&ArchetypeId::of::<Body>())
+            .unwrap();
+        let row = table.row_of(ids[1]).unwrap();
+        assert_eq!(row, 1);
+        assert_eq!(table.row_of(EntityId(2)), None);
+
+        let column = &mut table
+            .columns
+            .get_mut(&WorldPos::component_id())
+            .unwrap()
+            .buffer;
+        let pos = [0.0, 0.0, 0.0, 1.0, 4.0, 5.0, 6.0];
+        column.set(row, &pos).unwrap();
+        assert_eq!(column.get::<f64>(row), Some(&pos[..]));
+        assert_eq!(column.get::<f64>(0), Some(&[0.0; 7][..]));
+        assert_eq!(column.get::<f64>(2), None);
+        assert_eq!(column.get::<f32>(row), None);
+
+        assert!(matches!(
+            column.set(row, &[0.0f32; 7]),
+            Err(Error::ElementTypeMismatch { .. })
+        ));
+        assert!(matches!(
+            column.set(row, &[0.0f64; 3]),
+            Err(Error::ValueSizeMismatch)
+        ));
+        assert!(matches!(column.set(2, &pos), Err(Error::RowOutOfBounds(2))));
+    }
+
+    #[test]
+    fn test_cross_archetype_query() {
+        use six_dof::WorldVel;
+
+        let mut world = World::default();
+        let ids: Vec<EntityId> = (0..4)
+            .map(|i| {
+                let inner = nox::nalgebra::SVector::<f64, 7>::repeat(i as f64).into();
+                world.spawn(WorldPos(nox::SpatialTransform { inner })).id()
+            })
+            .collect();
+        // only the even bodies are moving
+        for &id in ids.iter().step_by(2) {
+            let inner = nox::nalgebra::SVector::<f64, 6>::repeat(10.0 + id.0 as f64).into();
+            world.spawn_with_id(WorldVel(nox::SpatialMotion { inner }), id);
+        }
+
+        let values = |value: &ComponentValue<'_>| match value {
+            ComponentValue::F64(array) => array.iter().copied().collect::<Vec<f64>>(),
+            _ => panic!("expected f64 component"),
+        };
+        let joined: Vec<_> = world
+            .query(&[WorldVel::component_id(), WorldPos::component_id()])
+            .map(|(id, row)| (id, row.iter().map(values).collect::<Vec<_>>()))
+            .collect();
+        assert_eq!(
+            joined,
+            vec![
+                (ids[0], vec![vec![10.0; 6], vec![0.0; 7]]),
+                (ids[2], vec![vec![12.0; 6], vec![2.0; 7]]),
+            ]
+        );
+
+        assert_eq!(
+            world
+                .query(&[WorldPos::component_id(), ComponentId::new("missing")])
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_entity_reuse() {
+        #[derive(Component)]
+        struct A(Scalar<f64>);
+
+        let mut world = World::default();
+        let ids: Vec<EntityId> = (0..3)
+            .map(|i| world.spawn(A::host(i as f64)).id())
+            .collect();
+        world.despawn(ids[1]).unwrap();
+        let reused = world.spawn(A::host(10.0)).id();
+        assert_eq!(reused.index(), ids[1].index());
+        assert_eq!(reused.generation(), 1);
+        assert_eq!(world.entity_len, 3);
+
+        assert!(matches!(
+            world.despawn(ids[1]),
+            Err(Error::StaleEntity(id)) if id == ids[1]
+        ));
+        assert!(matches!(
+            world.add_component(ids[1], A::host(1.0)),
+            Err(Error::StaleEntity(_))
+        ));
+        let fresh = world.spawn(A::host(3.0)).id();
+        assert_eq!(fresh, EntityId(3));
+
+        world.despawn(reused).unwrap();
+        assert!(matches!(world.despawn(reused), Err(Error::EntityNotFound)));
+        assert!(matches!(world.despawn(ids[1]), Err(Error::StaleEntity(_))));
+    }
+
+    #[test]
+    fn test_entity_reuse_raw_round_trip() {
+        #[derive(Component)]
+        struct A(Scalar<f64>);
+
+        let mut world = World::default();
+        let ids: Vec<EntityId> = (0..2)
+            .map(|i| world.spawn(A::host(i as f64)).id())
+            .collect();
+        world.despawn(ids[0]).unwrap();
+        let reused = world.spawn(A::host(10.0)).id();
+        assert_eq!(reused.generation(), 1);
+
+        let mut builder = RawWorldBuilder::default();
+        builder.register_component(world.component_metadata(A::component_id()).unwrap().clone());
+        for (entity, value) in [(reused, 10.0f64), (ids[1], 1.0)] {
+            let components = HashMap::from([(A::component_id(), value.to_ne_bytes().to_vec())]);
+            builder.add_entity(ArchetypeId::of::<A>(), entity, components);
+        }
+        let mut raw = builder.build().unwrap();
+        assert_eq!(raw.entity_len, 2);
+        let fresh = raw.spawn(A::host(2.0)).id();
+        assert_eq!(fresh, EntityId(2));
+
+        // respawning a freed id by hand takes its index off the free list
+        world.despawn(ids[1]).unwrap();
+        world.spawn_with_id(A::host(1.0), ids[1]);
+        let next = world.spawn(A::host(3.0)).id();
+        assert_eq!(next, EntityId(2));
+    }
+
+    #[test]
+    fn test_add_component() {
+        #[derive(Component)]
+        struct X(Scalar<f64>);
+
+        #[derive(Component)]
+        struct V(Scalar<f64>);
+
+        #[derive(Component)]
+        struct Drag(Scalar<f64>);
+
+        #[derive(Archetype)]
+        struct Body {
+            x: X,
+            v: V,
+        }
+
+        let mut world = World::default();
+        let a = world
+            .spawn(Body {
+                x: X::host(1.0),
+                v: V::host(2.0),
+            })
+            .id();
+        let b = world
+            .spawn(Body {
+                x: X::host(3.0),
+                v: V::host(4.0),
+            })
+            .id();
+        world.add_component(b, Drag::host(0.5)).unwrap();
+        assert_eq!(world.entity_len, 2);
+
+        let values = |value: &ComponentValue<'_>| match value {
+            ComponentValue::F64(array) => array.iter().copied().collect::<Vec<f64>>(),
+            _ => panic!("expected f64 component"),
+        };
+        let joined: Vec<_> = world
+            .query(&[X::component_id(), V::component_id(), Drag::component_id()])
+            .map(|(id, row)| (id, row.iter().map(values).collect::<Vec<_>>()))
+            .collect();
+        assert_eq!(joined, vec![(b, vec![vec![3.0], vec![4.0], vec![0.5]])]);
+        assert_eq!(
+            world.component_map[&Drag::component_id()],
+            ArchetypeId::of::<Drag>()
+        );
+        assert_eq!(world.query(&[X::component_id()]).count(), 2);
+
+        assert!(matches!(
+            world.add_component(b, Drag::host(1.0)),
+            Err(Error::DuplicateEntity(id)) if id == b
+        ));
+        assert!(matches!(
+            world.add_component(EntityId(7), Drag::host(1.0)),
+            Err(Error::EntityNotFound)
+        ));
+        assert!(matches!(
+            world.add_component(a, V::host(1.0)),
+            Err(Error::InconsistentArchetype(_))
+        ));
+    }
+
+    #[test]
+    fn test_run_resident() {
+        #[derive(Component)]
+        struct X(Scalar<f64>);
+
+        #[derive(Component)]
+        struct V(Scalar<f64>);
+
+        #[derive(Archetype)]
+        struct Body {
+            x: X,
+            v: V,
+        }
+
+        fn step(q: Query<(X, V)>) -> Query<X> {
+            q.map(|x: X, v: V| X(x.0.clone() + v.0 * 0.1 - x.0 * 0.01))
+                .unwrap()
+        }
+
+        let mut world = World::default();
+        for i in 0..4 {
+            world.spawn(Body {
+                x: X::host(i as f64),
+                v: V::host(1.0 - i as f64),
+            });
+        }
+        let client = nox::Client::cpu().unwrap();
+        let mut host = world.clone().builder().tick_pipeline(step).build().unwrap();
+        let mut resident = world.builder().tick_pipeline(step).build().unwrap();
+        for _ in 0..50 {
+            host.run(&client).unwrap();
+            resident.run_resident(&client).unwrap();
+        }
+        assert!(resident.world.loaded_components.is_empty());
+        let expected = host.to_polars().unwrap();
+        let actual = resident.to_polars().unwrap();
+        assert_eq!(expected.archetypes, actual.archetypes);
+        assert_eq!(resident.world.host.tick, 50);
+    }
+
+    #[test]
+    fn test_client_world_to_polars() {
+        #[derive(Component)]
+        struct X(Scalar<f64>);
+
+        #[derive(Component)]
+        struct V(Scalar<f64>);
+
+        #[derive(Archetype)]
+        struct Body {
+            x: X,
+            v: V,
+        }
+
+        fn step(q: Query<(X, V)>) -> Query<X> {
+            q.map(|x: X, v: V| X(x.0.clone() + v.0 * 0.1 - x.0 * 0.01))
+                .unwrap()
+        }
+
+        let mut world = World::default();
+        for i in 0..4 {
+            world.spawn(Body {
+                x: X::host(i as f64),
+                v: V::host(1.0 - i as f64),
+            });
+        }
+        let client = nox::Client::cpu().unwrap();
+        let mut host = world.clone().builder().tick_pipeline(step).build().unwrap();
+        let mut device = world.builder().tick_pipeline(step).build().unwrap();
+        for _ in 0..20 {
+            host.run(&client).unwrap();
+            device.run_resident(&client).unwrap();
+        }
+        let client_world = device.world.client.get().unwrap();
+        let expected = host.to_polars().unwrap();
+        let actual = client_world.to_polars().unwrap();
+        assert_eq!(expected.archetypes, actual.archetypes);
+    }
+
     #[test]
     fn test_startup() {
         #[derive(Component)]
@@ -1484,6 +2925,74 @@ mod tests {
         assert_eq!(c.typed_buf::<f64>().unwrap(), &[4.0]);
     }
 
+    #[test]
+    fn test_fused_systems() {
+        #[derive(Component)]
+        struct X(Scalar<f64>);
+
+        #[derive(Component)]
+        struct V(Scalar<f64>);
+
+        #[derive(Archetype)]
+        struct Body {
+            x: X,
+            v: V,
+        }
+
+        fn gravity(v: ComponentArray<V>) -> ComponentArray<V> {
+            v.map(|v: V| V(v.0 + -9.8)).unwrap()
+        }
+
+        fn drag(v: ComponentArray<V>) -> ComponentArray<V> {
+            v.map(|v: V| V(v.0 * 0.5)).unwrap()
+        }
+
+        fn integrate(q: Query<(X, V)>) -> Query<X> {
+            q.map(|x: X, v: V| X(x.0 + v.0)).unwrap()
+        }
+
+        let mut world = World::default();
+        world.spawn(Body {
+            x: X(Scalar::host(1.0)),
+            v: V(Scalar::host(2.0)),
+        });
+        world.spawn(Body {
+            x: X(Scalar::host(-3.0)),
+            v: V(Scalar::host(0.5)),
+        });
+        let client = nox::Client::cpu().unwrap();
+
+        let mut fused = world
+            .clone()
+            .builder()
+            .tick_pipeline(systems![gravity, drag, integrate])
+            .build()
+            .unwrap();
+        fused.run(&client).unwrap();
+
+        fn run_system<M, A, R>(
+            world: World,
+            sys: impl IntoSystem<M, A, R>,
+            client: &nox::Client,
+        ) -> World {
+            let mut exec = world.builder().tick_pipeline(sys).build().unwrap();
+            exec.run(client).unwrap();
+            std::mem::take(&mut exec.world.host)
+        }
+        let sequential = run_system(world, gravity, &client);
+        let sequential = run_system(sequential, drag, &client);
+        let sequential = run_system(sequential, integrate, &client);
+
+        for id in [X::component_id(), V::component_id()] {
+            let fused = fused.column(id).unwrap();
+            let sequential = sequential.column_by_id(id).unwrap();
+            assert_eq!(
+                fused.typed_buf::<f64>().unwrap(),
+                sequential.typed_buf::<f64>().unwrap()
+            );
+        }
+    }
+
     #[test]
     fn test_write_read() {
         #[derive(Component)]
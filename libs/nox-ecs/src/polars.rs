@@ -1,29 +1,54 @@
 use arrow::array::{ArrayData, LargeListArray, ListArray, MapArray, StructArray, UnionArray};
 use arrow::datatypes::{Field, Schema};
 use arrow::ffi::FFI_ArrowArray;
-use arrow::record_batch::RecordBatch;
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+use bytemuck::Pod;
 use conduit::{ComponentId, ComponentType, EntityId, PrimitiveTy};
-use parquet::arrow::ArrowWriter;
-use parquet::file::properties::WriterProperties;
-use polars::prelude::SerReader;
+use nox::xla::ArrayElement;
+use parquet::arrow::arrow_reader::{ArrowReaderMetadata, ParquetRecordBatchReaderBuilder};
+use parquet::arrow::{ArrowWriter, ProjectionMask};
+use parquet::basic::{Compression, GzipLevel, ZstdLevel};
+use parquet::file::properties::{EnabledStatistics, WriterProperties};
+use polars::prelude::{CsvWriter, IntoSeries, NamedFrom, SerReader, SerWriter};
 use polars::{frame::DataFrame, series::Series};
 use polars_arrow::{
-    array::{Array, PrimitiveArray},
+    array::{Array, BooleanArray, PrimitiveArray},
     datatypes::ArrowDataType,
 };
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::io::Write;
 use std::marker::PhantomData;
-use std::sync::Arc;
-use std::{collections::BTreeMap, fs::File, path::Path};
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    path::{Path, PathBuf},
+};
 
 use crate::{
-    ArchetypeId, AssetStore, Column, ColumnRef, ColumnStore, Error, HostColumn, HostStore, Table,
-    World, WorldStore,
+    ArchetypeId, AssetStore, Column, ColumnRef, ColumnStore, EntityAllocator, Error, HostColumn,
+    HostColumnRef, HostStore, Table, World, WorldStore,
 };
 
-const ENTITY_ID_COMPONENT: ComponentId = ComponentId::new("entity_id");
+pub(crate) const ENTITY_ID_COMPONENT: ComponentId = ComponentId::new("entity_id");
+pub const DEFAULT_ROW_GROUP_SIZE: usize = 64 * 1024;
+const SHAPE_METADATA_KEY: &str = "ARROW:extension:metadata";
+const COMPONENT_ID_METADATA_KEY: &str = "elodin:component_id";
+const UNIT_METADATA_KEY: &str = "elodin:unit";
+const DESCRIPTION_METADATA_KEY: &str = "elodin:description";
+const DECIMAL_PRECISION_METADATA_KEY: &str = "elodin:decimal_precision";
+const DECIMAL_SCALE_METADATA_KEY: &str = "elodin:decimal_scale";
+const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// The layout version of `metadata.json` written by this crate, see [`Metadata::upgrade`].
+const METADATA_VERSION: u32 = 1;
+/// Marks an `assets.bin` with a codec header; older files are a bare postcard stream.
+const ASSETS_MAGIC: &[u8] = b"noxasset";
+/// Starts a blob written by [`PolarsWorld::to_ipc_bytes`].
+const WORLD_BYTES_MAGIC: &[u8] = b"noxworld";
 
 #[derive(Debug, Clone)]
 pub struct PolarsWorld {
@@ -35,15 +60,104 @@ pub struct PolarsWorld {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Metadata {
     pub archetypes: BTreeMap<ArchetypeId, ArchetypeMetadata>,
-    pub component_map: HashMap<ComponentId, ArchetypeId>,
+    pub component_map: BTreeMap<ComponentId, ArchetypeId>,
     pub tick: u64,
     pub entity_len: u64,
+    /// The ticks recorded by [`PolarsWorld::append_tick_to_dir`], in ascending order
+    #[serde(default)]
+    pub ticks: Vec<u64>,
+    /// The version of nox-ecs that wrote the checkpoint, missing from checkpoints that predate it
+    #[serde(default)]
+    pub crate_version: Option<String>,
+    /// The layout version of `metadata.json`, missing (and so 0) from checkpoints that predate it
+    #[serde(default)]
+    pub version: u32,
+}
+
+impl Metadata {
+    /// Fills in the fields that `version` predates, bringing the metadata up to [`METADATA_VERSION`].
+    fn upgrade(&mut self) -> Result<(), Error> {
+        match self.version {
+            0 => {
+                for archetype in self.archetypes.values_mut() {
+                    if archetype.column_index.is_empty() {
+                        archetype.rebuild_column_index();
+                    }
+                }
+            }
+            METADATA_VERSION => {}
+            version => return Err(Error::UnsupportedVersion(version)),
+        }
+        self.version = METADATA_VERSION;
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ArchetypeMetadata {
     pub columns: Vec<ColumnMetadata>,
     pub entity_map: BTreeMap<EntityId, usize>,
+    /// The position of each component's series (including the entity id) in the archetype's `DataFrame`
+    #[serde(default)]
+    pub column_index: BTreeMap<ComponentId, usize>,
+}
+
+impl ArchetypeMetadata {
+    /// Rebuilds `column_index` from the order of `columns`, followed by the entity id series,
+    /// which is the layout produced by `Table::to_polars` before the index was stored.
+    fn rebuild_column_index(&mut self) {
+        self.column_index = self
+            .columns
+            .iter()
+            .map(|c| c.metadata.component_id)
+            .chain(std::iter::once(ENTITY_ID_COMPONENT))
+            .enumerate()
+            .map(|(index, id)| (id, index))
+            .collect();
+    }
+
+    /// Looks up a component's column through `column_index`, whose positions match `columns`.
+    fn column(&self, id: ComponentId) -> Option<&ColumnMetadata> {
+        let column = self.columns.get(*self.column_index.get(&id)?)?;
+        (column.metadata.component_id == id).then_some(column)
+    }
+
+    /// Replaces `entity_map` with the row of each id in the `entity_id` series of `df`.
+    ///
+    /// The stored map is only trusted for the set of entities it contains, so a disagreement
+    /// in membership fails with [`Error::EntityMapMismatch`] rather than being papered over.
+    fn rebuild_entity_map(&mut self, id: ArchetypeId, df: &DataFrame) -> Result<(), Error> {
+        let entity_ids = self.series(df, ENTITY_ID_COMPONENT)?.u64()?;
+        let mut entity_map = BTreeMap::new();
+        for (row, entity_id) in entity_ids.into_iter().enumerate() {
+            let entity_id = EntityId(entity_id.ok_or(Error::EntityMapMismatch(id))?);
+            if entity_map.insert(entity_id, row).is_some() {
+                return Err(Error::DuplicateEntity(entity_id));
+            }
+        }
+        if !entity_map.keys().eq(self.entity_map.keys()) {
+            return Err(Error::EntityMapMismatch(id));
+        }
+        self.entity_map = entity_map;
+        Ok(())
+    }
+
+    fn host_column(&self, df: &DataFrame, column: &ColumnMetadata) -> Result<HostColumn, Error> {
+        let id = column.metadata.component_id;
+        HostColumn::from_series_with_id(
+            self.series(df, id)?,
+            id,
+            column.metadata.component_type.clone(),
+            column.asset,
+        )
+    }
+
+    fn series<'a>(&self, df: &'a DataFrame, id: ComponentId) -> Result<&'a Series, Error> {
+        self.column_index
+            .get(&id)
+            .and_then(|index| df.select_at_idx(*index))
+            .ok_or(Error::ComponentNotFound)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -52,674 +166,5017 @@ pub struct ColumnMetadata {
     pub asset: bool,
 }
 
+/// Options controlling how a [`PolarsWorld`] is encoded to parquet.
+#[derive(Debug, Clone)]
+pub struct WriteOptions {
+    /// The level at which column statistics (min / max / null count) are computed.
+    pub statistics: EnabledStatistics,
+    /// Physically order each archetype's rows by ascending entity id before writing, so that
+    /// range filters on entity id read contiguous row groups.
+    pub sort_by_entity_id: bool,
+    /// The number of rows encoded per batch by [`PolarsWorld::write_to_dir_streaming`].
+    pub row_group_size: usize,
+    /// Store the `entity_id` column as a dictionary-encoded array, which is smaller when many
+    /// archetypes share overlapping entity id ranges. It is decoded back to `u64` on read.
+    pub dictionary_entity_ids: bool,
+    /// Compress `assets.bin` with zstd at this level. Readers detect the codec from the file's
+    /// header, and uncompressed asset stores are written in the original headerless format.
+    pub assets_zstd_level: Option<i32>,
+    /// The codec used for every parquet column.
+    pub compression: CompressionOptions,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self {
+            statistics: parquet::file::properties::DEFAULT_STATISTICS_ENABLED,
+            sort_by_entity_id: false,
+            row_group_size: DEFAULT_ROW_GROUP_SIZE,
+            dictionary_entity_ids: false,
+            assets_zstd_level: None,
+            compression: CompressionOptions::uncompressed(),
+        }
+    }
+}
+
+/// Options controlling how a [`PolarsWorld`] is read back from a directory.
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    /// Fail with [`Error::NewerCrateVersion`] rather than logging a warning when the checkpoint
+    /// was written by a newer version of this crate.
+    pub strict_version: bool,
+    /// Rebuild each archetype's entity map from its `entity_id` column instead of trusting
+    /// `metadata.json`, so that files whose rows were reordered by another tool still load.
+    pub rebuild_entity_map: bool,
+    /// Decode each parquet file through a memory map rather than reading it, see
+    /// [`PolarsWorld::read_from_dir_mmap`].
+    pub mmap: bool,
+}
+
+impl WriteOptions {
+    fn writer_properties(&self) -> WriterProperties {
+        self.writer_properties_builder().build()
+    }
+
+    fn writer_properties_builder(&self) -> parquet::file::properties::WriterPropertiesBuilder {
+        WriterProperties::builder()
+            .set_statistics_enabled(self.statistics)
+            .set_compression(self.compression.compression)
+    }
+}
+
+/// A parquet compression codec and level, see [`WriteOptions::compression`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionOptions {
+    pub compression: Compression,
+}
+
+impl CompressionOptions {
+    pub fn uncompressed() -> Self {
+        Self {
+            compression: Compression::UNCOMPRESSED,
+        }
+    }
+
+    pub fn snappy() -> Self {
+        Self {
+            compression: Compression::SNAPPY,
+        }
+    }
+
+    pub fn zstd(level: i32) -> Result<Self, Error> {
+        Ok(Self {
+            compression: Compression::ZSTD(ZstdLevel::try_new(level)?),
+        })
+    }
+
+    pub fn gzip(level: u32) -> Result<Self, Error> {
+        Ok(Self {
+            compression: Compression::GZIP(GzipLevel::try_new(level)?),
+        })
+    }
+}
+
+/// Writes `metadata` to `metadata.json` in `path` through a temporary file, so readers never see
+/// a partially written file.
+fn write_metadata_file(path: &Path, metadata: &Metadata) -> Result<(), Error> {
+    let tmp_path = path.join("metadata.json.tmp");
+    let mut file = File::create(&tmp_path)?;
+    serde_json::to_writer(&mut file, metadata)?;
+    file.sync_all()?;
+    std::fs::rename(tmp_path, path.join("metadata.json"))?;
+    Ok(())
+}
+
 impl PolarsWorld {
     pub fn write_to_dir(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        self.write_to_dir_with_props(path, WriterProperties::default())
+    }
+
+    pub fn write_to_dir_with_options(
+        &mut self,
+        path: impl AsRef<Path>,
+        options: &WriteOptions,
+    ) -> Result<(), Error> {
+        if options.sort_by_entity_id {
+            self.sort_by_entity_id()?;
+        }
+        self.write_archetypes(
+            path,
+            options.writer_properties(),
+            None,
+            options.dictionary_entity_ids,
+            options.assets_zstd_level,
+        )
+    }
+
+    /// Writes each archetype in slices of `options.row_group_size` rows, so that only one slice
+    /// is converted to a `RecordBatch` at a time rather than the entire `DataFrame`.
+    pub fn write_to_dir_streaming(
+        &mut self,
+        path: impl AsRef<Path>,
+        options: &WriteOptions,
+    ) -> Result<(), Error> {
+        if options.sort_by_entity_id {
+            self.sort_by_entity_id()?;
+        }
+        let props = options
+            .writer_properties_builder()
+            .set_max_row_group_size(options.row_group_size)
+            .build();
+        self.write_archetypes(
+            path,
+            props,
+            Some(options.row_group_size),
+            options.dictionary_entity_ids,
+            options.assets_zstd_level,
+        )
+    }
+
+    /// Reorders the rows of every archetype by ascending entity id, updating each `entity_map` to match
+    pub fn sort_by_entity_id(&mut self) -> Result<(), Error> {
+        let entity_id_string = ENTITY_ID_COMPONENT.0.to_string();
+        for (id, df) in &mut self.archetypes {
+            let metadata = self
+                .metadata
+                .archetypes
+                .get_mut(id)
+                .ok_or(Error::ComponentNotFound)?;
+            df.sort_in_place([entity_id_string.as_str()], false, true)?;
+            for (row, index) in metadata.entity_map.values_mut().enumerate() {
+                *index = row;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn write_to_dir_with_props(
+        &mut self,
+        path: impl AsRef<Path>,
+        props: WriterProperties,
+    ) -> Result<(), Error> {
+        self.write_archetypes(path, props, None, false, None)
+    }
+
+    fn write_archetypes(
+        &mut self,
+        path: impl AsRef<Path>,
+        props: WriterProperties,
+        batch_size: Option<usize>,
+        dictionary_entity_ids: bool,
+        assets_zstd_level: Option<i32>,
+    ) -> Result<(), Error> {
         let path = path.as_ref();
         std::fs::create_dir_all(path)?;
-        let mut metadata = File::create(path.join("metadata.json"))?;
-        serde_json::to_writer(&mut metadata, &self.metadata)?;
+        let mut written = vec![];
         for (archetype_id, df) in &mut self.archetypes {
+            let metadata = self
+                .metadata
+                .archetypes
+                .get(archetype_id)
+                .ok_or(Error::ComponentNotFound)?;
             let path = path.join(format!("{}.parquet", archetype_id.to_raw()));
-            let file = std::fs::File::create(&path)?;
-            let props = WriterProperties::default();
-            let record_batch = df.to_record_batch()?;
-            let mut writer =
-                ArrowWriter::try_new(file, record_batch.record_batch().schema(), Some(props))
-                    .unwrap();
-            writer.write(record_batch.record_batch()).unwrap();
-            writer.close().unwrap();
-        }
-        let path = path.join("assets.bin");
-        let file = std::fs::File::create(path)?;
-        postcard::to_io(&self.assets, file).unwrap();
-        Ok(())
+            write_archetype(
+                &path,
+                metadata,
+                df,
+                &props,
+                batch_size,
+                dictionary_entity_ids,
+            )
+            .map_err(|err| Error::PartialWrite {
+                written: written.clone(),
+                failed: *archetype_id,
+                source: Box::new(err),
+            })?;
+            written.push(*archetype_id);
+        }
+        self.write_assets(path, assets_zstd_level)?;
+        // written last, so a directory without `metadata.json` is never mistaken for a checkpoint
+        self.write_metadata(path)
     }
 
-    pub fn read_from_dir(path: impl AsRef<Path>) -> Result<Self, Error> {
+    /// Writes only the archetypes in `ids`, leaving every other archetype file in `path` untouched.
+    ///
+    /// Their entries are merged into the `metadata.json` already in `path`, if there is one, so a
+    /// checkpoint can be updated one archetype at a time. The metadata is written last and swapped
+    /// in with a rename, so a failed write leaves the previous metadata in place, though any
+    /// archetype file written before the failure has already been replaced.
+    pub fn write_archetypes_to_dir(
+        &mut self,
+        path: impl AsRef<Path>,
+        ids: &[ArchetypeId],
+    ) -> Result<(), Error> {
         let path = path.as_ref();
-        let mut archetypes = BTreeMap::new();
-        let mut metadata = File::open(path.join("metadata.json"))?;
-        let metadata: Metadata = serde_json::from_reader(&mut metadata)?;
-        for id in metadata.archetypes.keys() {
-            let path = path.join(format!("{}.parquet", id.to_raw()));
-            let file = File::open(&path)?;
-            let df = polars::prelude::ParquetReader::new(file).finish()?;
-            archetypes.insert(*id, df);
+        std::fs::create_dir_all(path)?;
+        let metadata_path = path.join("metadata.json");
+        let mut metadata = if metadata_path.exists() {
+            let mut metadata: Metadata = serde_json::from_reader(File::open(&metadata_path)?)?;
+            metadata.upgrade()?;
+            metadata
+        } else {
+            Metadata {
+                archetypes: BTreeMap::new(),
+                component_map: BTreeMap::new(),
+                ..self.metadata.clone()
+            }
+        };
+        let props = WriterProperties::default();
+        for id in ids {
+            let df = self
+                .archetypes
+                .get_mut(id)
+                .ok_or(Error::ComponentNotFound)?;
+            let archetype = self
+                .metadata
+                .archetypes
+                .get(id)
+                .ok_or(Error::ComponentNotFound)?;
+            let file = path.join(ArchetypeFormat::Parquet.file_name(*id));
+            write_archetype(&file, archetype, df, &props, None, false)?;
+            metadata.archetypes.insert(*id, archetype.clone());
+            metadata
+                .component_map
+                .retain(|_, archetype_id| archetype_id != id);
+            metadata.component_map.extend(
+                self.metadata
+                    .component_map
+                    .iter()
+                    .filter(|(_, archetype_id)| *archetype_id == id),
+            );
         }
-        let assets_buf = std::fs::read(path.join("assets.bin"))?;
-        let assets = postcard::from_bytes(&assets_buf)?;
-        Ok(Self {
-            archetypes,
-            metadata,
-            assets,
-        })
+        metadata.tick = self.metadata.tick;
+        metadata.entity_len = self.metadata.entity_len;
+        metadata.crate_version = Some(CRATE_VERSION.to_string());
+        metadata.version = METADATA_VERSION;
+        self.write_assets(path, None)?;
+        write_metadata_file(path, &metadata)
     }
-}
 
-impl World<HostStore> {
-    pub fn to_polars(&self) -> Result<PolarsWorld, Error> {
-        let mut archetypes = BTreeMap::new();
-        let mut archetype_metadata = BTreeMap::new();
-        for (id, table) in &self.archetypes {
-            let (metadata, df) = table.to_polars()?;
-            archetypes.insert(*id, df);
-            archetype_metadata.insert(*id, metadata);
+    /// Like [`PolarsWorld::write_to_dir`], but encodes each archetype's parquet file on the rayon thread pool.
+    pub fn write_to_dir_parallel(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        self.write_to_dir_parallel_with_options(path, &WriteOptions::default())
+    }
+
+    /// Like [`PolarsWorld::write_to_dir_with_options`], but encodes each archetype's parquet file
+    /// on the rayon thread pool.
+    pub fn write_to_dir_parallel_with_options(
+        &mut self,
+        path: impl AsRef<Path>,
+        options: &WriteOptions,
+    ) -> Result<(), Error> {
+        let path = path.as_ref();
+        std::fs::create_dir_all(path)?;
+        if options.sort_by_entity_id {
+            self.sort_by_entity_id()?;
+        }
+        let props = options.writer_properties();
+        let dictionary_entity_ids = options.dictionary_entity_ids;
+        let results = self
+            .archetypes
+            .par_iter_mut()
+            .map(|(archetype_id, df)| {
+                let Some(metadata) = self.metadata.archetypes.get(archetype_id) else {
+                    return (*archetype_id, Err(Error::ComponentNotFound));
+                };
+                let path = path.join(format!("{}.parquet", archetype_id.to_raw()));
+                let res = write_archetype(&path, metadata, df, &props, None, dictionary_entity_ids);
+                (*archetype_id, res)
+            })
+            .collect::<Vec<_>>();
+        // every archetype is attempted, so `written` may include archetypes after `failed`
+        let mut written = vec![];
+        let mut failure = None;
+        for (archetype_id, res) in results {
+            match res {
+                Ok(()) => written.push(archetype_id),
+                Err(err) => {
+                    failure.get_or_insert((archetype_id, err));
+                }
+            }
         }
+        if let Some((failed, source)) = failure {
+            return Err(Error::PartialWrite {
+                written,
+                failed,
+                source: Box::new(source),
+            });
+        }
+        self.write_assets(path, options.assets_zstd_level)?;
+        self.write_metadata(path)
+    }
 
-        let metadata = Metadata {
-            archetypes: archetype_metadata,
-            component_map: self.component_map.clone(),
-            tick: self.tick,
-            entity_len: self.entity_len,
+    fn write_metadata(&mut self, path: &Path) -> Result<(), Error> {
+        self.metadata.crate_version = Some(CRATE_VERSION.to_string());
+        self.metadata.version = METADATA_VERSION;
+        write_metadata_file(path, &self.metadata)
+    }
+
+    fn write_assets(&self, path: &Path, zstd_level: Option<i32>) -> Result<(), Error> {
+        let mut file = std::fs::File::create(path.join("assets.bin"))?;
+        let Some(level) = zstd_level else {
+            postcard::to_io(&self.assets, file)?;
+            return Ok(());
         };
+        file.write_all(ASSETS_MAGIC)?;
+        file.write_all(&[AssetsCodec::Zstd as u8])?;
+        let mut encoder = zstd::Encoder::new(file, level)?;
+        postcard::to_io(&self.assets, &mut encoder)?;
+        encoder.finish()?;
+        Ok(())
+    }
 
-        Ok(PolarsWorld {
-            archetypes,
-            metadata,
-            assets: self.assets.clone(),
-        })
+    /// Like [`PolarsWorld::write_to_dir`], but writes each archetype as an Arrow IPC file, which
+    /// readers can map directly instead of decoding parquet pages.
+    pub fn write_ipc_to_dir(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        std::fs::create_dir_all(path)?;
+        self.write_metadata(path)?;
+        for (archetype_id, df) in &mut self.archetypes {
+            let metadata = self
+                .metadata
+                .archetypes
+                .get(archetype_id)
+                .ok_or(Error::ComponentNotFound)?;
+            let path = path.join(ArchetypeFormat::Ipc.file_name(*archetype_id));
+            write_archetype_ipc(&path, metadata, df)?;
+        }
+        self.write_assets(path, None)
     }
-}
 
-impl TryFrom<PolarsWorld> for World<HostStore> {
-    type Error = Error;
+    /// Serializes the world into a single in-memory blob, without touching the filesystem.
+    ///
+    /// The blob is [`WORLD_BYTES_MAGIC`] followed by length-prefixed sections: the metadata as
+    /// json, the assets as postcard, and then one Arrow IPC stream per archetype, in the order of
+    /// `metadata.archetypes`. Every length is a little-endian `u64`.
+    pub fn to_ipc_bytes(&mut self) -> Result<Vec<u8>, Error> {
+        self.metadata.crate_version = Some(CRATE_VERSION.to_string());
+        self.metadata.version = METADATA_VERSION;
+        let mut buf = WORLD_BYTES_MAGIC.to_vec();
+        write_section(&mut buf, &serde_json::to_vec(&self.metadata)?);
+        write_section(&mut buf, &postcard::to_allocvec(&self.assets)?);
+        for (archetype_id, metadata) in &self.metadata.archetypes {
+            let df = self
+                .archetypes
+                .get_mut(archetype_id)
+                .ok_or(Error::ComponentNotFound)?;
+            let batch = archetype_ipc_batch(metadata, df)?;
+            let mut writer = arrow::ipc::writer::StreamWriter::try_new(vec![], &batch.schema())?;
+            writer.write(&batch)?;
+            writer.finish()?;
+            write_section(&mut buf, &writer.into_inner()?);
+        }
+        Ok(buf)
+    }
 
-    fn try_from(polars: PolarsWorld) -> Result<Self, Self::Error> {
-        let Metadata {
-            archetypes,
-            component_map,
-            tick,
-            entity_len,
-        } = polars.metadata;
-        let archetypes = polars
-            .archetypes
-            .into_iter()
-            .zip(archetypes.into_values())
-            .map(|((id, df), metadata)| {
-                let table = Table::from_dataframe(df, metadata)?;
-                Ok((id, table))
-            })
-            .collect::<Result<_, Error>>()?;
-        Ok(World {
+    /// Reads a blob written by [`PolarsWorld::to_ipc_bytes`].
+    pub fn from_ipc_bytes(mut buf: &[u8]) -> Result<Self, Error> {
+        buf = buf
+            .strip_prefix(WORLD_BYTES_MAGIC)
+            .ok_or(Error::MalformedWorldBytes)?;
+        let mut metadata: Metadata = serde_json::from_slice(read_section(&mut buf)?)?;
+        check_crate_version(&metadata, false)?;
+        metadata.upgrade()?;
+        let assets = postcard::from_bytes(read_section(&mut buf)?)?;
+        let mut archetypes = BTreeMap::new();
+        for (id, archetype) in &mut metadata.archetypes {
+            let df = read_archetype_ipc_stream(read_section(&mut buf)?, archetype)?;
+            archetypes.insert(*id, df);
+        }
+        if !buf.is_empty() {
+            return Err(Error::MalformedWorldBytes);
+        }
+        validate_component_map(&metadata)?;
+        Ok(Self {
             archetypes,
-            component_map,
-            assets: polars.assets,
-            tick,
-            entity_len,
+            metadata,
+            assets,
         })
     }
-}
 
-impl<W: WorldStore> PartialEq for Column<W>
-where
-    W::Column: PartialEq,
-{
-    fn eq(&self, other: &Self) -> bool {
-        self.buffer == other.buffer
+    pub fn read_from_dir(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::read_dir(
+            path.as_ref(),
+            None,
+            &ReadOptions::default(),
+            ArchetypeFormat::Parquet,
+        )
     }
-}
 
-impl PartialEq for Table<HostStore> {
-    fn eq(&self, other: &Self) -> bool {
-        self.columns == other.columns
-            && self.entity_buffer == other.entity_buffer
-            && self.entity_map == other.entity_map
+    /// Reads a directory written by [`PolarsWorld::write_ipc_to_dir`].
+    ///
+    /// Each archetype file is memory-mapped rather than read, so its pages are only faulted in
+    /// as they are accessed. The frames keep the mapping alive on their own, but the files must
+    /// not be modified or truncated while the world, or any frame cloned from it, is alive.
+    pub fn read_ipc_from_dir(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::read_dir(
+            path.as_ref(),
+            None,
+            &ReadOptions::default(),
+            ArchetypeFormat::Ipc,
+        )
     }
-}
-
-impl Table<HostStore> {
-    pub fn from_dataframe(df: DataFrame, metadata: ArchetypeMetadata) -> Result<Self, Error> {
-        let entity_id_string = ENTITY_ID_COMPONENT.0.to_string();
-        let columns = metadata
-            .columns
-            .iter()
-            .zip(df.iter().filter(|s| s.name() != entity_id_string))
-            .map(|(metadata, series)| {
-                let asset = metadata.asset;
-                let buffer = HostColumn::from_series(
-                    series,
-                    metadata.metadata.component_type.clone(),
-                    asset,
-                )?;
-                let column = Column {
-                    buffer,
-                    metadata: metadata.metadata.clone(),
-                };
-                Ok((metadata.metadata.component_id, column))
-            })
-            .collect::<Result<_, Error>>()?;
-        let column = df
-            .column(&entity_id_string)
-            .map_err(|_| Error::ComponentNotFound)?;
-        let entity_buffer = HostColumn::from_series(column, ComponentType::u64(), false)?;
 
-        Ok(Self {
-            columns,
-            entity_buffer,
-            entity_map: metadata.entity_map,
-        })
+    /// Like [`PolarsWorld::read_from_dir`], but memory-maps each archetype's parquet file, so its
+    /// pages are faulted in as polars decodes them instead of being read into a buffer up front.
+    ///
+    /// Parquet pages are encoded, so the decoded frames own their data and the returned world
+    /// doesn't borrow the mappings, which are dropped once each file is decoded. The files must
+    /// not be modified or truncated while they are being read.
+    pub fn read_from_dir_mmap(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let options = ReadOptions {
+            mmap: true,
+            ..Default::default()
+        };
+        Self::read_from_dir_with_options(path, &options)
     }
 
-    pub fn to_polars(&self) -> Result<(ArchetypeMetadata, DataFrame), Error> {
-        let columns = self
-            .columns
-            .values()
-            .map(|c| ColumnMetadata {
-                metadata: c.metadata.clone(),
-                asset: c.buffer.asset,
-            })
-            .collect();
-        let metadata = ArchetypeMetadata {
-            columns,
-            entity_map: self.entity_map.clone(),
-        };
+    pub fn read_from_dir_with_options(
+        path: impl AsRef<Path>,
+        options: &ReadOptions,
+    ) -> Result<Self, Error> {
+        Self::read_dir(path.as_ref(), None, options, ArchetypeFormat::Parquet)
+    }
 
-        Ok((
-            metadata,
-            self.columns
-                .values()
-                .map(|c| &c.buffer)
-                .chain(std::iter::once(&self.entity_buffer))
-                .map(HostColumn::to_series)
-                .collect::<Result<DataFrame, Error>>()?,
-        ))
+    /// Reads only the archetypes in `ids`, leaving every other archetype and its components out of the world.
+    pub fn read_archetypes_from_dir(
+        path: impl AsRef<Path>,
+        ids: &[ArchetypeId],
+    ) -> Result<Self, Error> {
+        Self::read_dir(
+            path.as_ref(),
+            Some(ids),
+            &ReadOptions::default(),
+            ArchetypeFormat::Parquet,
+        )
     }
-}
 
-impl HostColumn {
-    pub fn from_series(
-        series: &Series,
-        component_type: ComponentType,
-        asset: bool,
+    fn read_dir(
+        path: &Path,
+        ids: Option<&[ArchetypeId]>,
+        options: &ReadOptions,
+        format: ArchetypeFormat,
     ) -> Result<Self, Error> {
-        let buf = series.to_bytes();
-        let len = series.len();
-        let component_id: u64 = series
-            .name()
-            .parse()
-            .map_err(|_| Error::InvalidComponentId)?;
-        let component_id = ComponentId(component_id);
+        let mut archetypes = BTreeMap::new();
+        let mut metadata = File::open(path.join("metadata.json"))?;
+        let mut metadata: Metadata = serde_json::from_reader(&mut metadata)?;
+        check_crate_version(&metadata, options.strict_version)?;
+        metadata.upgrade()?;
+        if let Some(ids) = ids {
+            metadata.archetypes.retain(|id, _| ids.contains(id));
+            metadata
+                .component_map
+                .retain(|_, archetype_id| ids.contains(archetype_id));
+        }
+        for (id, archetype) in &mut metadata.archetypes {
+            let path = path.join(format.file_name(*id));
+            let df = match format {
+                ArchetypeFormat::Parquet => {
+                    let df = read_archetype(&path, archetype, options.mmap)?;
+                    let arrow_metadata =
+                        ArrowReaderMetadata::load(&File::open(&path)?, Default::default())?;
+                    restore_field_metadata(arrow_metadata.schema(), archetype);
+                    df
+                }
+                ArchetypeFormat::Ipc => read_archetype_ipc(&path, archetype)?,
+            };
+            if options.rebuild_entity_map {
+                archetype.rebuild_entity_map(*id, &df)?;
+            }
+            archetypes.insert(*id, df);
+        }
+        validate_component_map(&metadata)?;
+        let assets = read_assets(path)?;
         Ok(Self {
-            buf,
-            len,
-            component_id,
-            component_type,
-            asset,
+            archetypes,
+            metadata,
+            assets,
         })
     }
 
-    pub fn to_series(&self) -> Result<Series, Error> {
-        let array = match self.component_type.primitive_ty {
-            PrimitiveTy::F64 => tensor_array(&self.component_type, self.prim_array::<f64>()),
-            PrimitiveTy::F32 => tensor_array(&self.component_type, self.prim_array::<f32>()),
-            PrimitiveTy::U64 => tensor_array(&self.component_type, self.prim_array::<u64>()),
-            PrimitiveTy::U32 => tensor_array(&self.component_type, self.prim_array::<u32>()),
-            PrimitiveTy::U16 => tensor_array(&self.component_type, self.prim_array::<u16>()),
-            PrimitiveTy::U8 => tensor_array(&self.component_type, self.prim_array::<u8>()),
-            PrimitiveTy::I64 => tensor_array(&self.component_type, self.prim_array::<i64>()),
-            PrimitiveTy::I32 => tensor_array(&self.component_type, self.prim_array::<i32>()),
-            PrimitiveTy::I16 => tensor_array(&self.component_type, self.prim_array::<i16>()),
-            PrimitiveTy::I8 => tensor_array(&self.component_type, self.prim_array::<i8>()),
-            PrimitiveTy::Bool => todo!(),
+    /// Appends the current tick to a history directory, writing each archetype to
+    /// `<archetype>/tick=<n>.parquet`.
+    ///
+    /// The first append writes the entity and component maps to `metadata.json`; later appends
+    /// reuse them, so the world must keep the same entities in the same rows between ticks.
+    pub fn append_tick_to_dir(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        std::fs::create_dir_all(path)?;
+        let metadata_path = path.join("metadata.json");
+        let mut history = if metadata_path.exists() {
+            let mut history: Metadata = serde_json::from_reader(File::open(&metadata_path)?)?;
+            history.upgrade()?;
+            if history.component_map != self.metadata.component_map {
+                return Err(Error::SchemaMismatch);
+            }
+            history
+        } else {
+            Metadata {
+                ticks: vec![],
+                ..self.metadata.clone()
+            }
         };
-        Series::from_arrow(&self.component_id.0.to_string(), array).map_err(Error::from)
+        let tick = self.metadata.tick;
+        let props = WriterProperties::default();
+        for (archetype_id, df) in &mut self.archetypes {
+            let metadata = history
+                .archetypes
+                .get(archetype_id)
+                .ok_or(Error::SchemaMismatch)?;
+            let current = self
+                .metadata
+                .archetypes
+                .get(archetype_id)
+                .ok_or(Error::SchemaMismatch)?;
+            if current.entity_map != metadata.entity_map {
+                return Err(Error::SchemaMismatch);
+            }
+            check_same_columns(&metadata.columns, &current.columns)?;
+            let dir = path.join(archetype_id.to_raw().to_string());
+            std::fs::create_dir_all(&dir)?;
+            write_archetype(
+                &dir.join(format!("tick={}.parquet", tick)),
+                metadata,
+                df,
+                &props,
+                None,
+                false,
+            )?;
+        }
+        if let Err(index) = history.ticks.binary_search(&tick) {
+            history.ticks.insert(index, tick);
+        }
+        history.tick = tick;
+        history.crate_version = Some(CRATE_VERSION.to_string());
+        history.version = METADATA_VERSION;
+        self.write_assets(path, None)?;
+        write_metadata_file(path, &history)
     }
 
-    fn prim_array<T: polars_arrow::types::NativeType + nox::xla::ArrayElement>(
-        &self,
-    ) -> Box<dyn Array> {
-        Box::new(PrimitiveArray::from_slice(self.typed_buf::<T>().unwrap()))
+    /// Reads every tick recorded by [`PolarsWorld::append_tick_to_dir`], in ascending tick order.
+    pub fn read_history_from_dir(path: impl AsRef<Path>) -> Result<Vec<Self>, Error> {
+        let replay = WorldReplay::open(path)?;
+        (0..replay.len())
+            .map(|index| replay.polars(index))
+            .collect()
     }
-}
 
-fn arrow_data_type(ty: PrimitiveTy) -> ArrowDataType {
-    match ty {
-        PrimitiveTy::U8 => ArrowDataType::UInt8,
-        PrimitiveTy::U16 => ArrowDataType::UInt16,
-        PrimitiveTy::U32 => ArrowDataType::UInt32,
-        PrimitiveTy::U64 => ArrowDataType::UInt64,
-        PrimitiveTy::I8 => ArrowDataType::Int8,
-        PrimitiveTy::I16 => ArrowDataType::Int16,
-        PrimitiveTy::I32 => ArrowDataType::Int32,
-        PrimitiveTy::I64 => ArrowDataType::Int64,
-        PrimitiveTy::F32 => ArrowDataType::Float32,
-        PrimitiveTy::F64 => ArrowDataType::Float64,
-        PrimitiveTy::Bool => ArrowDataType::Boolean,
+    /// Returns the metadata of the column storing `id`, if any archetype contains it.
+    pub fn component_metadata(&self, id: ComponentId) -> Option<&ColumnMetadata> {
+        let archetype_id = self.metadata.component_map.get(&id)?;
+        self.metadata.archetypes.get(archetype_id)?.column(id)
     }
-}
 
-fn tensor_array(ty: &ComponentType, inner: Box<dyn Array>) -> Box<dyn Array> {
-    let data_type = arrow_data_type(ty.primitive_ty);
-    if ty.shape.is_empty() {
-        return inner;
+    /// Returns a single-row series for each of `entity`'s components.
+    pub fn entity_row(&self, entity: EntityId) -> Result<BTreeMap<ComponentId, Series>, Error> {
+        for (id, metadata) in &self.metadata.archetypes {
+            let Some(row) = metadata.entity_map.get(&entity) else {
+                continue;
+            };
+            let df = self.archetypes.get(id).ok_or(Error::ComponentNotFound)?;
+            return metadata
+                .columns
+                .iter()
+                .map(|c| {
+                    let component_id = c.metadata.component_id;
+                    let series = metadata.series(df, component_id)?;
+                    Ok((component_id, series.slice(*row as i64, 1)))
+                })
+                .collect();
+        }
+        Err(Error::ComponentNotFound)
     }
-    let data_type = ArrowDataType::FixedSizeList(
-        Box::new(polars_arrow::datatypes::Field::new(
-            "inner", data_type, false,
-        )),
-        ty.shape.iter().product::<usize>(),
-    );
-    Box::new(polars_arrow::array::FixedSizeListArray::new(
-        data_type, inner, None,
-    ))
-    // let metadata = HashMap::from_iter([(
-    //     "ARROW:extension:metadata".to_string(),
-    //     format!("{{ \"shape\": {:?} }}", shape),
-    // )]);
-    // (data_type, Some(metadata))
-}
 
-pub struct RecordBatchRef<'a> {
-    phantom_data: PhantomData<&'a ()>,
-    record_batch: arrow::record_batch::RecordBatch,
-}
+    /// Reads the value of component `id` stored in `row` of its archetype.
+    ///
+    /// `T` must match the component's primitive type and `N` the number of elements in its shape,
+    /// otherwise this fails with [`Error::ElementTypeMismatch`] or [`Error::ValueSizeMismatch`].
+    pub fn column_tensor<T: ArrayElement + Pod, const N: usize>(
+        &self,
+        id: ComponentId,
+        row: usize,
+    ) -> Result<[T; N], Error> {
+        let archetype_id = self
+            .metadata
+            .component_map
+            .get(&id)
+            .ok_or(Error::ComponentNotFound)?;
+        let archetype = self
+            .metadata
+            .archetypes
+            .get(archetype_id)
+            .ok_or(Error::ComponentNotFound)?;
+        let column = archetype.column(id).ok_or(Error::ComponentNotFound)?;
+        let component_type = column.metadata.component_type.clone();
+        let element_type = component_type.primitive_ty.element_type();
+        if element_type != T::TY {
+            return Err(Error::ElementTypeMismatch {
+                expected: element_type,
+                found: T::TY,
+            });
+        }
+        if component_type.shape.iter().product::<usize>() != N {
+            return Err(Error::ValueSizeMismatch);
+        }
+        let df = self
+            .archetypes
+            .get(archetype_id)
+            .ok_or(Error::ComponentNotFound)?;
+        if row >= df.height() {
+            return Err(Error::RowOutOfBounds(row));
+        }
+        let series = archetype.series(df, id)?.slice(row as i64, 1);
+        let host = HostColumn::from_series_with_id(&series, id, component_type, column.asset)?;
+        let value = host.rows::<T>()?.next().ok_or(Error::RowOutOfBounds(row))?;
+        value.try_into().map_err(|_| Error::ValueSizeMismatch)
+    }
 
-impl<'a> RecordBatchRef<'a> {
-    fn record_batch<'b>(&'b self) -> &'a arrow::record_batch::RecordBatch
-    where
-        'b: 'a,
-    {
-        &self.record_batch
+    /// Returns rows `[start, start + len)` of archetype `id`, including its `entity_id` column.
+    ///
+    /// The slice shares its buffers with the stored frame rather than copying them.
+    pub fn slice_archetype(
+        &self,
+        id: ArchetypeId,
+        start: usize,
+        len: usize,
+    ) -> Result<DataFrame, Error> {
+        let df = self.archetypes.get(&id).ok_or(Error::ComponentNotFound)?;
+        if start.saturating_add(len) > df.height() {
+            return Err(Error::SliceOutOfBounds {
+                start,
+                len,
+                height: df.height(),
+            });
+        }
+        Ok(df.slice(start as i64, len))
     }
-}
 
-pub trait DataFrameConv {
-    fn to_record_batch(&self) -> Result<RecordBatchRef<'_>, Error>;
-}
+    /// Combines `other` into this world, appending the rows of archetypes present in both.
+    ///
+    /// Entity ids must be unique across the two worlds, and identical assets are stored only once.
+    /// Everything is validated before this world is modified, so a failed merge leaves it unchanged.
+    pub fn merge(&mut self, mut other: PolarsWorld) -> Result<(), Error> {
+        let entities = self
+            .metadata
+            .archetypes
+            .values()
+            .flat_map(|a| a.entity_map.keys())
+            .collect::<std::collections::BTreeSet<_>>();
+        for archetype in other.metadata.archetypes.values() {
+            if let Some(id) = archetype.entity_map.keys().find(|id| entities.contains(id)) {
+                return Err(Error::DuplicateEntity(*id));
+            }
+        }
+        for (id, archetype) in &other.metadata.archetypes {
+            if let Some(existing) = self.metadata.archetypes.get(id) {
+                check_same_columns(&existing.columns, &archetype.columns)?;
+            }
+        }
+        for (component_id, archetype_id) in &other.metadata.component_map {
+            match self.metadata.component_map.get(component_id) {
+                Some(existing) if existing != archetype_id => return Err(Error::SchemaMismatch),
+                _ => {}
+            }
+        }
 
-impl DataFrameConv for DataFrame {
-    fn to_record_batch(&self) -> Result<RecordBatchRef<'_>, Error> {
-        let mut fields = vec![];
-        let mut columns = vec![];
-        for series in self.iter() {
-            let name = series.name();
-            // safety: `to_array_data` is unsafe because it creates a unlifetimed
-            // reference to `Series`, using `RecordBatchRef` we ensure
-            // that Series's lifetime is tied to the RecordBatch lifetime,
-            // so the `Series` will always be alive while the `RecordBatch` is
-            let array_data = unsafe { series.to_array_data() };
-            let array: Arc<dyn arrow::array::Array> = match array_data.data_type() {
-                arrow::datatypes::DataType::Null => {
-                    Arc::new(arrow::array::NullArray::from(array_data))
-                }
-                arrow::datatypes::DataType::Boolean => {
-                    Arc::new(arrow::array::BooleanArray::from(array_data))
-                }
-                arrow::datatypes::DataType::Int8 => {
-                    Arc::new(arrow::array::Int8Array::from(array_data))
-                }
-                arrow::datatypes::DataType::Int16 => {
-                    Arc::new(arrow::array::Int16Array::from(array_data))
-                }
-                arrow::datatypes::DataType::Int32 => {
-                    Arc::new(arrow::array::Int32Array::from(array_data))
+        let asset_ids = self.assets.merged_ids(&other.assets);
+        let mut archetypes = Vec::with_capacity(other.archetypes.len());
+        for (id, mut df) in std::mem::take(&mut other.archetypes) {
+            let mut metadata = other
+                .metadata
+                .archetypes
+                .remove(&id)
+                .ok_or(Error::ComponentNotFound)?;
+            for column in metadata.columns.iter().filter(|c| c.asset) {
+                let name = column.metadata.component_id.0.to_string();
+                let handles = df
+                    .column(&name)?
+                    .u64()?
+                    .into_no_null_iter()
+                    .map(|handle| asset_ids.get(handle as usize).copied())
+                    .collect::<Option<Vec<u64>>>()
+                    .ok_or(Error::AssetNotFound)?;
+                df.replace(&name, Series::new(&name, handles))?;
+            }
+            if let Some(existing) = self.archetypes.get(&id) {
+                if existing.schema() != df.schema() {
+                    return Err(Error::SchemaMismatch);
                 }
-                arrow::datatypes::DataType::Int64 => {
-                    Arc::new(arrow::array::Int64Array::from(array_data))
-                }
-                arrow::datatypes::DataType::UInt8 => {
-                    Arc::new(arrow::array::UInt8Array::from(array_data))
+            } else if metadata.column_index.is_empty() {
+                metadata.rebuild_column_index();
+            }
+            archetypes.push((id, df, metadata));
+        }
+
+        // everything is validated, so nothing below can leave `self` partially merged
+        self.assets.merge(std::mem::take(&mut other.assets));
+        for (id, df, metadata) in archetypes {
+            match self.archetypes.get_mut(&id) {
+                Some(existing) => {
+                    let existing_metadata = self
+                        .metadata
+                        .archetypes
+                        .get_mut(&id)
+                        .ok_or(Error::ComponentNotFound)?;
+                    let offset = existing.height();
+                    existing.vstack_mut(&df)?;
+                    existing.as_single_chunk_par();
+                    existing_metadata.entity_map.extend(
+                        metadata
+                            .entity_map
+                            .into_iter()
+                            .map(|(entity_id, row)| (entity_id, row + offset)),
+                    );
                 }
-                arrow::datatypes::DataType::UInt16 => {
-                    Arc::new(arrow::array::UInt16Array::from(array_data))
+                None => {
+                    self.archetypes.insert(id, df);
+                    self.metadata.archetypes.insert(id, metadata);
                 }
-                arrow::datatypes::DataType::UInt32 => {
-                    Arc::new(arrow::array::UInt32Array::from(array_data))
+            }
+        }
+        self.metadata
+            .component_map
+            .extend(other.metadata.component_map);
+        self.metadata.tick = self.metadata.tick.max(other.metadata.tick);
+        self.metadata.entity_len = self.metadata.entity_len.max(other.metadata.entity_len);
+        Ok(())
+    }
+
+    /// Writes one JSON object per line for every entity, keyed by component name (or id, for
+    /// components without a `name` tag), along with its `entity_id` and `archetype_id`.
+    ///
+    /// Component values are written as flat arrays in row-major order, so `world_pos` becomes a
+    /// seven element array.
+    pub fn write_ndjson(&self, mut writer: impl Write) -> Result<(), Error> {
+        for (archetype_id, df) in &self.archetypes {
+            let metadata = self
+                .metadata
+                .archetypes
+                .get(archetype_id)
+                .ok_or(Error::ComponentNotFound)?;
+            let columns = metadata
+                .columns
+                .iter()
+                .map(|c| {
+                    let id = c.metadata.component_id;
+                    let buffer = HostColumn::from_series_with_id(
+                        metadata.series(df, id)?,
+                        id,
+                        c.metadata.component_type.clone(),
+                        c.asset,
+                    )?;
+                    Ok((column_name(&c.metadata), buffer))
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            for (entity_id, row) in &metadata.entity_map {
+                let mut object = serde_json::Map::new();
+                object.insert("entity_id".to_string(), entity_id.0.into());
+                object.insert(
+                    "archetype_id".to_string(),
+                    archetype_id.to_raw().to_string().into(),
+                );
+                for (name, buffer) in &columns {
+                    object.insert(name.clone(), row_to_json(buffer, *row)?);
                 }
-                arrow::datatypes::DataType::UInt64 => {
-                    Arc::new(arrow::array::UInt64Array::from(array_data))
+                serde_json::to_writer(&mut writer, &object)?;
+                writer.write_all(b"\n")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a single archetype as CSV, with an `entity_id` column followed by one column per
+    /// component, named like [`PolarsWorld::write_ndjson`] keys.
+    ///
+    /// Tensor components are flattened in row-major order into indexed columns, so `world_pos`
+    /// becomes `world_pos_0` through `world_pos_6`.
+    pub fn write_archetype_csv(
+        &self,
+        id: ArchetypeId,
+        path: impl AsRef<Path>,
+    ) -> Result<(), Error> {
+        let df = self.archetypes.get(&id).ok_or(Error::ComponentNotFound)?;
+        let metadata = self
+            .metadata
+            .archetypes
+            .get(&id)
+            .ok_or(Error::ComponentNotFound)?;
+        let mut columns = vec![metadata
+            .series(df, ENTITY_ID_COMPONENT)?
+            .clone()
+            .with_name("entity_id")];
+        for column in &metadata.columns {
+            let name = column_name(&column.metadata);
+            let shape = &column.metadata.component_type.shape;
+            let mut values = metadata.series(df, column.metadata.component_id)?.clone();
+            while let Ok(array) = values.array() {
+                values = array.get_inner();
+            }
+            if shape.is_empty() {
+                columns.push(values.with_name(&name));
+                continue;
+            }
+            let width = shape.iter().product::<usize>();
+            for i in 0..width {
+                columns.push(
+                    values
+                        .gather_every(width, i)
+                        .with_name(&format!("{name}_{i}")),
+                );
+            }
+        }
+        let mut df = DataFrame::new(columns)?;
+        let file = File::create(path)?;
+        CsvWriter::new(file).finish(&mut df)?;
+        Ok(())
+    }
+
+    pub fn set_component(
+        &mut self,
+        id: ComponentId,
+        values: ndarray::Array2<f64>,
+    ) -> Result<(), Error> {
+        let archetype_id = self
+            .metadata
+            .component_map
+            .get(&id)
+            .ok_or(Error::ComponentNotFound)?;
+        let archetype = self
+            .metadata
+            .archetypes
+            .get(archetype_id)
+            .ok_or(Error::ComponentNotFound)?;
+        let column = archetype
+            .columns
+            .iter()
+            .find(|c| c.metadata.component_id == id)
+            .ok_or(Error::ComponentNotFound)?;
+        let component_type = column.metadata.component_type.clone();
+        check_precision(PrimitiveTy::F64, component_type.primitive_ty)?;
+        let (rows, cols) = values.dim();
+        if rows != archetype.entity_map.len()
+            || cols != component_type.shape.iter().product::<usize>()
+        {
+            return Err(Error::ValueSizeMismatch);
+        }
+        let values = values.iter().copied().collect::<Vec<f64>>();
+        let host_column = HostColumn {
+            buf: bytemuck::cast_slice(&values).to_vec(),
+            len: rows,
+            component_id: id,
+            component_type,
+            asset: column.asset,
+        };
+        let series = host_column.to_series()?;
+        let df = self
+            .archetypes
+            .get_mut(archetype_id)
+            .ok_or(Error::ComponentNotFound)?;
+        df.replace(&id.0.to_string(), series)?;
+        Ok(())
+    }
+
+    /// Compares this world against `other`, matching rows by entity id so that a different row
+    /// order alone isn't reported as a difference.
+    pub fn diff(&self, other: &PolarsWorld) -> WorldDiff {
+        let mut diff = WorldDiff::default();
+        for (archetype_id, a) in &self.metadata.archetypes {
+            let Some(b) = other.metadata.archetypes.get(archetype_id) else {
+                diff.archetypes_only_in_self.push(*archetype_id);
+                continue;
+            };
+            let (Some(a_df), Some(b_df)) = (
+                self.archetypes.get(archetype_id),
+                other.archetypes.get(archetype_id),
+            ) else {
+                continue;
+            };
+            for column in &a.columns {
+                let component_id = column.metadata.component_id;
+                if b.column(component_id).is_none() {
+                    diff.components_only_in_self
+                        .push((*archetype_id, component_id));
+                    continue;
                 }
-                arrow::datatypes::DataType::Float16 => {
-                    Arc::new(arrow::array::Float16Array::from(array_data))
+                let component = diff_component(*archetype_id, component_id, (a, a_df), (b, b_df));
+                diff.components.extend(component);
+            }
+            for column in &b.columns {
+                let component_id = column.metadata.component_id;
+                if a.column(component_id).is_none() {
+                    diff.components_only_in_other
+                        .push((*archetype_id, component_id));
                 }
-                arrow::datatypes::DataType::Float32 => {
-                    Arc::new(arrow::array::Float32Array::from(array_data))
+            }
+        }
+        diff.archetypes_only_in_other = other
+            .metadata
+            .archetypes
+            .keys()
+            .filter(|id| !self.metadata.archetypes.contains_key(id))
+            .copied()
+            .collect();
+        diff
+    }
+
+    /// Writes only the values that changed since `baseline` to the directory at `path`.
+    ///
+    /// Each archetype with a change gets a `<archetype>.parquet` file holding the entity ids that
+    /// changed in any component and the series of every component that changed for any entity.
+    /// Each component series is followed by a boolean `<component>.changed` series marking which
+    /// of those entities changed in that component. Unchanged archetypes get no file at all.
+    ///
+    /// Both worlds must have the same components, with the same entities in the same rows,
+    /// otherwise this fails with [`Error::SchemaMismatch`].
+    pub fn write_delta_to_dir(
+        &self,
+        baseline: &PolarsWorld,
+        path: impl AsRef<Path>,
+    ) -> Result<(), Error> {
+        let path = path.as_ref();
+        if self.metadata.component_map != baseline.metadata.component_map {
+            return Err(Error::SchemaMismatch);
+        }
+        std::fs::create_dir_all(path)?;
+        let mut delta = DeltaMetadata {
+            tick: self.metadata.tick,
+            base_tick: baseline.metadata.tick,
+            archetypes: BTreeMap::new(),
+        };
+        for (archetype_id, metadata) in &self.metadata.archetypes {
+            let base_metadata = baseline
+                .metadata
+                .archetypes
+                .get(archetype_id)
+                .ok_or(Error::SchemaMismatch)?;
+            if base_metadata.entity_map != metadata.entity_map {
+                return Err(Error::SchemaMismatch);
+            }
+            let (Some(df), Some(base_df)) = (
+                self.archetypes.get(archetype_id),
+                baseline.archetypes.get(archetype_id),
+            ) else {
+                return Err(Error::ComponentNotFound);
+            };
+            let mut changed_columns = vec![];
+            for column in &metadata.columns {
+                let id = column.metadata.component_id;
+                let host = metadata.host_column(df, column)?;
+                let base = base_metadata.host_column(
+                    base_df,
+                    base_metadata.column(id).ok_or(Error::SchemaMismatch)?,
+                )?;
+                if host.buf.len() != base.buf.len() || host.len != base.len {
+                    return Err(Error::SchemaMismatch);
                 }
-                arrow::datatypes::DataType::Float64 => {
-                    Arc::new(arrow::array::Float64Array::from(array_data))
+                if host.len == 0 {
+                    continue;
                 }
-                arrow::datatypes::DataType::Timestamp(_, _) => todo!(),
-                arrow::datatypes::DataType::Date32 => {
-                    Arc::new(arrow::array::Date32Array::from(array_data))
+                let stride = host.buf.len() / host.len;
+                let changed = host
+                    .buf
+                    .chunks_exact(stride)
+                    .zip(base.buf.chunks_exact(stride))
+                    .map(|(a, b)| a != b)
+                    .collect::<Vec<_>>();
+                if changed.contains(&true) {
+                    changed_columns.push((host, changed));
                 }
-                arrow::datatypes::DataType::Date64 => {
-                    Arc::new(arrow::array::Date64Array::from(array_data))
+            }
+            if changed_columns.is_empty() {
+                continue;
+            }
+            let mut rows = metadata
+                .entity_map
+                .iter()
+                .map(|(entity_id, row)| (*row, *entity_id))
+                .filter(|(row, _)| changed_columns.iter().any(|(_, changed)| changed[*row]))
+                .collect::<Vec<_>>();
+            rows.sort();
+            let entity_ids = rows.iter().map(|(_, id)| id.0).collect::<Vec<_>>();
+            let mut series = vec![Series::new(&ENTITY_ID_COMPONENT.0.to_string(), entity_ids)];
+            let mut components = vec![];
+            for (host, changed) in changed_columns {
+                let stride = host.buf.len() / host.len;
+                let buf = rows
+                    .iter()
+                    .flat_map(|(row, _)| &host.buf[row * stride..(row + 1) * stride])
+                    .copied()
+                    .collect();
+                let mask = rows
+                    .iter()
+                    .map(|(row, _)| changed[*row])
+                    .collect::<Vec<_>>();
+                let id = host.component_id;
+                let values = HostColumn {
+                    buf,
+                    len: rows.len(),
+                    ..host
+                };
+                series.push(values.to_series()?);
+                series.push(Series::new(&changed_column_name(id), mask));
+                components.push(id);
+            }
+            let mut df = DataFrame::new(series)?;
+            let file = File::create(path.join(format!("{}.parquet", archetype_id.to_raw())))?;
+            polars::prelude::ParquetWriter::new(file).finish(&mut df)?;
+            delta.archetypes.insert(*archetype_id, components);
+        }
+        serde_json::to_writer(File::create(path.join("delta.json"))?, &delta)?;
+        Ok(())
+    }
+
+    /// Applies a delta written by [`PolarsWorld::write_delta_to_dir`] onto this world, which
+    /// must be the baseline the delta was written against, and advances it to the delta's tick.
+    ///
+    /// Fails with [`Error::DeltaBaselineMismatch`] if this world is not at the baseline's tick.
+    /// Every archetype's delta is read and validated before any is applied, so an error leaves
+    /// the world unchanged.
+    pub fn apply_delta_from_dir(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        let delta: DeltaMetadata = serde_json::from_reader(File::open(path.join("delta.json"))?)?;
+        if delta.base_tick != self.metadata.tick {
+            return Err(Error::DeltaBaselineMismatch {
+                expected: delta.base_tick,
+                found: self.metadata.tick,
+            });
+        }
+        let mut archetypes = Vec::with_capacity(delta.archetypes.len());
+        for (archetype_id, components) in &delta.archetypes {
+            let metadata = self
+                .metadata
+                .archetypes
+                .get(archetype_id)
+                .ok_or(Error::SchemaMismatch)?;
+            let file = File::open(path.join(format!("{}.parquet", archetype_id.to_raw())))?;
+            let delta_df = polars::prelude::ParquetReader::new(file).finish()?;
+            let rows = delta_df
+                .column(&ENTITY_ID_COMPONENT.0.to_string())?
+                .u64()?
+                .into_iter()
+                .map(|entity_id| {
+                    let entity_id = EntityId(entity_id.ok_or(Error::SchemaMismatch)?);
+                    metadata
+                        .entity_map
+                        .get(&entity_id)
+                        .copied()
+                        .ok_or(Error::EntityMapMismatch(*archetype_id))
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            // polars series are reference counted, so this copies no column data
+            let mut df = self
+                .archetypes
+                .get(archetype_id)
+                .ok_or(Error::ComponentNotFound)?
+                .clone();
+            for id in components {
+                let column = metadata.column(*id).ok_or(Error::SchemaMismatch)?;
+                let mut host = metadata.host_column(&df, column)?;
+                let values = HostColumn::from_series_with_id(
+                    delta_df.column(&id.0.to_string())?,
+                    *id,
+                    column.metadata.component_type.clone(),
+                    column.asset,
+                )?;
+                let changed = delta_df.column(&changed_column_name(*id))?.bool()?;
+                let stride = host.buf.len() / host.len.max(1);
+                if values.buf.len() != rows.len() * stride || changed.len() != rows.len() {
+                    return Err(Error::ValueSizeMismatch);
                 }
-                arrow::datatypes::DataType::Time32(u) => match u {
-                    arrow::datatypes::TimeUnit::Second => {
-                        Arc::new(arrow::array::Time32SecondArray::from(array_data))
-                    }
-                    arrow::datatypes::TimeUnit::Millisecond => {
-                        Arc::new(arrow::array::Time32MillisecondArray::from(array_data))
-                    }
-                    arrow::datatypes::TimeUnit::Microsecond => {
-                        unimplemented!()
-                    }
-                    arrow::datatypes::TimeUnit::Nanosecond => {
-                        unimplemented!()
-                    }
-                },
-                arrow::datatypes::DataType::Time64(u) => match u {
-                    arrow::datatypes::TimeUnit::Second => {
-                        todo!()
-                    }
-                    arrow::datatypes::TimeUnit::Millisecond => {
-                        todo!()
-                    }
-                    arrow::datatypes::TimeUnit::Microsecond => {
-                        Arc::new(arrow::array::Time64MicrosecondArray::from(array_data))
-                    }
-                    arrow::datatypes::TimeUnit::Nanosecond => {
-                        Arc::new(arrow::array::Time64NanosecondArray::from(array_data))
+                for (i, (row, changed)) in rows.iter().zip(changed).enumerate() {
+                    if changed == Some(true) {
+                        host.buf[row * stride..(row + 1) * stride]
+                            .copy_from_slice(&values.buf[i * stride..(i + 1) * stride]);
                     }
-                },
-                arrow::datatypes::DataType::Duration(_) => todo!(),
-                arrow::datatypes::DataType::Interval(_) => todo!(),
-                arrow::datatypes::DataType::Binary => {
-                    Arc::new(arrow::array::BinaryArray::from(array_data))
-                }
-                arrow::datatypes::DataType::FixedSizeBinary(_) => {
-                    Arc::new(arrow::array::FixedSizeBinaryArray::from(array_data))
-                }
-                arrow::datatypes::DataType::LargeBinary => {
-                    Arc::new(arrow::array::LargeBinaryArray::from(array_data))
-                }
-                arrow::datatypes::DataType::Utf8 => todo!(),
-                arrow::datatypes::DataType::LargeUtf8 => todo!(),
-                arrow::datatypes::DataType::List(_) => Arc::new(ListArray::from(array_data)),
-                arrow::datatypes::DataType::FixedSizeList(_, _) => {
-                    Arc::new(arrow::array::FixedSizeListArray::from(array_data))
                 }
-                arrow::datatypes::DataType::LargeList(_) => {
-                    Arc::new(LargeListArray::from(array_data))
-                }
-                arrow::datatypes::DataType::Struct(_) => Arc::new(StructArray::from(array_data)),
-                arrow::datatypes::DataType::Union(_, _) => Arc::new(UnionArray::from(array_data)),
-                arrow::datatypes::DataType::Dictionary(_, _) => {
-                    todo!()
-                }
-                arrow::datatypes::DataType::Decimal128(_, _) => todo!(),
-                arrow::datatypes::DataType::Decimal256(_, _) => todo!(),
-                arrow::datatypes::DataType::Map(_, _) => Arc::new(MapArray::from(array_data)),
-                arrow::datatypes::DataType::RunEndEncoded(_, _) => todo!(),
-            };
-
-            let field = Field::new(name, array.data_type().clone(), false);
-            fields.push(field);
-            columns.push(array);
+                df.replace(&id.0.to_string(), host.to_series()?)?;
+            }
+            archetypes.push((*archetype_id, df));
         }
-        let schema = Arc::new(Schema::new(fields));
-        let batch = RecordBatch::try_new(schema, columns)?;
-        Ok(RecordBatchRef {
-            phantom_data: PhantomData,
-            record_batch: batch,
-        })
+
+        // every delta is validated, so nothing below can leave `self` partially updated
+        self.archetypes.extend(archetypes);
+        self.metadata.tick = delta.tick;
+        Ok(())
     }
 }
 
-pub trait SeriesExt {
-    fn to_bytes(&self) -> Vec<u8>;
-    unsafe fn to_array_data(&self) -> ArrayData;
+/// Describes a directory written by [`PolarsWorld::write_delta_to_dir`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct DeltaMetadata {
+    tick: u64,
+    /// The tick of the baseline the delta was written against
+    base_tick: u64,
+    /// The changed components of each archetype that has a delta file
+    archetypes: BTreeMap<ArchetypeId, Vec<ComponentId>>,
 }
 
-impl SeriesExt for Series {
-    fn to_bytes(&self) -> Vec<u8> {
-        // safety: we ensure that we only use the
-        // returned `ArrayData` while `Series` is in
-        // scope, so this is safe
-        let data = unsafe { self.to_array_data() };
-        let mut out = Vec::default();
-        recurse_array_data(&data, &mut out);
-        out
+/// The name of the series marking which entities of a delta changed in component `id`.
+fn changed_column_name(id: ComponentId) -> String {
+    format!("{}.changed", id.0)
+}
+
+/// Steps through a history directory written by [`PolarsWorld::append_tick_to_dir`], reading
+/// each tick's archetypes only when that tick is requested.
+pub struct WorldReplay {
+    path: PathBuf,
+    metadata: Metadata,
+    assets: AssetStore,
+}
+
+impl WorldReplay {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let mut metadata: Metadata =
+            serde_json::from_reader(File::open(path.join("metadata.json"))?)?;
+        metadata.upgrade()?;
+        validate_component_map(&metadata)?;
+        let assets = read_assets(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            metadata,
+            assets,
+        })
     }
 
-    unsafe fn to_array_data(&self) -> ArrayData {
-        let array = self.to_arrow(0, false);
-        let field = self.field();
-        let field = field.to_arrow(false);
-        let schema = polars_arrow::ffi::export_field_to_c(&field);
-        // safety: these two types have identical layouts
-        // as they are both guarenteed to match the c-ffi layout
-        let schema = unsafe { std::mem::transmute(schema) };
-        let array = polars_arrow::ffi::export_array_to_c(array);
-        // safety: these two types have identical layouts
-        // as they are both guarenteed to match the c-ffi layout
-        let array: FFI_ArrowArray = unsafe { std::mem::transmute(array) };
-        // safety: this function requires the user ensure that `Series`
-        // is alive while `ArrayData` is accessible
-        unsafe { arrow::ffi::from_ffi(array, &schema) }.expect("polars arrow layout disagreement")
+    /// The recorded ticks, in ascending order; replays are indexed by position in this list.
+    pub fn ticks(&self) -> &[u64] {
+        &self.metadata.ticks
+    }
+
+    pub fn len(&self) -> usize {
+        self.metadata.ticks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.metadata.ticks.is_empty()
+    }
+
+    /// Reads the `index`th recorded tick.
+    pub fn polars(&self, index: usize) -> Result<PolarsWorld, Error> {
+        let tick = *self
+            .metadata
+            .ticks
+            .get(index)
+            .ok_or(Error::TickOutOfBounds(index))?;
+        let archetypes = self
+            .metadata
+            .archetypes
+            .iter()
+            .map(|(id, archetype)| {
+                let path = self
+                    .path
+                    .join(id.to_raw().to_string())
+                    .join(format!("tick={}.parquet", tick));
+                Ok((*id, read_archetype(&path, archetype, false)?))
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(PolarsWorld {
+            archetypes,
+            metadata: Metadata {
+                tick,
+                ..self.metadata.clone()
+            },
+            assets: self.assets.clone(),
+        })
+    }
+
+    /// Reconstructs the world as it was at the `index`th recorded tick.
+    pub fn world(&self, index: usize) -> Result<World<HostStore>, Error> {
+        self.polars(index)?.try_into()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Result<World<HostStore>, Error>> + '_ {
+        (0..self.len()).map(|index| self.world(index))
     }
 }
 
-pub fn recurse_array_data(array_data: &ArrayData, out: &mut Vec<u8>) {
-    for child in array_data.child_data() {
-        recurse_array_data(child, out)
+/// The differences between two worlds, see [`PolarsWorld::diff`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorldDiff {
+    pub archetypes_only_in_self: Vec<ArchetypeId>,
+    pub archetypes_only_in_other: Vec<ArchetypeId>,
+    pub components_only_in_self: Vec<(ArchetypeId, ComponentId)>,
+    pub components_only_in_other: Vec<(ArchetypeId, ComponentId)>,
+    /// The components whose values differ for at least one entity
+    pub components: Vec<ComponentDiff>,
+}
+
+impl WorldDiff {
+    pub fn is_empty(&self) -> bool {
+        self.archetypes_only_in_self.is_empty()
+            && self.archetypes_only_in_other.is_empty()
+            && self.components_only_in_self.is_empty()
+            && self.components_only_in_other.is_empty()
+            && self.components.is_empty()
     }
-    for buffer in array_data.buffers() {
-        out.extend_from_slice(buffer.as_slice())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentDiff {
+    pub archetype_id: ArchetypeId,
+    pub component_id: ComponentId,
+    /// The entities whose values differ, in ascending order, including entities that only one
+    /// of the worlds has in this archetype
+    pub entities: Vec<EntityId>,
+    /// The largest absolute difference between the values of an entity present in both worlds.
+    /// Columns whose shapes differ, or that can't be read as numbers, report infinity.
+    pub max_abs_delta: f64,
+}
+
+fn diff_component(
+    archetype_id: ArchetypeId,
+    component_id: ComponentId,
+    (a, a_df): (&ArchetypeMetadata, &DataFrame),
+    (b, b_df): (&ArchetypeMetadata, &DataFrame),
+) -> Option<ComponentDiff> {
+    let values = |metadata: &ArchetypeMetadata, df: &DataFrame| -> Option<Vec<f64>> {
+        let series = metadata.series(df, component_id).ok()?.explode().ok()?;
+        let series = series.cast(&polars::prelude::DataType::Float64).ok()?;
+        let values = series.f64().ok()?.into_iter();
+        Some(values.map(|v| v.unwrap_or(f64::NAN)).collect())
+    };
+    let shape = &a.column(component_id)?.metadata.component_type.shape;
+    let width = shape.iter().product::<usize>();
+    let values = (*shape == b.column(component_id)?.metadata.component_type.shape)
+        .then(|| Some((values(a, a_df)?, values(b, b_df)?)))
+        .flatten();
+
+    let mut entities = vec![];
+    let mut max_abs_delta = 0.0f64;
+    let all_entities = a
+        .entity_map
+        .keys()
+        .chain(b.entity_map.keys())
+        .collect::<std::collections::BTreeSet<_>>();
+    for entity in all_entities {
+        let (Some(a_row), Some(b_row)) = (a.entity_map.get(entity), b.entity_map.get(entity))
+        else {
+            entities.push(*entity);
+            continue;
+        };
+        let rows = values.as_ref().and_then(|(a_values, b_values)| {
+            let a_row = a_values.get(a_row * width..(a_row + 1) * width)?;
+            let b_row = b_values.get(b_row * width..(b_row + 1) * width)?;
+            Some((a_row, b_row))
+        });
+        let delta = match rows {
+            Some((a_row, b_row)) => a_row
+                .iter()
+                .zip(b_row)
+                .map(|(a, b)| value_delta(*a, *b))
+                .fold(0.0, f64::max),
+            None => f64::INFINITY,
+        };
+        if delta > 0.0 {
+            entities.push(*entity);
+            max_abs_delta = max_abs_delta.max(delta);
+        }
+    }
+    (!entities.is_empty()).then_some(ComponentDiff {
+        archetype_id,
+        component_id,
+        entities,
+        max_abs_delta,
+    })
+}
+
+/// The absolute difference between two values, where NaNs are equal to each other and
+/// infinitely far from any number.
+fn value_delta(a: f64, b: f64) -> f64 {
+    if a == b || (a.is_nan() && b.is_nan()) {
+        return 0.0;
+    }
+    let delta = (a - b).abs();
+    if delta.is_nan() {
+        f64::INFINITY
+    } else {
+        delta
+    }
+}
+
+impl World<HostStore> {
+    pub fn to_polars(&self) -> Result<PolarsWorld, Error> {
+        let mut archetypes = BTreeMap::new();
+        let mut archetype_metadata = BTreeMap::new();
+        for (id, table) in &self.archetypes {
+            let (metadata, df) = table.to_polars()?;
+            archetypes.insert(*id, df);
+            archetype_metadata.insert(*id, metadata);
+        }
+
+        let metadata = Metadata {
+            archetypes: archetype_metadata,
+            component_map: self
+                .component_map
+                .iter()
+                .map(|(id, archetype_id)| (*id, *archetype_id))
+                .collect(),
+            tick: self.tick,
+            entity_len: self.entity_len,
+            ticks: vec![],
+            crate_version: None,
+            version: METADATA_VERSION,
+        };
+
+        Ok(PolarsWorld {
+            archetypes,
+            metadata,
+            assets: self.assets.clone(),
+        })
+    }
+}
+
+impl TryFrom<PolarsWorld> for World<HostStore> {
+    type Error = Error;
+
+    fn try_from(polars: PolarsWorld) -> Result<Self, Self::Error> {
+        let Metadata {
+            archetypes,
+            component_map,
+            tick,
+            entity_len,
+            ..
+        } = polars.metadata;
+        let archetypes = polars
+            .archetypes
+            .into_iter()
+            .zip(archetypes.into_values())
+            .map(|((id, df), metadata)| {
+                let table = Table::from_dataframe(df, metadata)?;
+                Ok((id, table))
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(World {
+            archetypes,
+            component_map: component_map.into_iter().collect(),
+            assets: polars.assets,
+            tick,
+            entity_len,
+            entity_allocator: EntityAllocator::default(),
+            registered_components: HashMap::new(),
+        })
+    }
+}
+
+impl<W: WorldStore> PartialEq for Column<W>
+where
+    W::Column: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.buffer == other.buffer
+    }
+}
+
+impl PartialEq for Table<HostStore> {
+    fn eq(&self, other: &Self) -> bool {
+        self.columns == other.columns
+            && self.entity_buffer == other.entity_buffer
+            && self.entity_map == other.entity_map
+    }
+}
+
+impl Table<HostStore> {
+    pub fn from_dataframe(df: DataFrame, metadata: ArchetypeMetadata) -> Result<Self, Error> {
+        let columns = metadata
+            .columns
+            .iter()
+            .map(|c| {
+                let id = c.metadata.component_id;
+                let series = metadata.series(&df, id)?;
+                let buffer = HostColumn::from_series_with_id(
+                    series,
+                    id,
+                    c.metadata.component_type.clone(),
+                    c.asset,
+                )?;
+                let column = Column {
+                    buffer,
+                    metadata: c.metadata.clone(),
+                };
+                Ok((id, column))
+            })
+            .collect::<Result<_, Error>>()?;
+        let column = metadata.series(&df, ENTITY_ID_COMPONENT)?;
+        let entity_buffer = HostColumn::from_series_with_id(
+            column,
+            ENTITY_ID_COMPONENT,
+            ComponentType::u64(),
+            false,
+        )?;
+
+        Ok(Self {
+            columns,
+            entity_buffer,
+            entity_map: metadata.entity_map,
+        })
+    }
+
+    pub fn to_polars(&self) -> Result<(ArchetypeMetadata, DataFrame), Error> {
+        let columns = self
+            .columns
+            .values()
+            .map(|c| ColumnMetadata {
+                metadata: c.metadata.clone(),
+                asset: c.buffer.asset,
+            })
+            .collect();
+        let mut metadata = ArchetypeMetadata {
+            columns,
+            entity_map: self.entity_map.clone(),
+            column_index: BTreeMap::new(),
+        };
+        metadata.rebuild_column_index();
+
+        Ok((
+            metadata,
+            self.columns
+                .values()
+                .map(|c| match c.metadata.decimal() {
+                    Some((precision, scale)) => c.buffer.to_decimal_series(precision, scale),
+                    None => c.buffer.to_series(),
+                })
+                .chain(std::iter::once(self.entity_buffer.to_series()))
+                .collect::<Result<DataFrame, Error>>()?,
+        ))
+    }
+}
+
+impl HostColumn {
+    pub fn from_series(
+        series: &Series,
+        component_type: ComponentType,
+        asset: bool,
+    ) -> Result<Self, Error> {
+        let component_id: u64 = series
+            .name()
+            .parse()
+            .map_err(|_| Error::InvalidComponentId)?;
+        let component_id = ComponentId(component_id);
+        Self::from_series_with_id(series, component_id, component_type, asset)
+    }
+
+    /// Copies `series` into a host column.
+    ///
+    /// Decimal series are stored as their unscaled `i64` values, so `component_type` must be a
+    /// scalar `I64` type and every value must be non-null and fit in an `i64`, or this returns
+    /// [`Error::InvalidDecimal`]. [`HostColumn::to_decimal_series`] restores them given the scale.
+    ///
+    /// A host column has no validity bitmap, so nulls in a scalar series are filled with NaN for
+    /// floats and zero (or `false`) otherwise. Nulls inside a list or array series would misalign
+    /// the buffer, and return [`Error::NullsUnsupported`].
+    pub fn from_series_with_id(
+        series: &Series,
+        component_id: ComponentId,
+        component_type: ComponentType,
+        asset: bool,
+    ) -> Result<Self, Error> {
+        let series = fill_nulls(series, component_id)?;
+        let buf = match series.dtype() {
+            polars::prelude::DataType::Decimal(_, _) => {
+                if component_type != ComponentType::i64() {
+                    return Err(Error::InvalidDecimal(component_id));
+                }
+                let unscaled = series
+                    .decimal()?
+                    .into_iter()
+                    .map(|v| v.and_then(|v| i64::try_from(v).ok()))
+                    .collect::<Option<Vec<i64>>>()
+                    .ok_or(Error::InvalidDecimal(component_id))?;
+                bytemuck::cast_slice(&unscaled).to_vec()
+            }
+            _ if component_type.primitive_ty == PrimitiveTy::F16 => series
+                .to_bytes()
+                .chunks_exact(std::mem::size_of::<f32>())
+                .flat_map(|b| {
+                    let value = f32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+                    half::f16::from_f32(value).to_le_bytes()
+                })
+                .collect(),
+            _ => series.to_bytes(),
+        };
+        Ok(Self {
+            buf,
+            len: series.len(),
+            component_id,
+            component_type,
+            asset,
+        })
+    }
+
+    /// Converts a scalar `I64` column of unscaled values, as produced from a decimal series by
+    /// [`HostColumn::from_series_with_id`], back into a decimal series.
+    pub fn to_decimal_series(
+        &self,
+        precision: Option<usize>,
+        scale: usize,
+    ) -> Result<Series, Error> {
+        if self.component_type != ComponentType::i64() {
+            return Err(Error::InvalidDecimal(self.component_id));
+        }
+        let values = self
+            .typed_buf::<i64>()
+            .ok_or(Error::InvalidDecimal(self.component_id))?;
+        let unscaled = polars::prelude::Int128Chunked::from_vec(
+            &self.component_id.0.to_string(),
+            values.iter().map(|v| *v as i128).collect(),
+        );
+        Ok(unscaled.into_decimal(precision, scale)?.into_series())
+    }
+
+    pub fn to_series(&self) -> Result<Series, Error> {
+        let array = match self.component_type.primitive_ty {
+            PrimitiveTy::F64 => tensor_array(&self.component_type, self.prim_array::<f64>())?,
+            PrimitiveTy::F32 => tensor_array(&self.component_type, self.prim_array::<f32>())?,
+            PrimitiveTy::U64 => tensor_array(&self.component_type, self.prim_array::<u64>())?,
+            PrimitiveTy::U32 => tensor_array(&self.component_type, self.prim_array::<u32>())?,
+            PrimitiveTy::U16 => tensor_array(&self.component_type, self.prim_array::<u16>())?,
+            PrimitiveTy::U8 => tensor_array(&self.component_type, self.prim_array::<u8>())?,
+            PrimitiveTy::I64 => tensor_array(&self.component_type, self.prim_array::<i64>())?,
+            PrimitiveTy::I32 => tensor_array(&self.component_type, self.prim_array::<i32>())?,
+            PrimitiveTy::I16 => tensor_array(&self.component_type, self.prim_array::<i16>())?,
+            PrimitiveTy::I8 => tensor_array(&self.component_type, self.prim_array::<i8>())?,
+            PrimitiveTy::Bool => {
+                let values = self.buf.iter().map(|b| *b != 0).collect::<Vec<_>>();
+                tensor_array(
+                    &self.component_type,
+                    Box::new(BooleanArray::from_slice(values)),
+                )?
+            }
+            PrimitiveTy::F16 => {
+                let values = self
+                    .buf
+                    .chunks_exact(std::mem::size_of::<half::f16>())
+                    .map(|b| half::f16::from_le_bytes([b[0], b[1]]).to_f32())
+                    .collect::<Vec<_>>();
+                tensor_array(
+                    &self.component_type,
+                    Box::new(PrimitiveArray::from_vec(values)),
+                )?
+            }
+        };
+        Series::from_arrow(&self.component_id.0.to_string(), array).map_err(Error::from)
+    }
+
+    fn prim_array<T: polars_arrow::types::NativeType + nox::xla::ArrayElement>(
+        &self,
+    ) -> Box<dyn Array> {
+        Box::new(PrimitiveArray::from_slice(self.typed_buf::<T>().unwrap()))
+    }
+}
+
+/// The `name` tag of a component, falling back to its id for components without one.
+fn column_name(metadata: &conduit::Metadata) -> String {
+    match metadata.tags.get("name").and_then(|n| n.as_str()) {
+        Some(name) => name.to_string(),
+        None => metadata.component_id.0.to_string(),
+    }
+}
+
+pub(crate) fn check_same_columns(a: &[ColumnMetadata], b: &[ColumnMetadata]) -> Result<(), Error> {
+    if a.len() != b.len() {
+        return Err(Error::SchemaMismatch);
+    }
+    for (a, b) in a.iter().zip(b) {
+        if a.metadata.component_id != b.metadata.component_id || a.asset != b.asset {
+            return Err(Error::SchemaMismatch);
+        }
+        let (a, b) = (&a.metadata.component_type, &b.metadata.component_type);
+        check_precision(a.primitive_ty, b.primitive_ty)?;
+        if a.shape != b.shape {
+            return Err(Error::ShapeMismatch {
+                expected: a.shape.to_vec(),
+                found: b.shape.to_vec(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Ensures that a column stored as `found` can be combined with data of type `expected`.
+///
+/// Operations that combine columns across archetypes or checkpoints use this rather than
+/// silently reinterpreting the bytes of, say, an f32 column as f64.
+pub(crate) fn check_precision(expected: PrimitiveTy, found: PrimitiveTy) -> Result<(), Error> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(Error::PrecisionMismatch { expected, found })
+    }
+}
+
+fn write_archetype(
+    path: &Path,
+    metadata: &ArchetypeMetadata,
+    df: &mut DataFrame,
+    props: &WriterProperties,
+    batch_size: Option<usize>,
+    dictionary_entity_ids: bool,
+) -> Result<(), Error> {
+    let file = std::fs::File::create(path)?;
+    // slices are converted using only their first chunk, so the frame must be contiguous
+    df.as_single_chunk_par();
+    let len = df.height();
+    let batch_size = batch_size.unwrap_or(len).max(1);
+    let encode = |batch: &RecordBatch| {
+        if dictionary_entity_ids {
+            dictionary_encode_entity_ids(batch)
+        } else {
+            Ok(batch.clone())
+        }
+    };
+    let schema = encode(df.head(Some(0)).to_record_batch()?.record_batch())?.schema();
+    let schema = with_shape_metadata(&schema, metadata);
+    let schema = Arc::new(with_column_names(&schema, metadata));
+    let mut writer = ArrowWriter::try_new(file, schema.clone(), Some(props.clone()))?;
+    for offset in (0..len.max(1)).step_by(batch_size) {
+        let slice = df.slice(offset as i64, batch_size);
+        let record_batch = slice.to_record_batch()?;
+        let batch = encode(record_batch.record_batch())?;
+        let batch = RecordBatch::try_new(schema.clone(), batch.columns().to_vec())?;
+        writer.write(&batch)?;
+    }
+    writer.close()?;
+    Ok(())
+}
+
+/// The on-disk format of each archetype in a world directory.
+#[derive(Clone, Copy)]
+enum ArchetypeFormat {
+    Parquet,
+    Ipc,
+}
+
+impl ArchetypeFormat {
+    fn file_name(self, id: ArchetypeId) -> String {
+        match self {
+            ArchetypeFormat::Parquet => format!("{}.parquet", id.to_raw()),
+            ArchetypeFormat::Ipc => format!("{}.arrow", id.to_raw()),
+        }
+    }
+}
+
+fn write_archetype_ipc(
+    path: &Path,
+    metadata: &ArchetypeMetadata,
+    df: &mut DataFrame,
+) -> Result<(), Error> {
+    let file = std::fs::File::create(path)?;
+    let batch = archetype_ipc_batch(metadata, df)?;
+    let mut writer = arrow::ipc::writer::FileWriter::try_new(file, &batch.schema())?;
+    writer.write(&batch)?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Converts an archetype to a single record batch, with the shape and column name metadata
+/// the IPC readers expect.
+fn archetype_ipc_batch(
+    metadata: &ArchetypeMetadata,
+    df: &mut DataFrame,
+) -> Result<RecordBatch, Error> {
+    df.as_single_chunk_par();
+    let record_batch = df.to_record_batch()?;
+    let batch = record_batch.record_batch();
+    let schema = with_shape_metadata(&batch.schema(), metadata);
+    let schema = Arc::new(with_column_names(&schema, metadata));
+    Ok(RecordBatch::try_new(schema, batch.columns().to_vec())?)
+}
+
+fn read_archetype_ipc_stream(
+    buf: &[u8],
+    metadata: &mut ArchetypeMetadata,
+) -> Result<DataFrame, Error> {
+    let reader = arrow::ipc::reader::StreamReader::try_new(buf, None)?;
+    let schema = reader.schema();
+    validate_shape_metadata(&schema, metadata)?;
+    let mut df = polars::prelude::IpcStreamReader::new(std::io::Cursor::new(buf))
+        .set_rechunk(true)
+        .finish()?;
+    rename_to_component_ids(&mut df, &schema)?;
+    validate_schema(&df, metadata)?;
+    restore_field_metadata(&schema, metadata);
+    Ok(df)
+}
+
+fn write_section(buf: &mut Vec<u8>, section: &[u8]) {
+    buf.extend_from_slice(&(section.len() as u64).to_le_bytes());
+    buf.extend_from_slice(section);
+}
+
+/// Splits the next length-prefixed section written by [`write_section`] off the front of `buf`.
+fn read_section<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8], Error> {
+    if buf.len() < 8 {
+        return Err(Error::MalformedWorldBytes);
+    }
+    let (len, rest) = buf.split_at(8);
+    let len = u64::from_le_bytes(len.try_into().expect("length prefix is 8 bytes"));
+    let len = usize::try_from(len).map_err(|_| Error::MalformedWorldBytes)?;
+    if rest.len() < len {
+        return Err(Error::MalformedWorldBytes);
+    }
+    let (section, rest) = rest.split_at(len);
+    *buf = rest;
+    Ok(section)
+}
+
+fn read_archetype_ipc(path: &Path, metadata: &ArchetypeMetadata) -> Result<DataFrame, Error> {
+    let reader = arrow::ipc::reader::FileReader::try_new(File::open(path)?, None)?;
+    validate_shape_metadata(&reader.schema(), metadata)?;
+    let mut df = polars::prelude::IpcReader::new(File::open(path)?)
+        .set_rechunk(true)
+        .finish()?;
+    rename_to_component_ids(&mut df, &reader.schema())?;
+    validate_schema(&df, metadata)?;
+    Ok(df)
+}
+
+/// The codec of an `assets.bin` that starts with [`ASSETS_MAGIC`].
+#[repr(u8)]
+enum AssetsCodec {
+    None = 0,
+    Zstd = 1,
+}
+
+/// Reads `assets.bin`, which is either a bare postcard stream or [`ASSETS_MAGIC`] followed by
+/// an [`AssetsCodec`] byte and the encoded stream.
+fn read_assets(path: &Path) -> Result<AssetStore, Error> {
+    let buf = std::fs::read(path.join("assets.bin"))?;
+    let Some(rest) = buf.strip_prefix(ASSETS_MAGIC) else {
+        return Ok(postcard::from_bytes(&buf)?);
+    };
+    let (&codec, rest) = rest.split_first().ok_or(Error::UnknownAssetsCodec(None))?;
+    match codec {
+        c if c == AssetsCodec::None as u8 => Ok(postcard::from_bytes(rest)?),
+        c if c == AssetsCodec::Zstd as u8 => Ok(postcard::from_bytes(&zstd::decode_all(rest)?)?),
+        c => Err(Error::UnknownAssetsCodec(Some(c))),
+    }
+}
+
+/// Replaces the `entity_id` column of `batch` with a `Dictionary(UInt32, UInt64)` array.
+fn dictionary_encode_entity_ids(batch: &RecordBatch) -> Result<RecordBatch, Error> {
+    let dictionary_type = arrow::datatypes::DataType::Dictionary(
+        Box::new(arrow::datatypes::DataType::UInt32),
+        Box::new(arrow::datatypes::DataType::UInt64),
+    );
+    let entity_id_string = ENTITY_ID_COMPONENT.0.to_string();
+    let schema = batch.schema();
+    let mut fields = vec![];
+    let mut columns = vec![];
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        if field.name() == &entity_id_string {
+            columns.push(arrow::compute::cast(column, &dictionary_type)?);
+            fields.push(
+                field
+                    .as_ref()
+                    .clone()
+                    .with_data_type(dictionary_type.clone()),
+            );
+        } else {
+            columns.push(column.clone());
+            fields.push(field.as_ref().clone());
+        }
     }
+    Ok(RecordBatch::try_new(
+        Arc::new(Schema::new(fields)),
+        columns,
+    )?)
 }
 
-pub struct PolarsColumnRef<'a> {
-    entity_series: &'a Series,
-    buf: &'a Series,
-}
+fn read_archetype(
+    path: &Path,
+    metadata: &ArchetypeMetadata,
+    mmap: bool,
+) -> Result<DataFrame, Error> {
+    let file = File::open(path)?;
+    let map = if mmap {
+        // safety: the mapping is only read while the frame is decoded, and the file must not be
+        // modified in the meantime, see `PolarsWorld::read_from_dir_mmap`
+        Some(unsafe { memmap2::Mmap::map(&file)? })
+    } else {
+        None
+    };
+    let decode = |columns: Option<Vec<String>>| -> Result<DataFrame, Error> {
+        let df = match &map {
+            Some(map) => polars::prelude::ParquetReader::new(std::io::Cursor::new(&map[..]))
+                .with_columns(columns)
+                .set_rechunk(true)
+                .finish()?,
+            None => polars::prelude::ParquetReader::new(file.try_clone()?)
+                .with_columns(columns)
+                .set_rechunk(true)
+                .finish()?,
+        };
+        Ok(df)
+    };
+    let arrow_metadata = ArrowReaderMetadata::load(&file, Default::default())?;
+    validate_shape_metadata(arrow_metadata.schema(), metadata)?;
+    let entity_id_string = ENTITY_ID_COMPONENT.0.to_string();
+    let dictionary_index = arrow_metadata.schema().fields().iter().position(|field| {
+        field.name() == &entity_id_string
+            && matches!(
+                field.data_type(),
+                arrow::datatypes::DataType::Dictionary(_, _)
+            )
+    });
+    let Some(dictionary_index) = dictionary_index else {
+        let mut df = decode(None)?;
+        rename_to_component_ids(&mut df, arrow_metadata.schema())?;
+        validate_schema(&df, metadata)?;
+        return Ok(df);
+    };
+    // polars only supports string dictionaries, so the entity ids are decoded through arrow
+    let columns = arrow_metadata
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| field.name().clone())
+        .filter(|name| name != &entity_id_string)
+        .collect();
+    let mut df = decode(Some(columns))?;
+    rename_to_component_ids(&mut df, arrow_metadata.schema())?;
+    let mask = ProjectionMask::roots(arrow_metadata.parquet_schema(), [dictionary_index]);
+    let reader = ParquetRecordBatchReaderBuilder::new_with_metadata(file, arrow_metadata)
+        .with_projection(mask)
+        .build()?;
+    let mut entity_ids: Vec<u64> = vec![];
+    for batch in reader {
+        let column = arrow::compute::cast(batch?.column(0), &arrow::datatypes::DataType::UInt64)?;
+        let column = column
+            .as_any()
+            .downcast_ref::<arrow::array::UInt64Array>()
+            .ok_or(Error::SchemaMismatch)?;
+        entity_ids.extend(column.values().iter().copied());
+    }
+    df.with_column(Series::new(&entity_id_string, entity_ids))?;
+    validate_schema(&df, metadata)?;
+    Ok(df)
+}
+
+/// Checks that `df` has a series for every column in `metadata`, holding elements of the
+/// column's primitive type.
+fn validate_schema(df: &DataFrame, metadata: &ArchetypeMetadata) -> Result<(), Error> {
+    for column in &metadata.columns {
+        let series = df
+            .column(&column.metadata.component_id.0.to_string())
+            .map_err(|_| Error::SchemaMismatch)?;
+        let dtype = series.dtype();
+        let found = dtype.inner_dtype().unwrap_or(dtype);
+        let expected = polars::prelude::DataType::from(&polars_data_type(
+            column.metadata.component_type.primitive_ty,
+        ));
+        // decimals are read back into scalar i64 columns of their unscaled values
+        let decimal = matches!(found, polars::prelude::DataType::Decimal(_, _))
+            && column.metadata.component_type == ComponentType::i64();
+        if *found != expected && !decimal {
+            return Err(Error::SchemaMismatch);
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the checkpoint wasn't written by a newer version of this crate, whose format may
+/// differ in ways this version can't detect. Versions that can't be parsed are treated as newer.
+fn check_crate_version(metadata: &Metadata, strict: bool) -> Result<(), Error> {
+    let Some(found) = &metadata.crate_version else {
+        return Ok(());
+    };
+    let newer = match (
+        semver::Version::parse(found),
+        semver::Version::parse(CRATE_VERSION),
+    ) {
+        (Ok(found), Ok(current)) => found > current,
+        _ => true,
+    };
+    if !newer {
+        return Ok(());
+    }
+    if strict {
+        return Err(Error::NewerCrateVersion {
+            found: found.clone(),
+            current: CRATE_VERSION.to_string(),
+        });
+    }
+    tracing::warn!(
+        %found,
+        current = CRATE_VERSION,
+        "checkpoint was written by a newer version of nox-ecs"
+    );
+    Ok(())
+}
+
+/// Checks that every entry in `component_map` names a column of its archetype.
+///
+/// The readers check each archetype's frame against its columns with [`validate_schema`], so
+/// this also guarantees that the component resolves to a series in the loaded frame.
+fn validate_component_map(metadata: &Metadata) -> Result<(), Error> {
+    for (component_id, archetype_id) in &metadata.component_map {
+        let has_column = metadata
+            .archetypes
+            .get(archetype_id)
+            .is_some_and(|archetype| {
+                archetype
+                    .columns
+                    .iter()
+                    .any(|c| c.metadata.component_id == *component_id)
+            });
+        if !has_column {
+            return Err(Error::DanglingComponent(*component_id));
+        }
+    }
+    Ok(())
+}
+
+/// Records the shape of each tensor column as arrow extension metadata, as a `FixedSizeList`
+/// only carries the flattened length.
+fn with_shape_metadata(schema: &Schema, metadata: &ArchetypeMetadata) -> Schema {
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let field = field.as_ref().clone();
+            let shape = metadata
+                .columns
+                .iter()
+                .find(|c| c.metadata.component_id.0.to_string() == *field.name())
+                .map(|c| &c.metadata.component_type.shape)
+                .filter(|shape| !shape.is_empty());
+            match shape {
+                Some(shape) => field.with_metadata(HashMap::from([(
+                    SHAPE_METADATA_KEY.to_string(),
+                    serde_json::json!({ "shape": shape.as_slice() }).to_string(),
+                )])),
+                None => field,
+            }
+        })
+        .collect::<Vec<_>>();
+    Schema::new_with_metadata(fields, schema.metadata().clone())
+}
+
+/// Names each component field after its `name` tag, keeping the numeric id in the field
+/// metadata so the column can still be resolved by id when read back. A component's unit and
+/// description are copied into the field metadata as well.
+fn with_column_names(schema: &Schema, metadata: &ArchetypeMetadata) -> Schema {
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let field = field.as_ref().clone();
+            let Some(column) = metadata
+                .columns
+                .iter()
+                .find(|c| c.metadata.component_id.0.to_string() == *field.name())
+            else {
+                return field;
+            };
+            let mut field_metadata = field.metadata().clone();
+            field_metadata.insert(COMPONENT_ID_METADATA_KEY.to_string(), field.name().clone());
+            if let Some(unit) = column.metadata.unit() {
+                field_metadata.insert(UNIT_METADATA_KEY.to_string(), unit.to_string());
+            }
+            if let Some(description) = column.metadata.description() {
+                field_metadata.insert(
+                    DESCRIPTION_METADATA_KEY.to_string(),
+                    description.to_string(),
+                );
+            }
+            if let Some((precision, scale)) = column.metadata.decimal() {
+                if let Some(precision) = precision {
+                    field_metadata.insert(
+                        DECIMAL_PRECISION_METADATA_KEY.to_string(),
+                        precision.to_string(),
+                    );
+                }
+                field_metadata.insert(DECIMAL_SCALE_METADATA_KEY.to_string(), scale.to_string());
+            }
+            field
+                .with_name(column_name(&column.metadata))
+                .with_metadata(field_metadata)
+        })
+        .collect::<Vec<_>>();
+    Schema::new_with_metadata(fields, schema.metadata().clone())
+}
+
+/// Finds the field holding the component `id`, whether it is named by id or by its `name` tag.
+fn component_field<'a>(schema: &'a Schema, id: &str) -> Option<&'a arrow::datatypes::Field> {
+    schema.fields().iter().map(|f| f.as_ref()).find(|field| {
+        match field.metadata().get(COMPONENT_ID_METADATA_KEY) {
+            Some(field_id) => field_id == id,
+            None => field.name() == id,
+        }
+    })
+}
+
+/// Fills in any unit, description, or decimal precision and scale missing from `metadata` with
+/// the one stored on the component's field, so files whose `metadata.json` predates these fields
+/// keep them.
+fn restore_field_metadata(schema: &Schema, metadata: &mut ArchetypeMetadata) {
+    for column in &mut metadata.columns {
+        let Some(field) = component_field(schema, &column.metadata.component_id.0.to_string())
+        else {
+            continue;
+        };
+        let field_metadata = field.metadata();
+        if column.metadata.unit().is_none() {
+            if let Some(unit) = field_metadata.get(UNIT_METADATA_KEY) {
+                column.metadata.set_unit(unit);
+            }
+        }
+        if column.metadata.description().is_none() {
+            if let Some(description) = field_metadata.get(DESCRIPTION_METADATA_KEY) {
+                column.metadata.set_description(description);
+            }
+        }
+        if column.metadata.decimal().is_none() {
+            let scale = field_metadata
+                .get(DECIMAL_SCALE_METADATA_KEY)
+                .and_then(|s| s.parse().ok());
+            if let Some(scale) = scale {
+                let precision = field_metadata
+                    .get(DECIMAL_PRECISION_METADATA_KEY)
+                    .and_then(|p| p.parse().ok());
+                column.metadata.set_decimal(precision, scale);
+            }
+        }
+    }
+}
+
+/// Renames the columns of `df` that were written under their `name` tag back to their component id.
+fn rename_to_component_ids(df: &mut DataFrame, schema: &Schema) -> Result<(), Error> {
+    for field in schema.fields() {
+        if let Some(id) = field.metadata().get(COMPONENT_ID_METADATA_KEY) {
+            if field.name() != id {
+                df.rename(field.name(), id)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks the shapes recorded in `metadata` against the extension metadata written to the parquet file.
+fn validate_shape_metadata(schema: &Schema, metadata: &ArchetypeMetadata) -> Result<(), Error> {
+    for column in &metadata.columns {
+        let Some(field) = component_field(schema, &column.metadata.component_id.0.to_string())
+        else {
+            continue;
+        };
+        let Some(extension) = field.metadata().get(SHAPE_METADATA_KEY) else {
+            continue;
+        };
+        let extension: serde_json::Value = serde_json::from_str(extension)?;
+        let found = serde_json::from_value::<Vec<usize>>(extension["shape"].clone())?;
+        let expected = column.metadata.component_type.shape.to_vec();
+        if expected != found {
+            return Err(Error::ShapeMismatch { expected, found });
+        }
+    }
+    Ok(())
+}
+
+/// Returns the flattened values of `row` in `column` as a JSON array.
+fn row_to_json(column: &HostColumn, row: usize) -> Result<serde_json::Value, Error> {
+    fn values<T: ArrayElement + Pod + Into<serde_json::Value>>(
+        column: &HostColumn,
+        range: Range<usize>,
+    ) -> Result<serde_json::Value, Error> {
+        let buf = column.typed_buf::<T>().ok_or(Error::ValueSizeMismatch)?;
+        let values = buf.get(range).ok_or(Error::ValueSizeMismatch)?;
+        Ok(values.iter().copied().collect())
+    }
+    let width = column.component_type.shape.iter().product::<usize>();
+    let range = row * width..(row + 1) * width;
+    match column.component_type.primitive_ty {
+        PrimitiveTy::U8 => values::<u8>(column, range),
+        PrimitiveTy::U16 => values::<u16>(column, range),
+        PrimitiveTy::U32 => values::<u32>(column, range),
+        PrimitiveTy::U64 => values::<u64>(column, range),
+        PrimitiveTy::I8 => values::<i8>(column, range),
+        PrimitiveTy::I16 => values::<i16>(column, range),
+        PrimitiveTy::I32 => values::<i32>(column, range),
+        PrimitiveTy::I64 => values::<i64>(column, range),
+        PrimitiveTy::F32 => values::<f32>(column, range),
+        PrimitiveTy::F64 => values::<f64>(column, range),
+        PrimitiveTy::Bool => {
+            let values = column.buf.get(range).ok_or(Error::ValueSizeMismatch)?;
+            Ok(values.iter().map(|b| *b != 0).collect())
+        }
+        PrimitiveTy::F16 => {
+            let size = std::mem::size_of::<half::f16>();
+            let values = column
+                .buf
+                .get(range.start * size..range.end * size)
+                .ok_or(Error::ValueSizeMismatch)?;
+            Ok(values
+                .chunks_exact(size)
+                .map(|b| f64::from(half::f16::from_le_bytes([b[0], b[1]])))
+                .collect())
+        }
+    }
+}
+
+/// Returns the number of elements in a tensor of `shape`, which arrow stores as an i32 list size.
+fn flattened_len(shape: &[usize]) -> Result<usize, Error> {
+    let len = shape
+        .iter()
+        .try_fold(1usize, |len, dim| len.checked_mul(*dim))
+        .ok_or(Error::ShapeTooLarge)?;
+    i32::try_from(len).map_err(|_| Error::ShapeTooLarge)?;
+    Ok(len)
+}
+
+fn arrow_data_type(ty: PrimitiveTy) -> ArrowDataType {
+    match ty {
+        PrimitiveTy::U8 => ArrowDataType::UInt8,
+        PrimitiveTy::U16 => ArrowDataType::UInt16,
+        PrimitiveTy::U32 => ArrowDataType::UInt32,
+        PrimitiveTy::U64 => ArrowDataType::UInt64,
+        PrimitiveTy::I8 => ArrowDataType::Int8,
+        PrimitiveTy::I16 => ArrowDataType::Int16,
+        PrimitiveTy::I32 => ArrowDataType::Int32,
+        PrimitiveTy::I64 => ArrowDataType::Int64,
+        PrimitiveTy::F32 => ArrowDataType::Float32,
+        PrimitiveTy::F64 => ArrowDataType::Float64,
+        PrimitiveTy::Bool => ArrowDataType::Boolean,
+        PrimitiveTy::F16 => ArrowDataType::Float16,
+    }
+}
+
+/// The type a column is held as in a `DataFrame`. Polars has no half precision type, and widens
+/// `Float16` arrays to `Float32` on import, so `F16` columns are held (and written) as `f32`.
+fn polars_data_type(ty: PrimitiveTy) -> ArrowDataType {
+    match ty {
+        PrimitiveTy::F16 => ArrowDataType::Float32,
+        ty => arrow_data_type(ty),
+    }
+}
+
+fn tensor_array(ty: &ComponentType, inner: Box<dyn Array>) -> Result<Box<dyn Array>, Error> {
+    let data_type = polars_data_type(ty.primitive_ty);
+    if ty.shape.is_empty() {
+        return Ok(inner);
+    }
+    let data_type = ArrowDataType::FixedSizeList(
+        Box::new(polars_arrow::datatypes::Field::new(
+            "inner", data_type, false,
+        )),
+        flattened_len(&ty.shape)?,
+    );
+    Ok(Box::new(polars_arrow::array::FixedSizeListArray::new(
+        data_type, inner, None,
+    )))
+}
+
+pub struct RecordBatchRef<'a> {
+    phantom_data: PhantomData<&'a ()>,
+    record_batch: arrow::record_batch::RecordBatch,
+}
+
+impl<'a> RecordBatchRef<'a> {
+    fn record_batch<'b>(&'b self) -> &'a arrow::record_batch::RecordBatch
+    where
+        'b: 'a,
+    {
+        &self.record_batch
+    }
+}
+
+pub trait DataFrameConv {
+    fn to_record_batch(&self) -> Result<RecordBatchRef<'_>, Error>;
+}
+
+impl DataFrameConv for DataFrame {
+    fn to_record_batch(&self) -> Result<RecordBatchRef<'_>, Error> {
+        let mut fields = vec![];
+        let mut columns = vec![];
+        for series in self.iter() {
+            let name = series.name();
+            // safety: `to_array_data` is unsafe because it creates a unlifetimed
+            // reference to `Series`, using `RecordBatchRef` we ensure
+            // that Series's lifetime is tied to the RecordBatch lifetime,
+            // so the `Series` will always be alive while the `RecordBatch` is
+            let array_data = unsafe { series.to_array_data() };
+            let array: Arc<dyn arrow::array::Array> = match array_data.data_type() {
+                arrow::datatypes::DataType::Null => {
+                    Arc::new(arrow::array::NullArray::from(array_data))
+                }
+                arrow::datatypes::DataType::Boolean => {
+                    Arc::new(arrow::array::BooleanArray::from(array_data))
+                }
+                arrow::datatypes::DataType::Int8 => {
+                    Arc::new(arrow::array::Int8Array::from(array_data))
+                }
+                arrow::datatypes::DataType::Int16 => {
+                    Arc::new(arrow::array::Int16Array::from(array_data))
+                }
+                arrow::datatypes::DataType::Int32 => {
+                    Arc::new(arrow::array::Int32Array::from(array_data))
+                }
+                arrow::datatypes::DataType::Int64 => {
+                    Arc::new(arrow::array::Int64Array::from(array_data))
+                }
+                arrow::datatypes::DataType::UInt8 => {
+                    Arc::new(arrow::array::UInt8Array::from(array_data))
+                }
+                arrow::datatypes::DataType::UInt16 => {
+                    Arc::new(arrow::array::UInt16Array::from(array_data))
+                }
+                arrow::datatypes::DataType::UInt32 => {
+                    Arc::new(arrow::array::UInt32Array::from(array_data))
+                }
+                arrow::datatypes::DataType::UInt64 => {
+                    Arc::new(arrow::array::UInt64Array::from(array_data))
+                }
+                arrow::datatypes::DataType::Float16 => {
+                    Arc::new(arrow::array::Float16Array::from(array_data))
+                }
+                arrow::datatypes::DataType::Float32 => {
+                    Arc::new(arrow::array::Float32Array::from(array_data))
+                }
+                arrow::datatypes::DataType::Float64 => {
+                    Arc::new(arrow::array::Float64Array::from(array_data))
+                }
+                arrow::datatypes::DataType::Timestamp(u, _) => match u {
+                    arrow::datatypes::TimeUnit::Second => {
+                        Arc::new(arrow::array::TimestampSecondArray::from(array_data))
+                    }
+                    arrow::datatypes::TimeUnit::Millisecond => {
+                        Arc::new(arrow::array::TimestampMillisecondArray::from(array_data))
+                    }
+                    arrow::datatypes::TimeUnit::Microsecond => {
+                        Arc::new(arrow::array::TimestampMicrosecondArray::from(array_data))
+                    }
+                    arrow::datatypes::TimeUnit::Nanosecond => {
+                        Arc::new(arrow::array::TimestampNanosecondArray::from(array_data))
+                    }
+                },
+                arrow::datatypes::DataType::Date32 => {
+                    Arc::new(arrow::array::Date32Array::from(array_data))
+                }
+                arrow::datatypes::DataType::Date64 => {
+                    Arc::new(arrow::array::Date64Array::from(array_data))
+                }
+                arrow::datatypes::DataType::Time32(u) => match u {
+                    arrow::datatypes::TimeUnit::Second => {
+                        Arc::new(arrow::array::Time32SecondArray::from(array_data))
+                    }
+                    arrow::datatypes::TimeUnit::Millisecond => {
+                        Arc::new(arrow::array::Time32MillisecondArray::from(array_data))
+                    }
+                    arrow::datatypes::TimeUnit::Microsecond => {
+                        unimplemented!()
+                    }
+                    arrow::datatypes::TimeUnit::Nanosecond => {
+                        unimplemented!()
+                    }
+                },
+                arrow::datatypes::DataType::Time64(u) => match u {
+                    arrow::datatypes::TimeUnit::Second => {
+                        todo!()
+                    }
+                    arrow::datatypes::TimeUnit::Millisecond => {
+                        todo!()
+                    }
+                    arrow::datatypes::TimeUnit::Microsecond => {
+                        Arc::new(arrow::array::Time64MicrosecondArray::from(array_data))
+                    }
+                    arrow::datatypes::TimeUnit::Nanosecond => {
+                        Arc::new(arrow::array::Time64NanosecondArray::from(array_data))
+                    }
+                },
+                arrow::datatypes::DataType::Duration(_) => todo!(),
+                arrow::datatypes::DataType::Interval(_) => todo!(),
+                arrow::datatypes::DataType::Binary => {
+                    Arc::new(arrow::array::BinaryArray::from(array_data))
+                }
+                arrow::datatypes::DataType::FixedSizeBinary(_) => {
+                    Arc::new(arrow::array::FixedSizeBinaryArray::from(array_data))
+                }
+                arrow::datatypes::DataType::LargeBinary => {
+                    Arc::new(arrow::array::LargeBinaryArray::from(array_data))
+                }
+                arrow::datatypes::DataType::Utf8 => {
+                    Arc::new(arrow::array::StringArray::from(array_data))
+                }
+                arrow::datatypes::DataType::LargeUtf8 => {
+                    Arc::new(arrow::array::LargeStringArray::from(array_data))
+                }
+                arrow::datatypes::DataType::List(_) => Arc::new(ListArray::from(array_data)),
+                arrow::datatypes::DataType::FixedSizeList(_, _) => {
+                    Arc::new(arrow::array::FixedSizeListArray::from(array_data))
+                }
+                arrow::datatypes::DataType::LargeList(_) => {
+                    Arc::new(LargeListArray::from(array_data))
+                }
+                arrow::datatypes::DataType::Struct(_) => Arc::new(StructArray::from(array_data)),
+                arrow::datatypes::DataType::Union(_, _) => Arc::new(UnionArray::from(array_data)),
+                arrow::datatypes::DataType::Dictionary(key, _) => {
+                    // the key type borrows from `array_data`, which the array takes ownership of
+                    dictionary_array(key.as_ref().clone(), array_data)
+                }
+                arrow::datatypes::DataType::Decimal128(_, _) => {
+                    Arc::new(arrow::array::Decimal128Array::from(array_data))
+                }
+                arrow::datatypes::DataType::Decimal256(_, _) => todo!(),
+                arrow::datatypes::DataType::Map(_, _) => Arc::new(MapArray::from(array_data)),
+                arrow::datatypes::DataType::RunEndEncoded(_, _) => todo!(),
+            };
+
+            let field = Field::new(name, array.data_type().clone(), false);
+            fields.push(field);
+            columns.push(array);
+        }
+        let schema = Arc::new(Schema::new(fields));
+        let batch = RecordBatch::try_new(schema, columns)?;
+        Ok(RecordBatchRef {
+            phantom_data: PhantomData,
+            record_batch: batch,
+        })
+    }
+}
+
+/// Builds the `DictionaryArray` for `array_data` with keys of type `key`, such as the `UInt32`
+/// keyed arrays polars exports for categorical series.
+fn dictionary_array(
+    key: arrow::datatypes::DataType,
+    array_data: ArrayData,
+) -> Arc<dyn arrow::array::Array> {
+    use arrow::datatypes::{
+        Int16Type, Int32Type, Int64Type, Int8Type, UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+    };
+    match key {
+        arrow::datatypes::DataType::Int8 => {
+            Arc::new(arrow::array::DictionaryArray::<Int8Type>::from(array_data))
+        }
+        arrow::datatypes::DataType::Int16 => {
+            Arc::new(arrow::array::DictionaryArray::<Int16Type>::from(array_data))
+        }
+        arrow::datatypes::DataType::Int32 => {
+            Arc::new(arrow::array::DictionaryArray::<Int32Type>::from(array_data))
+        }
+        arrow::datatypes::DataType::Int64 => {
+            Arc::new(arrow::array::DictionaryArray::<Int64Type>::from(array_data))
+        }
+        arrow::datatypes::DataType::UInt8 => {
+            Arc::new(arrow::array::DictionaryArray::<UInt8Type>::from(array_data))
+        }
+        arrow::datatypes::DataType::UInt16 => Arc::new(
+            arrow::array::DictionaryArray::<UInt16Type>::from(array_data),
+        ),
+        arrow::datatypes::DataType::UInt32 => Arc::new(
+            arrow::array::DictionaryArray::<UInt32Type>::from(array_data),
+        ),
+        arrow::datatypes::DataType::UInt64 => Arc::new(
+            arrow::array::DictionaryArray::<UInt64Type>::from(array_data),
+        ),
+        _ => arrow::array::make_array(array_data),
+    }
+}
+
+fn fill_nulls(series: &Series, component_id: ComponentId) -> Result<Cow<'_, Series>, Error> {
+    let dtype = series.dtype();
+    if matches!(
+        dtype,
+        polars::prelude::DataType::Array(..) | polars::prelude::DataType::List(_)
+    ) {
+        // safety: `data` is dropped before `series`
+        let data = unsafe { series.to_array_data() };
+        if has_nulls(&data) {
+            return Err(Error::NullsUnsupported(component_id));
+        }
+        return Ok(Cow::Borrowed(series));
+    }
+    let fillable =
+        dtype.is_float() || dtype.is_integer() || dtype == &polars::prelude::DataType::Boolean;
+    if series.null_count() == 0 || !fillable {
+        return Ok(Cow::Borrowed(series));
+    }
+    let fill = if dtype.is_float() {
+        Series::new(series.name(), vec![f64::NAN; series.len()])
+    } else {
+        Series::new(series.name(), vec![0u8; series.len()])
+    };
+    let filled = series.zip_with(&series.is_not_null(), &fill.cast(dtype)?)?;
+    Ok(Cow::Owned(filled))
+}
+
+fn has_nulls(data: &ArrayData) -> bool {
+    data.null_count() > 0 || data.child_data().iter().any(has_nulls)
+}
+
+pub trait SeriesExt {
+    fn to_bytes(&self) -> Vec<u8>;
+    unsafe fn to_array_data(&self) -> ArrayData;
+}
+
+impl SeriesExt for Series {
+    fn to_bytes(&self) -> Vec<u8> {
+        // safety: we ensure that we only use the
+        // returned `ArrayData` while `Series` is in
+        // scope, so this is safe
+        let data = unsafe { self.to_array_data() };
+        let mut out = Vec::default();
+        recurse_array_data(&data, &mut out);
+        out
+    }
+
+    unsafe fn to_array_data(&self) -> ArrayData {
+        let array = self.to_arrow(0, false);
+        let field = self.field();
+        let field = field.to_arrow(false);
+        let schema = polars_arrow::ffi::export_field_to_c(&field);
+        // safety: these two types have identical layouts
+        // as they are both guarenteed to match the c-ffi layout
+        let schema = unsafe { std::mem::transmute(schema) };
+        let array = polars_arrow::ffi::export_array_to_c(array);
+        // safety: these two types have identical layouts
+        // as they are both guarenteed to match the c-ffi layout
+        let array: FFI_ArrowArray = unsafe { std::mem::transmute(array) };
+        // safety: this function requires the user ensure that `Series`
+        // is alive while `ArrayData` is accessible
+        unsafe { arrow::ffi::from_ffi(array, &schema) }.expect("polars arrow layout disagreement")
+    }
+}
+
+pub fn recurse_array_data(array_data: &ArrayData, out: &mut Vec<u8>) {
+    // arrow packs booleans into bits, which are expanded to the one byte per value that
+    // `PrimitiveTy::Bool` columns hold
+    if array_data.data_type() == &arrow::datatypes::DataType::Boolean {
+        let bits = arrow::buffer::BooleanBuffer::new(
+            array_data.buffers()[0].clone(),
+            array_data.offset(),
+            array_data.len(),
+        );
+        out.extend(bits.iter().map(u8::from));
+        return;
+    }
+    for child in array_data.child_data() {
+        recurse_array_data(child, out)
+    }
+    for buffer in array_data.buffers() {
+        out.extend_from_slice(buffer.as_slice())
+    }
+}
+
+pub struct PolarsColumnRef<'a> {
+    entity_series: &'a Series,
+    buf: &'a Series,
+    asset: bool,
+}
+
+impl<'a> ColumnStore for &'a PolarsWorld {
+    type Column<'b> = PolarsColumnRef<'b> where Self: 'b;
+
+    fn transfer_column(&mut self, _id: ComponentId) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn column(&self, id: ComponentId) -> Result<Self::Column<'_>, Error> {
+        let archetype = self
+            .metadata
+            .component_map
+            .get(&id)
+            .ok_or(Error::ComponentNotFound)?;
+        let table = self
+            .archetypes
+            .get(archetype)
+            .ok_or(Error::ComponentNotFound)?;
+        let metadata = self
+            .metadata
+            .archetypes
+            .get(archetype)
+            .ok_or(Error::ComponentNotFound)?;
+        let asset = metadata
+            .columns
+            .iter()
+            .find(|c| c.metadata.component_id == id)
+            .map(|c| c.asset)
+            .unwrap_or_default();
+        Ok(PolarsColumnRef {
+            entity_series: metadata.series(table, ENTITY_ID_COMPONENT)?,
+            buf: metadata.series(table, id)?,
+            asset,
+        })
+    }
+
+    fn assets(&self) -> Option<&AssetStore> {
+        Some(&self.assets)
+    }
+
+    fn tick(&self) -> u64 {
+        self.metadata.tick
+    }
+
+    fn component_ids(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        self.metadata.component_map.keys().copied()
+    }
+}
+
+impl ColumnRef for PolarsColumnRef<'_> {
+    fn len(&self) -> usize {
+        self.entity_series.len()
+    }
+
+    fn entity_buf(&self) -> std::borrow::Cow<'_, [u8]> {
+        Cow::Owned(self.entity_series.to_bytes())
+    }
+
+    fn value_buf(&self) -> std::borrow::Cow<'_, [u8]> {
+        Cow::Owned(self.buf.to_bytes())
+    }
+
+    fn is_asset(&self) -> bool {
+        self.asset
+    }
+}
+
+/// A [`PolarsWorld`] whose archetypes are converted to [`Table`]s on first access, rather than
+/// all at once as `World::try_from` does.
+///
+/// Decoded tables are kept in a cache bounded by a byte budget (unbounded by default); once the
+/// budget is exceeded, the least recently accessed tables are evicted and decoded again when
+/// they are next needed.
+pub struct LazyWorld {
+    polars: PolarsWorld,
+    cache: Mutex<TableCache>,
+    decode_count: AtomicUsize,
+}
+
+struct TableCache {
+    budget: usize,
+    size: usize,
+    /// The decoded tables and their sizes in bytes, from least to most recently accessed
+    tables: Vec<(ArchetypeId, Arc<Table<HostStore>>, usize)>,
+}
+
+impl TableCache {
+    fn get(&mut self, id: ArchetypeId) -> Option<Arc<Table<HostStore>>> {
+        let index = self.tables.iter().position(|(i, _, _)| *i == id)?;
+        let entry = self.tables.remove(index);
+        let table = entry.1.clone();
+        self.tables.push(entry);
+        Some(table)
+    }
+
+    fn insert(&mut self, id: ArchetypeId, table: Arc<Table<HostStore>>) {
+        let size = table
+            .columns
+            .values()
+            .map(|c| c.buffer.buf.len())
+            .sum::<usize>()
+            + table.entity_buffer.buf.len();
+        self.size += size;
+        self.tables.push((id, table, size));
+        self.evict();
+    }
+
+    /// Evicts the least recently accessed tables until the cache fits in its budget, always
+    /// keeping the most recent one.
+    fn evict(&mut self) {
+        while self.size > self.budget && self.tables.len() > 1 {
+            let (_, _, size) = self.tables.remove(0);
+            self.size -= size;
+        }
+    }
+}
+
+impl LazyWorld {
+    pub fn new(polars: PolarsWorld) -> Self {
+        Self {
+            polars,
+            cache: Mutex::new(TableCache {
+                budget: usize::MAX,
+                size: 0,
+                tables: vec![],
+            }),
+            decode_count: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn read_from_dir(path: impl AsRef<Path>) -> Result<Self, Error> {
+        PolarsWorld::read_from_dir(path).map(Self::new)
+    }
+
+    /// Limits the combined size of the decoded tables kept in memory to `bytes`, evicting tables
+    /// immediately if the cache is already over the new budget.
+    pub fn set_cache_budget(&self, bytes: usize) {
+        let mut cache = self.cache.lock().unwrap();
+        cache.budget = bytes;
+        cache.evict();
+    }
+
+    pub fn table(&self, id: ArchetypeId) -> Result<Arc<Table<HostStore>>, Error> {
+        let mut cache = self.cache.lock().unwrap();
+        if let Some(table) = cache.get(id) {
+            return Ok(table);
+        }
+        let df = self
+            .polars
+            .archetypes
+            .get(&id)
+            .ok_or(Error::ComponentNotFound)?;
+        let metadata = self
+            .polars
+            .metadata
+            .archetypes
+            .get(&id)
+            .ok_or(Error::ComponentNotFound)?;
+        self.decode_count.fetch_add(1, Ordering::Relaxed);
+        let table = Arc::new(Table::from_dataframe(df.clone(), metadata.clone())?);
+        cache.insert(id, table.clone());
+        Ok(table)
+    }
+
+    /// The number of times an archetype has been decoded so far
+    pub fn decode_count(&self) -> usize {
+        self.decode_count.load(Ordering::Relaxed)
+    }
+}
+
+impl ColumnStore for LazyWorld {
+    type Column<'a> = LazyColumnRef;
+
+    fn transfer_column(&mut self, _id: ComponentId) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn column(&self, id: ComponentId) -> Result<Self::Column<'_>, Error> {
+        let archetype_id = self
+            .polars
+            .metadata
+            .component_map
+            .get(&id)
+            .ok_or(Error::ComponentNotFound)?;
+        let table = self.table(*archetype_id)?;
+        if !table.columns.contains_key(&id) {
+            return Err(Error::ComponentNotFound);
+        }
+        Ok(LazyColumnRef { table, id })
+    }
+
+    fn assets(&self) -> Option<&AssetStore> {
+        Some(&self.polars.assets)
+    }
+
+    fn tick(&self) -> u64 {
+        self.polars.metadata.tick
+    }
+
+    fn component_ids(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        self.polars.metadata.component_map.keys().copied()
+    }
+}
+
+/// A column of a table decoded by [`LazyWorld`]. It holds on to the table, so the column stays
+/// valid even if the table is evicted from the cache in the meantime.
+pub struct LazyColumnRef {
+    table: Arc<Table<HostStore>>,
+    id: ComponentId,
+}
+
+impl LazyColumnRef {
+    pub fn host_column(&self) -> HostColumnRef<'_> {
+        HostColumnRef {
+            column: self.column(),
+            entities: &self.table.entity_buffer,
+            entity_map: &self.table.entity_map,
+        }
+    }
+
+    fn column(&self) -> &Column<HostStore> {
+        &self.table.columns[&self.id]
+    }
+}
+
+impl ColumnRef for LazyColumnRef {
+    fn len(&self) -> usize {
+        self.column().buffer.len
+    }
+
+    fn entity_buf(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(&self.table.entity_buffer.buf)
+    }
+
+    fn value_buf(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(&self.column().buffer.buf)
+    }
+
+    fn is_asset(&self) -> bool {
+        self.column().buffer.asset
+    }
+}
+
+/// A checkpoint written by [`PolarsWorld::write_to_dir`] that is read from disk on demand.
+///
+/// Opening it only loads `metadata.json`, the assets and each archetype's parquet footer. Columns
+/// from [`StreamingPolarsWorld::streaming_column`] are decoded one row group at a time by
+/// [`StreamingColumnRef::chunks`], projecting away every other component, so worlds larger than
+/// memory can still be inspected or replayed.
+pub struct StreamingPolarsWorld {
+    path: PathBuf,
+    metadata: Metadata,
+    assets: AssetStore,
+    files: BTreeMap<ArchetypeId, ArrowReaderMetadata>,
+}
+
+impl StreamingPolarsWorld {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let mut metadata: Metadata =
+            serde_json::from_reader(File::open(path.join("metadata.json"))?)?;
+        check_crate_version(&metadata, false)?;
+        metadata.upgrade()?;
+        validate_component_map(&metadata)?;
+        let mut files = BTreeMap::new();
+        for (id, archetype) in &metadata.archetypes {
+            let file = File::open(path.join(ArchetypeFormat::Parquet.file_name(*id)))?;
+            let arrow_metadata = ArrowReaderMetadata::load(&file, Default::default())?;
+            validate_shape_metadata(arrow_metadata.schema(), archetype)?;
+            files.insert(*id, arrow_metadata);
+        }
+        let assets = read_assets(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            metadata,
+            assets,
+            files,
+        })
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Opens the column storing `id` without decoding any of it yet.
+    pub fn streaming_column(&self, id: ComponentId) -> Result<StreamingColumnRef<'_>, Error> {
+        let archetype_id = self
+            .metadata
+            .component_map
+            .get(&id)
+            .ok_or(Error::ComponentNotFound)?;
+        let arrow_metadata = self
+            .files
+            .get(archetype_id)
+            .ok_or(Error::ComponentNotFound)?;
+        let asset = self
+            .metadata
+            .archetypes
+            .get(archetype_id)
+            .and_then(|archetype| {
+                archetype
+                    .columns
+                    .iter()
+                    .find(|c| c.metadata.component_id == id)
+            })
+            .ok_or(Error::ComponentNotFound)?
+            .asset;
+        let schema = arrow_metadata.schema();
+        let field_index = |id: ComponentId| {
+            let field = component_field(schema, &id.0.to_string()).ok_or(Error::SchemaMismatch)?;
+            Ok::<_, Error>(schema.index_of(field.name())?)
+        };
+        Ok(StreamingColumnRef {
+            file: File::open(
+                self.path
+                    .join(ArchetypeFormat::Parquet.file_name(*archetype_id)),
+            )?,
+            arrow_metadata,
+            entity_index: field_index(ENTITY_ID_COMPONENT)?,
+            value_index: field_index(id)?,
+            asset,
+        })
+    }
+}
+
+impl ColumnStore for StreamingPolarsWorld {
+    type Column<'a> = StreamingChunk;
+
+    fn transfer_column(&mut self, _id: ComponentId) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Decodes every row group of the column, see [`StreamingColumnRef::decode`].
+    fn column(&self, id: ComponentId) -> Result<Self::Column<'_>, Error> {
+        self.streaming_column(id)?.decode()
+    }
+
+    fn assets(&self) -> Option<&AssetStore> {
+        Some(&self.assets)
+    }
+
+    fn tick(&self) -> u64 {
+        self.metadata.tick
+    }
+
+    fn component_ids(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        self.metadata.component_map.keys().copied()
+    }
+}
+
+/// A single component of a [`StreamingPolarsWorld`], backed by its archetype's parquet file.
+///
+/// Use [`StreamingColumnRef::chunks`] to keep only one row group in memory at a time, or
+/// [`StreamingColumnRef::decode`] to read the whole column at once.
+pub struct StreamingColumnRef<'a> {
+    file: File,
+    arrow_metadata: &'a ArrowReaderMetadata,
+    entity_index: usize,
+    value_index: usize,
+    asset: bool,
+}
+
+impl StreamingColumnRef<'_> {
+    pub fn num_row_groups(&self) -> usize {
+        self.arrow_metadata.metadata().num_row_groups()
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.arrow_metadata.metadata().file_metadata().num_rows() as usize
+    }
+
+    /// Decodes the entity ids and values stored in the `index`th row group.
+    fn chunk(&self, index: usize) -> Result<StreamingChunk, Error> {
+        let row_group = self.arrow_metadata.metadata().row_group(index);
+        let mask = ProjectionMask::roots(
+            self.arrow_metadata.parquet_schema(),
+            [self.entity_index, self.value_index],
+        );
+        let reader = ParquetRecordBatchReaderBuilder::new_with_metadata(
+            self.file.try_clone()?,
+            self.arrow_metadata.clone(),
+        )
+        .with_projection(mask)
+        .with_row_groups(vec![index])
+        .with_batch_size(row_group.num_rows().max(1) as usize)
+        .build()?;
+        let schema = reader.schema();
+        let batches = reader.collect::<Result<Vec<_>, _>>()?;
+        let batch = arrow::compute::concat_batches(&schema, &batches)?;
+        // the projected batch keeps the file's column order, so the columns are looked up by name
+        let column = |index: usize| {
+            let name = self.arrow_metadata.schema().field(index).name();
+            batch
+                .column_by_name(name)
+                .cloned()
+                .ok_or(Error::SchemaMismatch)
+        };
+        let entity_ids = arrow::compute::cast(
+            &column(self.entity_index)?,
+            &arrow::datatypes::DataType::UInt64,
+        )?;
+        Ok(StreamingChunk {
+            entity_ids,
+            values: column(self.value_index)?,
+            asset: self.asset,
+        })
+    }
+
+    /// Decodes the column one row group at a time.
+    pub fn chunks(&self) -> impl Iterator<Item = Result<StreamingChunk, Error>> + '_ {
+        (0..self.num_row_groups()).map(|index| self.chunk(index))
+    }
+
+    /// Decodes every row group and concatenates them into a single chunk.
+    pub fn decode(&self) -> Result<StreamingChunk, Error> {
+        let chunks = self.chunks().collect::<Result<Vec<_>, _>>()?;
+        let concat = |arrays: Vec<&dyn arrow::array::Array>,
+                      data_type: &arrow::datatypes::DataType| {
+            if arrays.is_empty() {
+                return Ok::<_, Error>(arrow::array::new_empty_array(data_type));
+            }
+            Ok(arrow::compute::concat(&arrays)?)
+        };
+        let entity_ids = concat(
+            chunks.iter().map(|c| c.entity_ids.as_ref()).collect(),
+            &arrow::datatypes::DataType::UInt64,
+        )?;
+        let value_type = self
+            .arrow_metadata
+            .schema()
+            .field(self.value_index)
+            .data_type();
+        let values = concat(
+            chunks.iter().map(|c| c.values.as_ref()).collect(),
+            value_type,
+        )?;
+        Ok(StreamingChunk {
+            entity_ids,
+            values,
+            asset: self.asset,
+        })
+    }
+}
+
+/// One or more row groups of a [`StreamingColumnRef`].
+pub struct StreamingChunk {
+    entity_ids: arrow::array::ArrayRef,
+    values: arrow::array::ArrayRef,
+    asset: bool,
+}
+
+impl ColumnRef for StreamingChunk {
+    fn len(&self) -> usize {
+        self.entity_ids.len()
+    }
+
+    fn entity_buf(&self) -> Cow<'_, [u8]> {
+        let mut out = vec![];
+        recurse_array_data(&self.entity_ids.to_data(), &mut out);
+        Cow::Owned(out)
+    }
+
+    fn value_buf(&self) -> Cow<'_, [u8]> {
+        let mut out = vec![];
+        recurse_array_data(&self.values.to_data(), &mut out);
+        Cow::Owned(out)
+    }
+
+    fn is_asset(&self) -> bool {
+        self.asset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        six_dof::{Body, Force, Inertia, WorldAccel, WorldVel},
+        Component, Handle, WorldPos,
+    };
+    use conduit::{
+        well_known::{Material, Mesh, Pbr},
+        ComponentId,
+    };
+    use nox::{
+        nalgebra::{self, vector},
+        SpatialForce, SpatialInertia, SpatialMotion, SpatialTransform,
+    };
+    use polars::prelude::*;
+    use polars_arrow::array::Float64Array;
+
+    use super::*;
+
+    fn body(pbr: Handle<Pbr>, x: f64) -> Body {
+        Body {
+            pos: WorldPos(SpatialTransform {
+                inner: vector![1.0, 0.0, 0.0, 0.0, x, 0.0, 0.0].into(),
+            }),
+            vel: WorldVel(SpatialMotion {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 1.0].into(),
+            }),
+            accel: WorldAccel(SpatialMotion {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            pbr,
+            force: Force(SpatialForce {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            mass: Inertia(SpatialInertia {
+                inner: vector![1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0].into(),
+            }),
+        }
+    }
+
+    fn spawn_bodies(world: &mut World, count: usize) {
+        let pbr = world.insert_asset(Pbr::Bundle {
+            mesh: Mesh::sphere(0.1, 36, 18),
+            material: Material::color(1.0, 1.0, 1.0),
+        });
+        for i in 0..count {
+            world.spawn(body(pbr, i as f64));
+        }
+    }
+
+    #[test]
+    fn test_convert_to_df() {
+        let mut world = World::default();
+        let pbr = world.insert_asset(Pbr::Bundle {
+            mesh: Mesh::sphere(0.1, 36, 18),
+            material: Material::color(1.0, 1.0, 1.0),
+        });
+
+        world.spawn(Body {
+            pos: WorldPos(SpatialTransform {
+                inner: vector![1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0].into(),
+            }),
+            vel: WorldVel(SpatialMotion {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 1.0].into(),
+            }),
+            accel: WorldAccel(SpatialMotion {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            pbr,
+            force: Force(SpatialForce {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            mass: Inertia(SpatialInertia {
+                inner: vector![1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0].into(),
+            }),
+        });
+        let polars = world.to_polars().unwrap();
+        let df = polars.archetypes[&ArchetypeId::of::<Body>()].clone();
+        let out = df
+            .lazy()
+            .select(&[col(&ComponentId::new("world_pos").0.to_string())])
+            .collect()
+            .unwrap();
+        let pos = out
+            .iter()
+            .next()
+            .unwrap()
+            .array()
+            .unwrap()
+            .get(0)
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap()
+            .iter()
+            .filter_map(|f| f.copied())
+            .collect::<Vec<_>>();
+        assert_eq!(pos, &[1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_write_read_file() {
+        let mut world = World::default();
+        let pbr = world.insert_asset(Pbr::Bundle {
+            mesh: Mesh::sphere(0.1, 36, 18),
+            material: Material::color(1.0, 1.0, 1.0),
+        });
+        world.spawn(Body {
+            pos: WorldPos(SpatialTransform {
+                inner: vector![1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0].into(),
+            }),
+            vel: WorldVel(SpatialMotion {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 1.0].into(),
+            }),
+            accel: WorldAccel(SpatialMotion {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            pbr,
+            force: Force(SpatialForce {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            mass: Inertia(SpatialInertia {
+                inner: vector![1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0].into(),
+            }),
+        });
+        let mut polars = world.to_polars().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        polars.write_to_dir(&dir).unwrap();
+        let new_polars = PolarsWorld::read_from_dir(&dir).unwrap();
+        assert_eq!(polars.archetypes, new_polars.archetypes);
+    }
+
+    #[test]
+    fn test_ipc_round_trip() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 4);
+        let mut polars = world.to_polars().unwrap();
+        let parquet_dir = tempfile::tempdir().unwrap();
+        let ipc_dir = tempfile::tempdir().unwrap();
+        polars.write_to_dir(parquet_dir.path()).unwrap();
+        polars.write_ipc_to_dir(ipc_dir.path()).unwrap();
+        assert!(ipc_dir
+            .path()
+            .join(format!("{}.arrow", ArchetypeId::of::<Body>().to_raw()))
+            .exists());
+
+        let from_parquet = PolarsWorld::read_from_dir(parquet_dir.path()).unwrap();
+        let from_ipc = PolarsWorld::read_ipc_from_dir(ipc_dir.path()).unwrap();
+        assert_eq!(from_ipc.archetypes, from_parquet.archetypes);
+        assert_eq!(from_ipc.archetypes, polars.archetypes);
+
+        let pos = WorldPos::component_id().0.to_string();
+        let df = &from_ipc.archetypes[&ArchetypeId::of::<Body>()];
+        assert_eq!(
+            df.column(&pos).unwrap().dtype(),
+            &DataType::Array(Box::new(DataType::Float64), 7)
+        );
+        let world = World::try_from(from_ipc).unwrap();
+        assert_eq!(world.archetypes.len(), 1);
+    }
+
+    #[test]
+    fn test_read_from_dir_mmap() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 4);
+        let mut polars = world.to_polars().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        polars.write_to_dir(dir.path()).unwrap();
+
+        let eager = PolarsWorld::read_from_dir(dir.path()).unwrap();
+        let mapped = PolarsWorld::read_from_dir_mmap(dir.path()).unwrap();
+        assert_eq!(mapped.archetypes, eager.archetypes);
+        assert!(mapped.diff(&eager).is_empty());
+        let world = World::try_from(mapped).unwrap();
+        let pos = world.column_by_id(WorldPos::component_id()).unwrap();
+        assert_eq!(pos.typed_buf::<f64>().unwrap()[4], 0.0);
+        assert_eq!(pos.typed_buf::<f64>().unwrap()[11], 1.0);
+
+        let dir = tempfile::tempdir().unwrap();
+        let options = WriteOptions {
+            dictionary_entity_ids: true,
+            ..Default::default()
+        };
+        polars
+            .write_to_dir_with_options(dir.path(), &options)
+            .unwrap();
+        let eager = PolarsWorld::read_from_dir(dir.path()).unwrap();
+        let mapped = PolarsWorld::read_from_dir_mmap(dir.path()).unwrap();
+        assert_eq!(mapped.archetypes, eager.archetypes);
+    }
+
+    #[test]
+    fn test_ipc_bytes_round_trip() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 4);
+        let mut polars = world.to_polars().unwrap();
+        let bytes = polars.to_ipc_bytes().unwrap();
+        let new_polars = PolarsWorld::from_ipc_bytes(&bytes).unwrap();
+        assert_eq!(new_polars.archetypes, polars.archetypes);
+        assert_eq!(
+            new_polars.metadata.component_map,
+            polars.metadata.component_map
+        );
+
+        assert!(matches!(
+            PolarsWorld::from_ipc_bytes(&bytes[..bytes.len() - 1]),
+            Err(Error::MalformedWorldBytes)
+        ));
+    }
+
+    #[test]
+    fn test_read_validates_component_columns() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 2);
+        let mut polars = world.to_polars().unwrap();
+        let body_id = ArchetypeId::of::<Body>();
+        let pos = WorldPos::component_id();
+        // drop the column from the frame and its archetype, but leave it in the component map
+        let df = polars.archetypes.get_mut(&body_id).unwrap();
+        *df = df.drop(&pos.0.to_string()).unwrap();
+        let archetype = polars.metadata.archetypes.get_mut(&body_id).unwrap();
+        archetype.columns.retain(|c| c.metadata.component_id != pos);
+        archetype.rebuild_column_index();
+        assert!(polars.metadata.component_map.contains_key(&pos));
+
+        let dir = tempfile::tempdir().unwrap();
+        polars.write_to_dir(dir.path()).unwrap();
+        let res = PolarsWorld::read_from_dir(dir.path());
+        assert!(matches!(res, Err(Error::DanglingComponent(id)) if id == pos));
+    }
+
+    #[test]
+    fn test_to_world() {
+        let mut world = World::default();
+        let pbr = world.insert_asset(Pbr::Bundle {
+            mesh: Mesh::sphere(0.1, 36, 18),
+            material: Material::color(1.0, 1.0, 1.0),
+        });
+
+        world.spawn(Body {
+            pos: WorldPos(SpatialTransform {
+                inner: vector![1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0].into(),
+            }),
+            vel: WorldVel(SpatialMotion {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 1.0].into(),
+            }),
+            accel: WorldAccel(SpatialMotion {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            pbr,
+            force: Force(SpatialForce {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            mass: Inertia(SpatialInertia {
+                inner: vector![1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0].into(),
+            }),
+        });
+        let polars = world.to_polars().unwrap();
+        let new_world = World::try_from(polars).unwrap();
+        assert_eq!(new_world.archetypes, world.archetypes);
+    }
+
+    #[test]
+    fn test_write_read_world() {
+        let mut world = World::default();
+        let pbr = world.insert_asset(Pbr::Bundle {
+            mesh: Mesh::sphere(0.1, 36, 18),
+            material: Material::color(1.0, 1.0, 1.0),
+        });
+        world.spawn(Body {
+            pos: WorldPos(SpatialTransform {
+                inner: vector![1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0].into(),
+            }),
+            vel: WorldVel(SpatialMotion {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 1.0].into(),
+            }),
+            accel: WorldAccel(SpatialMotion {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            pbr,
+            force: Force(SpatialForce {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            mass: Inertia(SpatialInertia {
+                inner: vector![1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0].into(),
+            }),
+        });
+        let mut polars = world.to_polars().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        polars.write_to_dir(&dir).unwrap();
+        let new_polars = PolarsWorld::read_from_dir(&dir).unwrap();
+        let new_world = World::try_from(new_polars).unwrap();
+        assert_eq!(new_world.archetypes, world.archetypes);
+    }
+
+    #[test]
+    fn test_deterministic_write() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 4);
+        let a = tempfile::tempdir().unwrap();
+        let b = tempfile::tempdir().unwrap();
+        world.to_polars().unwrap().write_to_dir(a.path()).unwrap();
+        world
+            .clone()
+            .to_polars()
+            .unwrap()
+            .write_to_dir(b.path())
+            .unwrap();
+        let mut files = std::fs::read_dir(a.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect::<Vec<_>>();
+        files.sort();
+        assert!(!files.is_empty());
+        for file in files {
+            let a = std::fs::read(a.path().join(&file)).unwrap();
+            let b = std::fs::read(b.path().join(&file)).unwrap();
+            assert_eq!(a, b, "{:?} differs between writes", file);
+        }
+    }
+
+    #[test]
+    fn test_set_component() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 2);
+        let mut polars = world.to_polars().unwrap();
+        let id = ComponentId::new("world_pos");
+        let values = ndarray::array![
+            [0.0, 0.0, 0.0, 1.0, 5.0, 6.0, 7.0],
+            [0.0, 0.0, 0.0, 1.0, 8.0, 9.0, 10.0],
+        ];
+        polars.set_component(id, values).unwrap();
+
+        let df = &polars.archetypes[&ArchetypeId::of::<Body>()];
+        let series = df.column(&id.0.to_string()).unwrap();
+        let column = HostColumn::from_series(series, WorldPos::component_type(), false).unwrap();
+        assert_eq!(
+            column.typed_buf::<f64>().unwrap(),
+            &[0.0, 0.0, 0.0, 1.0, 5.0, 6.0, 7.0, 0.0, 0.0, 0.0, 1.0, 8.0, 9.0, 10.0]
+        );
+
+        let wrong_rows = ndarray::Array2::zeros((3, 7));
+        assert!(matches!(
+            polars.set_component(id, wrong_rows),
+            Err(Error::ValueSizeMismatch)
+        ));
+        let wrong_cols = ndarray::Array2::zeros((2, 6));
+        assert!(matches!(
+            polars.set_component(id, wrong_cols),
+            Err(Error::ValueSizeMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_set_component_precision_mismatch() {
+        #[derive(crate::Component)]
+        struct Mass32(nox::Scalar<f32>);
+
+        let mut world = World::default();
+        spawn_bodies(&mut world, 1);
+        world.spawn(Mass32(nox::Scalar::host(1.0)));
+        let mut polars = world.to_polars().unwrap();
+
+        let res = polars.set_component(Mass32::component_id(), ndarray::Array2::zeros((1, 1)));
+        assert!(matches!(
+            res,
+            Err(Error::PrecisionMismatch {
+                expected: PrimitiveTy::F64,
+                found: PrimitiveTy::F32,
+            })
+        ));
+        let res = polars.set_component(
+            ComponentId::new("world_pos"),
+            ndarray::Array2::zeros((1, 7)),
+        );
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn test_write_without_statistics() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let mut world = World::default();
+        spawn_bodies(&mut world, 2);
+        let mut polars = world.to_polars().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let options = WriteOptions {
+            statistics: EnabledStatistics::None,
+            ..Default::default()
+        };
+        polars.write_to_dir_with_options(dir, &options).unwrap();
+
+        let archetype_id = ArchetypeId::of::<Body>();
+        let file = File::open(dir.join(format!("{}.parquet", archetype_id.to_raw()))).unwrap();
+        let reader = SerializedFileReader::new(file).unwrap();
+        let row_group = reader.metadata().row_group(0);
+        assert!(row_group.columns().iter().all(|c| c.statistics().is_none()));
+
+        let new_polars = PolarsWorld::read_from_dir(dir).unwrap();
+        assert_eq!(polars.archetypes, new_polars.archetypes);
+    }
+
+    #[test]
+    fn test_timestamp_record_batch() {
+        let time = Series::new("time", &[1_000i64, 2_000, 3_000])
+            .cast(&DataType::Datetime(
+                TimeUnit::Milliseconds,
+                Some("UTC".to_string()),
+            ))
+            .unwrap();
+        let df = DataFrame::new(vec![time]).unwrap();
+        let record_batch = df.to_record_batch().unwrap();
+        let record_batch = record_batch.record_batch();
+        assert_eq!(
+            record_batch.schema().field(0).data_type(),
+            &arrow::datatypes::DataType::Timestamp(
+                arrow::datatypes::TimeUnit::Millisecond,
+                Some("UTC".into())
+            )
+        );
+
+        let mut buf = vec![];
+        let mut writer = ArrowWriter::try_new(&mut buf, record_batch.schema(), None).unwrap();
+        writer.write(record_batch).unwrap();
+        writer.close().unwrap();
+        let new_df = ParquetReader::new(std::io::Cursor::new(buf))
+            .finish()
+            .unwrap();
+        assert_eq!(df, new_df);
+    }
+
+    #[test]
+    fn test_utf8_record_batch() {
+        let df = df!(
+            "name" => &["foo", "bar", "baz"],
+            "value" => &[1.0, 2.0, 3.0],
+        )
+        .unwrap();
+        let record_batch = df.to_record_batch().unwrap();
+        let record_batch = record_batch.record_batch();
+        let schema = record_batch.schema();
+        assert_eq!(
+            schema.field(0).data_type(),
+            &arrow::datatypes::DataType::LargeUtf8
+        );
+        assert_eq!(schema.field(0).name(), "name");
+        let names = record_batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow::array::LargeStringArray>()
+            .unwrap()
+            .iter()
+            .map(|s| s.unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, &["foo", "bar", "baz"]);
+    }
+
+    #[test]
+    fn test_decimal_round_trip() {
+        let unscaled = Int128Chunked::from_vec("fuel", vec![1234, -5000, 7]);
+        let series = unscaled.into_decimal(Some(10), 3).unwrap().into_series();
+
+        let df = DataFrame::new(vec![series.clone()]).unwrap();
+        let record_batch = df.to_record_batch().unwrap();
+        let array = record_batch
+            .record_batch()
+            .column(0)
+            .as_any()
+            .downcast_ref::<arrow::array::Decimal128Array>()
+            .unwrap();
+        assert_eq!((array.precision(), array.scale()), (10, 3));
+        assert_eq!(array.values().as_ref(), &[1234, -5000, 7]);
+
+        let id = ComponentId::new("fuel");
+        let column =
+            HostColumn::from_series_with_id(&series, id, ComponentType::i64(), false).unwrap();
+        assert_eq!(column.typed_buf::<i64>().unwrap(), &[1234, -5000, 7]);
+        let series = column.to_decimal_series(Some(10), 3).unwrap();
+        assert_eq!(series.dtype(), &DataType::Decimal(Some(10), Some(3)));
+        let values = series.decimal().unwrap().into_iter().collect::<Vec<_>>();
+        assert_eq!(values, &[Some(1234), Some(-5000), Some(7)]);
+
+        let column = HostColumn::from_series_with_id(&series, id, ComponentType::f64(), false);
+        assert!(matches!(column, Err(Error::InvalidDecimal(_))));
+
+        let overflow = Int128Chunked::from_vec("fuel", vec![i128::from(i64::MAX) + 1])
+            .into_decimal(None, 0)
+            .unwrap()
+            .into_series();
+        let column = HostColumn::from_series_with_id(&overflow, id, ComponentType::i64(), false);
+        assert!(matches!(column, Err(Error::InvalidDecimal(_))));
+    }
+
+    #[test]
+    fn test_decimal_write_read() {
+        let mut world = World::default();
+        let mut metadata = conduit::Metadata::builder()
+            .name("fuel")
+            .scalar(PrimitiveTy::I64)
+            .build()
+            .unwrap();
+        metadata.set_decimal(Some(10), 3);
+        let id = world.register_component(metadata);
+        for value in [1234i64, -5000, 7] {
+            world.spawn_dynamic(&[(id, &value.to_le_bytes())]);
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        world.to_polars().unwrap().write_to_dir(dir).unwrap();
+
+        let polars = PolarsWorld::read_from_dir(dir).unwrap();
+        let archetype_id = polars.metadata.component_map[&id];
+        let series = polars.archetypes[&archetype_id]
+            .column(&id.0.to_string())
+            .unwrap();
+        assert_eq!(series.dtype(), &DataType::Decimal(Some(10), Some(3)));
+        let world = World::try_from(polars).unwrap();
+        let metadata = world.component_metadata(id).unwrap();
+        assert_eq!(metadata.decimal(), Some((Some(10), 3)));
+        let column = world.column_by_id(id).unwrap();
+        let values = column.column.buffer.typed_buf::<i64>().unwrap();
+        assert_eq!(values, &[1234, -5000, 7]);
+
+        // a metadata.json without the tags picks them up from the parquet footer
+        let metadata_path = dir.join("metadata.json");
+        let mut metadata: serde_json::Value =
+            serde_json::from_reader(File::open(&metadata_path).unwrap()).unwrap();
+        for archetype in metadata["archetypes"].as_object_mut().unwrap().values_mut() {
+            for column in archetype["columns"].as_array_mut().unwrap() {
+                let tags = column["metadata"]["tags"].as_object_mut().unwrap();
+                tags.remove("decimal_precision");
+                tags.remove("decimal_scale");
+            }
+        }
+        serde_json::to_writer(File::create(&metadata_path).unwrap(), &metadata).unwrap();
+        let polars = PolarsWorld::read_from_dir(dir).unwrap();
+        let metadata = &polars.component_metadata(id).unwrap().metadata;
+        assert_eq!(metadata.decimal(), Some((Some(10), 3)));
+    }
+
+    #[test]
+    fn test_from_series_nulls() {
+        let id = ComponentId::new("sensor");
+        let series = Series::new("sensor", &[Some(1.0), None, Some(3.0)]);
+        let column =
+            HostColumn::from_series_with_id(&series, id, ComponentType::f64(), false).unwrap();
+        let values = column.typed_buf::<f64>().unwrap();
+        assert_eq!((values[0], values[2]), (1.0, 3.0));
+        assert!(values[1].is_nan());
+
+        let series = Series::new("sensor", &[None, Some(-4i64)]);
+        let column =
+            HostColumn::from_series_with_id(&series, id, ComponentType::i64(), false).unwrap();
+        assert_eq!(column.typed_buf::<i64>().unwrap(), &[0, -4]);
+
+        let rows = [
+            Series::new("", &[Some(1.0), Some(2.0)]),
+            Series::new("", &[None, Some(4.0)]),
+        ];
+        let series = Series::new("sensor", &rows);
+        let component_type = ComponentType {
+            primitive_ty: PrimitiveTy::F64,
+            shape: vec![2].into(),
+        };
+        assert!(matches!(
+            HostColumn::from_series_with_id(&series, id, component_type, false),
+            Err(Error::NullsUnsupported(found)) if found == id
+        ));
+    }
+
+    #[test]
+    fn test_f16_round_trip() {
+        let id = ComponentId::new("half_state");
+        let component_type = ComponentType {
+            primitive_ty: PrimitiveTy::F16,
+            shape: vec![3].into(),
+        };
+        let mut column = HostColumn::new(component_type.clone(), id);
+        // 65504 is the largest finite f16, and 0.099975586 the closest one to 0.1
+        let rows = [[1.0, -2.5, 0.0], [65504.0, 0.099975586, 0.5]];
+        for row in rows {
+            let row = row
+                .iter()
+                .flat_map(|v| half::f16::from_f32(*v).to_le_bytes())
+                .collect::<Vec<_>>();
+            column.push_raw(&row);
+        }
+
+        let series = column.to_series().unwrap();
+        assert_eq!(
+            series.dtype(),
+            &DataType::Array(Box::new(DataType::Float32), 3)
+        );
+        let df = DataFrame::new(vec![series]).unwrap();
+        let record_batch = df.to_record_batch().unwrap();
+        let record_batch = record_batch.record_batch();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("half.parquet");
+        let mut writer =
+            ArrowWriter::try_new(File::create(&path).unwrap(), record_batch.schema(), None)
+                .unwrap();
+        writer.write(record_batch).unwrap();
+        writer.close().unwrap();
+
+        let df = ParquetReader::new(File::open(&path).unwrap())
+            .finish()
+            .unwrap();
+        let read = HostColumn::from_series_with_id(&df.get_columns()[0], id, component_type, false)
+            .unwrap();
+        assert_eq!(read.len, 2);
+        assert_eq!(read.buf, column.buf);
+        assert_eq!(
+            row_to_json(&read, 0).unwrap(),
+            serde_json::json!([1.0, -2.5, 0.0])
+        );
+    }
+
+    #[test]
+    fn test_derived_vector_component() {
+        #[derive(crate::Component)]
+        struct Thrust(nox::Vector<f64, 3>);
+
+        assert_eq!(Thrust::component_id(), ComponentId::new("thrust"));
+        assert_eq!(Thrust::component_type().primitive_ty, PrimitiveTy::F64);
+        assert_eq!(Thrust::component_type().shape.as_slice(), &[3]);
+
+        let mut world = World::default();
+        world.spawn(Thrust(vector![1.0, 2.0, 3.0].into()));
+        world.spawn(Thrust(vector![-4.0, 0.5, 0.0].into()));
+        let polars = world.to_polars().unwrap();
+        let archetype_id = polars.metadata.component_map[&Thrust::component_id()];
+        let series = polars.archetypes[&archetype_id]
+            .column(&Thrust::component_id().0.to_string())
+            .unwrap();
+        assert_eq!(
+            series.dtype(),
+            &DataType::Array(Box::new(DataType::Float64), 3)
+        );
+        let values = bytemuck::pod_collect_to_vec::<u8, f64>(&series.to_bytes());
+        assert_eq!(values, &[1.0, 2.0, 3.0, -4.0, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn test_bool_round_trip() {
+        let id = ComponentId::new("thrusting");
+        let mut column = HostColumn::new(ComponentType::bool(), id);
+        // 13 values so the packed arrow bitmap ends partway through a byte
+        let values = (0..13).map(|i| i % 3 == 0).collect::<Vec<_>>();
+        for value in &values {
+            column.push_raw(&[u8::from(*value)]);
+        }
+
+        let series = column.to_series().unwrap();
+        let found = series
+            .bool()
+            .unwrap()
+            .into_no_null_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(found, values);
+        assert_eq!(series.to_bytes(), column.buf);
+
+        let df = DataFrame::new(vec![series]).unwrap();
+        let record_batch = df.to_record_batch().unwrap();
+        let record_batch = record_batch.record_batch();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bool.parquet");
+        let mut writer =
+            ArrowWriter::try_new(File::create(&path).unwrap(), record_batch.schema(), None)
+                .unwrap();
+        writer.write(record_batch).unwrap();
+        writer.close().unwrap();
+
+        let df = ParquetReader::new(File::open(&path).unwrap())
+            .finish()
+            .unwrap();
+        let read =
+            HostColumn::from_series_with_id(&df.get_columns()[0], id, ComponentType::bool(), false)
+                .unwrap();
+        assert_eq!(read.len, 13);
+        assert_eq!(read.buf, column.buf);
+    }
+
+    #[test]
+    fn test_dictionary_record_batch() {
+        use arrow::array::{Array, AsArray};
+        use arrow::datatypes::{Int16Type, Int32Type, Int8Type};
+        use polars::prelude::{CategoricalOrdering, DataType};
+
+        let series = Series::new("tag", &["rock", "ice", "rock", "dust"])
+            .cast(&DataType::Categorical(None, CategoricalOrdering::Physical))
+            .unwrap();
+        let df = DataFrame::new(vec![series]).unwrap();
+        let record_batch = df.to_record_batch().unwrap();
+        let dictionary = record_batch.record_batch().column(0).as_any_dictionary();
+        let values =
+            arrow::compute::cast(dictionary.values(), &arrow::datatypes::DataType::Utf8).unwrap();
+        let values = values.as_string::<i32>();
+        let tags = dictionary
+            .normalized_keys()
+            .into_iter()
+            .map(|key| values.value(key))
+            .collect::<Vec<_>>();
+        assert_eq!(tags, &["rock", "ice", "rock", "dust"]);
+
+        let strs = ["a", "b", "a", "c"];
+        let arrays: [Arc<dyn Array>; 3] = [
+            Arc::new(
+                strs.into_iter()
+                    .collect::<arrow::array::DictionaryArray<Int8Type>>(),
+            ),
+            Arc::new(
+                strs.into_iter()
+                    .collect::<arrow::array::DictionaryArray<Int16Type>>(),
+            ),
+            Arc::new(
+                strs.into_iter()
+                    .collect::<arrow::array::DictionaryArray<Int32Type>>(),
+            ),
+        ];
+        for array in arrays {
+            let arrow::datatypes::DataType::Dictionary(key, _) = array.data_type() else {
+                unreachable!("every array is dictionary encoded");
+            };
+            let rebuilt = dictionary_array(key.as_ref().clone(), array.to_data());
+            assert_eq!(rebuilt.data_type(), array.data_type());
+            let dictionary = rebuilt.as_any_dictionary();
+            assert_eq!(dictionary.normalized_keys(), &[0, 1, 0, 2]);
+            let values = dictionary.values().as_string::<i32>();
+            assert_eq!(
+                values.iter().flatten().collect::<Vec<_>>(),
+                &["a", "b", "c"]
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_read_zstd() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let mut world = World::default();
+        spawn_bodies(&mut world, 2);
+        let mut polars = world.to_polars().unwrap();
+        let options = WriteOptions {
+            compression: CompressionOptions::zstd(3).unwrap(),
+            statistics: EnabledStatistics::None,
+            ..Default::default()
+        };
+        let archetype_id = ArchetypeId::of::<Body>();
+        for path in 0..3 {
+            let dir = tempfile::tempdir().unwrap();
+            let dir = dir.path();
+            match path {
+                0 => polars.write_to_dir_with_options(dir, &options),
+                1 => polars.write_to_dir_streaming(dir, &options),
+                _ => polars.write_to_dir_parallel_with_options(dir, &options),
+            }
+            .unwrap();
+
+            let file = File::open(dir.join(format!("{}.parquet", archetype_id.to_raw()))).unwrap();
+            let reader = SerializedFileReader::new(file).unwrap();
+            let column = reader.metadata().row_group(0).column(0);
+            assert!(matches!(column.compression(), Compression::ZSTD(_)));
+            assert!(column.statistics().is_none());
+
+            let new_polars = PolarsWorld::read_from_dir(dir).unwrap();
+            assert_eq!(polars.archetypes, new_polars.archetypes);
+        }
+        assert!(CompressionOptions::zstd(100).is_err());
+    }
+
+    #[test]
+    fn test_write_sorted_by_entity_id() {
+        let mut world = World::default();
+        let pbr = world.insert_asset(Pbr::Url("foo".to_string()));
+        for id in [5, 2, 9] {
+            world.spawn_with_id(body(pbr, id as f64), EntityId(id));
+        }
+        let mut polars = world.to_polars().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let options = WriteOptions {
+            sort_by_entity_id: true,
+            ..Default::default()
+        };
+        polars.write_to_dir_with_options(dir, &options).unwrap();
+
+        let new_polars = PolarsWorld::read_from_dir(dir).unwrap();
+        let archetype_id = ArchetypeId::of::<Body>();
+        let df = &new_polars.archetypes[&archetype_id];
+        let entity_ids = df.column(&ENTITY_ID_COMPONENT.0.to_string()).unwrap();
+        let entity_ids = entity_ids
+            .u64()
+            .unwrap()
+            .into_no_null_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(entity_ids, &[2, 5, 9]);
+
+        let new_world = World::try_from(new_polars).unwrap();
+        let table = &new_world.archetypes[&archetype_id];
+        let pos = table.columns[&WorldPos::component_id()]
+            .buffer
+            .typed_buf::<f64>()
+            .unwrap();
+        for (entity_id, row) in &table.entity_map {
+            assert_eq!(pos[row * 7 + 4], entity_id.0 as f64);
+        }
+    }
+
+    #[test]
+    fn test_write_dictionary_entity_ids() {
+        let mut world = World::default();
+        let pbr = world.insert_asset(Pbr::Url("foo".to_string()));
+        for id in [5, 2, 9] {
+            world.spawn_with_id(body(pbr, id as f64), EntityId(id));
+        }
+        let mut polars = world.to_polars().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let options = WriteOptions {
+            dictionary_entity_ids: true,
+            ..Default::default()
+        };
+        polars.write_to_dir_with_options(dir, &options).unwrap();
+
+        let archetype_id = ArchetypeId::of::<Body>();
+        let file = File::open(dir.join(format!("{}.parquet", archetype_id.to_raw()))).unwrap();
+        let arrow_metadata = ArrowReaderMetadata::load(&file, Default::default()).unwrap();
+        let field = arrow_metadata
+            .schema()
+            .field_with_name(&ENTITY_ID_COMPONENT.0.to_string())
+            .unwrap()
+            .clone();
+        assert!(matches!(
+            field.data_type(),
+            arrow::datatypes::DataType::Dictionary(_, _)
+        ));
+
+        let new_polars = PolarsWorld::read_from_dir(dir).unwrap();
+        assert_eq!(polars.archetypes, new_polars.archetypes);
+        let new_world = World::try_from(new_polars).unwrap();
+        let table = &new_world.archetypes[&archetype_id];
+        assert_eq!(table.entity_map, world.archetypes[&archetype_id].entity_map);
+        let entity_ids = table.entity_buffer.typed_buf::<u64>().unwrap();
+        for (entity_id, row) in &table.entity_map {
+            assert_eq!(entity_ids[*row], entity_id.0);
+        }
+    }
+
+    #[test]
+    fn test_write_streaming() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let mut world = World::default();
+        spawn_bodies(&mut world, 10);
+        let streamed = tempfile::tempdir().unwrap();
+        let single = tempfile::tempdir().unwrap();
+        let options = WriteOptions {
+            row_group_size: 3,
+            ..Default::default()
+        };
+        world
+            .to_polars()
+            .unwrap()
+            .write_to_dir_streaming(streamed.path(), &options)
+            .unwrap();
+        world
+            .to_polars()
+            .unwrap()
+            .write_to_dir(single.path())
+            .unwrap();
+
+        let archetype_id = ArchetypeId::of::<Body>();
+        let file = File::open(
+            streamed
+                .path()
+                .join(format!("{}.parquet", archetype_id.to_raw())),
+        )
+        .unwrap();
+        let reader = SerializedFileReader::new(file).unwrap();
+        assert_eq!(reader.metadata().num_row_groups(), 4);
+
+        let streamed = PolarsWorld::read_from_dir(streamed.path()).unwrap();
+        let single = PolarsWorld::read_from_dir(single.path()).unwrap();
+        assert_eq!(streamed.archetypes, single.archetypes);
+        let new_world = World::try_from(streamed).unwrap();
+        assert_eq!(new_world.archetypes, world.archetypes);
+    }
+
+    #[test]
+    fn test_read_without_column_index() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 3);
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        world.to_polars().unwrap().write_to_dir(dir).unwrap();
+
+        let metadata_path = dir.join("metadata.json");
+        let mut metadata: serde_json::Value =
+            serde_json::from_reader(File::open(&metadata_path).unwrap()).unwrap();
+        metadata.as_object_mut().unwrap().remove("version");
+        for archetype in metadata["archetypes"].as_object_mut().unwrap().values_mut() {
+            archetype.as_object_mut().unwrap().remove("column_index");
+        }
+        serde_json::to_writer(File::create(&metadata_path).unwrap(), &metadata).unwrap();
+
+        let polars = PolarsWorld::read_from_dir(dir).unwrap();
+        let archetype_metadata = &polars.metadata.archetypes[&ArchetypeId::of::<Body>()];
+        assert_eq!(
+            archetype_metadata.column_index[&ENTITY_ID_COMPONENT],
+            archetype_metadata.columns.len()
+        );
+        let new_world = World::try_from(polars).unwrap();
+        assert_eq!(new_world.archetypes, world.archetypes);
+    }
+
+    #[test]
+    fn test_metadata_version() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 2);
+        let mut polars = world.to_polars().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        polars.write_to_dir(dir).unwrap();
+
+        let metadata_path = dir.join("metadata.json");
+        let mut metadata: serde_json::Value =
+            serde_json::from_reader(File::open(&metadata_path).unwrap()).unwrap();
+        assert_eq!(metadata["version"], METADATA_VERSION);
+
+        // a v0 checkpoint has none of the fields added since
+        let object = metadata.as_object_mut().unwrap();
+        for field in ["version", "ticks", "crate_version"] {
+            object.remove(field);
+        }
+        for archetype in metadata["archetypes"].as_object_mut().unwrap().values_mut() {
+            archetype.as_object_mut().unwrap().remove("column_index");
+        }
+        serde_json::to_writer(File::create(&metadata_path).unwrap(), &metadata).unwrap();
+        let v0 = PolarsWorld::read_from_dir(dir).unwrap();
+        assert_eq!(v0.metadata.version, METADATA_VERSION);
+        assert_eq!(v0.archetypes, polars.archetypes);
+        assert_eq!(World::try_from(v0).unwrap().archetypes, world.archetypes);
+
+        metadata["version"] = serde_json::json!(METADATA_VERSION + 1);
+        serde_json::to_writer(File::create(&metadata_path).unwrap(), &metadata).unwrap();
+        assert!(matches!(
+            PolarsWorld::read_from_dir(dir),
+            Err(Error::UnsupportedVersion(v)) if v == METADATA_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn test_shape_too_large() {
+        let inner = || -> Box<dyn polars_arrow::array::Array> {
+            Box::new(polars_arrow::array::PrimitiveArray::<f64>::from_slice([]))
+        };
+        let ty = ComponentType {
+            primitive_ty: PrimitiveTy::F64,
+            shape: vec![1 << 16, 1 << 16].into(),
+        };
+        assert!(matches!(
+            tensor_array(&ty, inner()),
+            Err(Error::ShapeTooLarge)
+        ));
+        let ty = ComponentType {
+            primitive_ty: PrimitiveTy::F64,
+            shape: vec![usize::MAX, 2].into(),
+        };
+        assert!(matches!(
+            tensor_array(&ty, inner()),
+            Err(Error::ShapeTooLarge)
+        ));
+    }
+
+    #[test]
+    fn test_column_store_assets() {
+        let mut world = World::default();
+        let bundle = Pbr::Bundle {
+            mesh: Mesh::sphere(0.1, 36, 18),
+            material: Material::color(1.0, 0.0, 0.0),
+        };
+        let pbr = world.insert_asset(bundle.clone());
+        world.spawn(body(pbr, 0.0));
+        let dir = tempfile::tempdir().unwrap();
+        world.to_polars().unwrap().write_to_dir(dir.path()).unwrap();
+
+        let polars = PolarsWorld::read_from_dir(dir.path()).unwrap();
+        let store = &polars;
+        let column = store.column(Handle::<Pbr>::component_id()).unwrap();
+        assert!(column.is_asset());
+        let pos = store.column(WorldPos::component_id()).unwrap();
+        assert!(!pos.is_asset());
+
+        let id = u64::from_le_bytes(column.value_buf()[..8].try_into().unwrap());
+        let item = store
+            .assets()
+            .unwrap()
+            .value(Handle::<Pbr>::new(id))
+            .unwrap();
+        assert_eq!(item.inner, postcard::to_allocvec(&bundle).unwrap());
+    }
+
+    #[test]
+    fn test_write_ndjson() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 2);
+        let mut buf = vec![];
+        world.to_polars().unwrap().write_ndjson(&mut buf).unwrap();
+
+        let lines = std::str::from_utf8(&buf)
+            .unwrap()
+            .lines()
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        let archetype_id = ArchetypeId::of::<Body>().to_raw().to_string();
+        for (i, line) in lines.into_iter().enumerate() {
+            let object: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(object["entity_id"], i as u64);
+            assert_eq!(object["archetype_id"], archetype_id.as_str());
+            let pos = object["world_pos"].as_array().unwrap();
+            assert_eq!(pos.len(), 7);
+            assert_eq!(pos[4], i as f64);
+        }
+    }
+
+    #[test]
+    fn test_write_archetype_csv() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 3);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("body.csv");
+        world
+            .to_polars()
+            .unwrap()
+            .write_archetype_csv(ArchetypeId::of::<Body>(), &path)
+            .unwrap();
+
+        let csv = std::fs::read_to_string(&path).unwrap();
+        let mut lines = csv.lines();
+        let header = lines.next().unwrap().split(',').collect::<Vec<_>>();
+        assert_eq!(header[0], "entity_id");
+        // 1 entity id, 1 handle, 7 world_pos, 6 each of vel, accel, force and 7 inertia
+        assert_eq!(header.len(), 1 + 1 + 7 + 6 * 3 + 7);
+        for i in 0..7 {
+            assert!(header.contains(&format!("world_pos_{i}").as_str()));
+        }
+        let pbr = Handle::<Pbr>::component_id().0.to_string();
+        assert!(header.contains(&pbr.as_str()));
+
+        let rows = lines.collect::<Vec<_>>();
+        assert_eq!(rows.len(), 3);
+        let row = rows[2].split(',').collect::<Vec<_>>();
+        let x = header.iter().position(|h| *h == "world_pos_4").unwrap();
+        assert_eq!(row[0], "2");
+        assert_eq!(row[x].parse::<f64>().unwrap(), 2.0);
+    }
+
+    #[test]
+    fn test_compressed_assets() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 2);
+        let mut polars = world.to_polars().unwrap();
+        let legacy = tempfile::tempdir().unwrap();
+        polars.write_to_dir(legacy.path()).unwrap();
+        let compressed = tempfile::tempdir().unwrap();
+        let options = WriteOptions {
+            assets_zstd_level: Some(3),
+            ..Default::default()
+        };
+        polars
+            .write_to_dir_with_options(compressed.path(), &options)
+            .unwrap();
+
+        let legacy_buf = std::fs::read(legacy.path().join("assets.bin")).unwrap();
+        let compressed_buf = std::fs::read(compressed.path().join("assets.bin")).unwrap();
+        assert_eq!(legacy_buf, postcard::to_allocvec(&polars.assets).unwrap());
+        assert!(compressed_buf.starts_with(ASSETS_MAGIC));
+        assert!(compressed_buf.len() < legacy_buf.len());
+        for dir in [legacy.path(), compressed.path()] {
+            let read = PolarsWorld::read_from_dir(dir).unwrap();
+            assert_eq!(postcard::to_allocvec(&read.assets).unwrap(), legacy_buf);
+        }
+
+        let mut unknown = ASSETS_MAGIC.to_vec();
+        unknown.push(7);
+        std::fs::write(compressed.path().join("assets.bin"), unknown).unwrap();
+        assert!(matches!(
+            PolarsWorld::read_from_dir(compressed.path()),
+            Err(Error::UnknownAssetsCodec(Some(7)))
+        ));
+    }
+
+    #[test]
+    fn test_write_error() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 1);
+        let mut polars = world.to_polars().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let archetype_id = ArchetypeId::of::<Body>();
+        std::fs::create_dir(dir.join(format!("{}.parquet", archetype_id.to_raw()))).unwrap();
+        assert!(polars.write_to_dir(dir).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        std::fs::create_dir(dir.join("assets.bin")).unwrap();
+        assert!(polars.write_to_dir(dir).is_err());
+    }
+
+    #[test]
+    fn test_write_parallel() {
+        #[derive(crate::Component)]
+        struct Temperature(nox::Scalar<f64>);
+
+        let mut world = World::default();
+        spawn_bodies(&mut world, 3);
+        world.spawn(Temperature(nox::Scalar::host(21.0)));
+        let serial = tempfile::tempdir().unwrap();
+        let parallel = tempfile::tempdir().unwrap();
+        world
+            .to_polars()
+            .unwrap()
+            .write_to_dir(serial.path())
+            .unwrap();
+        world
+            .to_polars()
+            .unwrap()
+            .write_to_dir_parallel(parallel.path())
+            .unwrap();
+
+        let mut files = std::fs::read_dir(serial.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(files.len(), 4);
+        for file in files {
+            let a = std::fs::read(serial.path().join(&file)).unwrap();
+            let b = std::fs::read(parallel.path().join(&file)).unwrap();
+            assert_eq!(
+                a, b,
+                "{:?} differs between serial and parallel writes",
+                file
+            );
+        }
+    }
+
+    #[test]
+    fn test_shape_metadata() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 1);
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        world.to_polars().unwrap().write_to_dir(dir).unwrap();
+
+        let archetype_id = ArchetypeId::of::<Body>();
+        let file = File::open(dir.join(format!("{}.parquet", archetype_id.to_raw()))).unwrap();
+        let arrow_metadata = ArrowReaderMetadata::load(&file, Default::default()).unwrap();
+        let field = arrow_metadata
+            .schema()
+            .field_with_name("world_pos")
+            .unwrap();
+        assert_eq!(field.metadata()[SHAPE_METADATA_KEY], r#"{"shape":[7]}"#);
+        assert!(PolarsWorld::read_from_dir(dir).is_ok());
+
+        let metadata_path = dir.join("metadata.json");
+        let mut metadata: serde_json::Value =
+            serde_json::from_reader(File::open(&metadata_path).unwrap()).unwrap();
+        for archetype in metadata["archetypes"].as_object_mut().unwrap().values_mut() {
+            for column in archetype["columns"].as_array_mut().unwrap() {
+                let shape = &mut column["metadata"]["component_type"]["shape"];
+                if *shape == serde_json::json!([7]) {
+                    *shape = serde_json::json!([7, 1]);
+                }
+            }
+        }
+        serde_json::to_writer(File::create(&metadata_path).unwrap(), &metadata).unwrap();
+        assert!(matches!(
+            PolarsWorld::read_from_dir(dir),
+            Err(Error::ShapeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_named_columns() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 2);
+        let mut polars = world.to_polars().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        polars.write_to_dir(dir).unwrap();
+
+        let archetype_id = ArchetypeId::of::<Body>();
+        let file = File::open(dir.join(format!("{}.parquet", archetype_id.to_raw()))).unwrap();
+        let arrow_metadata = ArrowReaderMetadata::load(&file, Default::default()).unwrap();
+        let schema = arrow_metadata.schema();
+        let id = WorldPos::component_id().0.to_string();
+        let field = schema.field_with_name("world_pos").unwrap();
+        assert_eq!(field.metadata()[COMPONENT_ID_METADATA_KEY], id);
+        assert!(schema.field_with_name(&id).is_err());
+
+        let new_polars = PolarsWorld::read_from_dir(dir).unwrap();
+        assert_eq!(polars.archetypes, new_polars.archetypes);
+        assert!(new_polars.archetypes[&archetype_id].column(&id).is_ok());
+    }
+
+    #[test]
+    fn test_component_unit() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 2);
+        let archetype_id = ArchetypeId::of::<Body>();
+        let id = WorldVel::component_id();
+        let column = world
+            .archetypes
+            .get_mut(&archetype_id)
+            .unwrap()
+            .columns
+            .get_mut(&id)
+            .unwrap();
+        column.metadata.set_unit("m/s");
+        column
+            .metadata
+            .set_description("velocity in the world frame");
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        world.to_polars().unwrap().write_to_dir(dir).unwrap();
+
+        let file = File::open(dir.join(format!("{}.parquet", archetype_id.to_raw()))).unwrap();
+        let arrow_metadata = ArrowReaderMetadata::load(&file, Default::default()).unwrap();
+        let field = arrow_metadata
+            .schema()
+            .field_with_name("world_vel")
+            .unwrap();
+        assert_eq!(field.metadata()[UNIT_METADATA_KEY], "m/s");
+        assert_eq!(
+            field.metadata()[DESCRIPTION_METADATA_KEY],
+            "velocity in the world frame"
+        );
+
+        let polars = PolarsWorld::read_from_dir(dir).unwrap();
+        let metadata = &polars.component_metadata(id).unwrap().metadata;
+        assert_eq!(metadata.unit(), Some("m/s"));
+        let world = World::try_from(polars).unwrap();
+        assert_eq!(world.component_metadata(id).unwrap().unit(), Some("m/s"));
+
+        // a metadata.json without the tags picks them up from the parquet footer
+        let metadata_path = dir.join("metadata.json");
+        let mut metadata: serde_json::Value =
+            serde_json::from_reader(File::open(&metadata_path).unwrap()).unwrap();
+        for archetype in metadata["archetypes"].as_object_mut().unwrap().values_mut() {
+            for column in archetype["columns"].as_array_mut().unwrap() {
+                let tags = column["metadata"]["tags"].as_object_mut().unwrap();
+                tags.remove("unit");
+                tags.remove("description");
+            }
+        }
+        serde_json::to_writer(File::create(&metadata_path).unwrap(), &metadata).unwrap();
+        let polars = PolarsWorld::read_from_dir(dir).unwrap();
+        let metadata = &polars.component_metadata(id).unwrap().metadata;
+        assert_eq!(metadata.unit(), Some("m/s"));
+        assert_eq!(metadata.description(), Some("velocity in the world frame"));
+        let pos = &polars
+            .component_metadata(WorldPos::component_id())
+            .unwrap()
+            .metadata;
+        assert_eq!(pos.unit(), None);
+    }
+
+    #[test]
+    fn test_lazy_world() {
+        #[derive(crate::Component)]
+        struct Temperature(nox::Scalar<f64>);
+
+        let mut world = World::default();
+        spawn_bodies(&mut world, 2);
+        world.spawn(Temperature(nox::Scalar::host(21.0)));
+        let dir = tempfile::tempdir().unwrap();
+        world.to_polars().unwrap().write_to_dir(dir.path()).unwrap();
+
+        let lazy = LazyWorld::read_from_dir(dir.path()).unwrap();
+        assert_eq!(lazy.decode_count(), 0);
+        let column = lazy.column(Temperature::component_id()).unwrap();
+        assert_eq!(
+            column
+                .host_column()
+                .column
+                .buffer
+                .typed_buf::<f64>()
+                .unwrap(),
+            &[21.0]
+        );
+        assert_eq!(lazy.decode_count(), 1);
+        lazy.column(Temperature::component_id()).unwrap();
+        assert_eq!(lazy.decode_count(), 1);
+
+        let table = lazy.table(ArchetypeId::of::<Body>()).unwrap();
+        assert_eq!(&*table, &world.archetypes[&ArchetypeId::of::<Body>()]);
+        assert_eq!(lazy.decode_count(), 2);
+    }
+
+    #[test]
+    fn test_lazy_world_cache_budget() {
+        #[derive(crate::Component)]
+        struct Temperature(nox::Scalar<f64>);
+
+        let mut world = World::default();
+        spawn_bodies(&mut world, 2);
+        world.spawn(Temperature(nox::Scalar::host(21.0)));
+        let lazy = LazyWorld::new(world.to_polars().unwrap());
+        let body_id = ArchetypeId::of::<Body>();
+        let temperature_id = ArchetypeId::of::<Temperature>();
+
+        // both tables fit, so they're only decoded once
+        lazy.table(body_id).unwrap();
+        lazy.table(temperature_id).unwrap();
+        lazy.table(body_id).unwrap();
+        assert_eq!(lazy.decode_count(), 2);
+
+        // a single byte only leaves room for the most recently accessed table
+        lazy.set_cache_budget(1);
+        lazy.table(body_id).unwrap();
+        assert_eq!(lazy.decode_count(), 2);
+        let column = lazy.column(Temperature::component_id()).unwrap();
+        assert_eq!(lazy.decode_count(), 3);
+        lazy.table(body_id).unwrap();
+        assert_eq!(lazy.decode_count(), 4);
+        lazy.table(body_id).unwrap();
+        assert_eq!(lazy.decode_count(), 4);
+        // the evicted table stays alive for as long as its columns are in use
+        assert_eq!(column.len(), 1);
+    }
+
+    #[test]
+    fn test_merge() {
+        let bundle = || Pbr::Bundle {
+            mesh: Mesh::sphere(0.1, 36, 18),
+            material: Material::color(1.0, 1.0, 1.0),
+        };
+        let mut a = World::default();
+        let pbr = a.insert_asset(bundle());
+        a.spawn_with_id(body(pbr, 1.0), EntityId(0));
+        let mut b = World::default();
+        let pbr = b.insert_asset(bundle());
+        b.spawn_with_id(body(pbr, 2.0), EntityId(1));
+
+        let mut merged = a.to_polars().unwrap();
+        merged.merge(b.to_polars().unwrap()).unwrap();
+        let archetype_id = ArchetypeId::of::<Body>();
+        assert_eq!(merged.archetypes[&archetype_id].height(), 2);
+        assert_eq!(
+            merged.metadata.archetypes[&archetype_id].entity_map,
+            BTreeMap::from([(EntityId(0), 0), (EntityId(1), 1)])
+        );
+
+        let world = World::try_from(merged).unwrap();
+        let table = &world.archetypes[&archetype_id];
+        let pos = table.columns[&WorldPos::component_id()]
+            .buffer
+            .typed_buf::<f64>()
+            .unwrap();
+        assert_eq!(pos[4], 1.0);
+        assert_eq!(pos[7 + 4], 2.0);
+        let handles = table.columns[&Handle::<Pbr>::component_id()]
+            .buffer
+            .typed_buf::<u64>()
+            .unwrap();
+        assert_eq!(handles, &[0, 0]);
+
+        let mut duplicate = a.to_polars().unwrap();
+        assert!(matches!(
+            duplicate.merge(a.to_polars().unwrap()),
+            Err(Error::DuplicateEntity(EntityId(0)))
+        ));
+    }
+
+    #[test]
+    fn test_merge_dangling_asset() {
+        let mut a = World::default();
+        let pbr = a.insert_asset(Pbr::Bundle {
+            mesh: Mesh::sphere(0.1, 36, 18),
+            material: Material::color(1.0, 1.0, 1.0),
+        });
+        a.spawn_with_id(body(pbr, 1.0), EntityId(0));
+        let mut b = World::default();
+        b.insert_asset(Pbr::Bundle {
+            mesh: Mesh::sphere(0.2, 36, 18),
+            material: Material::color(0.0, 1.0, 0.0),
+        });
+        b.spawn_with_id(body(Handle::new(5), 2.0), EntityId(1));
+
+        // the dangling handle fails the merge before any assets or rows are added
+        let mut merged = a.to_polars().unwrap();
+        let assets = serde_json::to_value(&merged.assets).unwrap();
+        assert!(matches!(
+            merged.merge(b.to_polars().unwrap()),
+            Err(Error::AssetNotFound)
+        ));
+        assert_eq!(serde_json::to_value(&merged.assets).unwrap(), assets);
+        let archetype_id = ArchetypeId::of::<Body>();
+        assert_eq!(merged.archetypes[&archetype_id].height(), 1);
+        assert_eq!(
+            merged.metadata.archetypes[&archetype_id].entity_map,
+            BTreeMap::from([(EntityId(0), 0)])
+        );
+    }
+
+    #[test]
+    fn test_merge_precision_mismatch() {
+        let world = |primitive_ty: PrimitiveTy, value: &[u8], first_id: u64| {
+            let mut world = World::default();
+            let pos = world.register_component(
+                conduit::Metadata::builder()
+                    .name("pos")
+                    .scalar(primitive_ty)
+                    .build()
+                    .unwrap(),
+            );
+            // spawn and then despawn placeholders, so each world's entity gets a different id and
+            // the merge gets past its duplicate entity check
+            let placeholders: Vec<_> = (0..first_id)
+                .map(|_| world.spawn_dynamic(&[(pos, value)]))
+                .collect();
+            world.spawn_dynamic(&[(pos, value)]);
+            for id in placeholders {
+                world.despawn(id).unwrap();
+            }
+            world
+        };
+        let f64_world = world(PrimitiveTy::F64, &1.0f64.to_le_bytes(), 0);
+        let f32_world = world(PrimitiveTy::F32, &2.0f32.to_le_bytes(), 1);
+        let is_mismatch = |result: Result<(), Error>| {
+            matches!(
+                result,
+                Err(Error::PrecisionMismatch {
+                    expected: PrimitiveTy::F64,
+                    found: PrimitiveTy::F32,
+                })
+            )
+        };
+
+        let mut merged = f64_world.to_polars().unwrap();
+        assert!(is_mismatch(merged.merge(f32_world.to_polars().unwrap())));
+
+        let history = crate::history::History {
+            worlds: vec![
+                f64_world.to_polars().unwrap(),
+                f32_world.to_polars().unwrap(),
+            ],
+        };
+        assert!(is_mismatch(history.compact_to_world().map(|_| ())));
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut tick = f64_world.to_polars().unwrap();
+        tick.append_tick_to_dir(dir.path()).unwrap();
+        let mut tick = world(PrimitiveTy::F32, &2.0f32.to_le_bytes(), 0)
+            .to_polars()
+            .unwrap();
+        tick.metadata.tick = 1;
+        assert!(is_mismatch(tick.append_tick_to_dir(dir.path())));
+    }
+
+    #[test]
+    fn test_read_archetypes_from_dir() {
+        #[derive(crate::Component)]
+        struct Temperature(nox::Scalar<f64>);
+
+        let mut world = World::default();
+        spawn_bodies(&mut world, 2);
+        world.spawn(Temperature(nox::Scalar::host(21.0)));
+        let dir = tempfile::tempdir().unwrap();
+        world.to_polars().unwrap().write_to_dir(dir.path()).unwrap();
+
+        let temperature_id = ArchetypeId::of::<Temperature>();
+        let polars = PolarsWorld::read_archetypes_from_dir(dir.path(), &[temperature_id]).unwrap();
+        assert_eq!(
+            polars.archetypes.keys().collect::<Vec<_>>(),
+            &[&temperature_id]
+        );
+        assert!(polars
+            .metadata
+            .component_map
+            .values()
+            .all(|id| *id == temperature_id));
+
+        let store = &polars;
+        assert!(store.column(Temperature::component_id()).is_ok());
+        assert!(matches!(
+            store.column(WorldPos::component_id()),
+            Err(Error::ComponentNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_write_archetypes_to_dir() {
+        #[derive(crate::Component)]
+        struct Temperature(nox::Scalar<f64>);
+
+        let mut world = World::default();
+        spawn_bodies(&mut world, 2);
+        world.spawn(Temperature(nox::Scalar::host(21.0)));
+        let mut polars = world.to_polars().unwrap();
+        let body_id = ArchetypeId::of::<Body>();
+        let temperature_id = ArchetypeId::of::<Temperature>();
+        let dir = tempfile::tempdir().unwrap();
+
+        polars
+            .write_archetypes_to_dir(dir.path(), &[body_id])
+            .unwrap();
+        let partial = PolarsWorld::read_from_dir(dir.path()).unwrap();
+        assert_eq!(partial.archetypes.keys().collect::<Vec<_>>(), &[&body_id]);
+
+        polars
+            .write_archetypes_to_dir(dir.path(), &[temperature_id])
+            .unwrap();
+        let full = PolarsWorld::read_from_dir(dir.path()).unwrap();
+        assert_eq!(full.archetypes, polars.archetypes);
+        assert_eq!(full.metadata.component_map, polars.metadata.component_map);
+        assert!(!dir.path().join("metadata.json.tmp").exists());
+    }
+
+    #[test]
+    fn test_entity_row() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 2);
+        let polars = world.to_polars().unwrap();
+        for i in 0..2 {
+            let row = polars.entity_row(EntityId(i)).unwrap();
+            let pos = row[&WorldPos::component_id()].explode().unwrap();
+            let pos = pos.f64().unwrap().into_no_null_iter().collect::<Vec<_>>();
+            assert_eq!(pos, &[1.0, 0.0, 0.0, 0.0, i as f64, 0.0, 0.0]);
+        }
+        assert!(matches!(
+            polars.entity_row(EntityId(2)),
+            Err(Error::ComponentNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_column_tensor() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 3);
+        let polars = world.to_polars().unwrap();
+        let row = polars.metadata.archetypes[&ArchetypeId::of::<Body>()].entity_map[&EntityId(2)];
+        let pos = polars
+            .column_tensor::<f64, 7>(WorldPos::component_id(), row)
+            .unwrap();
+        assert_eq!(pos, [1.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0]);
+
+        assert!(matches!(
+            polars.column_tensor::<f64, 6>(WorldPos::component_id(), row),
+            Err(Error::ValueSizeMismatch)
+        ));
+        assert!(matches!(
+            polars.column_tensor::<f32, 7>(WorldPos::component_id(), row),
+            Err(Error::ElementTypeMismatch { .. })
+        ));
+        assert!(matches!(
+            polars.column_tensor::<f64, 7>(WorldPos::component_id(), 3),
+            Err(Error::RowOutOfBounds(3))
+        ));
+    }
+
+    #[test]
+    fn test_slice_archetype() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 5);
+        let polars = world.to_polars().unwrap();
+        let archetype_id = ArchetypeId::of::<Body>();
+
+        let slice = polars.slice_archetype(archetype_id, 1, 2).unwrap();
+        assert_eq!(slice.height(), 2);
+        let metadata = &polars.metadata.archetypes[&archetype_id];
+        let entity_ids = metadata
+            .series(&slice, ENTITY_ID_COMPONENT)
+            .unwrap()
+            .u64()
+            .unwrap()
+            .into_no_null_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(entity_ids, [1, 2]);
+
+        assert!(polars.slice_archetype(archetype_id, 4, 1).is_ok());
+        assert!(matches!(
+            polars.slice_archetype(archetype_id, 4, 2),
+            Err(Error::SliceOutOfBounds {
+                start: 4,
+                len: 2,
+                height: 5
+            })
+        ));
+        assert!(matches!(
+            polars.slice_archetype(archetype_id, 7, 0),
+            Err(Error::SliceOutOfBounds { start: 7, .. })
+        ));
+        assert!(matches!(
+            polars.slice_archetype(ArchetypeId::of::<WorldPos>(), 0, 1),
+            Err(Error::ComponentNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_rebuild_entity_map() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 3);
+        let mut polars = world.to_polars().unwrap();
+        // reorder the rows without updating the stored entity map, as an external tool would
+        for df in polars.archetypes.values_mut() {
+            *df = df.reverse();
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        polars.write_to_dir(dir).unwrap();
+
+        let pos = |polars: &PolarsWorld, i: u64| {
+            let row = polars.entity_row(EntityId(i)).unwrap();
+            let pos = row[&WorldPos::component_id()].explode().unwrap();
+            pos.f64().unwrap().get(4).unwrap()
+        };
+        let stale = PolarsWorld::read_from_dir(dir).unwrap();
+        assert_eq!(pos(&stale, 0), 2.0);
 
-impl<'a> ColumnStore for &'a PolarsWorld {
-    type Column<'b> = PolarsColumnRef<'b> where Self: 'b;
+        let options = ReadOptions {
+            rebuild_entity_map: true,
+            ..Default::default()
+        };
+        let rebuilt = PolarsWorld::read_from_dir_with_options(dir, &options).unwrap();
+        for i in 0..3 {
+            assert_eq!(pos(&rebuilt, i), i as f64);
+        }
+        let world = World::try_from(rebuilt).unwrap();
+        let archetype_id = ArchetypeId::of::<Body>();
+        assert_eq!(world.archetypes[&archetype_id].entity_map[&EntityId(0)], 2);
 
-    fn transfer_column(&mut self, _id: ComponentId) -> Result<(), Error> {
-        Ok(())
+        let metadata_path = dir.join("metadata.json");
+        let mut metadata: serde_json::Value =
+            serde_json::from_reader(File::open(&metadata_path).unwrap()).unwrap();
+        for archetype in metadata["archetypes"].as_object_mut().unwrap().values_mut() {
+            archetype["entity_map"].as_object_mut().unwrap().remove("1");
+        }
+        serde_json::to_writer(File::create(&metadata_path).unwrap(), &metadata).unwrap();
+        assert!(matches!(
+            PolarsWorld::read_from_dir_with_options(dir, &options),
+            Err(Error::EntityMapMismatch(id)) if id == archetype_id
+        ));
     }
 
-    fn column(&self, id: ComponentId) -> Result<Self::Column<'_>, Error> {
-        let entity_id_string = ENTITY_ID_COMPONENT.0.to_string();
-        let archetype = self
-            .metadata
-            .component_map
-            .get(&id)
-            .ok_or(Error::ComponentNotFound)?;
-        let table = self
-            .archetypes
-            .get(archetype)
-            .ok_or(Error::ComponentNotFound)?;
-        Ok(PolarsColumnRef {
-            entity_series: table.column(&entity_id_string)?,
-            buf: table.column(&id.0.to_string())?, // TODO(sphw): add a map to metadata between component id and series offset
-        })
-    }
+    #[test]
+    fn test_diff() {
+        #[derive(crate::Component)]
+        struct Temperature(nox::Scalar<f64>);
 
-    fn assets(&self) -> Option<&AssetStore> {
-        None
-    }
+        let mut world = World::default();
+        spawn_bodies(&mut world, 3);
+        let polars = world.to_polars().unwrap();
+        assert!(polars.diff(&polars).is_empty());
 
-    fn tick(&self) -> u64 {
-        self.metadata.tick
-    }
-}
+        world.spawn(Temperature(nox::Scalar::host(21.0)));
+        let mut perturbed = world.to_polars().unwrap();
+        let id = WorldPos::component_id();
+        let values = ndarray::array![
+            [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0, 0.0, 2.25, 0.0, 0.0],
+        ];
+        perturbed.set_component(id, values).unwrap();
 
-impl ColumnRef for PolarsColumnRef<'_> {
-    fn len(&self) -> usize {
-        self.entity_series.len()
-    }
+        let diff = polars.diff(&perturbed);
+        assert!(diff.archetypes_only_in_self.is_empty());
+        assert_eq!(
+            diff.archetypes_only_in_other,
+            &[ArchetypeId::of::<Temperature>()]
+        );
+        assert_eq!(
+            diff.components,
+            &[ComponentDiff {
+                archetype_id: ArchetypeId::of::<Body>(),
+                component_id: id,
+                entities: vec![EntityId(2)],
+                max_abs_delta: 0.25,
+            }]
+        );
 
-    fn entity_buf(&self) -> std::borrow::Cow<'_, [u8]> {
-        Cow::Owned(self.entity_series.to_bytes())
+        let reversed = perturbed.diff(&polars);
+        assert_eq!(
+            reversed.archetypes_only_in_self,
+            &[ArchetypeId::of::<Temperature>()]
+        );
+        assert_eq!(reversed.components[0].max_abs_delta, 0.25);
     }
 
-    fn value_buf(&self) -> std::borrow::Cow<'_, [u8]> {
-        Cow::Owned(self.buf.to_bytes())
+    #[test]
+    fn test_append_tick_history() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 2);
+        let mut polars = world.to_polars().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let id = WorldPos::component_id();
+        for tick in 0..3 {
+            polars.metadata.tick = tick;
+            let x = tick as f64;
+            let values = ndarray::array![
+                [1.0, 0.0, 0.0, 0.0, x, 0.0, 0.0],
+                [1.0, 0.0, 0.0, 0.0, x, 1.0, 0.0],
+            ];
+            polars.set_component(id, values).unwrap();
+            polars.append_tick_to_dir(dir.path()).unwrap();
+        }
+
+        let body_dir = dir
+            .path()
+            .join(ArchetypeId::of::<Body>().to_raw().to_string());
+        assert!(body_dir.join("tick=2.parquet").exists());
+        let history = PolarsWorld::read_history_from_dir(dir.path()).unwrap();
+        assert_eq!(history.len(), 3);
+        for (tick, polars) in history.into_iter().enumerate() {
+            assert_eq!(polars.metadata.tick, tick as u64);
+            assert_eq!(polars.metadata.ticks, &[0, 1, 2]);
+            let x = tick as f64;
+            let world = World::try_from(polars).unwrap();
+            let pos = world.column_by_id(id).unwrap();
+            assert_eq!(
+                pos.typed_buf::<f64>().unwrap(),
+                &[1.0, 0.0, 0.0, 0.0, x, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, x, 1.0, 0.0]
+            );
+        }
+
+        let pbr = world.insert_asset(Pbr::Url("foo".to_string()));
+        world.spawn(body(pbr, 3.0));
+        let mut polars = world.to_polars().unwrap();
+        assert!(matches!(
+            polars.append_tick_to_dir(dir.path()),
+            Err(Error::SchemaMismatch)
+        ));
     }
 
-    fn is_asset(&self) -> bool {
-        false
+    #[test]
+    fn test_world_replay() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 2);
+        let mut polars = world.to_polars().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let id = WorldPos::component_id();
+        for tick in 0..10 {
+            polars.metadata.tick = tick;
+            let x = tick as f64;
+            let values = ndarray::array![
+                [1.0, 0.0, 0.0, 0.0, x, 0.0, 0.0],
+                [1.0, 0.0, 0.0, 0.0, 0.0, x, 0.0],
+            ];
+            polars.set_component(id, values).unwrap();
+            polars.append_tick_to_dir(dir.path()).unwrap();
+        }
+
+        let replay = WorldReplay::open(dir.path()).unwrap();
+        assert_eq!(replay.len(), 10);
+        let world = replay.world(7).unwrap();
+        assert_eq!(world.tick, 7);
+        let pos = world.column_by_id(id).unwrap();
+        assert_eq!(
+            pos.typed_buf::<f64>().unwrap(),
+            &[1.0, 0.0, 0.0, 0.0, 7.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 7.0, 0.0]
+        );
+        assert_eq!(replay.iter().count(), 10);
+        assert!(matches!(replay.world(10), Err(Error::TickOutOfBounds(10))));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::{
-        six_dof::{Body, Force, Inertia, WorldAccel, WorldVel},
-        WorldPos,
-    };
-    use conduit::{
-        well_known::{Material, Mesh, Pbr},
-        ComponentId,
-    };
-    use nox::{
-        nalgebra::{self, vector},
-        SpatialForce, SpatialInertia, SpatialMotion, SpatialTransform,
-    };
-    use polars::prelude::*;
-    use polars_arrow::array::Float64Array;
+    #[test]
+    fn test_delta_trajectory() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 64);
+        let mut polars = world.to_polars().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let parquet_bytes = |path: &Path| -> u64 {
+            std::fs::read_dir(path)
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "parquet"))
+                .map(|path| std::fs::metadata(path).unwrap().len())
+                .sum()
+        };
+        let id = WorldPos::component_id();
+        let mut states = vec![polars.clone()];
+        for tick in 1..5 {
+            let mut values = ndarray::Array2::zeros((64, 7));
+            for (row, mut value) in values.rows_mut().into_iter().enumerate() {
+                value[0] = 1.0;
+                value[4] = row as f64;
+            }
+            values[[0, 5]] = tick as f64 * 0.5;
+            polars.metadata.tick = tick;
+            polars.set_component(id, values).unwrap();
+            states.push(polars.clone());
+        }
 
-    use super::*;
+        let mut full_bytes = 0;
+        let mut delta_bytes = 0;
+        for (tick, pair) in states.windows(2).enumerate() {
+            let mut full = pair[1].clone();
+            let full_dir = dir.path().join(format!("full={tick}"));
+            full.write_to_dir(&full_dir).unwrap();
+            full_bytes += parquet_bytes(&full_dir);
+            let delta_dir = dir.path().join(format!("delta={tick}"));
+            pair[1].write_delta_to_dir(&pair[0], &delta_dir).unwrap();
+            delta_bytes += parquet_bytes(&delta_dir);
+        }
+        assert!(
+            delta_bytes * 4 < full_bytes,
+            "{delta_bytes} vs {full_bytes}"
+        );
+
+        let mut replay = states[0].clone();
+        for (tick, state) in states.iter().enumerate().skip(1) {
+            replay
+                .apply_delta_from_dir(dir.path().join(format!("delta={}", tick - 1)))
+                .unwrap();
+            assert_eq!(replay.metadata.tick, tick as u64);
+            assert!(replay.diff(state).is_empty());
+            let replayed = World::try_from(replay.clone()).unwrap();
+            let expected = World::try_from(state.clone()).unwrap();
+            assert_eq!(
+                replayed.column_by_id(id).unwrap().typed_buf::<f64>(),
+                expected.column_by_id(id).unwrap().typed_buf::<f64>()
+            );
+        }
+
+        // replaying a delta onto anything but its baseline is rejected without touching the world
+        let stale = replay.clone();
+        assert!(matches!(
+            replay.apply_delta_from_dir(dir.path().join("delta=0")),
+            Err(Error::DeltaBaselineMismatch {
+                expected: 0,
+                found: 4
+            })
+        ));
+        assert_eq!(replay.metadata.tick, 4);
+        assert!(replay.diff(&stale).is_empty());
+    }
 
     #[test]
-    fn test_convert_to_df() {
+    fn test_read_validates_metadata() {
         let mut world = World::default();
-        let pbr = world.insert_asset(Pbr::Bundle {
-            mesh: Mesh::sphere(0.1, 36, 18),
-            material: Material::color(1.0, 1.0, 1.0),
+        spawn_bodies(&mut world, 2);
+        let dir = tempfile::tempdir().unwrap();
+        world.to_polars().unwrap().write_to_dir(dir.path()).unwrap();
+        let metadata_path = dir.path().join("metadata.json");
+        let original = std::fs::read(&metadata_path).unwrap();
+        let corrupt = |f: &dyn Fn(&mut Metadata)| {
+            let mut metadata: Metadata = serde_json::from_slice(&original).unwrap();
+            f(&mut metadata);
+            std::fs::write(&metadata_path, serde_json::to_vec(&metadata).unwrap()).unwrap();
+            PolarsWorld::read_from_dir(dir.path())
+        };
+        let body_id = ArchetypeId::of::<Body>();
+
+        let res = corrupt(&|metadata| {
+            metadata
+                .component_map
+                .insert(ComponentId::new("ghost"), ArchetypeId::new(0));
         });
+        assert!(
+            matches!(res, Err(Error::DanglingComponent(id)) if id == ComponentId::new("ghost"))
+        );
 
-        world.spawn(Body {
-            pos: WorldPos(SpatialTransform {
-                inner: vector![1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0].into(),
-            }),
-            vel: WorldVel(SpatialMotion {
-                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 1.0].into(),
-            }),
-            accel: WorldAccel(SpatialMotion {
-                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
-            }),
-            pbr,
-            force: Force(SpatialForce {
-                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
-            }),
-            mass: Inertia(SpatialInertia {
-                inner: vector![1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0].into(),
-            }),
+        let res = corrupt(&|metadata| {
+            let columns = &mut metadata.archetypes.get_mut(&body_id).unwrap().columns;
+            columns[0].metadata.component_type.primitive_ty = PrimitiveTy::F32;
+        });
+        assert!(matches!(res, Err(Error::SchemaMismatch)));
+
+        let res = corrupt(&|metadata| {
+            let columns = &mut metadata.archetypes.get_mut(&body_id).unwrap().columns;
+            columns[0].metadata.component_id = ComponentId::new("ghost");
         });
+        assert!(matches!(res, Err(Error::SchemaMismatch)));
+
+        assert!(corrupt(&|_| {}).is_ok());
+    }
+
+    #[test]
+    fn test_component_metadata() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 1);
         let polars = world.to_polars().unwrap();
-        let df = polars.archetypes[&ArchetypeId::of::<Body>()].clone();
-        let out = df
-            .lazy()
-            .select(&[col(&ComponentId::new("world_pos").0.to_string())])
-            .collect()
-            .unwrap();
-        let pos = out
-            .iter()
-            .next()
-            .unwrap()
-            .array()
-            .unwrap()
-            .get(0)
-            .unwrap()
-            .as_any()
-            .downcast_ref::<Float64Array>()
-            .unwrap()
-            .iter()
-            .filter_map(|f| f.copied())
-            .collect::<Vec<_>>();
-        assert_eq!(pos, &[1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+        let id = WorldPos::component_id();
+        let column = polars.component_metadata(id).unwrap();
+        assert_eq!(column.metadata.component_id, id);
+        assert_eq!(column.metadata.component_type.shape.as_slice(), &[7]);
+        assert!(!column.asset);
+        assert_eq!(world.component_metadata(id), Some(&column.metadata));
+        assert!(polars
+            .component_metadata(ComponentId::new("ghost"))
+            .is_none());
+        assert!(world
+            .component_metadata(ComponentId::new("ghost"))
+            .is_none());
     }
 
     #[test]
-    fn test_write_read_file() {
+    fn test_crate_version() {
         let mut world = World::default();
-        let pbr = world.insert_asset(Pbr::Bundle {
-            mesh: Mesh::sphere(0.1, 36, 18),
-            material: Material::color(1.0, 1.0, 1.0),
-        });
-        world.spawn(Body {
-            pos: WorldPos(SpatialTransform {
-                inner: vector![1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0].into(),
-            }),
-            vel: WorldVel(SpatialMotion {
-                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 1.0].into(),
-            }),
-            accel: WorldAccel(SpatialMotion {
-                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
-            }),
-            pbr,
-            force: Force(SpatialForce {
-                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
-            }),
-            mass: Inertia(SpatialInertia {
-                inner: vector![1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0].into(),
-            }),
-        });
+        spawn_bodies(&mut world, 1);
+        let dir = tempfile::tempdir().unwrap();
+        world.to_polars().unwrap().write_to_dir(dir.path()).unwrap();
+        let strict = ReadOptions {
+            strict_version: true,
+            ..Default::default()
+        };
+        let polars = PolarsWorld::read_from_dir_with_options(dir.path(), &strict).unwrap();
+        assert_eq!(
+            polars.metadata.crate_version.as_deref(),
+            Some(CRATE_VERSION)
+        );
+
+        let metadata_path = dir.path().join("metadata.json");
+        let mut metadata: Metadata =
+            serde_json::from_slice(&std::fs::read(&metadata_path).unwrap()).unwrap();
+        metadata.crate_version = Some("999.0.0".to_string());
+        std::fs::write(&metadata_path, serde_json::to_vec(&metadata).unwrap()).unwrap();
+        assert!(PolarsWorld::read_from_dir(dir.path()).is_ok());
+        assert!(matches!(
+            PolarsWorld::read_from_dir_with_options(dir.path(), &strict),
+            Err(Error::NewerCrateVersion { found, .. }) if found == "999.0.0"
+        ));
+    }
+
+    #[test]
+    fn test_partial_write() {
+        #[derive(crate::Component)]
+        struct Temperature(nox::Scalar<f64>);
+
+        let mut world = World::default();
+        spawn_bodies(&mut world, 2);
+        world.spawn(Temperature(nox::Scalar::host(21.0)));
         let mut polars = world.to_polars().unwrap();
+        let ids = polars.archetypes.keys().copied().collect::<Vec<_>>();
+        assert_eq!(ids.len(), 2);
+
+        // a directory in place of the second archetype's file makes its writer fail
         let dir = tempfile::tempdir().unwrap();
-        let dir = dir.path();
-        polars.write_to_dir(&dir).unwrap();
-        let new_polars = PolarsWorld::read_from_dir(&dir).unwrap();
-        assert_eq!(polars.archetypes, new_polars.archetypes);
+        std::fs::create_dir(dir.path().join(format!("{}.parquet", ids[1].to_raw()))).unwrap();
+        let (written, failed, source) = match polars.write_to_dir(dir.path()) {
+            Err(Error::PartialWrite {
+                written,
+                failed,
+                source,
+            }) => (written, failed, source),
+            res => panic!("expected a partial write, got {:?}", res),
+        };
+        assert_eq!(written, &[ids[0]]);
+        assert_eq!(failed, ids[1]);
+        assert!(matches!(*source, Error::Io(_)));
+        assert!(!dir.path().join("metadata.json").exists());
+
+        let (written, failed) = match polars.write_to_dir_parallel(dir.path()) {
+            Err(Error::PartialWrite {
+                written, failed, ..
+            }) => (written, failed),
+            res => panic!("expected a partial write, got {:?}", res),
+        };
+        assert_eq!(written, &[ids[0]]);
+        assert_eq!(failed, ids[1]);
+        assert!(!dir.path().join("metadata.json").exists());
     }
 
+    #[cfg(target_os = "linux")]
     #[test]
-    fn test_to_world() {
+    fn test_parquet_write_error() {
         let mut world = World::default();
-        let pbr = world.insert_asset(Pbr::Bundle {
-            mesh: Mesh::sphere(0.1, 36, 18),
-            material: Material::color(1.0, 1.0, 1.0),
-        });
+        spawn_bodies(&mut world, 2);
+        let mut polars = world.to_polars().unwrap();
+        let id = ArchetypeId::of::<Body>();
 
-        world.spawn(Body {
-            pos: WorldPos(SpatialTransform {
-                inner: vector![1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0].into(),
-            }),
-            vel: WorldVel(SpatialMotion {
-                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 1.0].into(),
-            }),
-            accel: WorldAccel(SpatialMotion {
-                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
-            }),
-            pbr,
-            force: Force(SpatialForce {
-                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
-            }),
-            mass: Inertia(SpatialInertia {
-                inner: vector![1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0].into(),
-            }),
-        });
-        let polars = world.to_polars().unwrap();
-        let new_world = World::try_from(polars).unwrap();
-        assert_eq!(new_world.archetypes, world.archetypes);
+        // the file opens fine, but every write to it fails inside the parquet writer
+        let dir = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(
+            "/dev/full",
+            dir.path().join(format!("{}.parquet", id.to_raw())),
+        )
+        .unwrap();
+        let source = match polars.write_to_dir(dir.path()) {
+            Err(Error::PartialWrite { source, .. }) => source,
+            res => panic!("expected a partial write, got {:?}", res),
+        };
+        assert!(matches!(*source, Error::Parquet(_)));
+        let message = source.to_string();
+        assert!(message.starts_with("parquet "));
+        assert!(message.contains("No space left on device"), "{}", message);
     }
 
     #[test]
-    fn test_write_read_world() {
+    fn test_components_iter() {
         let mut world = World::default();
-        let pbr = world.insert_asset(Pbr::Bundle {
-            mesh: Mesh::sphere(0.1, 36, 18),
-            material: Material::color(1.0, 1.0, 1.0),
-        });
-        world.spawn(Body {
-            pos: WorldPos(SpatialTransform {
-                inner: vector![1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0].into(),
-            }),
-            vel: WorldVel(SpatialMotion {
-                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 1.0].into(),
-            }),
-            accel: WorldAccel(SpatialMotion {
-                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
-            }),
-            pbr,
-            force: Force(SpatialForce {
-                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
-            }),
-            mass: Inertia(SpatialInertia {
-                inner: vector![1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0].into(),
-            }),
-        });
+        spawn_bodies(&mut world, 3);
         let mut polars = world.to_polars().unwrap();
         let dir = tempfile::tempdir().unwrap();
-        let dir = dir.path();
-        polars.write_to_dir(&dir).unwrap();
-        let new_polars = PolarsWorld::read_from_dir(&dir).unwrap();
-        let new_world = World::try_from(new_polars).unwrap();
-        assert_eq!(new_world.archetypes, world.archetypes);
+        polars.write_to_dir(dir.path()).unwrap();
+        let polars = PolarsWorld::read_from_dir(dir.path()).unwrap();
+
+        let store = &polars;
+        let mut ids = vec![];
+        for (id, column) in store.components() {
+            assert_eq!(column.len(), 3);
+            ids.push(id);
+        }
+        let expected = polars
+            .metadata
+            .component_map
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(ids, expected);
+    }
+
+    #[test]
+    fn test_streaming_world() {
+        let mut world = World::default();
+        spawn_bodies(&mut world, 10);
+        let dir = tempfile::tempdir().unwrap();
+        let options = WriteOptions {
+            row_group_size: 3,
+            ..Default::default()
+        };
+        world
+            .to_polars()
+            .unwrap()
+            .write_to_dir_streaming(dir.path(), &options)
+            .unwrap();
+
+        let streaming = StreamingPolarsWorld::open(dir.path()).unwrap();
+        let column = streaming
+            .streaming_column(WorldPos::component_id())
+            .unwrap();
+        assert_eq!(column.num_row_groups(), 4);
+        let mut entity_buf = vec![];
+        let mut value_buf = vec![];
+        for chunk in column.chunks() {
+            let chunk = chunk.unwrap();
+            assert!(chunk.len() <= 3);
+            entity_buf.extend_from_slice(&chunk.entity_buf());
+            value_buf.extend_from_slice(&chunk.value_buf());
+        }
+
+        let polars = PolarsWorld::read_from_dir(dir.path()).unwrap();
+        let store = &polars;
+        let expected = store.column(WorldPos::component_id()).unwrap();
+        assert_eq!(column.num_rows(), 10);
+        assert_eq!(entity_buf, expected.entity_buf().as_ref());
+        assert_eq!(value_buf, expected.value_buf().as_ref());
+
+        let decoded = streaming.column(WorldPos::component_id()).unwrap();
+        assert_eq!(decoded.len(), 10);
+        assert_eq!(decoded.entity_buf(), expected.entity_buf());
+        assert_eq!(decoded.value_buf(), expected.value_buf());
+
+        // a file that can no longer be decoded is reported rather than panicking
+        let file = dir
+            .path()
+            .join(ArchetypeFormat::Parquet.file_name(ArchetypeId::of::<Body>()));
+        std::fs::write(&file, b"not parquet").unwrap();
+        assert!(streaming.column(WorldPos::component_id()).is_err());
     }
 }
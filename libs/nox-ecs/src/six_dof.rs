@@ -1,11 +1,20 @@
 use conduit::well_known::Pbr;
-use nox::{SpatialForce, SpatialInertia, SpatialMotion};
+use conduit::{ComponentValue, EntityId};
+use nox::{
+    nalgebra, xla, ArrayTy, ConstantExt, FixedSliceExt, IntoOp, Noxpr, NoxprNode, Scalar,
+    SpatialForce, SpatialInertia, SpatialMotion, SpatialTransform, Vector,
+};
+use nox_ecs::graph::{Edge, EdgeComponent, GraphQuery};
 use nox_ecs::{Archetype, Component};
-use nox_ecs::{Handle, IntoSystem, Query, Rk4Ext, System, WorldPos};
+use nox_ecs::{Handle, Integrator, IntegratorExt, IntoSystem, Query, System, WorldPos};
 use nox_ecs_macros::{ComponentGroup, FromBuilder, IntoOp};
-use std::ops::{Add, Mul};
+use smallvec::smallvec;
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use std::ops::{Add, Deref, Mul};
 
-use crate::ComponentArray;
+use crate::query::filter_index;
+use crate::{ComponentArray, Error, HostStore, PipelineBuilder, SystemParam, World};
 
 #[derive(Clone, Component)]
 pub struct WorldVel(pub SpatialMotion<f64>);
@@ -71,6 +80,346 @@ fn clear_forces(q: ComponentArray<Force>) -> ComponentArray<Force> {
     q.map(|_| Force(SpatialForce::zero())).unwrap()
 }
 
+/// Newton's gravitational constant in m^3 kg^-1 s^-2
+pub const G: f64 = 6.6743e-11;
+
+/// A uniform gravitational acceleration applied to a body by [`uniform_gravity`]
+#[derive(Clone, Component)]
+pub struct Gravity(pub Vector<f64, 3>);
+
+/// Marks a pair of bodies that attract each other in [`point_mass_gravity`].
+///
+/// The edge only applies force to its `from` body, so mutual attraction needs an edge in each direction.
+#[derive(Clone, Component)]
+pub struct GravityEdge(pub Edge);
+
+impl GravityEdge {
+    pub fn new(from: impl Into<EntityId>, to: impl Into<EntityId>) -> Self {
+        Self(Edge::new(from, to))
+    }
+}
+
+impl EdgeComponent for GravityEdge {
+    fn to_edge(&self) -> Edge {
+        self.0.clone()
+    }
+
+    fn from_value(value: ComponentValue<'_>) -> Option<Self> {
+        Edge::from_value(value).map(GravityEdge)
+    }
+}
+
+/// Adds `mass * g` to the force of every body with a [`Gravity`] component
+pub fn uniform_gravity(q: Query<(Gravity, Inertia, Force)>) -> Query<Force> {
+    q.map(|gravity: Gravity, inertia: Inertia, force: Force| {
+        Force(force.0 + SpatialForce::from_linear(gravity.0 * inertia.0.mass()))
+    })
+    .unwrap()
+}
+
+/// Adds the Newtonian attraction between each pair of bodies joined by a [`GravityEdge`]
+pub fn point_mass_gravity(
+    graph: GraphQuery<GravityEdge, (WorldPos, Inertia)>,
+    forces: Query<Force>,
+) -> Query<Force> {
+    let attraction: Query<Force> = graph
+        .edge_fold(
+            Force(SpatialForce::zero()),
+            |acc: Force,
+             ((a_pos, a_inertia), (b_pos, b_inertia)): (
+                (WorldPos, Inertia),
+                (WorldPos, Inertia),
+            )| {
+                let r = a_pos.0.linear() - b_pos.0.linear();
+                let norm = r.clone().norm();
+                let f = r / (norm.clone() * norm.clone() * norm)
+                    * G
+                    * a_inertia.0.mass()
+                    * b_inertia.0.mass();
+                Force(acc.0 - SpatialForce::from_linear(f))
+            },
+        )
+        .into();
+    forces
+        .join_query(attraction)
+        .map(|force: Force, attraction: Force| Force(force.0 + attraction.0))
+        .unwrap()
+}
+
+/// A point-to-point joint pinning a point on its `from` body to a point on its `to` body, enforced
+/// by [`joint_constraints`].
+///
+/// Each anchor is given in its own body's frame, so one body can be joined at different points by
+/// different joints, and a single joint corrects both of its bodies.
+#[derive(Archetype)]
+pub struct Joint {
+    pub edge: JointEdge,
+    pub anchors: JointAnchors,
+}
+
+impl Joint {
+    pub fn new(
+        from: impl Into<EntityId>,
+        to: impl Into<EntityId>,
+        from_anchor: nalgebra::Vector3<f64>,
+        to_anchor: nalgebra::Vector3<f64>,
+    ) -> Self {
+        let (f, t) = (from_anchor, to_anchor);
+        Self {
+            edge: JointEdge(Edge::new(from, to)),
+            anchors: JointAnchors(nalgebra::Vector6::new(f.x, f.y, f.z, t.x, t.y, t.z).into()),
+        }
+    }
+}
+
+#[derive(Clone, Component)]
+pub struct JointEdge(pub Edge);
+
+impl EdgeComponent for JointEdge {
+    fn to_edge(&self) -> Edge {
+        self.0.clone()
+    }
+
+    fn from_value(value: ComponentValue<'_>) -> Option<Self> {
+        Edge::from_value(value).map(JointEdge)
+    }
+}
+
+/// The anchor on a [`Joint`]'s `from` body followed by the anchor on its `to` body
+#[derive(Clone, Component)]
+pub struct JointAnchors(pub Vector<f64, 6>);
+
+/// Every [`Joint`] in the world, read when the pipeline is built like the edges of a [`GraphQuery`]
+pub struct Joints {
+    joints: Vec<(Edge, [f64; 6])>,
+}
+
+impl SystemParam for Joints {
+    type Item = Self;
+
+    fn init(_builder: &mut PipelineBuilder) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn from_builder(builder: &PipelineBuilder) -> Self::Item {
+        let world = &builder.world;
+        let (Some(edges), Some(anchors)) =
+            (world.column::<JointEdge>(), world.column::<JointAnchors>())
+        else {
+            return Joints { joints: vec![] };
+        };
+        let anchors = anchors
+            .iter()
+            .filter_map(|(id, value)| {
+                let ComponentValue::F64(value) = value else {
+                    return None;
+                };
+                Some((id, <[f64; 6]>::try_from(value.as_slice()?).ok()?))
+            })
+            .collect::<BTreeMap<_, _>>();
+        let joints = edges
+            .iter()
+            .filter_map(|(id, value)| Some((JointEdge::from_value(value)?.0, *anchors.get(&id)?)))
+            .collect();
+        Joints { joints }
+    }
+
+    fn insert_into_builder(self, _builder: &mut PipelineBuilder) {}
+}
+
+#[derive(Clone, Component)]
+struct JointCorrection(Vector<f64, 3>);
+
+#[derive(FromBuilder, ComponentGroup, IntoOp)]
+struct JointCorrections {
+    from_pos: JointCorrection,
+    from_vel: JointCorrection,
+    to_pos: JointCorrection,
+    to_vel: JointCorrection,
+}
+
+type JointBody = (WorldPos, WorldVel, Inertia);
+
+/// Projects every body joined by a [`Joint`] back onto the constraint after it has been integrated,
+/// so it should be piped after [`six_dof`].
+///
+/// Each joint removes the separation and relative velocity of its two anchor points by
+/// translating both bodies, split by mass so the lighter body moves further. Only the linear parts
+/// are corrected, and a body with several joints sums their corrections.
+pub fn joint_constraints(
+    joints: Joints,
+    bodies: Query<JointBody>,
+    pos: Query<WorldPos>,
+    vel: Query<WorldVel>,
+) -> (Query<WorldPos>, Query<WorldVel>) {
+    let joints = joints
+        .joints
+        .iter()
+        .filter_map(|(edge, anchors)| {
+            let from = *bodies.entity_map.get(&edge.from)? as u32;
+            let to = *bodies.entity_map.get(&edge.to)? as u32;
+            Some((from, to, anchors))
+        })
+        .collect::<Vec<_>>();
+    if joints.is_empty() {
+        return (pos, vel);
+    }
+    let (n, m) = (joints.len(), bodies.len);
+
+    // gather both bodies of every joint into one row per joint
+    let from_rows = joints.iter().map(|(from, ..)| *from).collect::<Vec<_>>();
+    let to_rows = joints.iter().map(|(_, to, _)| *to).collect::<Vec<_>>();
+    let anchors = joints
+        .iter()
+        .flat_map(|(.., anchors)| anchors.iter().copied())
+        .collect::<Vec<_>>();
+    let anchors = Noxpr::constant(
+        xla::Literal::vector(&anchors)
+            .reshape(&[n as i64, 6])
+            .unwrap(),
+        ArrayTy {
+            element_type: xla::ElementType::F64,
+            shape: smallvec![n as i64, 6],
+        },
+    );
+    let exprs = bodies
+        .exprs
+        .iter()
+        .map(|expr| filter_index(&from_rows, expr))
+        .chain(bodies.exprs.iter().map(|expr| filter_index(&to_rows, expr)))
+        .chain(std::iter::once(anchors))
+        .collect();
+    // the rows are joints rather than entities, so they're keyed by index
+    let pairs: Query<(JointBody, JointBody, JointAnchors)> = Query {
+        exprs,
+        entity_map: (0..n).map(|i| (EntityId(i as u64), i)).collect(),
+        len: n,
+        phantom_data: PhantomData,
+    };
+    let corrections = pairs
+        .map(
+            |(a_pos, a_vel, a_inertia): JointBody,
+             (b_pos, b_vel, b_inertia): JointBody,
+             anchors: JointAnchors| {
+                let a_anchor: Vector<f64, 3> = anchors.0.fixed_slice([0]);
+                let b_anchor: Vector<f64, 3> = anchors.0.fixed_slice([3]);
+                let r_a = a_pos.0.angular() * a_anchor;
+                let r_b = b_pos.0.angular() * b_anchor;
+                let error = (a_pos.0.linear() + r_a.clone()) - (b_pos.0.linear() + r_b.clone());
+                let error_vel = (a_vel.0.linear() + a_vel.0.angular().cross(&r_a))
+                    - (b_vel.0.linear() + b_vel.0.angular().cross(&r_b));
+                let (m_a, m_b) = (a_inertia.0.mass(), b_inertia.0.mass());
+                let total = m_a.clone() + m_b.clone();
+                let (a_share, b_share) = (m_b / total.clone(), m_a / total);
+                JointCorrections {
+                    from_pos: JointCorrection(-error.clone() * a_share.clone()),
+                    from_vel: JointCorrection(-error_vel.clone() * a_share),
+                    to_pos: JointCorrection(error * b_share.clone()),
+                    to_vel: JointCorrection(error_vel * b_share),
+                }
+            },
+        )
+        .unwrap();
+
+    // sum each joint's corrections back onto its bodies with one-hot [body, joint] matrices
+    let one_hot = |rows: &[u32]| {
+        let mut values = vec![0.0; m * n];
+        for (joint, row) in rows.iter().enumerate() {
+            values[*row as usize * n + joint] = 1.0;
+        }
+        Noxpr::constant(
+            xla::Literal::vector(&values)
+                .reshape(&[m as i64, n as i64])
+                .unwrap(),
+            ArrayTy {
+                element_type: xla::ElementType::F64,
+                shape: smallvec![m as i64, n as i64],
+            },
+        )
+    };
+    let (from_sum, to_sum) = (one_hot(&from_rows), one_hot(&to_rows));
+    let correction = |from: &Noxpr, to: &Noxpr| -> Query<JointCorrection> {
+        ComponentArray {
+            buffer: from_sum.clone().dot(from) + to_sum.clone().dot(to),
+            len: m,
+            entity_map: bodies.entity_map.clone(),
+            phantom_data: PhantomData,
+        }
+        .into()
+    };
+    let dx = correction(&corrections.exprs[0], &corrections.exprs[2]);
+    let dv = correction(&corrections.exprs[1], &corrections.exprs[3]);
+    let pos = pos
+        .join_query(dx)
+        .map(|pos: WorldPos, dx: JointCorrection| {
+            WorldPos(SpatialTransform::new(
+                pos.0.angular(),
+                pos.0.linear() + dx.0,
+            ))
+        })
+        .unwrap();
+    let vel = vel
+        .join_query(dv)
+        .map(|vel: WorldVel, dv: JointCorrection| {
+            WorldVel(SpatialMotion::new(vel.0.angular(), vel.0.linear() + dv.0))
+        })
+        .unwrap();
+    (pos, vel)
+}
+
+/// The coefficient of the drag term proportional to velocity, see [`Drag`]
+#[derive(Clone, Component)]
+pub struct LinearDrag(pub Scalar<f64>);
+
+/// The coefficient of the drag term proportional to the square of velocity, see [`Drag`]
+#[derive(Clone, Component)]
+pub struct QuadraticDrag(pub Scalar<f64>);
+
+/// Velocity dependent damping applied to a body by [`drag`].
+///
+/// Each coefficient is stored as its own component, so `Drag` is inserted alongside a body's
+/// archetype rather than as one of its fields.
+#[derive(Archetype, ComponentGroup, FromBuilder)]
+pub struct Drag {
+    pub linear: LinearDrag,
+    pub quadratic: QuadraticDrag,
+}
+
+impl Drag {
+    pub fn new(linear: f64, quadratic: f64) -> Self {
+        Drag {
+            linear: LinearDrag::host(linear),
+            quadratic: QuadraticDrag::host(quadratic),
+        }
+    }
+
+    fn damp(&self, v: Vector<f64, 3>) -> Vector<f64, 3> {
+        // the derivative of the square root is infinite at zero, which a select would still turn
+        // into a NaN gradient at rest, so the root is taken of one there instead
+        let speed_squared = v.norm_squared();
+        let tiny = f64::MIN_POSITIVE.constant();
+        let at_rest = |on_true: Scalar<f64>, on_false: Scalar<f64>| {
+            speed_squared.select_less(&tiny, on_true, on_false)
+        };
+        let speed = at_rest(
+            0.0f64.constant(),
+            at_rest(1.0f64.constant(), speed_squared.clone()).sqrt(),
+        );
+        -(v.clone() * self.linear.0.clone() + v * (speed * self.quadratic.0.clone()))
+    }
+}
+
+/// Adds `-(linear * v + quadratic * |v| * v)` to the force of every body with a [`Drag`] component,
+/// damping the angular and linear velocity independently
+pub fn drag(q: Query<(Drag, WorldVel, Force)>) -> Query<Force> {
+    q.map(|drag: Drag, vel: WorldVel, force: Force| {
+        let torque = drag.damp(vel.0.angular());
+        let linear = drag.damp(vel.0.linear());
+        Force(force.0 + SpatialForce::new(torque, linear))
+    })
+    .unwrap()
+}
+
 #[derive(Archetype)]
 pub struct Body {
     pub pos: WorldPos,
@@ -81,7 +430,174 @@ pub struct Body {
     pub pbr: Handle<Pbr>,
 }
 
+impl World<HostStore> {
+    /// Changes the velocity of `entity` by `impulse / inertia`, as [`calc_accel`] does for a
+    /// force, without waiting for the force to be integrated over a tick.
+    ///
+    /// `impulse` must be a host value rather than a traced expression.
+    pub fn apply_impulse(
+        &mut self,
+        entity: EntityId,
+        impulse: SpatialForce<f64>,
+    ) -> Result<(), Error> {
+        let op = impulse.into_op();
+        let NoxprNode::Constant(impulse) = op.deref() else {
+            return Err(Error::NonConstant);
+        };
+        let impulse: Vec<f64> = bytemuck::pod_collect_to_vec(impulse.data.raw_buf());
+        let inertia = self.column::<Inertia>().ok_or(Error::ComponentNotFound)?;
+        let row = *inertia
+            .entity_map
+            .get(&entity)
+            .ok_or(Error::ComponentNotFound)?;
+        let inertia = inertia
+            .column
+            .buffer
+            .get::<f64>(row)
+            .ok_or(Error::ComponentNotFound)?
+            .to_vec();
+        let mut vel = self
+            .column_mut::<WorldVel>()
+            .ok_or(Error::ComponentNotFound)?;
+        let row = *vel
+            .entity_map
+            .get(&entity)
+            .ok_or(Error::ComponentNotFound)?;
+        let mut value = vel
+            .column
+            .buffer
+            .get::<f64>(row)
+            .ok_or(Error::ComponentNotFound)?
+            .to_vec();
+        // [torque, force] / [inertia_diag, momentum, mass] -> [angular, linear]
+        let (angular, linear) = value.split_at_mut(3);
+        for ((v, torque), i) in angular.iter_mut().zip(&impulse[..3]).zip(&inertia[..3]) {
+            *v += torque / i;
+        }
+        for (v, force) in linear.iter_mut().zip(&impulse[3..]) {
+            *v += force / inertia[6];
+        }
+        vel.column.buffer.set(row, &value)
+    }
+
+    /// The total kinetic energy `0.5 * v · (I * v)` of every body with an [`Inertia`] and a
+    /// [`WorldVel`].
+    pub fn kinetic_energy(&self) -> Result<f64, Error> {
+        Ok(self
+            .body_momenta()?
+            .iter()
+            .map(|(_, vel, momentum)| {
+                0.5 * vel.iter().zip(momentum).map(|(v, h)| v * h).sum::<f64>()
+            })
+            .sum())
+    }
+
+    /// The total linear momentum of every body with an [`Inertia`] and a [`WorldVel`].
+    pub fn linear_momentum(&self) -> Result<[f64; 3], Error> {
+        let mut total = [0.0; 3];
+        for (_, _, momentum) in self.body_momenta()? {
+            for (t, p) in total.iter_mut().zip(&momentum[3..]) {
+                *t += p;
+            }
+        }
+        Ok(total)
+    }
+
+    /// The total angular momentum about the world origin, adding each body's own spin to the
+    /// moment of its linear momentum about the origin.
+    pub fn angular_momentum(&self) -> Result<[f64; 3], Error> {
+        let mut total = [0.0; 3];
+        for (pos, _, momentum) in self.body_momenta()? {
+            let orbital = cross(&pos[4..], &momentum[3..]);
+            for ((t, spin), orbital) in total.iter_mut().zip(&momentum[..3]).zip(orbital) {
+                *t += spin + orbital;
+            }
+        }
+        Ok(total)
+    }
+
+    /// Returns the pose, velocity and `[angular, linear]` momentum of every body, evaluating
+    /// `SpatialInertia * SpatialMotion` on the host.
+    #[allow(clippy::type_complexity)]
+    fn body_momenta(&self) -> Result<Vec<(Vec<f64>, Vec<f64>, [f64; 6])>, Error> {
+        let inertia = self.column::<Inertia>().ok_or(Error::ComponentNotFound)?;
+        let vel = self.column::<WorldVel>().ok_or(Error::ComponentNotFound)?;
+        let pos = self.column::<WorldPos>().ok_or(Error::ComponentNotFound)?;
+        let row =
+            |column: &crate::HostColumnRef<'_>, entity: &EntityId| -> Result<Vec<f64>, Error> {
+                let row = *column
+                    .entity_map
+                    .get(entity)
+                    .ok_or(Error::ComponentNotFound)?;
+                Ok(column
+                    .column
+                    .buffer
+                    .get::<f64>(row)
+                    .ok_or(Error::ComponentNotFound)?
+                    .to_vec())
+            };
+        inertia
+            .entity_map
+            .keys()
+            .map(|entity| {
+                let inertia = row(&inertia, entity)?;
+                let vel = row(&vel, entity)?;
+                let pos = row(&pos, entity)?;
+                // the inertia is expressed in the body frame, so the world velocity is rotated
+                // into it by the attitude and the resulting momentum is rotated back out
+                let attitude = &pos[..4];
+                let angular = rotate(attitude, &vel[..3], true);
+                let linear = rotate(attitude, &vel[3..], true);
+                // [inertia_diag, momentum, mass] * [angular, linear] -> [torque, force]
+                let (diag, first_moment, mass) = (&inertia[..3], &inertia[3..6], inertia[6]);
+                let c_linear = cross(first_moment, &linear);
+                let c_angular = cross(first_moment, &angular);
+                let spin: [f64; 3] = std::array::from_fn(|i| diag[i] * angular[i] + c_linear[i]);
+                let translation: [f64; 3] =
+                    std::array::from_fn(|i| mass * linear[i] - c_angular[i]);
+                let (spin, translation) = (
+                    rotate(attitude, &spin, false),
+                    rotate(attitude, &translation, false),
+                );
+                let momentum = std::array::from_fn(|i| match i {
+                    0..=2 => spin[i],
+                    _ => translation[i - 3],
+                });
+                Ok((pos, vel, momentum))
+            })
+            .collect()
+    }
+}
+
+fn cross(a: &[f64], b: &[f64]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// Rotates `v` by the unit quaternion `q = [x, y, z, w]`, or by its conjugate if `inverse` is set.
+fn rotate(q: &[f64], v: &[f64], inverse: bool) -> [f64; 3] {
+    let sign = if inverse { -1.0 } else { 1.0 };
+    let u = [sign * q[0], sign * q[1], sign * q[2]];
+    let t = cross(&u, v).map(|t| 2.0 * t);
+    let u_t = cross(&u, &t);
+    std::array::from_fn(|i| v[i] + q[3] * t[i] + u_t[i])
+}
+
 pub fn six_dof<Sys, M, A, R>(effectors: impl FnOnce() -> Sys, time_step: f64) -> impl System
+where
+    Sys: IntoSystem<M, A, R>,
+{
+    six_dof_with_integrator(effectors, time_step, Integrator::Rk4)
+}
+
+pub fn six_dof_with_integrator<Sys, M, A, R>(
+    effectors: impl FnOnce() -> Sys,
+    time_step: f64,
+    integrator: Integrator,
+) -> impl System
 where
     Sys: IntoSystem<M, A, R>,
 {
@@ -89,5 +605,494 @@ where
     clear_forces
         .pipe(effectors)
         .pipe(calc_accel)
-        .rk4_with_dt::<U, DU>(time_step)
+        .integrate_with_dt::<U, DU>(integrator, time_step)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::World;
+    use nox::nalgebra::{self, vector};
+    use nox::SpatialTransform;
+
+    #[test]
+    fn test_orbit_energy_drift() {
+        fn gravity(q: Query<(WorldPos, Inertia, Force)>) -> Query<Force> {
+            q.map(|pos: WorldPos, inertia: Inertia, force: Force| {
+                let r = pos.0.linear();
+                let norm = r.clone().norm();
+                let mass = inertia.0.mass();
+                Force(
+                    force.0
+                        + SpatialForce::from_linear(
+                            -r / (norm.clone() * norm.clone() * norm) * mass,
+                        ),
+                )
+            })
+            .unwrap()
+        }
+
+        #[derive(Archetype)]
+        struct Orbiter {
+            pos: WorldPos,
+            vel: WorldVel,
+            accel: WorldAccel,
+            force: Force,
+            mass: Inertia,
+        }
+
+        // specific orbital energy of a unit-mass body around a unit gravitational parameter
+        fn energy(pos: &[f64], vel: &[f64]) -> f64 {
+            let r = (pos[4] * pos[4] + pos[5] * pos[5] + pos[6] * pos[6]).sqrt();
+            let v2 = vel[3] * vel[3] + vel[4] * vel[4] + vel[5] * vel[5];
+            0.5 * v2 - 1.0 / r
+        }
+
+        let drift = |integrator: Integrator| -> f64 {
+            let mut world = World::default();
+            world.spawn(Orbiter {
+                pos: WorldPos(SpatialTransform {
+                    inner: vector![0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0].into(),
+                }),
+                vel: WorldVel(SpatialMotion {
+                    inner: vector![0.0, 0.0, 0.0, 0.0, 1.0, 0.0].into(),
+                }),
+                accel: WorldAccel(SpatialMotion {
+                    inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+                }),
+                force: Force(SpatialForce {
+                    inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+                }),
+                mass: Inertia(SpatialInertia {
+                    inner: vector![1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0].into(),
+                }),
+            });
+            let client = nox::Client::cpu().unwrap();
+            let mut exec = world
+                .builder()
+                .tick_pipeline(six_dof_with_integrator(|| gravity, 0.05, integrator))
+                .build()
+                .unwrap();
+            for _ in 0..200 {
+                exec.run(&client).unwrap();
+            }
+            let pos = exec.column(WorldPos::component_id()).unwrap();
+            let vel = exec.column(WorldVel::component_id()).unwrap();
+            let e = energy(
+                pos.typed_buf::<f64>().unwrap(),
+                vel.typed_buf::<f64>().unwrap(),
+            );
+            (e - -0.5).abs()
+        };
+
+        let euler = drift(Integrator::Euler);
+        let rk4 = drift(Integrator::Rk4);
+        assert!(rk4 < 1e-4, "rk4 drift {rk4}");
+        assert!(rk4 * 100.0 < euler, "rk4 drift {rk4}, euler drift {euler}");
+    }
+
+    #[test]
+    fn test_uniform_gravity_drop() {
+        #[derive(Archetype)]
+        struct Falling {
+            pos: WorldPos,
+            vel: WorldVel,
+            accel: WorldAccel,
+            force: Force,
+            mass: Inertia,
+            gravity: Gravity,
+        }
+
+        let mut world = World::default();
+        world.spawn(Falling {
+            pos: WorldPos(SpatialTransform {
+                inner: vector![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0].into(),
+            }),
+            vel: WorldVel(SpatialMotion {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            accel: WorldAccel(SpatialMotion {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            force: Force(SpatialForce {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            mass: Inertia(SpatialInertia {
+                inner: vector![1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 2.0].into(),
+            }),
+            gravity: Gravity(vector![0.0, 0.0, -9.81].into()),
+        });
+        let time_step = 1.0 / 60.0;
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(six_dof(|| uniform_gravity, time_step))
+            .build()
+            .unwrap();
+        let ticks = 60;
+        for _ in 0..ticks {
+            exec.run(&client).unwrap();
+        }
+        let t = ticks as f64 * time_step;
+        let pos = exec.column(WorldPos::component_id()).unwrap();
+        let z = pos.typed_buf::<f64>().unwrap()[6];
+        assert!((z - -0.5 * 9.81 * t * t).abs() < 1e-9, "z = {z}");
+    }
+
+    #[test]
+    fn test_point_mass_gravity() {
+        #[derive(Archetype)]
+        struct PointMass {
+            pos: WorldPos,
+            vel: WorldVel,
+            accel: WorldAccel,
+            force: Force,
+            mass: Inertia,
+        }
+
+        let point_mass = |x: f64| PointMass {
+            pos: WorldPos(SpatialTransform {
+                inner: vector![0.0, 0.0, 0.0, 1.0, x, 0.0, 0.0].into(),
+            }),
+            vel: WorldVel(SpatialMotion {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            accel: WorldAccel(SpatialMotion {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            force: Force(SpatialForce {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            mass: Inertia(SpatialInertia {
+                inner: vector![1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0 / G].into(),
+            }),
+        };
+
+        let mut world = World::default();
+        let a = world.spawn(point_mass(1.0)).id();
+        let b = world.spawn(point_mass(-1.0)).id();
+        world.spawn(GravityEdge::new(a, b));
+        world.spawn(GravityEdge::new(b, a));
+        let time_step = 1e-3;
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(six_dof(|| point_mass_gravity, time_step))
+            .build()
+            .unwrap();
+        exec.run(&client).unwrap();
+        // each body is pulled toward the other at G * (1 / G) / 2^2
+        let vel = exec.column(WorldVel::component_id()).unwrap();
+        let vel = vel.typed_buf::<f64>().unwrap();
+        assert!((vel[3] - -0.25 * time_step).abs() < 1e-9);
+        assert!((vel[9] - 0.25 * time_step).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_apply_impulse() {
+        let mut world = World::default();
+        let pbr = world.insert_asset(Pbr::Url("body".to_string()));
+        let body = world
+            .spawn(Body {
+                pos: WorldPos(SpatialTransform {
+                    inner: vector![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0].into(),
+                }),
+                vel: WorldVel(SpatialMotion {
+                    inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+                }),
+                accel: WorldAccel(SpatialMotion {
+                    inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+                }),
+                force: Force(SpatialForce {
+                    inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+                }),
+                mass: Inertia(SpatialInertia {
+                    inner: vector![1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 4.0].into(),
+                }),
+                pbr,
+            })
+            .id();
+        let impulse = SpatialForce {
+            inner: vector![0.0, 0.0, 0.0, 2.0, 0.0, -1.0].into(),
+        };
+        world.apply_impulse(body, impulse).unwrap();
+        let vel = world.column::<WorldVel>().unwrap();
+        assert_eq!(
+            vel.column.buffer.get::<f64>(0).unwrap(),
+            &[0.0, 0.0, 0.0, 0.5, 0.0, -0.25]
+        );
+
+        let marker = world
+            .spawn(WorldPos(SpatialTransform {
+                inner: vector![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0].into(),
+            }))
+            .id();
+        let impulse = SpatialForce {
+            inner: vector![0.0, 0.0, 0.0, 1.0, 0.0, 0.0].into(),
+        };
+        assert!(matches!(
+            world.apply_impulse(marker, impulse),
+            Err(Error::ComponentNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_spinning_body_conservation() {
+        let mut world = World::default();
+        let pbr = world.insert_asset(Pbr::Url("body".to_string()));
+        world.spawn(Body {
+            pos: WorldPos(SpatialTransform {
+                inner: vector![0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 0.0].into(),
+            }),
+            vel: WorldVel(SpatialMotion {
+                inner: vector![0.0, 0.0, 2.0, 1.0, 0.0, 0.0].into(),
+            }),
+            accel: WorldAccel(SpatialMotion {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            force: Force(SpatialForce {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            mass: Inertia(SpatialInertia {
+                inner: vector![1.0, 2.0, 3.0, 0.0, 0.0, 0.0, 2.0].into(),
+            }),
+            pbr,
+        });
+        // spin [0, 0, 3 * 2] plus the moment [0, 1, 0] x [2, 0, 0] of the linear momentum
+        let expected_angular = [0.0, 0.0, 4.0];
+        assert_eq!(world.angular_momentum().unwrap(), expected_angular);
+        assert_eq!(world.linear_momentum().unwrap(), [2.0, 0.0, 0.0]);
+        assert_eq!(world.kinetic_energy().unwrap(), 7.0);
+
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(six_dof(|| (), 0.01))
+            .build()
+            .unwrap();
+        for _ in 0..5 {
+            for _ in 0..100 {
+                exec.run(&client).unwrap();
+            }
+            for id in [
+                WorldPos::component_id(),
+                WorldVel::component_id(),
+                Inertia::component_id(),
+            ] {
+                exec.column(id).unwrap();
+            }
+            let world = &exec.world.host;
+            let angular = world.angular_momentum().unwrap();
+            for (found, expected) in angular.iter().zip(expected_angular) {
+                assert!((found - expected).abs() < 1e-9, "{angular:?}");
+            }
+            assert!((world.kinetic_energy().unwrap() - 7.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_rotated_body_momentum() {
+        let mut world = World::default();
+        let pbr = world.insert_asset(Pbr::Url("body".to_string()));
+        let half = std::f64::consts::FRAC_1_SQRT_2;
+        // rotated a quarter turn about x, so the body y axis points along world z
+        world.spawn(Body {
+            pos: WorldPos(SpatialTransform {
+                inner: vector![half, 0.0, 0.0, half, 0.0, 0.0, 0.0].into(),
+            }),
+            vel: WorldVel(SpatialMotion {
+                inner: vector![1.0, 0.0, 2.0, 0.0, 0.0, 0.0].into(),
+            }),
+            accel: WorldAccel(SpatialMotion {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            force: Force(SpatialForce {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            mass: Inertia(SpatialInertia {
+                inner: vector![1.0, 2.0, 3.0, 0.0, 0.0, 0.0, 2.0].into(),
+            }),
+            pbr,
+        });
+        // the body frame sees [1, 2, 0], so the spin is [1, 4, 0] in the body frame
+        let angular = world.angular_momentum().unwrap();
+        for (found, expected) in angular.iter().zip([1.0, 0.0, 4.0]) {
+            assert!((found - expected).abs() < 1e-12, "{angular:?}");
+        }
+        assert!((world.kinetic_energy().unwrap() - 4.5).abs() < 1e-12);
+    }
+
+    #[derive(Archetype)]
+    struct Link {
+        pos: WorldPos,
+        vel: WorldVel,
+        accel: WorldAccel,
+        force: Force,
+        mass: Inertia,
+        gravity: Gravity,
+    }
+
+    /// A body at rest at `pos`, rotated by the quaternion `rot`, with only gravity `g` along z
+    fn link(rot: [f64; 4], pos: [f64; 3], mass: f64, g: f64) -> Link {
+        let [qx, qy, qz, qw] = rot;
+        let [x, y, z] = pos;
+        Link {
+            pos: WorldPos(SpatialTransform {
+                inner: vector![qx, qy, qz, qw, x, y, z].into(),
+            }),
+            vel: WorldVel(SpatialMotion {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            accel: WorldAccel(SpatialMotion {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            force: Force(SpatialForce {
+                inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+            }),
+            mass: Inertia(SpatialInertia {
+                inner: vector![1.0, 1.0, 1.0, 0.0, 0.0, 0.0, mass].into(),
+            }),
+            gravity: Gravity(vector![0.0, 0.0, g].into()),
+        }
+    }
+
+    /// Runs `world` with joints for `ticks`, checking after every tick that the world-frame
+    /// anchors returned by `anchors` for the two bodies' positions stay coincident.
+    fn run_joint(
+        world: World,
+        (a, b): (EntityId, EntityId),
+        ticks: usize,
+        anchors: impl Fn(&[f64], &[f64]) -> ([f64; 3], [f64; 3]),
+    ) -> crate::WorldExec {
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(six_dof(|| uniform_gravity, 0.01).pipe(joint_constraints))
+            .build()
+            .unwrap();
+        for _ in 0..ticks {
+            exec.run(&client).unwrap();
+            let pos = exec.column(WorldPos::component_id()).unwrap();
+            let row = |id| pos.entity_map[&id] * 7;
+            let pos = pos.typed_buf::<f64>().unwrap();
+            let (a, b) = anchors(&pos[row(a)..row(a) + 7], &pos[row(b)..row(b) + 7]);
+            let gap = [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+            assert!(gap.iter().all(|d| d.abs() < 1e-9), "gap {gap:?}");
+        }
+        exec
+    }
+
+    #[test]
+    fn test_point_joint() {
+        // only `a` is pulled down, and it has to drag `b` along through the joint
+        let mut world = World::default();
+        let identity = [0.0, 0.0, 0.0, 1.0];
+        let a = world.spawn(link(identity, [0.0; 3], 1.0, -9.81)).id();
+        let b = world.spawn(link(identity, [1.0, 0.0, 0.0], 3.0, 0.0)).id();
+        world.spawn(Joint::new(
+            a,
+            b,
+            vector![0.5, 0.0, 0.0],
+            vector![-0.5, 0.0, 0.0],
+        ));
+        let ticks = 100;
+        let exec = run_joint(world, (a, b), ticks, |a, b| {
+            ([a[4] + 0.5, a[5], a[6]], [b[4] - 0.5, b[5], b[6]])
+        });
+        // a single joint moves both bodies, and the pair falls together at m_a * g / (m_a + m_b)
+        let t = ticks as f64 * 0.01;
+        let vel = exec.column(WorldVel::component_id()).unwrap();
+        let vel = vel.typed_buf::<f64>().unwrap();
+        for z in [vel[5], vel[11]] {
+            assert!((z - -9.81 / 4.0 * t).abs() < 1e-9, "vz = {z}");
+        }
+    }
+
+    #[test]
+    fn test_rotated_joint() {
+        // `a` is turned 90 degrees about z, so its +x anchor sits along +y in the world frame
+        let mut world = World::default();
+        let half = std::f64::consts::FRAC_PI_4;
+        let a = world
+            .spawn(link(
+                [0.0, 0.0, half.sin(), half.cos()],
+                [0.0; 3],
+                2.0,
+                -9.81,
+            ))
+            .id();
+        let b = world
+            .spawn(link([0.0, 0.0, 0.0, 1.0], [0.0, 1.0, 0.0], 2.0, 0.0))
+            .id();
+        world.spawn(Joint::new(
+            a,
+            b,
+            vector![0.5, 0.0, 0.0],
+            vector![0.0, -0.5, 0.0],
+        ));
+        let exec = run_joint(world, (a, b), 50, |a, b| {
+            ([a[4], a[5] + 0.5, a[6]], [b[4], b[5] - 0.5, b[6]])
+        });
+        let pos = exec.column(WorldPos::component_id()).unwrap();
+        let pos = pos.typed_buf::<f64>().unwrap();
+        // had the anchor been applied unrotated, the joint would have pulled the bodies along x
+        assert!(pos[4].abs() < 1e-9 && pos[7 + 4].abs() < 1e-9);
+        assert!(pos[6] < 0.0);
+    }
+
+    #[test]
+    fn test_drag_decay() {
+        #[derive(Archetype)]
+        struct Damped {
+            pos: WorldPos,
+            vel: WorldVel,
+            accel: WorldAccel,
+            force: Force,
+            mass: Inertia,
+        }
+
+        let mut world = World::default();
+        world
+            .spawn(Damped {
+                pos: WorldPos(SpatialTransform {
+                    inner: vector![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0].into(),
+                }),
+                vel: WorldVel(SpatialMotion {
+                    inner: vector![0.0, 0.0, 2.0, 3.0, 0.0, 0.0].into(),
+                }),
+                accel: WorldAccel(SpatialMotion {
+                    inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+                }),
+                force: Force(SpatialForce {
+                    inner: vector![0.0, 0.0, 0.0, 0.0, 0.0, 0.0].into(),
+                }),
+                mass: Inertia(SpatialInertia {
+                    inner: vector![1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0].into(),
+                }),
+            })
+            .insert(Drag::new(0.5, 0.1));
+        let client = nox::Client::cpu().unwrap();
+        let mut exec = world
+            .builder()
+            .tick_pipeline(six_dof(|| drag, 0.1))
+            .build()
+            .unwrap();
+        let (mut spin, mut speed) = (2.0, 3.0);
+        for _ in 0..200 {
+            exec.run(&client).unwrap();
+            let vel = exec.column(WorldVel::component_id()).unwrap();
+            let vel = vel.typed_buf::<f64>().unwrap();
+            // both parts must decay monotonically without overshooting through zero
+            assert!(vel[2] > 0.0 && vel[2] < spin, "spin {} -> {}", spin, vel[2]);
+            assert!(
+                vel[3] > 0.0 && vel[3] < speed,
+                "speed {} -> {}",
+                speed,
+                vel[3]
+            );
+            (spin, speed) = (vel[2], vel[3]);
+        }
+        assert!(spin < 1e-3, "spin {spin}");
+        assert!(speed < 1e-3, "speed {speed}");
+    }
 }
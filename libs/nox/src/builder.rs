@@ -1,10 +1,14 @@
 use crate::{Noxpr, Op, ScalarDim, Tensor};
-use std::cell::{RefCell, UnsafeCell};
+use std::cell::{Cell, RefCell, UnsafeCell};
 
 pub struct Builder {
     pub(crate) params: RefCell<Vec<Noxpr>>,
     pub(crate) mut_params: boxcar::Vec<UnsafeCell<Tensor<f32, ScalarDim, Op>>>,
     pub(crate) aliased_indexes: Vec<(u64, u64)>,
+    /// The indexes of the mutably borrowed parameters registered so far, in argument order.
+    pub(crate) mut_param_indexes: RefCell<Vec<u64>>,
+    /// The name for the next parameter registered, set by [`crate::Named`].
+    pub(crate) param_name: Cell<Option<&'static str>>,
 }
 
 impl Builder {
@@ -13,6 +17,8 @@ impl Builder {
             params: RefCell::new(vec![]),
             mut_params: boxcar::Vec::new(),
             aliased_indexes: vec![],
+            mut_param_indexes: RefCell::new(vec![]),
+            param_name: Cell::new(None),
         }
     }
 
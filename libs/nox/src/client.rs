@@ -8,16 +8,34 @@ impl Client {
         xla::PjRtClient::cpu().map(Client).map_err(Error::from)
     }
 
-    /// Create a new [`Client`] using a GPU based backend.
+    /// Create a new [`Client`] using the CPU based backend, split into `device_count` virtual
+    /// devices so that sharded execution can be exercised without accelerators.
+    pub fn cpu_with_devices(device_count: usize) -> Result<Self, Error> {
+        xla::PjRtClient::cpu_with_device_count(device_count)
+            .map(Client)
+            .map_err(Error::from)
+    }
+
+    /// The number of devices this client can run on.
+    pub fn device_count(&self) -> usize {
+        self.0.device_count()
+    }
+
+    /// Create a new [`Client`] on the GPU with the given device ordinal.
     /// By default the backend is either CUDA or Metal depending on your OS.
     ///
     /// This functions uses the default memory fraction of `0.95`,
-    /// and does not preallocate any memory
-    pub fn gpu() -> Result<Self, Error> {
+    /// and does not preallocate any memory.
+    /// Returns [`Error::GpuUnavailable`] if the backend fails to start or has no such device.
+    pub fn gpu(ordinal: usize) -> Result<Self, Error> {
         const DEFAULT_MEMORY_PERCENT: f64 = 0.95;
-        xla::PjRtClient::gpu(DEFAULT_MEMORY_PERCENT, false)
-            .map(Client)
-            .map_err(Error::from)
+        let unavailable = |reason: String| Error::GpuUnavailable { ordinal, reason };
+        let client = xla::PjRtClient::gpu(DEFAULT_MEMORY_PERCENT, false, Some(ordinal))
+            .map_err(|err| unavailable(err.to_string()))?;
+        if client.device_count() == 0 {
+            return Err(unavailable("no such device".to_string()));
+        }
+        Ok(Client(client))
     }
 
     /// Create a new [`Client`] using a GPU based backend, with the specified memory percent.
@@ -27,7 +45,7 @@ impl Client {
     /// The first argument is the memory limit in the range [0..1.0].
     /// The second paremeter is whether to preallocate memory or not.
     pub fn gpu_with_memory_limit(mem_limit: f64, prealloc: bool) -> Result<Self, Error> {
-        xla::PjRtClient::gpu(mem_limit, prealloc)
+        xla::PjRtClient::gpu(mem_limit, prealloc, None)
             .map(Client)
             .map_err(Error::from)
     }
@@ -1,22 +1,179 @@
-use std::marker::PhantomData;
+use std::{collections::HashMap, marker::PhantomData, path::Path, sync::Mutex};
 
-use crate::{BufferForm, Client, Exec};
+use crate::{BufferForm, Client, Error, Exec, NoxprFn, ShardedExec};
 
 pub struct Comp<T, R> {
     pub comp: xla::XlaComputation,
+    /// The [`NoxprFn::structural_key`] of the expression this was built from.
+    pub(crate) key: String,
     pub(crate) phantom: PhantomData<(T, R)>,
 }
 
-impl<T: BufferForm, R> Comp<T, R> {
+/// Prefixes serialized executables, ahead of the structural key and platform name.
+const EXECUTABLE_MAGIC: &[u8; 8] = b"noxexec2";
+
+impl<T, R> Comp<T, R> {
+    /// Lowers `expr` to an XLA computation, trusting the caller that `T` and `R` match its
+    /// argument and return types, which is why this is only reachable from within the crate.
+    pub(crate) fn from_expr(expr: &NoxprFn, name: &str) -> Result<Self, crate::Error> {
+        let comp = expr.build(name)?.build()?;
+        Ok(Comp {
+            comp,
+            key: expr.structural_key(),
+            phantom: PhantomData,
+        })
+    }
+
+    /// Renders the built computation as MHLO text, for inspecting what a [`crate::CompFn`]
+    /// lowered to. The `Display` impl of [`NoxprFn`] shows the same function before lowering.
     pub fn to_hlo_text(&self) -> Result<String, xla::Error> {
         self.comp.to_hlo_text()
     }
+}
 
+impl<T: BufferForm, R> Comp<T, R> {
     pub fn compile(&self, client: &Client) -> Result<Exec<T::BufferTy, R>, xla::Error> {
         let exec = client.0.compile(&self.comp)?;
-        Ok(Exec {
+        Exec::new(exec, &self.comp)
+    }
+
+    /// Compiles one replica per device of `client`, for running with [`ShardedExec::run`]. The
+    /// computation is written for a single shard, so its argument types give the per-device
+    /// slice of the batch rather than the whole batch.
+    pub fn compile_sharded(&self, client: &Client) -> Result<ShardedExec<T::BufferTy, R>, Error> {
+        let devices = client.device_count();
+        let exec = client.0.compile_replicated(&self.comp, devices)?;
+        Ok(ShardedExec {
             exec,
+            devices,
+            param_shapes: self.comp.parameter_shapes()?,
             phantom: PhantomData,
         })
     }
+
+    /// Compiles for `client` and writes the executable to `path`, so a later process can skip
+    /// compilation with [`Comp::load_executable`].
+    pub fn serialize_executable(
+        &self,
+        client: &Client,
+        path: impl AsRef<Path>,
+    ) -> Result<Exec<T::BufferTy, R>, Error> {
+        let exec = self.compile(client)?;
+        let platform = client.0.platform_name();
+        let mut out = EXECUTABLE_MAGIC.to_vec();
+        for field in [self.key.as_bytes(), platform.as_bytes()] {
+            out.extend_from_slice(&(field.len() as u64).to_le_bytes());
+            out.extend_from_slice(field);
+        }
+        out.extend_from_slice(&exec.exec.serialize()?);
+        std::fs::write(path, out)?;
+        Ok(exec)
+    }
+
+    /// Loads an executable written by [`Comp::serialize_executable`].
+    ///
+    /// Returns [`Error::StaleExecutable`] if the file was built from a different expression or for
+    /// a different platform than `client`.
+    pub fn load_executable(
+        &self,
+        path: impl AsRef<Path>,
+        client: &Client,
+    ) -> Result<Exec<T::BufferTy, R>, Error> {
+        let data = std::fs::read(path)?;
+        let mut rest = data
+            .strip_prefix(EXECUTABLE_MAGIC.as_slice())
+            .ok_or(Error::StaleExecutable)?;
+        let key = take_field(&mut rest).ok_or(Error::StaleExecutable)?;
+        let platform = take_field(&mut rest).ok_or(Error::StaleExecutable)?;
+        if key != self.key.as_bytes() || platform != client.0.platform_name().as_bytes() {
+            return Err(Error::StaleExecutable);
+        }
+        let exec = client.0.deserialize_executable(rest)?;
+        Ok(Exec::new(exec, &self.comp)?)
+    }
+}
+
+/// Splits a length-prefixed field off the front of `rest`.
+fn take_field<'a>(rest: &mut &'a [u8]) -> Option<&'a [u8]> {
+    let len = u64::from_le_bytes(rest.get(..8)?.try_into().ok()?);
+    let end = usize::try_from(len).ok()?.checked_add(8)?;
+    let field = rest.get(8..end)?;
+    *rest = &rest[end..];
+    Some(field)
+}
+
+/// Memoizes built computations by the [`NoxprFn::structural_key`] of their expression, so
+/// identical systems are only lowered to XLA once, and compiled once per client.
+#[derive(Default)]
+pub struct CompCache {
+    comps: Mutex<HashMap<String, CacheEntry>>,
+}
+
+struct CacheEntry {
+    comp: xla::XlaComputation,
+    /// Each client is kept alive alongside its executable, so its id can't be reused by another.
+    executables: Vec<(Client, xla::PjRtLoadedExecutable)>,
+}
+
+impl CompCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn get_or_build(
+        &self,
+        expr: &NoxprFn,
+        name: &str,
+    ) -> Result<xla::XlaComputation, crate::Error> {
+        let mut comps = self.comps.lock().unwrap();
+        Ok(Self::entry(&mut comps, expr, name)?.comp.clone())
+    }
+
+    pub(crate) fn get_or_compile(
+        &self,
+        expr: &NoxprFn,
+        name: &str,
+        client: &Client,
+    ) -> Result<(xla::XlaComputation, xla::PjRtLoadedExecutable), crate::Error> {
+        let mut comps = self.comps.lock().unwrap();
+        let entry = Self::entry(&mut comps, expr, name)?;
+        let cached = entry
+            .executables
+            .iter()
+            .find(|(c, _)| c.0.id() == client.0.id());
+        if let Some((_, exec)) = cached {
+            return Ok((entry.comp.clone(), exec.clone()));
+        }
+        let exec = client.0.compile(&entry.comp)?;
+        entry.executables.push((client.clone(), exec.clone()));
+        Ok((entry.comp.clone(), exec))
+    }
+
+    fn entry<'a>(
+        comps: &'a mut HashMap<String, CacheEntry>,
+        expr: &NoxprFn,
+        name: &str,
+    ) -> Result<&'a mut CacheEntry, crate::Error> {
+        let key = expr.structural_key();
+        if !comps.contains_key(&key) {
+            let comp = expr.build(name)?.build()?;
+            comps.insert(
+                key.clone(),
+                CacheEntry {
+                    comp,
+                    executables: vec![],
+                },
+            );
+        }
+        Ok(comps.get_mut(&key).expect("entry was just inserted"))
+    }
+
+    /// The number of distinct computations in the cache.
+    pub fn len(&self) -> usize {
+        self.comps.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
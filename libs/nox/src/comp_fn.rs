@@ -1,9 +1,9 @@
 use crate::{
-    ArrayTy, Builder, Comp, IntoOp, Noxpr, NoxprFn, NoxprTy, Op, Tensor, TensorDim, TensorItem,
-    XlaDim,
+    ArrayTy, BufferForm, Builder, Client, Comp, CompCache, Exec, IntoOp, Noxpr, NoxprFn, NoxprTy,
+    Op, Tensor, TensorDim, TensorItem, XlaDim,
 };
 use smallvec::SmallVec;
-use std::{any, marker::PhantomData};
+use std::{any, marker::PhantomData, ops::Deref};
 
 pub trait CompFn<T, R>: Send + Sync {
     fn compute(&self, builder: &mut Builder) -> R;
@@ -32,17 +32,60 @@ pub trait CompFn<T, R>: Send + Sync {
     }
 
     fn build(&self) -> Result<Comp<T, R>, crate::Error>
+    where
+        R: IntoOp,
+    {
+        Comp::from_expr(&self.build_expr()?, any::type_name::<Self>())
+    }
+
+    /// Builds the gradient of this function's scalar output with respect to argument `arg`,
+    /// see [`NoxprFn::grad`].
+    fn grad(&self, arg: usize) -> Result<NoxprFn, crate::Error>
+    where
+        R: IntoOp,
+    {
+        self.build_expr()?.grad(arg)
+    }
+
+    /// Builds this function lifted over a leading batch dimension of length `batch`, see
+    /// [`NoxprFn::vmap`].
+    fn vmap(&self, batch: usize) -> Result<NoxprFn, crate::Error>
+    where
+        R: IntoOp,
+    {
+        self.build_expr()?.vmap(batch)
+    }
+
+    /// Like [`CompFn::build`], but reuses the computation from `cache` if a structurally
+    /// identical function has already been built.
+    fn build_cached(&self, cache: &CompCache) -> Result<Comp<T, R>, crate::Error>
     where
         R: IntoOp,
     {
         let expr = self.build_expr()?;
-        let op = expr.build(any::type_name::<Self>())?;
-        let comp = op.build()?;
+        let comp = cache.get_or_build(&expr, any::type_name::<Self>())?;
         Ok(Comp {
             comp,
+            key: expr.structural_key(),
             phantom: PhantomData,
         })
     }
+
+    /// Like [`CompFn::build_cached`] followed by [`Comp::compile`], but also reuses the
+    /// executable if a structurally identical function has already been compiled for `client`.
+    fn compile_cached(
+        &self,
+        cache: &CompCache,
+        client: &Client,
+    ) -> Result<Exec<T::BufferTy, R>, crate::Error>
+    where
+        T: BufferForm,
+        R: IntoOp,
+    {
+        let expr = self.build_expr()?;
+        let (comp, exec) = cache.get_or_compile(&expr, any::type_name::<Self>(), client)?;
+        Ok(Exec::new(exec, &comp)?)
+    }
 }
 
 pub trait FromBuilder {
@@ -81,7 +124,11 @@ where
                 element_type: T::ELEM,
                 shape,
             }),
-            format!("param_{}", i),
+            builder
+                .param_name
+                .take()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("param_{}", i)),
         );
         params.push(inner.clone());
         Tensor {
@@ -91,6 +138,70 @@ where
     }
 }
 
+/// Registers `N` consecutive parameters of the same type, for functions that take a fixed-size
+/// list of inputs without spelling out each one as its own argument.
+impl<T: TensorItem, D: XlaDim + TensorDim, const N: usize> FromBuilder for [Tensor<T, D, Op>; N]
+where
+    T::Dim: XlaDim,
+    <T::Dim as XlaDim>::Array: AsRef<[i64]>,
+    D::Array: AsRef<[i64]>,
+{
+    type Item<'a> = Self;
+
+    fn from_builder(builder: &Builder) -> Self::Item<'_> {
+        std::array::from_fn(|_| Tensor::<T, D, Op>::from_builder(builder))
+    }
+}
+
+/// A parameter name known at compile time, for use with [`Named`].
+pub trait ParamName {
+    const NAME: &'static str;
+}
+
+/// Wraps a [`CompFn`] argument so its parameter is registered under `N::NAME` instead of
+/// `param_{i}`, which makes the lowered HLO easier to read.
+///
+/// Only the first parameter `T` registers is named, so this is meant for single tensor arguments.
+pub struct Named<T, N> {
+    inner: T,
+    phantom: PhantomData<N>,
+}
+
+impl<T, N> Named<T, N> {
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T, N> Deref for Named<T, N> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T, N: ParamName> FromBuilder for Named<T, N>
+where
+    T: for<'a> FromBuilder<Item<'a> = T>,
+{
+    type Item<'a> = Self;
+
+    fn from_builder(builder: &Builder) -> Self::Item<'_> {
+        builder.param_name.set(Some(N::NAME));
+        let inner = T::from_builder(builder);
+        builder.param_name.set(None);
+        Named {
+            inner,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: BufferForm, N> BufferForm for Named<T, N> {
+    type BufferTy = T::BufferTy;
+}
+
 // TODO(sphw): to make mutable params work again we will need to make some changes to this function
 // In particular we need to make this function perform the alias setup itself, right now it doesn't do that
 // The other complexity comes from the fact that we need to make sure that the alias setup needs to be done
@@ -147,7 +258,13 @@ macro_rules! impl_comp_fn {
 
               #[allow(unused_variables, clippy::unused_unit)]
               fn from_builder(builder: &Builder) -> Self::Item<'_> {
-                  ($($ty::from_builder(builder),)*)
+                  ($({
+                      if $ty::is_mut_borrowed() {
+                          let index = builder.params.borrow().len() as u64;
+                          builder.mut_param_indexes.borrow_mut().push(index);
+                      }
+                      $ty::from_builder(builder)
+                  },)*)
               }
           }
       };
@@ -162,10 +279,14 @@ impl_comp_fn!(T1, T2, T3, T4, T5);
 impl_comp_fn!(T1, T2, T3, T4, T5, T6);
 impl_comp_fn!(T1, T2, T3, T4, T5, T6, T7);
 impl_comp_fn!(T1, T2, T3, T4, T5, T6, T7, T8);
-impl_comp_fn!(T1, T2, T3, T4, T5, T6, T7, T9, T10);
-impl_comp_fn!(T1, T2, T3, T4, T5, T6, T7, T9, T10, T11);
-impl_comp_fn!(T1, T2, T3, T4, T5, T6, T7, T9, T10, T11, T12);
-impl_comp_fn!(T1, T2, T3, T4, T5, T6, T7, T9, T10, T11, T12, T13);
+impl_comp_fn!(T1, T2, T3, T4, T5, T6, T7, T8, T9);
+impl_comp_fn!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
+impl_comp_fn!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+impl_comp_fn!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
+impl_comp_fn!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13);
+impl_comp_fn!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14);
+impl_comp_fn!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15);
+impl_comp_fn!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16);
 
 impl<F, T, R> CompFn<T, R> for F
 where
@@ -174,11 +295,229 @@ where
     T: for<'a> FromBuilder<Item<'a> = T>,
 {
     fn compute(&self, builder: &mut Builder) -> R {
-        let param_index = builder.params.borrow().len();
-        if T::is_mut_borrowed() {
-            builder.setup_alias(param_index as u64, 1);
-        }
         let arg = T::from_builder(builder);
+        // `build_expr` outputs the mutable params after the result in reverse order, so the
+        // first one aliases the last tuple element
+        let mut_param_indexes = builder.mut_param_indexes.take();
+        let count = mut_param_indexes.len() as u64;
+        for (i, param_index) in mut_param_indexes.into_iter().enumerate() {
+            builder.setup_alias(param_index, count - i as u64);
+        }
         self.call(arg)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{Client, NoxprNode, Scalar, ToHost};
+    use std::ops::Deref;
+
+    use super::*;
+
+    #[test]
+    fn test_eight_arg_comp_fn() {
+        #[allow(clippy::too_many_arguments)]
+        fn sum(
+            a: Scalar<f64>,
+            b: Scalar<f64>,
+            c: Scalar<f64>,
+            d: Scalar<f64>,
+            e: Scalar<f64>,
+            f: Scalar<f64>,
+            g: Scalar<f64>,
+            h: Scalar<f64>,
+        ) -> Scalar<f64> {
+            a + b + c + d + e + f + g + h
+        }
+        let expr = sum.build_expr().unwrap();
+        assert_eq!(expr.args.len(), 8);
+    }
+
+    #[test]
+    fn test_fourteen_arg_comp_fn() {
+        #[allow(clippy::too_many_arguments)]
+        fn sum(
+            a: Scalar<f64>,
+            b: Scalar<f64>,
+            c: Scalar<f64>,
+            d: Scalar<f64>,
+            e: Scalar<f64>,
+            f: Scalar<f64>,
+            g: Scalar<f64>,
+            h: Scalar<f64>,
+            i: Scalar<f64>,
+            j: Scalar<f64>,
+            k: Scalar<f64>,
+            l: Scalar<f64>,
+            m: Scalar<f64>,
+            n: Scalar<f64>,
+        ) -> Scalar<f64> {
+            a + b + c + d + e + f + g + h + i + j + k + l + m + n
+        }
+        let expr = sum.build_expr().unwrap();
+        assert_eq!(expr.args.len(), 14);
+
+        let client = Client::cpu().unwrap();
+        let exec = sum.build().unwrap().compile(&client).unwrap();
+        let out = exec
+            .run(
+                &client, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0, 13.0, 14.0,
+            )
+            .unwrap()
+            .to_host();
+        assert_eq!(out, 105.0);
+    }
+
+    #[test]
+    fn test_mut_param_aliases() {
+        // stands in for `&mut Tensor`, whose `FromBuilder` impl is disabled above
+        struct MutScalar;
+        impl FromBuilder for MutScalar {
+            type Item<'a> = Self;
+
+            fn from_builder(builder: &Builder) -> Self::Item<'_> {
+                Scalar::<f64>::from_builder(builder);
+                MutScalar
+            }
+
+            fn is_mut_borrowed() -> bool {
+                true
+            }
+        }
+
+        #[allow(clippy::too_many_arguments)]
+        fn step(
+            a: Scalar<f64>,
+            b: Scalar<f64>,
+            c: Scalar<f64>,
+            _d: MutScalar,
+            e: Scalar<f64>,
+            f: Scalar<f64>,
+            g: Scalar<f64>,
+            h: Scalar<f64>,
+            i: Scalar<f64>,
+            j: Scalar<f64>,
+            _k: MutScalar,
+            l: Scalar<f64>,
+            m: Scalar<f64>,
+            n: Scalar<f64>,
+        ) -> Scalar<f64> {
+            a + b + c + e + f + g + h + i + j + l + m + n
+        }
+        let mut builder = Builder::new();
+        step.compute(&mut builder);
+        assert_eq!(builder.params.borrow().len(), 14);
+        assert_eq!(builder.aliased_indexes, [(3, 2), (10, 1)]);
+        assert!(builder.mut_param_indexes.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_tensor_array_arg() {
+        let sum = |xs: [Scalar<f64>; 3]| -> Scalar<f64> {
+            let [a, b, c] = xs;
+            a + b + c
+        };
+        let expr = sum.build_expr().unwrap();
+        let numbers: Vec<_> = expr
+            .args
+            .iter()
+            .map(|arg| match arg.deref() {
+                NoxprNode::Param(p) => p.number,
+                _ => panic!("expected a parameter"),
+            })
+            .collect();
+        assert_eq!(numbers, [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_named_params() {
+        struct Position;
+        impl ParamName for Position {
+            const NAME: &'static str = "position";
+        }
+        struct Velocity;
+        impl ParamName for Velocity {
+            const NAME: &'static str = "velocity";
+        }
+
+        let step = |p: Named<Scalar<f64>, Position>, v: Named<Scalar<f64>, Velocity>| {
+            p.into_inner() + v.into_inner()
+        };
+        let expr = step.build_expr().unwrap();
+        let names: Vec<_> = expr
+            .args
+            .iter()
+            .map(|arg| match arg.deref() {
+                NoxprNode::Param(p) => p.name.clone(),
+                _ => panic!("expected a parameter"),
+            })
+            .collect();
+        assert_eq!(names, ["position", "velocity"]);
+
+        let hlo = step.build().unwrap().to_hlo_text().unwrap();
+        assert!(hlo.contains("position"));
+        assert!(hlo.contains("velocity"));
+
+        let client = Client::cpu().unwrap();
+        let exec = step.build().unwrap().compile(&client).unwrap();
+        assert_eq!(exec.run(&client, 1.0, 2.0).unwrap().to_host(), 3.0);
+    }
+
+    #[test]
+    fn test_build_cached() {
+        let cache = CompCache::new();
+        let double = |a: Scalar<f64>| -> Scalar<f64> { a.clone() + a };
+        double.build_cached(&cache).unwrap();
+        double.build_cached(&cache).unwrap();
+        assert_eq!(cache.len(), 1);
+
+        let client = Client::cpu().unwrap();
+        let exec = double
+            .build_cached(&cache)
+            .unwrap()
+            .compile(&client)
+            .unwrap();
+        assert_eq!(exec.run(&client, 2.0).unwrap().to_host(), 4.0);
+
+        let triple = |a: Scalar<f64>| -> Scalar<f64> { a.clone() + a.clone() + a };
+        triple.build_cached(&cache).unwrap();
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_compile_cached() {
+        let cache = CompCache::new();
+        let client = Client::cpu().unwrap();
+        let double = |a: Scalar<f64>| -> Scalar<f64> { a.clone() + a };
+        let first = double.compile_cached(&cache, &client).unwrap();
+        let second = double.compile_cached(&cache, &client).unwrap();
+        assert_eq!(cache.len(), 1);
+        assert_eq!(first.run(&client, 2.0).unwrap().to_host(), 4.0);
+        assert_eq!(second.run(&client, 2.5).unwrap().to_host(), 5.0);
+
+        // a separate client gets its own executable from the same entry
+        let other = Client::cpu().unwrap();
+        let third = double.compile_cached(&cache, &other).unwrap();
+        assert_eq!(cache.len(), 1);
+        assert_eq!(third.run(&other, 1.5).unwrap().to_host(), 3.0);
+    }
+
+    #[test]
+    fn test_executable_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("double.exec");
+        let client = Client::cpu().unwrap();
+        let double = |a: Scalar<f64>| -> Scalar<f64> { a.clone() + a };
+        let comp = double.build().unwrap();
+        let fresh = comp.serialize_executable(&client, &path).unwrap();
+        let loaded = comp.load_executable(&path, &client).unwrap();
+        assert_eq!(
+            loaded.run(&client, 3.5).unwrap().to_host(),
+            fresh.run(&client, 3.5).unwrap().to_host()
+        );
+
+        let triple = |a: Scalar<f64>| -> Scalar<f64> { a.clone() + a.clone() + a };
+        let stale = triple.build().unwrap().load_executable(&path, &client);
+        assert!(matches!(stale, Err(crate::Error::StaleExecutable)));
+    }
+}
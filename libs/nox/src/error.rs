@@ -26,4 +26,29 @@ pub enum Error {
     ScanMissingArg,
     #[error("all scan arguments must have the same first dim")]
     ScanShapeMismatch,
+    #[error("gradients can only be taken of functions with a scalar output")]
+    GradNonScalarOutput,
+    #[error("cannot differentiate through {0}")]
+    Undifferentiable(&'static str),
+    #[error("dimension mismatch: expected {expected:?}, found {found:?}")]
+    DimensionMismatch {
+        expected: Vec<i64>,
+        found: Option<Vec<i64>>,
+    },
+    #[error("gpu {ordinal} is unavailable: {reason}")]
+    GpuUnavailable { ordinal: usize, reason: String },
+    #[error("io error {0}")]
+    Io(#[from] std::io::Error),
+    #[error("serialized executable was built from a different computation or platform")]
+    StaleExecutable,
+    #[error("a batch of {batch} can't be split evenly across {devices} devices")]
+    UnevenBatch { batch: usize, devices: usize },
+    #[error("axis {axis} must have a static, nonzero length, found {len}")]
+    InvalidAxisLen { axis: usize, len: i64 },
+    #[error("expected {expected} arguments, found {found}")]
+    WrongArgCount { expected: usize, found: usize },
+    #[error("inertia has products of inertia that a diagonal spatial inertia can't hold")]
+    OffDiagonalInertia,
+    #[error("inertia tensor is not symmetric")]
+    AsymmetricInertia,
 }
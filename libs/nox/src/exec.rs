@@ -1,18 +1,193 @@
-use crate::{AsBuffer, BufferArg, BufferForm, Client, FromPjrtBuffer};
+use crate::{AsBuffer, BufferArg, BufferForm, Client, Error, FromPjrtBuffer};
 use paste::paste;
+use std::io::Write;
 use std::marker::PhantomData;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
 pub struct Exec<T, R> {
     pub(crate) exec: xla::PjRtLoadedExecutable,
+    pub(crate) param_shapes: Vec<xla::Shape>,
     pub(crate) phantom: PhantomData<(T, R)>,
 }
 
+impl<T, R> Exec<T, R> {
+    pub(crate) fn new(
+        exec: xla::PjRtLoadedExecutable,
+        comp: &xla::XlaComputation,
+    ) -> Result<Self, xla::Error> {
+        Ok(Exec {
+            exec,
+            param_shapes: comp.parameter_shapes()?,
+            phantom: PhantomData,
+        })
+    }
+}
+
+fn shape_bytes(shape: &xla::Shape) -> usize {
+    match shape {
+        xla::Shape::Array(shape) => shape.element_count() * shape.ty().element_size_in_bytes(),
+        xla::Shape::Tuple(shapes) => shapes.iter().map(shape_bytes).sum(),
+    }
+}
+
 pub trait ToHost {
     type HostTy;
 
     fn to_host(&self) -> Self::HostTy;
 }
 
+/// The output of an [`Exec::run_async`] call whose device execution may still be in flight.
+///
+/// PJRT dispatches executions asynchronously, so the host only blocks once the result is copied
+/// back in [`ExecHandle::wait`]. Several handles can be outstanding at once.
+pub struct ExecHandle<B> {
+    buffers: B,
+}
+
+impl<B> ExecHandle<B> {
+    /// Returns the device buffers without waiting, for feeding into another run.
+    pub fn into_buffers(self) -> B {
+        self.buffers
+    }
+}
+
+impl<B: ToHost> ExecHandle<B> {
+    /// Blocks until the execution finishes and copies its result to the host.
+    pub fn wait(self) -> B::HostTy {
+        self.buffers.to_host()
+    }
+}
+
+/// Timing and transfer volumes for a single [`Exec::run_profiled`] call.
+///
+/// These are host-side measurements only. The XLA bindings don't expose a device profiler, so
+/// time spent on the device isn't broken out from the wall time.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RunProfile {
+    /// Host wall time of the whole run, from copying host arguments to the device until the
+    /// output is ready.
+    pub wall_time: Duration,
+    /// The size of the arguments, taken from the compiled parameter shapes.
+    pub input_bytes: usize,
+    /// The combined size of every output buffer, including those written back to mutably
+    /// borrowed arguments.
+    pub output_bytes: usize,
+}
+
+impl RunProfile {
+    /// Writes the profile as a Chrome trace event file, which can be opened in
+    /// `chrome://tracing` or Perfetto.
+    pub fn write_chrome_trace(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let mut file = std::fs::File::create(path)?;
+        let args = format!(
+            r#""args":{{"input_bytes":{},"output_bytes":{}}}"#,
+            self.input_bytes, self.output_bytes
+        );
+        let event = format!(
+            r#"{{"name":"exec","ph":"X","pid":0,"tid":0,"ts":0,"dur":{},{}}}"#,
+            self.wall_time.as_secs_f64() * 1e6,
+            args
+        );
+        write!(file, r#"{{"traceEvents":[{}]}}"#, event)
+    }
+}
+
+/// A computation compiled by [`crate::Comp::compile_sharded`], which runs the same program on
+/// every device of a client, each on its own slice of the batch.
+pub struct ShardedExec<T, R> {
+    pub(crate) exec: xla::PjRtLoadedExecutable,
+    pub(crate) devices: usize,
+    /// The per-shard parameter shapes the replicas were compiled with.
+    pub(crate) param_shapes: Vec<xla::Shape>,
+    pub(crate) phantom: PhantomData<(T, R)>,
+}
+
+impl<T, R> ShardedExec<T, R>
+where
+    R: BufferForm,
+    R::BufferTy: FromPjrtBuffer,
+{
+    /// Splits the leading (batch) axis of every input evenly across the devices, so device `i`
+    /// receives the `i`th slice, and returns each device's output in device order.
+    ///
+    /// Returns [`Error::UnevenBatch`] if an input's batch doesn't divide by the device count, and
+    /// [`Error::WrongArgCount`], [`Error::IncompatibleDType`], or [`Error::DimensionMismatch`] if
+    /// the inputs don't match the parameters the computation was compiled with.
+    pub fn run(&self, client: &Client, inputs: &[xla::Literal]) -> Result<Vec<R::BufferTy>, Error> {
+        if inputs.len() != self.param_shapes.len() {
+            return Err(Error::WrongArgCount {
+                expected: self.param_shapes.len(),
+                found: inputs.len(),
+            });
+        }
+        let mut shards: Vec<Vec<xla::PjRtBuffer>> = (0..self.devices).map(|_| vec![]).collect();
+        for (input, param) in inputs.iter().zip(&self.param_shapes) {
+            let shape = match input.shape()? {
+                xla::Shape::Array(shape) => shape,
+                got => {
+                    return Err(xla::Error::NotAnArray {
+                        expected: None,
+                        got,
+                    }
+                    .into())
+                }
+            };
+            let mut dims: Vec<usize> = shape.dims().iter().map(|d| *d as usize).collect();
+            let batch = dims.first().copied().unwrap_or_default();
+            if batch == 0 || batch % self.devices != 0 {
+                return Err(Error::UnevenBatch {
+                    batch,
+                    devices: self.devices,
+                });
+            }
+            dims[0] = batch / self.devices;
+            let param = match param {
+                xla::Shape::Array(param) => param,
+                got => {
+                    return Err(xla::Error::NotAnArray {
+                        expected: None,
+                        got: got.clone(),
+                    }
+                    .into())
+                }
+            };
+            if shape.ty() != param.ty() {
+                return Err(Error::IncompatibleDType);
+            }
+            let shard_dims: Vec<i64> = dims.iter().map(|d| *d as i64).collect();
+            if shard_dims != param.dims() {
+                return Err(Error::DimensionMismatch {
+                    expected: param.dims().to_vec(),
+                    found: Some(shard_dims),
+                });
+            }
+            let buf = input.raw_buf();
+            // a zero-sized trailing dim leaves every shard empty, which `chunks_exact` rejects
+            let shard_len = buf.len() / self.devices;
+            for (device, device_shards) in shards.iter_mut().enumerate() {
+                let chunk = &buf[device * shard_len..(device + 1) * shard_len];
+                let shard =
+                    client
+                        .0
+                        .copy_raw_host_buffer_to_device(shape.ty(), chunk, &dims, device)?;
+                device_shards.push(shard);
+            }
+        }
+        let args: Vec<xla::BufferArgsRef<'_>> = shards
+            .iter()
+            .map(|shard| {
+                shard
+                    .iter()
+                    .collect::<xla::BufferArgsRef<'_>>()
+                    .untuple_result(true)
+            })
+            .collect();
+        let outputs = self.exec.execute_replicated(&args)?;
+        Ok(outputs.into_iter().map(R::BufferTy::from_pjrt).collect())
+    }
+}
+
 // This macro allows us to implement the run function for a series of tuples easily.
 // This essentially a workaround for Rust lacking variadic types / generics.
 macro_rules! impl_exec {
@@ -25,20 +200,55 @@ macro_rules! impl_exec {
             $($ty: AsBuffer, )*
         {
             paste! {
-                pub fn run<$([<arg_$ty>]: BufferArg<$ty>,)*>(&self, client: &Client, $(mut $ty: [<arg_$ty>],)*) -> Result<R::BufferTy, xla::Error> {
+                pub fn run<$([<arg_$ty>]: BufferArg<$ty>,)*>(&self, client: &Client, $($ty: [<arg_$ty>],)*) -> Result<R::BufferTy, xla::Error> {
+                    let (res, ()) = self.run_inspect(client, $($ty,)* |_| Ok(()))?;
+                    Ok(res)
+                }
+
+                /// Like [`Self::run`], but blocks until every output buffer is ready and reports a
+                /// [`RunProfile`].
+                pub fn run_profiled<$([<arg_$ty>]: BufferArg<$ty>,)*>(&self, client: &Client, $($ty: [<arg_$ty>],)*) -> Result<(R::BufferTy, RunProfile), xla::Error> {
+                    let start = Instant::now();
+                    let (res, output_bytes) = self.run_inspect(client, $($ty,)* |outputs| {
+                        let mut bytes = 0;
+                        for buf in outputs {
+                            buf.block_until_ready()?;
+                            bytes += buf.on_device_size_in_bytes()?;
+                        }
+                        Ok(bytes)
+                    })?;
+                    let profile = RunProfile {
+                        wall_time: start.elapsed(),
+                        input_bytes: self.param_shapes.iter().map(shape_bytes).sum(),
+                        output_bytes,
+                    };
+                    Ok((res, profile))
+                }
+
+                /// Executes the computation, handing every raw output buffer to `inspect` before
+                /// they are split between the mutably borrowed arguments and the result.
+                fn run_inspect<$([<arg_$ty>]: BufferArg<$ty>,)* O>(&self, client: &Client, $(mut $ty: [<arg_$ty>],)* inspect: impl FnOnce(&[xla::PjRtBuffer]) -> Result<O, xla::Error>) -> Result<(R::BufferTy, O), xla::Error> {
                     let mut args = xla::BufferArgsRef::default();
                     $(
                         let [<buf_$ty>] = $ty.as_buffer(client);
                         args.push(&[<buf_$ty>]);
                     )*
                     let mut res = self.exec.execute_buffers(args.untuple_result(true))?;
+                    let inspected = inspect(&res)?;
                     $(
                         if [<arg_$ty>]::is_mut_borrowed() {
                             let buf = res.pop().unwrap();
                             $ty.replace_buffer(buf);
                         }
                     )*
-                    Ok(R::BufferTy::from_pjrt(res))
+                    Ok((R::BufferTy::from_pjrt(res), inspected))
+                }
+
+                /// Like [`Self::run`], but wraps the output in an [`ExecHandle`] so the host can keep
+                /// working and call [`ExecHandle::wait`] once it needs the result.
+                pub fn run_async<$([<arg_$ty>]: BufferArg<$ty>,)*>(&self, client: &Client, $($ty: [<arg_$ty>],)*) -> Result<ExecHandle<R::BufferTy>, xla::Error> {
+                    let buffers = self.run(client, $($ty,)*)?;
+                    Ok(ExecHandle { buffers })
                 }
             }
         }
@@ -54,7 +264,11 @@ impl_exec!(T1, T2, T3, T4, T5);
 impl_exec!(T1, T2, T3, T4, T5, T6);
 impl_exec!(T1, T2, T3, T4, T5, T6, T7);
 impl_exec!(T1, T2, T3, T4, T5, T6, T7, T8);
-impl_exec!(T1, T2, T3, T4, T5, T6, T7, T9, T10);
-impl_exec!(T1, T2, T3, T4, T5, T6, T7, T9, T10, T11);
-impl_exec!(T1, T2, T3, T4, T5, T6, T7, T9, T10, T11, T12);
-impl_exec!(T1, T2, T3, T4, T5, T6, T7, T9, T10, T11, T12, T13);
+impl_exec!(T1, T2, T3, T4, T5, T6, T7, T8, T9);
+impl_exec!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
+impl_exec!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+impl_exec!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
+impl_exec!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13);
+impl_exec!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14);
+impl_exec!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15);
+impl_exec!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16);
@@ -0,0 +1,450 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Deref,
+};
+
+use smallvec::{smallvec, SmallVec};
+use xla::ElementType;
+
+use crate::{ArrayTy, Error, Noxpr, NoxprFn, NoxprId, NoxprNode, ReduceOp};
+
+impl NoxprFn {
+    /// Builds the reverse-mode gradient of this function with respect to argument `arg`.
+    ///
+    /// The returned function takes the same arguments and returns a tensor with the shape of
+    /// `arg`. The function itself must return a scalar.
+    pub fn grad(&self, arg: usize) -> Result<NoxprFn, Error> {
+        let param = self.args.get(arg).ok_or(Error::OutOfBoundsAccess)?;
+        let shape = self.inner.shape().ok_or(Error::GradNonScalarOutput)?;
+        if !shape.is_empty() {
+            return Err(Error::GradNonScalarOutput);
+        }
+        let element_type = self.inner.element_type().ok_or(Error::IncompatibleDType)?;
+        let mut tracer = GradTracer::default();
+        tracer.sort(&self.inner);
+        tracer.accumulate(self.inner.id(), splat(1.0, element_type, &[])?);
+        tracer.backward()?;
+        let grad = match tracer.adjoints.remove(&param.id()) {
+            Some(grad) => grad,
+            None => {
+                let element_type = param.element_type().ok_or(Error::IncompatibleDType)?;
+                let shape = param.shape().ok_or(Error::IncompatibleDType)?;
+                splat(0.0, element_type, &shape)?
+            }
+        };
+        Ok(NoxprFn::new(self.args.clone(), grad))
+    }
+}
+
+#[derive(Default)]
+struct GradTracer {
+    /// Every node reachable from the output, with each node after all of its inputs.
+    order: Vec<Noxpr>,
+    visited: HashSet<NoxprId>,
+    adjoints: HashMap<NoxprId, Noxpr>,
+}
+
+impl GradTracer {
+    fn sort(&mut self, expr: &Noxpr) {
+        if !self.visited.insert(expr.id()) {
+            return;
+        }
+        for input in inputs(expr) {
+            self.sort(input);
+        }
+        self.order.push(expr.clone());
+    }
+
+    fn accumulate(&mut self, id: NoxprId, grad: Noxpr) {
+        let grad = match self.adjoints.remove(&id) {
+            Some(existing) => existing + grad,
+            None => grad,
+        };
+        self.adjoints.insert(id, grad);
+    }
+
+    fn backward(&mut self) -> Result<(), Error> {
+        let order = std::mem::take(&mut self.order);
+        for expr in order.iter().rev() {
+            let Some(g) = self.adjoints.get(&expr.id()).cloned() else {
+                continue;
+            };
+            let out_shape = shape(expr)?;
+            let element_type = expr.element_type().ok_or(Error::IncompatibleDType)?;
+            match expr.deref() {
+                NoxprNode::Param(_) | NoxprNode::Constant(_) => {}
+                NoxprNode::Add(b) => {
+                    self.accumulate_broadcast(&b.lhs, g.clone(), &out_shape)?;
+                    self.accumulate_broadcast(&b.rhs, g, &out_shape)?;
+                }
+                NoxprNode::Sub(b) => {
+                    self.accumulate_broadcast(&b.lhs, g.clone(), &out_shape)?;
+                    self.accumulate_broadcast(&b.rhs, -g, &out_shape)?;
+                }
+                NoxprNode::Mul(b) => {
+                    self.accumulate_broadcast(&b.lhs, g.clone() * b.rhs.clone(), &out_shape)?;
+                    self.accumulate_broadcast(&b.rhs, g * b.lhs.clone(), &out_shape)?;
+                }
+                NoxprNode::Div(b) => {
+                    let rhs_grad = -(g.clone() * b.lhs.clone()) / (b.rhs.clone() * b.rhs.clone());
+                    self.accumulate_broadcast(&b.lhs, g / b.rhs.clone(), &out_shape)?;
+                    self.accumulate_broadcast(&b.rhs, rhs_grad, &out_shape)?;
+                }
+                NoxprNode::Atan2(b) => {
+                    let (y, x) = (b.lhs.clone(), b.rhs.clone());
+                    let denom = x.clone() * x.clone() + y.clone() * y.clone();
+                    let y_grad = g.clone() * x / denom.clone();
+                    let x_grad = -(g * y) / denom;
+                    self.accumulate_broadcast(&b.lhs, y_grad, &out_shape)?;
+                    self.accumulate_broadcast(&b.rhs, x_grad, &out_shape)?;
+                }
+                NoxprNode::Min(b) | NoxprNode::Max(b) => {
+                    let lhs_wins = match expr.deref() {
+                        NoxprNode::Min(_) => b.lhs.clone().less_or_equal(b.rhs.clone()),
+                        _ => b.lhs.clone().greater_or_equal(b.rhs.clone()),
+                    };
+                    let zeros = splat(0.0, element_type, &out_shape)?;
+                    let lhs_grad = lhs_wins.select(g.clone(), zeros.clone());
+                    let rhs_grad = lhs_wins.select(zeros, g);
+                    self.accumulate_broadcast(&b.lhs, lhs_grad, &out_shape)?;
+                    self.accumulate_broadcast(&b.rhs, rhs_grad, &out_shape)?;
+                }
+                NoxprNode::Select(s) => {
+                    let zeros = splat(0.0, element_type, &out_shape)?;
+                    let true_grad = s.cond.select(g.clone(), zeros.clone());
+                    let false_grad = s.cond.select(zeros, g);
+                    self.accumulate(s.on_true.id(), true_grad);
+                    self.accumulate(s.on_false.id(), false_grad);
+                }
+                NoxprNode::Neg(e) => self.accumulate(e.id(), -g),
+                NoxprNode::Sqrt(e) => {
+                    let half = splat(0.5, element_type, &[])?;
+                    self.accumulate(e.id(), g * half / expr.clone());
+                }
+                NoxprNode::Log(e) => self.accumulate(e.id(), g / e.clone()),
+                NoxprNode::Sin(e) => self.accumulate(e.id(), g * e.clone().cos()),
+                NoxprNode::Cos(e) => self.accumulate(e.id(), -(g * e.clone().sin())),
+                NoxprNode::Exp(e) => self.accumulate(e.id(), g * expr.clone()),
+                NoxprNode::Tanh(e) => {
+                    let one = splat(1.0, element_type, &[])?;
+                    self.accumulate(e.id(), g * (one - expr.clone() * expr.clone()));
+                }
+                NoxprNode::Abs(e) => {
+                    let zeros = splat(0.0, element_type, &out_shape)?;
+                    let positive = e.clone().greater_or_equal(zeros);
+                    self.accumulate(e.id(), positive.select(g.clone(), -g));
+                }
+                NoxprNode::Dot(b) => {
+                    let (lhs, rhs) = (b.lhs.clone(), b.rhs.clone());
+                    let (lhs_shape, rhs_shape) = (shape(&lhs)?, shape(&rhs)?);
+                    let (lhs_grad, rhs_grad) = match (lhs_shape.len(), rhs_shape.len()) {
+                        (1, 1) => (g.clone() * rhs, g * lhs),
+                        (2, 1) => (
+                            outer(g.clone(), rhs, &lhs_shape),
+                            lhs.transpose(smallvec![1, 0]).dot(&g),
+                        ),
+                        (1, 2) => (rhs.dot(&g), outer(lhs, g, &rhs_shape)),
+                        (2, 2) => (
+                            g.clone().dot(&rhs.transpose(smallvec![1, 0])),
+                            lhs.transpose(smallvec![1, 0]).dot(&g),
+                        ),
+                        _ => return Err(Error::Undifferentiable(expr.name())),
+                    };
+                    self.accumulate(b.lhs.id(), lhs_grad);
+                    self.accumulate(b.rhs.id(), rhs_grad);
+                }
+                NoxprNode::Reshape(r) => {
+                    self.accumulate(r.expr.id(), g.reshape(shape(&r.expr)?));
+                }
+                NoxprNode::Broadcast(b) => {
+                    let keep: SmallVec<[usize; 4]> = (b.sizes.len()..out_shape.len()).collect();
+                    let grad = reduce_sum(g, element_type, &out_shape, &keep)?;
+                    self.accumulate(b.expr.id(), grad);
+                }
+                NoxprNode::BroadcastInDim(b) => {
+                    let in_shape = shape(&b.expr)?;
+                    let keep: SmallVec<[usize; 4]> = b
+                        .broadcast_dims
+                        .iter()
+                        .zip(&in_shape)
+                        .map(|(&dim, &len)| (dim as usize, len))
+                        .filter(|&(dim, len)| out_shape[dim] == len)
+                        .map(|(dim, _)| dim)
+                        .collect();
+                    let grad = reduce_sum(g, element_type, &out_shape, &keep)?;
+                    self.accumulate(b.expr.id(), grad.reshape(in_shape));
+                }
+                NoxprNode::Transpose(t) => {
+                    let mut inverse: SmallVec<[i64; 4]> = smallvec![0; t.permutation.len()];
+                    for (i, &p) in t.permutation.iter().enumerate() {
+                        inverse[p as usize] = i as i64;
+                    }
+                    self.accumulate(t.expr.id(), g.transpose(inverse));
+                }
+                NoxprNode::Slice(s) => {
+                    if s.strides.iter().any(|&stride| stride != 1) {
+                        return Err(Error::Undifferentiable(expr.name()));
+                    }
+                    // scatter the slice back into place by padding each dimension with zeros
+                    let in_shape = shape(&s.expr)?;
+                    let mut grad = g;
+                    let mut grad_shape = out_shape.clone();
+                    for dim in 0..in_shape.len() {
+                        let (start, stop) = (s.start_indices[dim], s.stop_indices[dim]);
+                        let mut parts = vec![];
+                        if start > 0 {
+                            grad_shape[dim] = start;
+                            parts.push(splat(0.0, element_type, &grad_shape)?);
+                        }
+                        parts.push(grad);
+                        if stop < in_shape[dim] {
+                            grad_shape[dim] = in_shape[dim] - stop;
+                            parts.push(splat(0.0, element_type, &grad_shape)?);
+                        }
+                        grad_shape[dim] = in_shape[dim];
+                        grad = match parts.len() {
+                            1 => parts.pop().expect("parts is non empty"),
+                            _ => Noxpr::concat_in_dim(parts, dim),
+                        };
+                    }
+                    self.accumulate(s.expr.id(), grad);
+                }
+                NoxprNode::Reduce(r) => {
+                    let in_shape = shape(&r.expr)?;
+                    let kept: SmallVec<[i64; 4]> = (0..in_shape.len() as i64)
+                        .filter(|dim| !r.dims.contains(dim))
+                        .collect();
+                    let g = g.broadcast_in_dim(in_shape.clone(), kept.clone());
+                    let grad = match r.op {
+                        ReduceOp::Sum => g,
+                        ReduceOp::Max => {
+                            // the gradient is split evenly between every element tied for the max
+                            let max = expr
+                                .clone()
+                                .broadcast_in_dim(in_shape.clone(), kept.clone());
+                            let is_max = r.expr.clone().greater_or_equal(max);
+                            let zeros = splat(0.0, element_type, &in_shape)?;
+                            let ones = splat(1.0, element_type, &in_shape)?;
+                            let count = is_max
+                                .clone()
+                                .select(ones, zeros.clone())
+                                .reduce_sum(r.dims.clone())
+                                .broadcast_in_dim(in_shape, kept);
+                            is_max.select(g / count, zeros)
+                        }
+                    };
+                    self.accumulate(r.expr.id(), grad);
+                }
+                NoxprNode::Concat(c) => {
+                    let mut offset = 0;
+                    for node in &c.nodes {
+                        let node_shape = shape(node)?;
+                        let mut start: SmallVec<[i64; 4]> = smallvec![0; out_shape.len()];
+                        let mut stop = out_shape.clone();
+                        start[c.dimension] = offset;
+                        offset += node_shape[c.dimension];
+                        stop[c.dimension] = offset;
+                        let strides = smallvec![1; out_shape.len()];
+                        self.accumulate(node.id(), g.clone().slice(start, stop, strides));
+                    }
+                }
+                _ => return Err(Error::Undifferentiable(expr.name())),
+            }
+        }
+        Ok(())
+    }
+
+    /// Accumulates the gradient of an elementwise binary operand, summing over the dimensions
+    /// it was implicitly broadcast along.
+    fn accumulate_broadcast(
+        &mut self,
+        operand: &Noxpr,
+        grad: Noxpr,
+        out_shape: &[i64],
+    ) -> Result<(), Error> {
+        let in_shape = shape(operand)?;
+        if in_shape[..] == out_shape[..] {
+            self.accumulate(operand.id(), grad);
+            return Ok(());
+        }
+        let element_type = operand.element_type().ok_or(Error::IncompatibleDType)?;
+        let offset = out_shape.len() - in_shape.len();
+        let keep: SmallVec<[usize; 4]> = (offset..out_shape.len())
+            .filter(|&dim| in_shape[dim - offset] == out_shape[dim])
+            .collect();
+        let grad = reduce_sum(grad, element_type, out_shape, &keep)?.reshape(in_shape);
+        self.accumulate(operand.id(), grad);
+        Ok(())
+    }
+}
+
+/// The inputs that gradients flow back into. Predicates, like the condition of a select, are
+/// left out since nothing is differentiable with respect to them.
+fn inputs(expr: &Noxpr) -> Vec<&Noxpr> {
+    match expr.deref() {
+        NoxprNode::Add(b)
+        | NoxprNode::Sub(b)
+        | NoxprNode::Mul(b)
+        | NoxprNode::Div(b)
+        | NoxprNode::Atan2(b)
+        | NoxprNode::Min(b)
+        | NoxprNode::Max(b)
+        | NoxprNode::Dot(b) => vec![&b.lhs, &b.rhs],
+        NoxprNode::Sqrt(e)
+        | NoxprNode::Neg(e)
+        | NoxprNode::Log(e)
+        | NoxprNode::Sin(e)
+        | NoxprNode::Cos(e)
+        | NoxprNode::Exp(e)
+        | NoxprNode::Tanh(e)
+        | NoxprNode::Abs(e) => vec![e],
+        NoxprNode::Select(s) => vec![&s.on_true, &s.on_false],
+        NoxprNode::Concat(c) => c.nodes.iter().collect(),
+        NoxprNode::Reshape(r) => vec![&r.expr],
+        NoxprNode::Broadcast(b) => vec![&b.expr],
+        NoxprNode::BroadcastInDim(b) => vec![&b.expr],
+        NoxprNode::Transpose(t) => vec![&t.expr],
+        NoxprNode::Slice(s) => vec![&s.expr],
+        NoxprNode::Reduce(r) => vec![&r.expr],
+        _ => vec![],
+    }
+}
+
+fn shape(expr: &Noxpr) -> Result<SmallVec<[i64; 4]>, Error> {
+    expr.shape().ok_or(Error::Undifferentiable(expr.name()))
+}
+
+/// A constant tensor with every element set to `value`.
+fn splat(value: f64, element_type: ElementType, shape: &[i64]) -> Result<Noxpr, Error> {
+    let literal = match element_type {
+        ElementType::F32 => xla::Literal::scalar(value as f32),
+        ElementType::F64 => xla::Literal::scalar(value),
+        _ => return Err(Error::IncompatibleDType),
+    };
+    let scalar = Noxpr::constant(literal, ArrayTy::new(element_type, smallvec![]));
+    if shape.is_empty() {
+        Ok(scalar)
+    } else {
+        Ok(scalar.broadcast(SmallVec::from_slice(shape)))
+    }
+}
+
+/// Sums `expr` over every dimension not in `keep`, returning the kept dimensions in the order
+/// they are listed.
+fn reduce_sum(
+    expr: Noxpr,
+    element_type: ElementType,
+    shape: &[i64],
+    keep: &[usize],
+) -> Result<Noxpr, Error> {
+    let reduced: SmallVec<[usize; 4]> = (0..shape.len()).filter(|d| !keep.contains(d)).collect();
+    if reduced.is_empty() {
+        return Ok(expr);
+    }
+    let reduced_len = reduced.iter().map(|&d| shape[d]).product::<i64>();
+    let kept_shape: SmallVec<[i64; 4]> = keep.iter().map(|&d| shape[d]).collect();
+    let kept_len = kept_shape.iter().product::<i64>();
+    let permutation = reduced.iter().chain(keep).map(|&d| d as i64).collect();
+    let matrix = expr
+        .transpose(permutation)
+        .reshape(smallvec![reduced_len, kept_len]);
+    let ones = splat(1.0, element_type, &[reduced_len])?;
+    Ok(ones.dot(&matrix).reshape(kept_shape))
+}
+
+/// The outer product of two vectors, as a matrix of `shape`.
+fn outer(lhs: Noxpr, rhs: Noxpr, shape: &[i64]) -> Noxpr {
+    let shape = SmallVec::from_slice(shape);
+    lhs.broadcast_in_dim(shape.clone(), smallvec![0]) * rhs.broadcast_in_dim(shape, smallvec![1])
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{matrix, vector, Vector4};
+
+    use crate::{Client, Comp, CompFn, Matrix, Quaternion, Scalar, ToHost, Vector};
+
+    use super::*;
+
+    #[test]
+    fn test_grad_norm_squared() {
+        let client = Client::cpu().unwrap();
+        let grad = (|x: Vector<f64, 3>| -> Scalar<f64> { x.norm_squared() })
+            .grad(0)
+            .unwrap();
+        let exec = Comp::<(Vector<f64, 3>,), Vector<f64, 3>>::from_expr(&grad, "grad")
+            .unwrap()
+            .compile(&client)
+            .unwrap();
+        let out = exec
+            .run(&client, vector![1.0, -2.0, 0.5])
+            .unwrap()
+            .to_host();
+        assert_eq!(out, vector![2.0, -4.0, 1.0]);
+    }
+
+    #[test]
+    fn test_grad_cross_and_quat_mul() {
+        let client = Client::cpu().unwrap();
+        // a . (b x c) is linear in `b`, with gradient c x a
+        let grad = (|a: Vector<f64, 3>, b: Vector<f64, 3>, c: Vector<f64, 3>| -> Scalar<f64> {
+            a.dot(&b.cross(&c))
+        })
+        .grad(1)
+        .unwrap();
+        type Args = (Vector<f64, 3>, Vector<f64, 3>, Vector<f64, 3>);
+        let exec = Comp::<Args, Vector<f64, 3>>::from_expr(&grad, "grad")
+            .unwrap()
+            .compile(&client)
+            .unwrap();
+        let (a, b, c) = (
+            vector![1.0, 2.0, 3.0],
+            vector![-1.0, 0.5, 2.0],
+            vector![0.0, 4.0, 1.0],
+        );
+        let out = exec.run(&client, a, b, c).unwrap().to_host();
+        approx::assert_relative_eq!(out, c.cross(&a), epsilon = 1e-12);
+
+        // (q * p) . w is linear in `q`, so each component of the gradient is the loss at the
+        // matching basis quaternion
+        let grad = (|q: Quaternion<f64>, p: Quaternion<f64>, w: Vector<f64, 4>| -> Scalar<f64> {
+            (q * p).0.dot(&w)
+        })
+        .grad(0)
+        .unwrap();
+        type QuatArgs = (Quaternion<f64>, Quaternion<f64>, Vector<f64, 4>);
+        let exec = Comp::<QuatArgs, Vector<f64, 4>>::from_expr(&grad, "grad")
+            .unwrap()
+            .compile(&client)
+            .unwrap();
+        let q = nalgebra::Quaternion::new(0.9, 0.1, -0.3, 0.2);
+        let p = nalgebra::Quaternion::new(0.5, -0.5, 0.25, 0.7);
+        let w = vector![1.0, -2.0, 0.5, 3.0];
+        let out = exec.run(&client, q, p, w).unwrap().to_host();
+        let expected = Vector4::from_fn(|i, _| {
+            let basis = nalgebra::Quaternion::from(Vector4::ith(i, 1.0));
+            (basis * p).coords.dot(&w)
+        });
+        approx::assert_relative_eq!(out, expected, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_grad_reduce() {
+        let client = Client::cpu().unwrap();
+        let grad = (|x: Matrix<f64, 2, 3>| -> Scalar<f64> {
+            x.max_axis::<1>().sum_axis::<0>() + x.sum_axis::<0>().sum_axis::<0>()
+        })
+        .grad(0)
+        .unwrap();
+        let exec = Comp::<(Matrix<f64, 2, 3>,), Matrix<f64, 2, 3>>::from_expr(&grad, "grad")
+            .unwrap()
+            .compile(&client)
+            .unwrap();
+        let out = exec
+            .run(&client, matrix![1.0, 3.0, 2.0; 4.0, 4.0, -1.0])
+            .unwrap()
+            .to_host();
+        // every element is in the sum, and the tied maxima of the second row share their slot
+        assert_eq!(out, matrix![1.0, 2.0, 1.0; 1.5, 1.5, 1.0]);
+    }
+}
@@ -1,15 +1,15 @@
 use pyo3::{
     exceptions::PyValueError,
-    types::{PyDict, PyTuple},
-    PyObject, PyResult, Python,
+    types::{IntoPyDict, PyDict, PyTuple},
+    IntoPy, PyObject, PyResult, Python,
 };
 use smallvec::SmallVec;
 use std::{collections::HashMap, ops::Deref};
 use xla::{ArrayElement, ElementType, Literal};
 
 use crate::{
-    BinaryOp, CompFn, Error, Field, IntoOp, Noxpr, NoxprFn, NoxprId, NoxprNode, Scalar, Tensor,
-    TensorItem,
+    BinaryOp, CompFn, Error, Field, IntoOp, Noxpr, NoxprFn, NoxprId, NoxprNode, ReduceOp, Scalar,
+    Tensor, TensorItem,
 };
 
 impl Noxpr {
@@ -75,6 +75,40 @@ impl JaxTracer {
                     .call_method1(py, "iota", (dtype, size))
                     .map_err(Error::PyO3)
             })?,
+            NoxprNode::RngUniform(r) => Python::with_gil(|py| {
+                let dtype = dtype(&r.shape.element_type)?;
+                let key = self
+                    .jnp
+                    .call_method1(py, "array", (r.state.to_vec(), "uint64"))?;
+                let algorithm = self
+                    .lax
+                    .getattr(py, "RandomAlgorithm")?
+                    .getattr(py, "RNG_THREE_FRY")?;
+                let kwargs =
+                    [("dtype", "uint32".into_py(py)), ("algorithm", algorithm)].into_py_dict(py);
+                let bits = self
+                    .lax
+                    .call_method(
+                        py,
+                        "rng_bit_generator",
+                        (key, r.shape.shape.to_vec()),
+                        Some(kwargs),
+                    )?
+                    .call_method1(py, "__getitem__", (1,))?;
+                let (bits, scale) = match r.shape.element_type {
+                    ElementType::F32 => (
+                        self.jnp
+                            .call_method1(py, "floor_divide", (bits, 1u32 << 8))?,
+                        2f64.powi(-24),
+                    ),
+                    ElementType::F64 => (bits, 2f64.powi(-32)),
+                    _ => return Err(Error::IncompatibleDType),
+                };
+                let uniform = bits.call_method1(py, "astype", (dtype,))?;
+                self.jnp
+                    .call_method1(py, "multiply", (uniform, scale))
+                    .map_err(Error::PyO3)
+            })?,
             NoxprNode::Add(op) => self.visit_binary_lax(op, "add")?,
             NoxprNode::Sub(op) => self.visit_binary_lax(op, "sub")?,
             NoxprNode::Mul(op) => self.visit_binary_lax(op, "mul")?,
@@ -85,6 +119,10 @@ impl JaxTracer {
             NoxprNode::GreaterOrEqual(op) => self.visit_binary_lax(op, "ge")?,
             NoxprNode::LessOrEqual(op) => self.visit_binary_lax(op, "le")?,
             NoxprNode::Less(op) => self.visit_binary_lax(op, "lt")?,
+            NoxprNode::Equal(op) => self.visit_binary_lax(op, "eq")?,
+            NoxprNode::Atan2(op) => self.visit_binary_lax(op, "atan2")?,
+            NoxprNode::Min(op) => self.visit_binary_lax(op, "min")?,
+            NoxprNode::Max(op) => self.visit_binary_lax(op, "max")?,
             NoxprNode::DotGeneral(d) => {
                 let lhs = self.visit(&d.lhs)?;
                 let rhs = self.visit(&d.rhs)?;
@@ -102,6 +140,11 @@ impl JaxTracer {
             NoxprNode::Sqrt(op) => self.visit_unary_lax(op, "sqrt")?,
             NoxprNode::Neg(op) => self.visit_unary_lax(op, "neg")?,
             NoxprNode::Log(op) => self.visit_unary_lax(op, "log")?,
+            NoxprNode::Tanh(op) => self.visit_unary_lax(op, "tanh")?,
+            NoxprNode::Exp(op) => self.visit_unary_lax(op, "exp")?,
+            NoxprNode::Abs(op) => self.visit_unary_lax(op, "abs")?,
+            NoxprNode::Cos(op) => self.visit_unary_lax(op, "cos")?,
+            NoxprNode::Sin(op) => self.visit_unary_lax(op, "sin")?,
             NoxprNode::Concat(c) => {
                 let nodes = c
                     .nodes
@@ -113,6 +156,15 @@ impl JaxTracer {
                         .call_method1(py, "concatenate", (nodes, c.dimension))
                 })?
             }
+            NoxprNode::Select(s) => {
+                let cond = self.visit(&s.cond)?;
+                let on_true = self.visit(&s.on_true)?;
+                let on_false = self.visit(&s.on_false)?;
+                Python::with_gil(|py| {
+                    self.lax
+                        .call_method1(py, "select", (cond, on_true, on_false))
+                })?
+            }
             NoxprNode::Reshape(r) => {
                 let expr = self.visit(&r.expr)?;
                 let sizes = r.new_sizes.to_vec();
@@ -137,6 +189,17 @@ impl JaxTracer {
                 let permutation = t.permutation.to_vec();
                 Python::with_gil(|py| self.lax.call_method1(py, "transpose", (expr, permutation)))?
             }
+            NoxprNode::Reduce(r) => {
+                let expr = self.visit(&r.expr)?;
+                let method = match r.op {
+                    ReduceOp::Sum => "sum",
+                    ReduceOp::Max => "max",
+                };
+                Python::with_gil(|py| {
+                    let dims = PyTuple::new(py, r.dims.iter());
+                    self.jnp.call_method1(py, method, (expr, dims))
+                })?
+            }
             NoxprNode::Gather(g) => {
                 let expr = self.visit(&g.expr)?;
                 let start_indices = self.visit(&g.indices)?;
@@ -207,6 +270,26 @@ impl JaxTracer {
                         .map_err(Error::PyO3)
                 })?
             }
+            NoxprNode::Scatter(s) => {
+                let expr = self.visit(&s.expr)?;
+                let indices = self.visit(&s.indices)?;
+                let updates = self.visit(&s.updates)?;
+                let scatter_dims = Python::with_gil(|py| {
+                    self.lax.call_method1(
+                        py,
+                        "ScatterDimensionNumbers",
+                        (
+                            s.update_window_dims.to_vec(),
+                            s.inserted_window_dims.to_vec(),
+                            s.scatter_dims_to_operand_dims.to_vec(),
+                        ),
+                    )
+                })?;
+                Python::with_gil(|py| {
+                    self.lax
+                        .call_method1(py, "scatter", (expr, indices, updates, scatter_dims))
+                })?
+            }
             NoxprNode::GetTupleElement(g) => match g.expr.deref() {
                 NoxprNode::Tuple(elems) => {
                     let elem = elems.get(g.index).ok_or(Error::OutOfBoundsAccess)?;
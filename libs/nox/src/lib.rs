@@ -8,6 +8,7 @@ mod constant;
 mod error;
 mod exec;
 mod fields;
+mod grad;
 mod matrix;
 mod noxpr;
 mod param;
@@ -163,14 +163,94 @@ where
     }
 }
 
+impl<T, const M: usize, const K: usize> Matrix<T, M, K, Op> {
+    /// Multiplies an `(M, K)` matrix by a `(K, N)` matrix, producing an `(M, N)` matrix.
+    ///
+    /// The shared inner dimension is part of the signature, so mismatched operands are rejected at compile time.
+    pub fn matmul<const N: usize>(&self, rhs: &Matrix<T, K, N, Op>) -> Matrix<T, M, N, Op> {
+        let inner = self.inner.clone().dot(&rhs.inner);
+        Matrix {
+            inner,
+            phantom: PhantomData,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use nalgebra::{matrix, vector};
 
-    use crate::{CompFn, FixedSliceExt, Vector};
+    use crate::{Axis, CompFn, Error, FixedSliceExt, Scalar, Vector};
 
     use super::*;
 
+    #[test]
+    fn test_sharded_batch() {
+        let batch = || {
+            let values: Vec<f64> = (0..12).map(|i| i as f64 - 5.0).collect();
+            xla::Literal::vector(&values).reshape(&[4, 3]).unwrap()
+        };
+
+        let client = Client::cpu_with_devices(1).unwrap();
+        let comp = (|m: Matrix<f64, 4, 3>| m.lp_norm::<1>(2.0))
+            .build()
+            .unwrap();
+        let exec = comp.compile_sharded(&client).unwrap();
+        let whole = exec.run(&client, &[batch()]).unwrap();
+        assert_eq!(whole.len(), 1);
+        let whole = whole[0].to_host();
+
+        let client = Client::cpu_with_devices(2).unwrap();
+        assert_eq!(client.device_count(), 2);
+        let comp = (|m: Matrix<f64, 2, 3>| m.lp_norm::<1>(2.0))
+            .build()
+            .unwrap();
+        let exec = comp.compile_sharded(&client).unwrap();
+        let shards = exec.run(&client, &[batch()]).unwrap();
+        let sharded: Vec<f64> = shards
+            .iter()
+            .flat_map(|shard| shard.to_host().iter().copied().collect::<Vec<_>>())
+            .collect();
+        assert_eq!(sharded, whole.iter().copied().collect::<Vec<_>>());
+
+        let uneven = xla::Literal::vector(&[1.0f64, 2.0, 3.0])
+            .reshape(&[3, 1])
+            .unwrap();
+        assert!(matches!(
+            exec.run(&client, &[uneven]),
+            Err(Error::UnevenBatch {
+                batch: 3,
+                devices: 2
+            })
+        ));
+        assert!(matches!(
+            exec.run(&client, &[batch(), batch()]),
+            Err(Error::WrongArgCount {
+                expected: 1,
+                found: 2
+            })
+        ));
+        let narrow = xla::Literal::vector(&[1.0f64; 8]).reshape(&[4, 2]).unwrap();
+        assert!(matches!(
+            exec.run(&client, &[narrow]),
+            Err(Error::DimensionMismatch { expected, found: Some(found) })
+                if expected == [2, 3] && found == [2, 2]
+        ));
+        let single = xla::Literal::vector(&[1.0f32; 12])
+            .reshape(&[4, 3])
+            .unwrap();
+        assert!(matches!(
+            exec.run(&client, &[single]),
+            Err(Error::IncompatibleDType)
+        ));
+
+        // shards of a batch with a zero-sized trailing dim hold no bytes
+        let comp = (|m: Matrix<f64, 1, 0>| m).build().unwrap();
+        let exec = comp.compile_sharded(&client).unwrap();
+        let empty = xla::Literal::vector::<f64>(&[]).reshape(&[2, 0]).unwrap();
+        assert_eq!(exec.run(&client, &[empty]).unwrap().len(), 2);
+    }
+
     #[test]
     fn test_add() {
         let client = Client::cpu().unwrap();
@@ -217,6 +297,103 @@ mod tests {
         assert_eq!(out, matrix![2., 6.; 8., 15.]);
     }
 
+    #[test]
+    fn test_matmul() {
+        let client = Client::cpu().unwrap();
+        let comp = (|a: Matrix<f32, 2, 3>, b: Matrix<f32, 3, 2>| a.matmul(&b))
+            .build()
+            .unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec
+            .run(
+                &client,
+                matrix![1.0f32, 2.0, 3.0; 4.0, 5.0, 6.0],
+                matrix![7.0, 8.0; 9.0, 10.0; 11.0, 12.0],
+            )
+            .unwrap()
+            .to_host();
+        assert_eq!(out, matrix![58.0, 64.0; 139.0, 154.0]);
+    }
+
+    #[test]
+    fn test_reduce_axis() {
+        let client = Client::cpu().unwrap();
+        let a = matrix![1.0f32, 2.0, 3.0, 4.0;
+                        5.0, 6.0, 7.0, 8.0;
+                        9.0, 10.0, 11.0, 12.0];
+
+        let comp = (|a: Matrix<f32, 3, 4>| a.sum_axis::<0>()).build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec.run(&client, a).unwrap().to_host();
+        assert_eq!(out, vector![15.0, 18.0, 21.0, 24.0]);
+
+        let comp = (|a: Matrix<f32, 3, 4>| a.sum_axis::<1>()).build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec.run(&client, a).unwrap().to_host();
+        assert_eq!(out, vector![10.0, 26.0, 42.0]);
+
+        let comp = (|a: Matrix<f32, 3, 4>| a.mean_axis::<1>()).build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec.run(&client, a).unwrap().to_host();
+        assert_eq!(out, vector![2.5, 6.5, 10.5]);
+
+        let comp = (|a: Matrix<f32, 3, 4>| a.max_axis::<0>()).build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec.run(&client, a).unwrap().to_host();
+        assert_eq!(out, vector![9.0, 10.0, 11.0, 12.0]);
+    }
+
+    #[test]
+    fn test_transpose() {
+        let client = Client::cpu().unwrap();
+        let comp = (|a: Matrix<f64, 2, 3>| a.transpose()).build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec
+            .run(&client, matrix![1.0, 2.0, 3.0; 4.0, 5.0, 6.0])
+            .unwrap()
+            .to_host();
+        assert_eq!(out, matrix![1.0, 4.0; 2.0, 5.0; 3.0, 6.0]);
+    }
+
+    #[test]
+    fn test_clamp_columns() {
+        let client = Client::cpu().unwrap();
+        let comp =
+            (|a: Matrix<f64, 2, 3>, min: Vector<f64, 3>, max: Vector<f64, 3>| a.clamp(&min, &max))
+                .build()
+                .unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec
+            .run(
+                &client,
+                matrix![-5.0, 0.5, 7.0; 3.0, -2.0, 0.0],
+                vector![-1.0, 0.0, 1.0],
+                vector![1.0, 2.0, 3.0],
+            )
+            .unwrap()
+            .to_host();
+        assert_eq!(out, matrix![-1.0, 0.5, 3.0; 1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_permute() {
+        let client = Client::cpu().unwrap();
+        let comp = (|a: Matrix<f64, 2, 3>| {
+            let b: Tensor<f64, (Const<1>, Const<2>, Const<3>)> = a.reshape();
+            let moved: Tensor<f64, (Const<3>, Const<1>, Const<2>)> =
+                b.permute::<(Axis<2>, Axis<0>, Axis<1>)>();
+            moved.reshape::<(Const<3>, Const<2>)>()
+        })
+        .build()
+        .unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec
+            .run(&client, matrix![1.0, 2.0, 3.0; 4.0, 5.0, 6.0])
+            .unwrap()
+            .to_host();
+        assert_eq!(out, matrix![1.0, 4.0; 2.0, 5.0; 3.0, 6.0]);
+    }
+
     #[test]
     fn test_fixed_slice() {
         let client = Client::cpu().unwrap();
@@ -269,4 +446,154 @@ mod tests {
                     7., 8., 9.]
         );
     }
+
+    #[test]
+    fn test_gather_scatter() {
+        let client = Client::cpu().unwrap();
+        fn gather(mat: Matrix<f32, 4, 3>, indices: Vector<u32, 2>) -> Matrix<f32, 2, 3> {
+            mat.gather::<0, _>(indices)
+        }
+        fn scatter(
+            mat: Matrix<f32, 4, 3>,
+            indices: Vector<u32, 2>,
+            updates: Matrix<f32, 2, 3>,
+        ) -> Matrix<f32, 4, 3> {
+            mat.scatter::<0, _>(indices, updates)
+        }
+        let a = matrix![0., 1., 2.;
+                        2., 3., 4.;
+                        4., 5., 6.;
+                        7., 8., 9.];
+
+        let exec = gather.build().unwrap().compile(&client).unwrap();
+        let out: nalgebra::Matrix<f32, Const<2>, Const<3>, ArrayStorage<f32, 2, 3>> =
+            exec.run(&client, a, vector![2, 0]).unwrap().to_host();
+        let mut expected = nalgebra::Matrix2x3::<f32>::zeros();
+        expected.set_row(0, &a.row(2));
+        expected.set_row(1, &a.row(0));
+        assert_eq!(out, expected);
+
+        let exec = scatter.build().unwrap().compile(&client).unwrap();
+        let updates = matrix![1., 2., 3.;
+                              4., 5., 6.];
+        let out: nalgebra::Matrix<f32, Const<4>, Const<3>, ArrayStorage<f32, 4, 3>> = exec
+            .run(
+                &client,
+                nalgebra::Matrix4x3::<f32>::zeros(),
+                vector![2, 0],
+                updates,
+            )
+            .unwrap()
+            .to_host();
+        let mut expected = nalgebra::Matrix4x3::<f32>::zeros();
+        expected.set_row(2, &updates.row(0));
+        expected.set_row(0, &updates.row(1));
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_gather_scatter_axis() {
+        let client = Client::cpu().unwrap();
+        fn gather(mat: Matrix<f32, 4, 3>, indices: Vector<u32, 2>) -> Matrix<f32, 4, 2> {
+            mat.gather::<1, _>(indices)
+        }
+        fn scatter(
+            mat: Matrix<f32, 4, 3>,
+            indices: Vector<u32, 2>,
+            updates: Matrix<f32, 4, 2>,
+        ) -> Matrix<f32, 4, 3> {
+            mat.scatter::<1, _>(indices, updates)
+        }
+        fn gather_grid(vec: Vector<f32, 4>, indices: Matrix<u32, 2, 2>) -> Matrix<f32, 2, 2> {
+            vec.gather::<0, _>(indices)
+        }
+        let a = matrix![0., 1., 2.;
+                        2., 3., 4.;
+                        4., 5., 6.;
+                        7., 8., 9.];
+
+        let exec = gather.build().unwrap().compile(&client).unwrap();
+        let out: nalgebra::Matrix<f32, Const<4>, Const<2>, ArrayStorage<f32, 4, 2>> =
+            exec.run(&client, a, vector![2, 0]).unwrap().to_host();
+        let mut expected = nalgebra::Matrix4x2::<f32>::zeros();
+        expected.set_column(0, &a.column(2));
+        expected.set_column(1, &a.column(0));
+        assert_eq!(out, expected);
+
+        let exec = scatter.build().unwrap().compile(&client).unwrap();
+        let out: nalgebra::Matrix<f32, Const<4>, Const<3>, ArrayStorage<f32, 4, 3>> = exec
+            .run(&client, a, vector![0, 2], expected)
+            .unwrap()
+            .to_host();
+        let mut swapped = a;
+        swapped.swap_columns(0, 2);
+        assert_eq!(out, swapped);
+
+        let exec = gather_grid.build().unwrap().compile(&client).unwrap();
+        let out: nalgebra::Matrix<f32, Const<2>, Const<2>, ArrayStorage<f32, 2, 2>> = exec
+            .run(&client, vector![10., 11., 12., 13.], matrix![3, 0; 1, 1])
+            .unwrap()
+            .to_host();
+        assert_eq!(out, matrix![13., 10.; 11., 11.]);
+    }
+
+    #[test]
+    fn test_cumsum() {
+        let client = Client::cpu().unwrap();
+        let a = matrix![1, 2, 3;
+                        4, 5, 6];
+
+        let comp = (|m: Matrix<i32, 2, 3>| m.cumsum::<0>()).build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec.run(&client, a).unwrap().to_host();
+        assert_eq!(out, matrix![1, 2, 3; 5, 7, 9]);
+
+        let comp = (|m: Matrix<i32, 2, 3>| m.cumsum::<1>()).build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec.run(&client, a).unwrap().to_host();
+        assert_eq!(out, matrix![1, 3, 6; 4, 9, 15]);
+    }
+
+    #[test]
+    fn test_broadcast_ops() {
+        let client = Client::cpu().unwrap();
+        let comp = (|a: Scalar<f32>, b: Vector<f32, 3>| a + b).build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec
+            .run(&client, 1.0f32, vector![1.0f32, 2.0, 3.0])
+            .unwrap()
+            .to_host();
+        assert_eq!(out, vector![2.0, 3.0, 4.0]);
+
+        let comp = (|a: Vector<f32, 3>, b: Matrix<f32, 3, 3>| a * b)
+            .build()
+            .unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec
+            .run(
+                &client,
+                vector![1.0f32, 2.0, 3.0],
+                matrix![1.0f32, 1.0, 1.0; 2.0, 2.0, 2.0; 3.0, 3.0, 3.0],
+            )
+            .unwrap()
+            .to_host();
+        assert_eq!(out, matrix![1.0, 2.0, 3.0; 2.0, 4.0, 6.0; 3.0, 6.0, 9.0]);
+
+        let comp = (|a: Matrix<f32, 2, 2>, b: Scalar<f32>| a - b)
+            .build()
+            .unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec
+            .run(&client, matrix![1.0f32, 2.0; 3.0, 4.0], 1.0f32)
+            .unwrap()
+            .to_host();
+        assert_eq!(out, matrix![0.0, 1.0; 2.0, 3.0]);
+
+        let comp = (|a: Vector<f32, 2>| a.broadcast_to::<(Const<3>, Const<2>)>())
+            .build()
+            .unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec.run(&client, vector![1.0f32, 2.0]).unwrap().to_host();
+        assert_eq!(out, matrix![1.0, 2.0; 1.0, 2.0; 1.0, 2.0]);
+    }
 }
@@ -28,6 +28,7 @@ pub enum NoxprNode {
     // Constants
     Constant(Constant),
     Iota(Iota),
+    RngUniform(RngUniform),
 
     // Element Wise Binary Ops
     Add(BinaryOp),
@@ -39,6 +40,10 @@ pub enum NoxprNode {
     GreaterOrEqual(BinaryOp),
     LessOrEqual(BinaryOp),
     Less(BinaryOp),
+    Equal(BinaryOp),
+    Atan2(BinaryOp),
+    Min(BinaryOp),
+    Max(BinaryOp),
 
     // Matrix Multiplication
     Dot(BinaryOp),
@@ -48,9 +53,15 @@ pub enum NoxprNode {
     Sqrt(Noxpr),
     Neg(Noxpr),
     Log(Noxpr),
+    Tanh(Noxpr),
+    Exp(Noxpr),
+    Abs(Noxpr),
+    Cos(Noxpr),
+    Sin(Noxpr),
 
     // Nary ops
     Concat(Concat),
+    Select(Select),
 
     // Reshape
     Reshape(Reshape),
@@ -63,6 +74,10 @@ pub enum NoxprNode {
     Slice(Slice),
     DynamicSlice(DynamicSlice),
     DynamicUpdateSlice(DynamicUpdateSlice),
+    Scatter(Scatter),
+
+    // Reduce
+    Reduce(Reduce),
 
     // Control Flow
     Scan(Scan),
@@ -151,6 +166,12 @@ pub struct Iota {
     pub dim: usize,
 }
 
+#[derive(Debug, Clone)]
+pub struct RngUniform {
+    pub state: [u64; 2],
+    pub shape: ArrayTy,
+}
+
 #[derive(Debug)]
 pub struct BinaryOp {
     pub lhs: Noxpr,
@@ -404,6 +425,50 @@ pub struct DynamicUpdateSlice {
     pub update: Noxpr,
 }
 
+/// Overwrites the windows of `expr` selected by `indices` with `updates`, the inverse of
+/// [`Gather`]. When an index repeats, which update wins is unspecified.
+#[derive(Debug)]
+pub struct Scatter {
+    pub expr: Noxpr,
+    pub indices: Noxpr,
+    pub updates: Noxpr,
+    pub update_window_dims: SmallVec<[i64; 4]>,
+    pub inserted_window_dims: SmallVec<[i64; 4]>,
+    pub scatter_dims_to_operand_dims: SmallVec<[i64; 4]>,
+    pub index_vector_dim: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReduceOp {
+    Sum,
+    Max,
+}
+
+#[derive(Debug)]
+pub struct Reduce {
+    pub expr: Noxpr,
+    pub op: ReduceOp,
+    pub dims: SmallVec<[i64; 4]>,
+}
+
+impl Reduce {
+    fn reduced_shape(&self, shape: &[i64]) -> SmallVec<[i64; 4]> {
+        shape
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !self.dims.contains(&(*i as i64)))
+            .map(|(_, dim)| *dim)
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct Select {
+    pub cond: Noxpr,
+    pub on_true: Noxpr,
+    pub on_false: Noxpr,
+}
+
 #[derive(Debug)]
 pub struct GetTupleElement {
     pub expr: Noxpr,
@@ -461,6 +526,26 @@ impl Noxpr {
         Self::new(NoxprNode::Sqrt(self))
     }
 
+    pub fn tanh(self) -> Self {
+        Self::new(NoxprNode::Tanh(self))
+    }
+
+    pub fn exp(self) -> Self {
+        Self::new(NoxprNode::Exp(self))
+    }
+
+    pub fn abs(self) -> Self {
+        Self::new(NoxprNode::Abs(self))
+    }
+
+    pub fn cos(self) -> Self {
+        Self::new(NoxprNode::Cos(self))
+    }
+
+    pub fn sin(self) -> Self {
+        Self::new(NoxprNode::Sin(self))
+    }
+
     pub fn constant(data: xla::Literal, ty: ArrayTy) -> Self {
         Self::new(NoxprNode::Constant(Constant { data, ty }))
     }
@@ -503,6 +588,20 @@ impl Noxpr {
         Self::new(NoxprNode::Broadcast(Broadcast { expr: self, sizes }))
     }
 
+    /// Broadcasts to `sizes` following numpy's rules, lining up trailing dimensions and
+    /// stretching any of size one. Returns `self` unchanged if it already has that shape.
+    pub fn broadcast_to(self, sizes: SmallVec<[i64; 4]>) -> Self {
+        let Some(shape) = self.shape() else {
+            return self;
+        };
+        if shape == sizes {
+            return self;
+        }
+        let offset = sizes.len().saturating_sub(shape.len()) as i64;
+        let broadcast_dims = (0..shape.len() as i64).map(|dim| dim + offset).collect();
+        self.broadcast_in_dim(sizes, broadcast_dims)
+    }
+
     pub fn transpose(self, permuation: SmallVec<[i64; 4]>) -> Self {
         Self::new(NoxprNode::Transpose(Transpose {
             expr: self,
@@ -510,6 +609,23 @@ impl Noxpr {
         }))
     }
 
+    /// Combines every element along `dims` with `op`, removing those dimensions from the shape.
+    pub fn reduce(self, op: ReduceOp, dims: SmallVec<[i64; 4]>) -> Self {
+        Self::new(NoxprNode::Reduce(Reduce {
+            expr: self,
+            op,
+            dims,
+        }))
+    }
+
+    pub fn reduce_sum(self, dims: SmallVec<[i64; 4]>) -> Self {
+        self.reduce(ReduceOp::Sum, dims)
+    }
+
+    pub fn reduce_max(self, dims: SmallVec<[i64; 4]>) -> Self {
+        self.reduce(ReduceOp::Max, dims)
+    }
+
     pub fn or(self, rhs: Noxpr) -> Self {
         Self::new(NoxprNode::Or(BinaryOp { lhs: self, rhs }))
     }
@@ -530,6 +646,22 @@ impl Noxpr {
         Self::new(NoxprNode::Less(BinaryOp { lhs: self, rhs }))
     }
 
+    pub fn equal(self, rhs: Noxpr) -> Self {
+        Self::new(NoxprNode::Equal(BinaryOp { lhs: self, rhs }))
+    }
+
+    pub fn atan2(self, rhs: Noxpr) -> Self {
+        Self::new(NoxprNode::Atan2(BinaryOp { lhs: self, rhs }))
+    }
+
+    pub fn min(self, rhs: Noxpr) -> Self {
+        Self::new(NoxprNode::Min(BinaryOp { lhs: self, rhs }))
+    }
+
+    pub fn max(self, rhs: Noxpr) -> Self {
+        Self::new(NoxprNode::Max(BinaryOp { lhs: self, rhs }))
+    }
+
     pub fn reshape(self, new_sizes: SmallVec<[i64; 4]>) -> Self {
         Self::new(NoxprNode::Reshape(Reshape {
             expr: self,
@@ -557,10 +689,38 @@ impl Noxpr {
         }))
     }
 
+    pub fn scatter(
+        self,
+        indices: Noxpr,
+        updates: Noxpr,
+        update_window_dims: SmallVec<[i64; 4]>,
+        inserted_window_dims: SmallVec<[i64; 4]>,
+        scatter_dims_to_operand_dims: SmallVec<[i64; 4]>,
+        index_vector_dim: i64,
+    ) -> Self {
+        Self::new(NoxprNode::Scatter(Scatter {
+            expr: self,
+            indices,
+            updates,
+            update_window_dims,
+            inserted_window_dims,
+            scatter_dims_to_operand_dims,
+            index_vector_dim,
+        }))
+    }
+
     pub fn iota(shape: ArrayTy, dim: usize) -> Self {
         Self::new(NoxprNode::Iota(Iota { shape, dim }))
     }
 
+    /// Samples in `[0, 1)` from XLA's stateless three-fry generator.
+    ///
+    /// The output is a pure function of `state`, so the same state gives bit-identical samples
+    /// on every run and device. Only `F32` and `F64` element types are supported.
+    pub fn rng_uniform(state: [u64; 2], shape: ArrayTy) -> Self {
+        Self::new(NoxprNode::RngUniform(RngUniform { state, shape }))
+    }
+
     pub fn get_tuple_element(&self, index: usize) -> Self {
         Self::new(NoxprNode::GetTupleElement(GetTupleElement {
             expr: self.clone(),
@@ -591,7 +751,11 @@ impl Noxpr {
             | NoxprNode::Or(ref b)
             | NoxprNode::GreaterOrEqual(ref b)
             | NoxprNode::LessOrEqual(ref b)
-            | NoxprNode::Less(ref b) => b.ty(),
+            | NoxprNode::Max(ref b)
+            | NoxprNode::Min(ref b)
+            | NoxprNode::Atan2(ref b)
+            | NoxprNode::Less(ref b)
+            | NoxprNode::Equal(ref b) => b.ty(),
 
             NoxprNode::Dot(b) => {
                 let NoxprTy::ArrayTy(lhs_ty) = b.lhs.ty()? else {
@@ -615,7 +779,13 @@ impl Noxpr {
                 }))
             }
             NoxprNode::DotGeneral(s) => s.ty(),
-            NoxprNode::Sqrt(expr) | NoxprNode::Neg(expr) => expr.ty(),
+            NoxprNode::Sqrt(expr)
+            | NoxprNode::Tanh(expr)
+            | NoxprNode::Exp(expr)
+            | NoxprNode::Abs(expr)
+            | NoxprNode::Cos(expr)
+            | NoxprNode::Sin(expr)
+            | NoxprNode::Neg(expr) => expr.ty(),
 
             NoxprNode::Concat(concat) => {
                 let tys = concat
@@ -730,6 +900,15 @@ impl Noxpr {
                     shape: new_shape,
                 }))
             }
+            NoxprNode::Reduce(r) => {
+                let NoxprTy::ArrayTy(ty) = r.expr.ty()? else {
+                    return None;
+                };
+                Some(NoxprTy::ArrayTy(ArrayTy {
+                    element_type: ty.element_type,
+                    shape: r.reduced_shape(&ty.shape),
+                }))
+            }
             NoxprNode::Gather(gather) => {
                 let NoxprTy::ArrayTy(ty) = gather.expr.ty()? else {
                     return None;
@@ -764,7 +943,10 @@ impl Noxpr {
                 }))
             }
             NoxprNode::Iota(i) => Some(NoxprTy::ArrayTy(i.shape.clone())),
+            NoxprNode::RngUniform(r) => Some(NoxprTy::ArrayTy(r.shape.clone())),
             NoxprNode::DynamicUpdateSlice(d) => d.expr.ty(),
+            NoxprNode::Scatter(s) => s.expr.ty(),
+            NoxprNode::Select(s) => s.on_true.ty(),
             NoxprNode::GetTupleElement(g) => {
                 let NoxprTy::Tuple(ty) = g.expr.ty()? else {
                     return None;
@@ -802,13 +984,23 @@ impl Noxpr {
             | NoxprNode::Div(ref b)
             | NoxprNode::Mul(ref b)
             | NoxprNode::And(ref b)
+            | NoxprNode::Max(ref b)
+            | NoxprNode::Min(ref b)
+            | NoxprNode::Atan2(ref b)
             | NoxprNode::Or(ref b) => b.rhs.element_type(),
-            NoxprNode::GreaterOrEqual(_) | NoxprNode::LessOrEqual(_) | NoxprNode::Less(_) => {
-                Some(ElementType::Pred)
-            }
+            NoxprNode::GreaterOrEqual(_)
+            | NoxprNode::LessOrEqual(_)
+            | NoxprNode::Less(_)
+            | NoxprNode::Equal(_) => Some(ElementType::Pred),
             NoxprNode::Dot(b) => b.rhs.element_type(),
             NoxprNode::DotGeneral(s) => s.rhs.element_type(),
-            NoxprNode::Sqrt(expr) | NoxprNode::Neg(expr) => expr.element_type(),
+            NoxprNode::Sqrt(expr)
+            | NoxprNode::Tanh(expr)
+            | NoxprNode::Exp(expr)
+            | NoxprNode::Abs(expr)
+            | NoxprNode::Cos(expr)
+            | NoxprNode::Sin(expr)
+            | NoxprNode::Neg(expr) => expr.element_type(),
             NoxprNode::Concat(concat) => concat.nodes.first()?.element_type(),
             NoxprNode::Slice(slice) => slice.expr.element_type(),
             NoxprNode::DynamicSlice(dynamic_slice) => dynamic_slice.expr.element_type(),
@@ -818,9 +1010,13 @@ impl Noxpr {
             NoxprNode::Broadcast(b) => b.expr.element_type(),
             NoxprNode::BroadcastInDim(b) => b.expr.element_type(),
             NoxprNode::Transpose(t) => t.expr.element_type(),
+            NoxprNode::Reduce(r) => r.expr.element_type(),
             NoxprNode::Gather(gather) => gather.expr.element_type(),
             NoxprNode::Iota(i) => Some(i.shape.element_type),
+            NoxprNode::RngUniform(r) => Some(r.shape.element_type),
             NoxprNode::DynamicUpdateSlice(d) => d.expr.element_type(),
+            NoxprNode::Scatter(s) => s.expr.element_type(),
+            NoxprNode::Select(s) => s.on_true.element_type(),
             NoxprNode::GetTupleElement(g) => match g.expr.deref() {
                 NoxprNode::Tuple(elems) => elems.get(g.index)?.element_type(),
                 NoxprNode::Param(p) => {
@@ -862,7 +1058,11 @@ impl Noxpr {
             | NoxprNode::Or(ref b)
             | NoxprNode::GreaterOrEqual(ref b)
             | NoxprNode::LessOrEqual(ref b)
-            | NoxprNode::Less(ref b) => b.shape(),
+            | NoxprNode::Max(ref b)
+            | NoxprNode::Min(ref b)
+            | NoxprNode::Atan2(ref b)
+            | NoxprNode::Less(ref b)
+            | NoxprNode::Equal(ref b) => b.shape(),
 
             NoxprNode::Dot(b) => {
                 let lhs_shape = b.lhs.shape()?;
@@ -875,7 +1075,13 @@ impl Noxpr {
                 }
             }
             NoxprNode::DotGeneral(s) => s.shape(),
-            NoxprNode::Sqrt(expr) | NoxprNode::Neg(expr) => expr.shape(),
+            NoxprNode::Sqrt(expr)
+            | NoxprNode::Tanh(expr)
+            | NoxprNode::Exp(expr)
+            | NoxprNode::Abs(expr)
+            | NoxprNode::Cos(expr)
+            | NoxprNode::Sin(expr)
+            | NoxprNode::Neg(expr) => expr.shape(),
 
             NoxprNode::Concat(concat) => {
                 let shapes = concat
@@ -937,6 +1143,7 @@ impl Noxpr {
                     .collect();
                 Some(new_shape)
             }
+            NoxprNode::Reduce(r) => Some(r.reduced_shape(&r.expr.shape()?)),
             NoxprNode::Gather(gather) => {
                 let indices_shape = gather.indices.shape()?;
                 let output_rank = gather.offset_dims.len() + indices_shape.len() - 1;
@@ -963,7 +1170,10 @@ impl Noxpr {
                 )
             }
             NoxprNode::Iota(i) => Some(i.shape.shape.clone()),
+            NoxprNode::RngUniform(r) => Some(r.shape.shape.clone()),
             NoxprNode::DynamicUpdateSlice(d) => d.expr.shape(),
+            NoxprNode::Scatter(s) => s.expr.shape(),
+            NoxprNode::Select(s) => s.on_true.shape(),
             NoxprNode::GetTupleElement(g) => match g.expr.deref() {
                 NoxprNode::Tuple(elems) => elems.get(g.index)?.shape(),
                 NoxprNode::Param(p) => {
@@ -1006,6 +1216,17 @@ impl Noxpr {
         }))
     }
 
+    /// Elementwise picks `on_true` where `self` is true and `on_false` elsewhere.
+    ///
+    /// `self` must be a predicate with the same shape as both branches.
+    pub fn select(&self, on_true: Noxpr, on_false: Noxpr) -> Noxpr {
+        Noxpr::new(NoxprNode::Select(Select {
+            cond: self.clone(),
+            on_true,
+            on_false,
+        }))
+    }
+
     #[cfg(feature = "jax")]
     pub fn jax(py: pyo3::PyObject) -> Noxpr {
         Noxpr::new(NoxprNode::Jax(py))
@@ -1022,6 +1243,7 @@ impl Noxpr {
             NoxprNode::GetTupleElement(_) => "GetTupleElement",
             NoxprNode::Constant(_) => "Constant",
             NoxprNode::Iota(_) => todo!(),
+            NoxprNode::RngUniform(_) => "RngUniform",
             NoxprNode::Add(_) => "Add",
             NoxprNode::Sub(_) => "Sub",
             NoxprNode::Mul(_) => "Mul",
@@ -1031,20 +1253,32 @@ impl Noxpr {
             NoxprNode::GreaterOrEqual(_) => "GreaterOrEqual",
             NoxprNode::LessOrEqual(_) => "LessOrEqual",
             NoxprNode::Less(_) => "Less",
+            NoxprNode::Equal(_) => "Equal",
+            NoxprNode::Atan2(_) => "Atan2",
+            NoxprNode::Min(_) => "Min",
+            NoxprNode::Max(_) => "Max",
             NoxprNode::Dot(_) => "Dot",
             NoxprNode::DotGeneral(_) => "DotGeneral",
             NoxprNode::Sqrt(_) => "Sqrt",
             NoxprNode::Neg(_) => "Neg",
             NoxprNode::Log(_) => "Log",
+            NoxprNode::Tanh(_) => "Tanh",
+            NoxprNode::Exp(_) => "Exp",
+            NoxprNode::Abs(_) => "Abs",
+            NoxprNode::Cos(_) => "Cos",
+            NoxprNode::Sin(_) => "Sin",
             NoxprNode::Concat(_) => "Concat",
+            NoxprNode::Select(_) => "Select",
             NoxprNode::Reshape(_) => "Reshape",
             NoxprNode::Broadcast(_) => "Broadcast",
             NoxprNode::BroadcastInDim(_) => "BroadcastInDim",
             NoxprNode::Transpose(_) => "Transpose",
+            NoxprNode::Reduce(_) => "Reduce",
             NoxprNode::Gather(_) => "Gather",
             NoxprNode::Slice(_) => "Slice",
             NoxprNode::DynamicSlice(_) => "DynamicSlice",
             NoxprNode::DynamicUpdateSlice(_) => "DynamicUpdateSlice",
+            NoxprNode::Scatter(_) => "Scatter",
             NoxprNode::Scan(_) => "Scan",
             NoxprNode::Jax(_) => "Jax",
         }
@@ -1185,6 +1419,22 @@ impl XlaTracer {
                 let (lhs, rhs) = self.visit_binary_op(b)?;
                 lhs.lt(&rhs)
             }
+            NoxprNode::Equal(b) => {
+                let (lhs, rhs) = self.visit_binary_op(b)?;
+                lhs.eq(&rhs)
+            }
+            NoxprNode::Atan2(b) => {
+                let (lhs, rhs) = self.visit_binary_op(b)?;
+                lhs.atan2(&rhs)
+            }
+            NoxprNode::Min(b) => {
+                let (lhs, rhs) = self.visit_binary_op(b)?;
+                lhs.min(&rhs)
+            }
+            NoxprNode::Max(b) => {
+                let (lhs, rhs) = self.visit_binary_op(b)?;
+                lhs.max(&rhs)
+            }
             NoxprNode::Sqrt(expr) => {
                 let expr = self.visit(expr)?;
                 expr.sqrt()
@@ -1193,6 +1443,26 @@ impl XlaTracer {
                 let expr = self.visit(expr)?;
                 expr.log()
             }
+            NoxprNode::Tanh(expr) => {
+                let expr = self.visit(expr)?;
+                expr.tanh()
+            }
+            NoxprNode::Exp(expr) => {
+                let expr = self.visit(expr)?;
+                expr.exp()
+            }
+            NoxprNode::Abs(expr) => {
+                let expr = self.visit(expr)?;
+                expr.abs()
+            }
+            NoxprNode::Cos(expr) => {
+                let expr = self.visit(expr)?;
+                expr.cos()
+            }
+            NoxprNode::Sin(expr) => {
+                let expr = self.visit(expr)?;
+                expr.sin()
+            }
             NoxprNode::Neg(expr) => {
                 let expr = self.visit(expr)?;
                 expr.neg()
@@ -1236,6 +1506,12 @@ impl XlaTracer {
                 let op = self.visit(&g.expr)?;
                 op.get_tuple_element(g.index as i64)
             }
+            NoxprNode::Select(s) => {
+                let cond = self.visit(&s.cond)?;
+                let on_true = self.visit(&s.on_true)?;
+                let on_false = self.visit(&s.on_false)?;
+                cond.select(&on_true, &on_false)
+            }
             NoxprNode::Broadcast(b) => {
                 let op = self.visit(&b.expr)?;
                 op.broadcast(&b.sizes)
@@ -1248,6 +1524,24 @@ impl XlaTracer {
                 let op = self.visit(&t.expr)?;
                 op.transpose(&t.permutation)
             }
+            NoxprNode::Reduce(r) => {
+                let op = self.visit(&r.expr)?;
+                let element_type = r.expr.element_type().ok_or(Error::IncompatibleDType)?;
+                let scalar = NoxprTy::ArrayTy(ArrayTy {
+                    element_type,
+                    shape: smallvec![],
+                });
+                let lhs = Noxpr::parameter(0, scalar.clone(), "lhs".to_string());
+                let rhs = Noxpr::parameter(1, scalar, "rhs".to_string());
+                let (inner, init) = match r.op {
+                    ReduceOp::Sum => (lhs.clone() + rhs.clone(), op.zero_like()),
+                    ReduceOp::Max => (lhs.clone().max(rhs.clone()), op.min_value_like()),
+                };
+                let comp = NoxprFn::new(vec![lhs, rhs], inner)
+                    .build("reduce")?
+                    .build()?;
+                op.reduce(&init, &comp, &r.dims)
+            }
             NoxprNode::Gather(g) => {
                 let op = self.visit(&g.expr)?;
                 let indices = self.visit(&g.indices)?;
@@ -1264,6 +1558,29 @@ impl XlaTracer {
                 self.builder
                     .iota(&i.shape.shape, i.shape.element_type, i.dim as i64)
             }
+            NoxprNode::RngUniform(r) => {
+                let ty = r.shape.element_type;
+                let bits_shape =
+                    xla::ArrayShape::new_with_type(ElementType::U32, r.shape.shape.to_vec());
+                let bits = self
+                    .builder
+                    .constant_vector(&r.state)
+                    .rng_bit_generator(&bits_shape)
+                    .get_tuple_element(1);
+                // keep only as many bits as the mantissa holds, so the result can never round up to 1
+                let (bits, scale) = match ty {
+                    ElementType::F32 => {
+                        (bits.div(&self.builder.constant(1u32 << 8)), 2f64.powi(-24))
+                    }
+                    ElementType::F64 => (bits, 2f64.powi(-32)),
+                    _ => return Err(Error::IncompatibleDType),
+                };
+                let scale = self
+                    .builder
+                    .constant(scale)
+                    .convert_element_type(ty.primitive_type());
+                bits.convert_element_type(ty.primitive_type()).mul(&scale)
+            }
             NoxprNode::DynamicUpdateSlice(d) => {
                 let inner = self.visit(&d.expr)?;
                 let update = self.visit(&d.update)?;
@@ -1278,6 +1595,42 @@ impl XlaTracer {
                     .collect::<SmallVec<[XlaOpRef<'_>; 4]>>();
                 inner.dynamic_update_slice(&update, &start)
             }
+            NoxprNode::Scatter(s) => {
+                let op = self.visit(&s.expr)?;
+                let indices = self.visit(&s.indices)?;
+                let updates = self.visit(&s.updates)?;
+                let element_type = s.expr.element_type().ok_or(Error::IncompatibleDType)?;
+                let scalar = NoxprTy::ArrayTy(ArrayTy {
+                    element_type,
+                    shape: smallvec![],
+                });
+                // the update computation keeps the incoming value, replacing the old one
+                let old = Noxpr::parameter(0, scalar.clone(), "old".to_string());
+                let new = Noxpr::parameter(1, scalar, "new".to_string());
+                let comp = NoxprFn::new(vec![old, new.clone()], new)
+                    .build("scatter")?
+                    .build()?;
+                let mut dims = xla::ScatterDimensionNumbers::new();
+                for dim in &s.update_window_dims {
+                    dims.add_window_dim(*dim);
+                }
+                for dim in &s.inserted_window_dims {
+                    dims.add_inserted_window_dim(*dim);
+                }
+                for dim in &s.scatter_dims_to_operand_dims {
+                    dims.add_scatter_dims_to_operand_dims(*dim);
+                }
+                dims.set_index_vector_dim(s.index_vector_dim);
+                op.scatter(
+                    &[op.as_ref()],
+                    &indices,
+                    &[updates.as_ref()],
+                    &comp,
+                    &dims,
+                    false,
+                    false,
+                )
+            }
             NoxprNode::Jax(_) => {
                 unimplemented!()
             }
@@ -1398,6 +1751,30 @@ impl NoxprFn {
         tracer.visit(&self.inner)
     }
 
+    /// Lifts this function over a new leading dimension of length `batch` on every argument,
+    /// applying it independently to each slice along that dimension.
+    pub fn vmap(&self, batch: usize) -> Result<Self, Error> {
+        let args = self
+            .args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| {
+                let Some(NoxprTy::ArrayTy(ty)) = arg.ty() else {
+                    return Err(Error::UnbatchableArgument);
+                };
+                let shape = iter::once(batch as i64).chain(ty.shape).collect();
+                Ok(Noxpr::parameter(
+                    i as i64,
+                    NoxprTy::ArrayTy(ArrayTy::new(ty.element_type, shape)),
+                    format!("param_{}", i),
+                ))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let in_axis = vec![0; args.len()];
+        let inner = Noxpr::vmap_with_axis(self.clone(), &in_axis, &args)?;
+        Ok(NoxprFn::new(args, inner))
+    }
+
     pub fn collapse_params(&self, mut init_tuple: Vec<NoxprTy>) -> Result<Self, Error> {
         let init_offset = init_tuple.len();
         for a in self.args.iter() {
@@ -1446,6 +1823,27 @@ impl NoxprFn {
     }
 }
 
+impl NoxprFn {
+    /// Renders the structure of the function, including parameter types and constant values, so
+    /// that functions traced separately from the same code have equal keys.
+    pub fn structural_key(&self) -> String {
+        let printer = PrettyPrintTracer {
+            print_constant_data: true,
+            ..Default::default()
+        };
+        let mut key = String::new();
+        for arg in &self.args {
+            if let Some(ty) = arg.ty() {
+                ty.pretty_print(&mut key)
+                    .expect("writing to a string is infallible");
+            }
+        }
+        self.pretty_print(&printer, &mut key)
+            .expect("writing to a string is infallible");
+        key
+    }
+}
+
 impl std::fmt::Display for NoxprFn {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let printer = PrettyPrintTracer::default();
@@ -1480,6 +1878,7 @@ impl ReplacementTracer {
             }
             NoxprNode::Constant(c) => Noxpr::new(NoxprNode::Constant(c.clone())),
             NoxprNode::Iota(i) => Noxpr::new(NoxprNode::Iota(i.clone())),
+            NoxprNode::RngUniform(r) => Noxpr::new(NoxprNode::RngUniform(r.clone())),
             NoxprNode::Add(a) => Noxpr::new(NoxprNode::Add(self.visit_binary_op(a))),
             NoxprNode::Sub(s) => Noxpr::new(NoxprNode::Sub(self.visit_binary_op(s))),
             NoxprNode::Mul(x) => Noxpr::new(NoxprNode::Mul(self.visit_binary_op(x))),
@@ -1492,6 +1891,10 @@ impl ReplacementTracer {
                 Noxpr::new(NoxprNode::LessOrEqual(self.visit_binary_op(x)))
             }
             NoxprNode::Less(x) => Noxpr::new(NoxprNode::Less(self.visit_binary_op(x))),
+            NoxprNode::Equal(x) => Noxpr::new(NoxprNode::Equal(self.visit_binary_op(x))),
+            NoxprNode::Atan2(x) => Noxpr::new(NoxprNode::Atan2(self.visit_binary_op(x))),
+            NoxprNode::Min(x) => Noxpr::new(NoxprNode::Min(self.visit_binary_op(x))),
+            NoxprNode::Max(x) => Noxpr::new(NoxprNode::Max(self.visit_binary_op(x))),
             NoxprNode::Or(x) => Noxpr::new(NoxprNode::Or(self.visit_binary_op(x))),
             NoxprNode::Dot(x) => Noxpr::new(NoxprNode::Dot(self.visit_binary_op(x))),
             NoxprNode::DotGeneral(d) => Noxpr::new(NoxprNode::DotGeneral(DotGeneral {
@@ -1502,6 +1905,11 @@ impl ReplacementTracer {
             NoxprNode::Sqrt(s) => Noxpr::new(NoxprNode::Sqrt(self.visit(s))),
             NoxprNode::Neg(n) => Noxpr::new(NoxprNode::Neg(self.visit(n))),
             NoxprNode::Log(l) => Noxpr::new(NoxprNode::Log(self.visit(l))),
+            NoxprNode::Tanh(e) => Noxpr::new(NoxprNode::Tanh(self.visit(e))),
+            NoxprNode::Exp(e) => Noxpr::new(NoxprNode::Exp(self.visit(e))),
+            NoxprNode::Abs(e) => Noxpr::new(NoxprNode::Abs(self.visit(e))),
+            NoxprNode::Cos(e) => Noxpr::new(NoxprNode::Cos(self.visit(e))),
+            NoxprNode::Sin(e) => Noxpr::new(NoxprNode::Sin(self.visit(e))),
             NoxprNode::Concat(c) => Noxpr::new(NoxprNode::Concat(Concat {
                 nodes: c.nodes.iter().map(|n| self.visit(n)).collect(),
                 dimension: c.dimension,
@@ -1523,6 +1931,7 @@ impl ReplacementTracer {
                 expr: self.visit(&t.expr),
                 permutation: t.permutation.clone(),
             })),
+            NoxprNode::Reduce(r) => self.visit(&r.expr).reduce(r.op, r.dims.clone()),
             NoxprNode::Gather(g) => Noxpr::new(NoxprNode::Gather(Gather {
                 expr: self.visit(&g.expr),
                 indices: self.visit(&g.indices),
@@ -1543,6 +1952,11 @@ impl ReplacementTracer {
                 start_indices: d.start_indices.iter().map(|e| self.visit(e)).collect(),
                 size_indices: d.size_indices.clone(),
             })),
+            NoxprNode::Select(s) => Noxpr::new(NoxprNode::Select(Select {
+                cond: self.visit(&s.cond),
+                on_true: self.visit(&s.on_true),
+                on_false: self.visit(&s.on_false),
+            })),
             NoxprNode::DynamicUpdateSlice(d) => {
                 Noxpr::new(NoxprNode::DynamicUpdateSlice(DynamicUpdateSlice {
                     expr: self.visit(&d.expr),
@@ -1550,6 +1964,15 @@ impl ReplacementTracer {
                     update: self.visit(&d.update),
                 }))
             }
+            NoxprNode::Scatter(s) => Noxpr::new(NoxprNode::Scatter(Scatter {
+                expr: self.visit(&s.expr),
+                indices: self.visit(&s.indices),
+                updates: self.visit(&s.updates),
+                update_window_dims: s.update_window_dims.clone(),
+                inserted_window_dims: s.inserted_window_dims.clone(),
+                scatter_dims_to_operand_dims: s.scatter_dims_to_operand_dims.clone(),
+                index_vector_dim: s.index_vector_dim,
+            })),
             NoxprNode::Scan(s) => Noxpr::new(NoxprNode::Scan(Scan {
                 inputs: s.inputs.iter().map(|e| self.visit(e)).collect(),
                 initial_state: self.visit(&s.initial_state),
@@ -1747,9 +2170,18 @@ impl BatchTracer {
             NoxprNode::GreaterOrEqual(b) => self.visit_binary_op(b, Noxpr::greater_or_equal)?,
             NoxprNode::LessOrEqual(b) => self.visit_binary_op(b, Noxpr::less_or_equal)?,
             NoxprNode::Less(b) => self.visit_binary_op(b, Noxpr::less)?,
+            NoxprNode::Equal(b) => self.visit_binary_op(b, Noxpr::equal)?,
+            NoxprNode::Atan2(b) => self.visit_binary_op(b, Noxpr::atan2)?,
+            NoxprNode::Min(b) => self.visit_binary_op(b, Noxpr::min)?,
+            NoxprNode::Max(b) => self.visit_binary_op(b, Noxpr::max)?,
             NoxprNode::Sqrt(e) => self.visit_unary_op(e, Noxpr::sqrt)?,
             NoxprNode::Neg(e) => self.visit_unary_op(e, Noxpr::neg)?,
             NoxprNode::Log(e) => self.visit_unary_op(e, Noxpr::log)?,
+            NoxprNode::Tanh(e) => self.visit_unary_op(e, Noxpr::tanh)?,
+            NoxprNode::Exp(e) => self.visit_unary_op(e, Noxpr::exp)?,
+            NoxprNode::Abs(e) => self.visit_unary_op(e, Noxpr::abs)?,
+            NoxprNode::Cos(e) => self.visit_unary_op(e, Noxpr::cos)?,
+            NoxprNode::Sin(e) => self.visit_unary_op(e, Noxpr::sin)?,
             NoxprNode::Concat(c) => {
                 let nodes = c
                     .nodes
@@ -1777,6 +2209,39 @@ impl BatchTracer {
                     batch_axis: BatchAxis::Mapped { index: 0, size },
                 }
             }
+            NoxprNode::Select(s) => {
+                let args = [
+                    self.visit(&s.cond)?,
+                    self.visit(&s.on_true)?,
+                    self.visit(&s.on_false)?,
+                ];
+                let size = args.iter().find_map(|a| match a.batch_axis {
+                    BatchAxis::NotMapped => None,
+                    BatchAxis::Mapped { size, .. } => Some(size),
+                });
+                match size {
+                    None => {
+                        let [cond, on_true, on_false] = args;
+                        BatchedExpr {
+                            inner: cond.inner.select(on_true.inner, on_false.inner),
+                            batch_axis: BatchAxis::NotMapped,
+                        }
+                        .move_batch_axis(self.out_axis.clone())
+                        .ok_or(Error::UnbatchableArgument)?
+                    }
+                    Some(size) => {
+                        let batch_axis = BatchAxis::Mapped { index: 0, size };
+                        let [cond, on_true, on_false] = args.map(|a| {
+                            a.move_batch_axis(batch_axis.clone())
+                                .ok_or(Error::UnbatchableArgument)
+                        });
+                        BatchedExpr {
+                            inner: cond?.inner.select(on_true?.inner, on_false?.inner),
+                            batch_axis,
+                        }
+                    }
+                }
+            }
             NoxprNode::DotGeneral(d) => {
                 self.visit_dot_general(&d.lhs, &d.rhs, d.dimensions.clone())?
             }
@@ -1890,6 +2355,28 @@ impl BatchTracer {
                     }
                 }
             }
+            NoxprNode::Reduce(r) => {
+                let expr = self.visit(&r.expr)?;
+                match expr.batch_axis {
+                    BatchAxis::NotMapped => BatchedExpr {
+                        inner: expr.inner.reduce(r.op, r.dims.clone()),
+                        batch_axis: BatchAxis::NotMapped,
+                    }
+                    .move_batch_axis(self.out_axis.clone())
+                    .ok_or(Error::UnbatchableArgument)?,
+                    BatchAxis::Mapped { size, .. } => {
+                        let batch_axis = BatchAxis::Mapped { index: 0, size };
+                        let expr = expr
+                            .move_batch_axis(batch_axis.clone())
+                            .ok_or(Error::UnbatchableArgument)?;
+                        let dims = r.dims.iter().map(|dim| dim + 1).collect();
+                        BatchedExpr {
+                            inner: expr.inner.reduce(r.op, dims),
+                            batch_axis,
+                        }
+                    }
+                }
+            }
             NoxprNode::Gather(g) => {
                 let expr = self.visit(&g.expr)?;
                 let indices = self.visit(&g.indices)?;
@@ -2042,10 +2529,17 @@ impl BatchTracer {
             }
             .move_batch_axis(self.out_axis.clone())
             .ok_or(Error::UnbatchableArgument)?,
+            NoxprNode::RngUniform(r) => BatchedExpr {
+                inner: Noxpr::new(NoxprNode::RngUniform(r.clone())),
+                batch_axis: BatchAxis::NotMapped,
+            }
+            .move_batch_axis(self.out_axis.clone())
+            .ok_or(Error::UnbatchableArgument)?,
             NoxprNode::DynamicUpdateSlice(_) => {
                 // TODO: dynamic update slice is a special case of scatter, add this when we add scatter
                 todo!()
             }
+            NoxprNode::Scatter(_) => return Err(Error::UnbatchableArgument),
             NoxprNode::Jax(_) => {
                 unimplemented!()
             }
@@ -2390,6 +2884,7 @@ impl<T: NativeType + ArrayElement> NoxprScalarExt for T {
 #[derive(Clone, Debug, Default)]
 pub struct PrettyPrintTracer {
     printed: HashMap<NoxprId, usize>,
+    print_constant_data: bool,
 }
 
 impl PrettyPrintTracer {
@@ -2450,6 +2945,9 @@ impl PrettyPrintTracer {
                 let num = self.print_var(id, writer)?;
                 write!(writer, "constant(")?;
                 c.ty.pretty_print(writer)?;
+                if self.print_constant_data {
+                    write!(writer, ", data = {:?}", c.data.raw_buf())?;
+                }
                 write!(writer, ")")?;
                 Ok(num)
             }
@@ -2460,6 +2958,13 @@ impl PrettyPrintTracer {
                 write!(writer, ")")?;
                 Ok(num)
             }
+            NoxprNode::RngUniform(r) => {
+                let num = self.print_var(id, writer)?;
+                write!(writer, "rng_uniform({:?}, ", r.state)?;
+                r.shape.pretty_print(writer)?;
+                write!(writer, ")")?;
+                Ok(num)
+            }
             NoxprNode::Add(a) => self.visit_binary_op(id, a, "+", writer),
             NoxprNode::Sub(s) => self.visit_binary_op(id, s, "-", writer),
             NoxprNode::Mul(m) => self.visit_binary_op(id, m, "*", writer),
@@ -2469,6 +2974,10 @@ impl PrettyPrintTracer {
             NoxprNode::GreaterOrEqual(g) => self.visit_binary_op(id, g, ">=", writer),
             NoxprNode::LessOrEqual(le) => self.visit_binary_op(id, le, "<=", writer),
             NoxprNode::Less(l) => self.visit_binary_op(id, l, "<", writer),
+            NoxprNode::Equal(e) => self.visit_binary_op(id, e, "==", writer),
+            NoxprNode::Atan2(b) => self.visit_binary_op(id, b, "atan2", writer),
+            NoxprNode::Min(b) => self.visit_binary_op(id, b, "min", writer),
+            NoxprNode::Max(b) => self.visit_binary_op(id, b, "max", writer),
             NoxprNode::Dot(d) => self.visit_binary_op(id, d, ".", writer),
             NoxprNode::DotGeneral(d) => {
                 let lhs = self.visit(&d.lhs, writer)?;
@@ -2499,6 +3008,36 @@ impl PrettyPrintTracer {
                 write!(writer, "log(var_{})", arg)?;
                 Ok(num)
             }
+            NoxprNode::Tanh(e) => {
+                let arg = self.visit(e, writer)?;
+                let num = self.print_var(id, writer)?;
+                write!(writer, "tanh(var_{})", arg)?;
+                Ok(num)
+            }
+            NoxprNode::Exp(e) => {
+                let arg = self.visit(e, writer)?;
+                let num = self.print_var(id, writer)?;
+                write!(writer, "exp(var_{})", arg)?;
+                Ok(num)
+            }
+            NoxprNode::Abs(e) => {
+                let arg = self.visit(e, writer)?;
+                let num = self.print_var(id, writer)?;
+                write!(writer, "abs(var_{})", arg)?;
+                Ok(num)
+            }
+            NoxprNode::Cos(e) => {
+                let arg = self.visit(e, writer)?;
+                let num = self.print_var(id, writer)?;
+                write!(writer, "cos(var_{})", arg)?;
+                Ok(num)
+            }
+            NoxprNode::Sin(e) => {
+                let arg = self.visit(e, writer)?;
+                let num = self.print_var(id, writer)?;
+                write!(writer, "sin(var_{})", arg)?;
+                Ok(num)
+            }
             NoxprNode::Concat(c) => {
                 let nums: Vec<_> = c
                     .nodes
@@ -2513,6 +3052,18 @@ impl PrettyPrintTracer {
                 write!(writer, "dim = {})", c.dimension)?;
                 Ok(num)
             }
+            NoxprNode::Select(s) => {
+                let cond = self.visit(&s.cond, writer)?;
+                let on_true = self.visit(&s.on_true, writer)?;
+                let on_false = self.visit(&s.on_false, writer)?;
+                let num = self.print_var(id, writer)?;
+                write!(
+                    writer,
+                    "select(var_{}, var_{}, var_{})",
+                    cond, on_true, on_false
+                )?;
+                Ok(num)
+            }
             NoxprNode::Reshape(r) => {
                 let arg = self.visit(&r.expr, writer)?;
                 let num = self.print_var(id, writer)?;
@@ -2541,6 +3092,16 @@ impl PrettyPrintTracer {
                 write!(writer, "transpose(var_{}, {:?})", arg, t.permutation)?;
                 Ok(num)
             }
+            NoxprNode::Reduce(r) => {
+                let arg = self.visit(&r.expr, writer)?;
+                let num = self.print_var(id, writer)?;
+                let op = match r.op {
+                    ReduceOp::Sum => "sum",
+                    ReduceOp::Max => "max",
+                };
+                write!(writer, "reduce_{}(var_{}, {:?})", op, arg, r.dims)?;
+                Ok(num)
+            }
             NoxprNode::Gather(g) => {
                 let expr = self.visit(&g.expr, writer)?;
                 let indices = self.visit(&g.indices, writer)?;
@@ -2601,6 +3162,18 @@ impl PrettyPrintTracer {
                 write!(writer, "])")?;
                 Ok(num)
             }
+            NoxprNode::Scatter(s) => {
+                let expr = self.visit(&s.expr, writer)?;
+                let indices = self.visit(&s.indices, writer)?;
+                let updates = self.visit(&s.updates, writer)?;
+                let num = self.print_var(id, writer)?;
+                write!(
+                    writer,
+                    "scatter(expr = var_{}, indices = var_{}, updates = var_{}, update_window_dims = {:?}, inserted_window_dims = {:?}, scatter_dims_to_operand_dims = {:?}, index_vector_dim = {})",
+                    expr, indices, updates, s.update_window_dims, s.inserted_window_dims, s.scatter_dims_to_operand_dims, s.index_vector_dim
+                )?;
+                Ok(num)
+            }
             NoxprNode::Scan(s) => {
                 let inputs = s
                     .inputs
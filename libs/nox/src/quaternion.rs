@@ -5,8 +5,9 @@ use num_traits::Zero;
 use xla::{ArrayElement, NativeType};
 
 use crate::{
-    AsBuffer, Buffer, BufferArg, BufferForm, Builder, Client, Field, FixedSliceExt, FromBuilder,
-    FromHost, FromOp, FromPjrtBuffer, IntoOp, MaybeOwned, Noxpr, Op, Param, ToHost, Vector,
+    AsBuffer, Buffer, BufferArg, BufferForm, Builder, Client, ConstantExt, Field, FixedSliceExt,
+    FromBuilder, FromHost, FromOp, FromPjrtBuffer, IntoOp, MaybeOwned, Noxpr, Op, Param, Scalar,
+    SpatialScalar, ToHost, Vector,
 };
 
 pub struct Quaternion<T, P: Param = Op>(pub Vector<T, 4, P>);
@@ -38,12 +39,47 @@ impl<T: Field> Quaternion<T> {
     }
 }
 
+/// Euler angle conversions, using the ZYX order: the rotation is yaw about z, then pitch about
+/// the new y axis, then roll about the new x axis, so `q = yaw * pitch * roll`.
+impl<T: Field> Quaternion<T> {
+    pub fn from_euler(roll: Scalar<T>, pitch: Scalar<T>, yaw: Scalar<T>) -> Self {
+        let two = T::one() + T::one();
+        let half = |angle: Scalar<T>| {
+            let angle = (angle / two.clone()).reshape::<Const<1>>();
+            (angle.sin(), angle.cos())
+        };
+        let (sr, cr) = half(roll);
+        let (sp, cp) = half(pitch);
+        let (sy, cy) = half(yaw);
+        let i = &sr * &cp * &cy - &cr * &sp * &sy;
+        let j = &cr * &sp * &cy + &sr * &cp * &sy;
+        let k = &cr * &cp * &sy - &sr * &sp * &cy;
+        let w = &cr * &cp * &cy + &sr * &sp * &sy;
+        Quaternion(Vector::from_arr([i, j, k, w]))
+    }
+
+    /// Returns `[roll, pitch, yaw]`, with pitch clamped into `[-pi/2, pi/2]`. The roll and yaw
+    /// are only determined up to their sum at the gimbal lock where pitch is +/- pi/2.
+    pub fn to_euler(&self) -> Vector<T, 3> {
+        let [i, j, k, w] = self.parts();
+        let one: Vector<T, 1> = T::one().reshape();
+        let two = &one + &one;
+        let roll = (&two * (&w * &i + &j * &k)).atan2(&(&one - &two * (&i * &i + &j * &j)));
+        let sin_pitch = (&two * (&w * &j - &k * &i)).min(&one).max(&-one.clone());
+        let cos_pitch = (&one - &sin_pitch * &sin_pitch).sqrt();
+        let pitch = sin_pitch.atan2(&cos_pitch);
+        let yaw = (&two * (&w * &k + &i * &j)).atan2(&(&one - &two * (&j * &j + &k * &k)));
+        Vector::from_arr([roll, pitch, yaw])
+    }
+}
+
 impl<T: Field> Quaternion<T> {
     fn parts(&self) -> [Vector<T, 1>; 4] {
         let Quaternion(v) = self;
         v.parts()
     }
 
+    /// Negates the imaginary part, which for a unit quaternion is the inverse rotation.
     pub fn conjugate(&self) -> Self {
         let [i, j, k, w] = self.parts();
         Quaternion(Vector::from_arr([-i, -j, -k, w]))
@@ -51,16 +87,60 @@ impl<T: Field> Quaternion<T> {
 }
 
 impl<T: Field> Quaternion<T> {
+    /// Returns the conjugate over the squared norm, so `q * q.inverse()` is the identity.
     pub fn inverse(&self) -> Self {
         // TODO: Check for division by zero
         Quaternion(self.conjugate().0 / self.0.norm_squared())
     }
 
+    /// Rotates `v` by this unit quaternion, computing `q * v * q^-1` as
+    /// `v + 2 r x (r x v + w v)` rather than through two full quaternion products.
+    pub fn rotate(&self, v: &Vector<T, 3>) -> Vector<T, 3> {
+        let r: Vector<T, 3> = self.0.fixed_slice([0]);
+        let w = self.0.get(3);
+        let t = r.cross(v) + v.clone() * w;
+        let u = r.cross(&t);
+        v.clone() + u.clone() + u
+    }
+
+    /// Scales the quaternion back to unit norm. Integrating orientation lets the norm drift away
+    /// from one, so this should be applied after every integration step.
     pub fn normalize(&self) -> Self {
         Quaternion(self.0.clone() / self.0.norm())
     }
 }
 
+impl<T: SpatialScalar> Quaternion<T> {
+    /// Interpolates from `self` at `t = 0` to `other` at `t = 1` at a constant angular rate,
+    /// taking the shorter of the two arcs between them. The slerp weights divide by the sine
+    /// of the angle between the inputs, so nearly parallel inputs use a normalized lerp instead.
+    pub fn slerp(&self, other: &Quaternion<T>, t: Scalar<T>) -> Self {
+        let zero = <T as Field>::zero();
+        let one = <T as Field>::one();
+        // `q` and `-q` are the same rotation, so flip `other` into the hemisphere of `self`
+        let other = self
+            .0
+            .dot(&other.0)
+            .select_less(&zero, -other.0.clone(), other.0.clone());
+        let cos_theta = self.0.dot(&other);
+        let threshold = nalgebra::convert::<f64, T>(0.9995).constant();
+        // both sides of a select are computed, so the slerp weights must stay finite (and
+        // differentiable) for parallel inputs too
+        let sin_squared = cos_theta.select_less(
+            &threshold,
+            (&one - &cos_theta * &cos_theta).max(&zero),
+            one.clone(),
+        );
+        let sin_theta = sin_squared.sqrt();
+        let theta = sin_theta.atan2(&cos_theta);
+        let slerp = (self.0.clone() * ((&one - &t) * &theta).sin()
+            + other.clone() * (&t * &theta).sin())
+            / sin_theta;
+        let nlerp = Quaternion(self.0.clone() * (&one - &t) + other * t).normalize();
+        Quaternion(cos_theta.select_less(&threshold, slerp, nlerp.0))
+    }
+}
+
 impl<T: Field> Mul for Quaternion<T> {
     type Output = Self;
 
@@ -179,6 +259,7 @@ where
 mod tests {
     use crate::Client;
     use crate::CompFn;
+    use crate::ConstantExt;
     use nalgebra::vector;
     use nalgebra::{UnitQuaternion, Vector3};
 
@@ -227,6 +308,123 @@ mod tests {
         assert_eq!(out, correct_out)
     }
 
+    #[test]
+    fn test_quat_rotate() {
+        let client = Client::cpu().unwrap();
+        let comp = (|a: Quaternion<f64>, b: Vector<f64, 3>| -> Vector<f64, 3> { a.rotate(&b) })
+            .build()
+            .unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let q = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 90f64.to_radians());
+        let out = exec
+            .run(&client, q.into_inner(), vector![1.0, 0.0, 0.0])
+            .unwrap()
+            .to_host();
+        approx::assert_relative_eq!(out, vector![0.0, 1.0, 0.0], epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_quat_euler_round_trip() {
+        let (roll, pitch, yaw) = (0.3, -0.7, 1.2);
+        let client = Client::cpu().unwrap();
+        let comp = (move || -> Quaternion<f64> {
+            Quaternion::from_euler(roll.constant(), pitch.constant(), yaw.constant())
+        })
+        .build()
+        .unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec.run(&client).unwrap().to_host();
+        let expected = UnitQuaternion::from_euler_angles(roll, pitch, yaw).into_inner();
+        approx::assert_relative_eq!(out, expected, epsilon = 1e-12);
+
+        let comp = (move || -> Vector<f64, 3> {
+            Quaternion::from_euler(roll.constant(), pitch.constant(), yaw.constant()).to_euler()
+        })
+        .build()
+        .unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(out, vector![roll, pitch, yaw], epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_quat_mul_inverse_identity() {
+        let client = Client::cpu().unwrap();
+        let comp = (|a: Quaternion<f64>| -> Quaternion<f64> { a.clone() * a.inverse() })
+            .build()
+            .unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let axis = nalgebra::Unit::new_normalize(Vector3::new(-2.0, 0.5, 1.0));
+        let q = UnitQuaternion::from_axis_angle(&axis, 2.3).into_inner();
+        let out = exec.run(&client, q).unwrap().to_host();
+        approx::assert_relative_eq!(out, nalgebra::Quaternion::identity(), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_quat_normalize() {
+        let client = Client::cpu().unwrap();
+        let comp = (|a: Quaternion<f64>| -> Quaternion<f64> { a.normalize() })
+            .build()
+            .unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let drifted = nalgebra::Quaternion::new(4.0, 1.0, 2.0, 3.0);
+        let out = exec.run(&client, drifted).unwrap().to_host();
+        approx::assert_relative_eq!(out.norm(), 1.0, epsilon = 1e-12);
+        approx::assert_relative_eq!(out, drifted.normalize(), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_quat_slerp() {
+        let client = Client::cpu().unwrap();
+        let comp = (|a: Quaternion<f64>, b: Quaternion<f64>, t: Scalar<f64>| -> Quaternion<f64> {
+            a.slerp(&b, t)
+        })
+        .build()
+        .unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let identity = nalgebra::Quaternion::identity();
+        let quarter_turn =
+            UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 90f64.to_radians()).into_inner();
+        let expected =
+            UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 45f64.to_radians()).into_inner();
+        let out = exec
+            .run(&client, identity, quarter_turn, 0.5)
+            .unwrap()
+            .to_host();
+        approx::assert_relative_eq!(out, expected, epsilon = 1e-12);
+
+        // the negated quaternion is the same rotation, so the result must not take the long way
+        let out = exec
+            .run(&client, identity, -quarter_turn, 0.5)
+            .unwrap()
+            .to_host();
+        approx::assert_relative_eq!(out, expected, epsilon = 1e-12);
+
+        let out = exec
+            .run(&client, expected, expected, 0.3)
+            .unwrap()
+            .to_host();
+        approx::assert_relative_eq!(out, expected, epsilon = 1e-12);
+
+        // interpolating between equal quaternions goes nowhere, so the gradient must be zero
+        // rather than NaN from the unused slerp branch
+        let grad = (|a: Quaternion<f64>, b: Quaternion<f64>, t: Scalar<f64>| -> Scalar<f64> {
+            a.slerp(&b, t).0.dot(&a.0)
+        })
+        .grad(2)
+        .unwrap();
+        type Args = (Quaternion<f64>, Quaternion<f64>, Scalar<f64>);
+        let exec = crate::Comp::<Args, Scalar<f64>>::from_expr(&grad, "grad")
+            .unwrap()
+            .compile(&client)
+            .unwrap();
+        let out = exec
+            .run(&client, expected, expected, 0.3)
+            .unwrap()
+            .to_host();
+        approx::assert_relative_eq!(out, 0.0, epsilon = 1e-12);
+    }
+
     #[test]
     fn test_quat_vec_mult() {
         let client = Client::cpu().unwrap();
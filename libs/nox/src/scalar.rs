@@ -1,8 +1,10 @@
 use crate::{
-    Buffer, BufferArg, Literal, MaybeOwned, NoxprScalarExt, Op, ScalarDim, Tensor, ToHost,
+    Buffer, BufferArg, Literal, MaybeOwned, NoxprScalarExt, Op, ScalarDim, Tensor, TensorDim,
+    ToHost, XlaDim,
 };
 use nalgebra::ClosedAdd;
 use nalgebra::Scalar as NalgebraScalar;
+use smallvec::SmallVec;
 
 use std::{marker::PhantomData, ops::Add};
 use xla::{ArrayElement, NativeType};
@@ -30,6 +32,27 @@ impl<T: ClosedAdd + ArrayElement + NativeType> Add<T> for Scalar<T, Op> {
     }
 }
 
+impl<T> Scalar<T, Op> {
+    /// Returns `on_true` if `self < rhs` and `on_false` otherwise, like [`Tensor::select`] with
+    /// the comparison broadcast over the shape of the branches.
+    pub fn select_less<U, D>(
+        &self,
+        rhs: &Self,
+        on_true: Tensor<U, D>,
+        on_false: Tensor<U, D>,
+    ) -> Tensor<U, D>
+    where
+        D: TensorDim + XlaDim,
+    {
+        let mask = Tensor::from_op(
+            self.less(rhs)
+                .inner
+                .broadcast(SmallVec::from_slice(D::dims().as_ref())),
+        );
+        Tensor::select(&mask, on_true, on_false)
+    }
+}
+
 pub trait ScalarExt: Sized {
     fn literal(self) -> Scalar<Self, Literal>;
     fn constant(self) -> Scalar<Self, Op>;
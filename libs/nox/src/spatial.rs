@@ -1,15 +1,47 @@
+use crate::ConstantExt;
+use crate::Error;
 use crate::Field;
 use crate::FixedSliceExt;
 use crate::Tensor;
 use crate::TensorItem;
+use crate::{Noxpr, NoxprNode};
 use crate::{Quaternion, Scalar, Vector};
 use nalgebra::Const;
 use nox_ecs_macros::{FromBuilder, FromOp, IntoOp};
-use std::ops::Div;
-use std::ops::{Add, Mul};
+use std::ops::{Add, Mul, Neg, Sub};
+use std::ops::{Deref, Div};
 use xla::ArrayElement;
 use xla::NativeType;
 
+/// Slices spatial types' backing vectors at a fixed `OFFSET`, so that a wrong offset in an
+/// accessor fails to compile instead of lowering an invalid slice.
+struct Offset<const OFFSET: usize>;
+
+impl<const OFFSET: usize> Offset<OFFSET> {
+    fn slice<T, const N: usize, const M: usize>(inner: &Vector<T, N>) -> Vector<T, M> {
+        let () = SliceBounds::<OFFSET, N, M>::VALID;
+        inner.fixed_slice([OFFSET])
+    }
+}
+
+struct SliceBounds<const OFFSET: usize, const N: usize, const M: usize>;
+
+impl<const OFFSET: usize, const N: usize, const M: usize> SliceBounds<OFFSET, N, M> {
+    const VALID: () = assert!(OFFSET + M <= N, "spatial slice is out of bounds");
+}
+
+/// The float types spatial math can be computed in, so that simulation code written once
+/// against this bound can run at either `f32` or `f64` precision.
+pub trait SpatialScalar:
+    TensorItem + Field + NativeType + ArrayElement + nalgebra::RealField
+{
+}
+
+impl<T> SpatialScalar for T where
+    T: TensorItem + Field + NativeType + ArrayElement + nalgebra::RealField
+{
+}
+
 #[derive(FromBuilder, IntoOp, Clone, Debug, FromOp)]
 pub struct SpatialTransform<T> {
     pub inner: Vector<T, 7>,
@@ -33,11 +65,11 @@ impl<T: TensorItem + Field> SpatialTransform<T> {
     }
 
     pub fn angular(&self) -> Quaternion<T> {
-        Quaternion(self.inner.fixed_slice([0]))
+        Quaternion(Offset::<0>::slice(&self.inner))
     }
 
     pub fn linear(&self) -> Vector<T, 3> {
-        self.inner.fixed_slice([4])
+        Offset::<4>::slice(&self.inner)
     }
 
     pub fn zero() -> Self {
@@ -45,6 +77,125 @@ impl<T: TensorItem + Field> SpatialTransform<T> {
             inner: Tensor::zeros(),
         }
     }
+
+    /// The transform with no rotation or translation, which leaves others unchanged under `Mul`.
+    pub fn identity() -> Self {
+        let zero = T::zero().broadcast::<Const<3>>();
+        let inner = zero.concat(T::one().reshape::<Const<1>>()).concat(zero);
+        SpatialTransform { inner }
+    }
+
+    /// Returns the 6x6 matrix mapping a motion into the frame this transform maps into,
+    /// `[[R, 0], [-[p]x R, R]]`, following the same convention as [`SpatialMotion::offset`].
+    pub fn adjoint(&self) -> Tensor<T, (Const<6>, Const<6>)> {
+        let p = self.linear();
+        let columns = basis::<T>().map(|e| self.angular() * e);
+        let zero = T::zero().broadcast::<Const<3>>();
+        let columns = [0, 1, 2, 3, 4, 5].map(|col| {
+            let column = if col < 3 {
+                let r = columns[col].clone();
+                let shift = -p.cross(&r);
+                r.concat(shift)
+            } else {
+                zero.clone().concat(columns[col - 3].clone())
+            };
+            column.parts()
+        });
+        let entries: [Vector<T, 1>; 36] = std::array::from_fn(|i| columns[i % 6][i / 6].clone());
+        Vector::from_arr(entries).reshape()
+    }
+
+    /// Re-expresses a motion in the frame this transform maps into, by applying [`Self::adjoint`].
+    pub fn transform_motion(&self, m: &SpatialMotion<T>) -> SpatialMotion<T> {
+        SpatialMotion {
+            inner: Tensor::from_op(self.adjoint().inner.dot(&m.inner)),
+        }
+    }
+
+    /// Returns the transform that undoes this one, such that `t * t.inverse()` is the identity.
+    /// The angular part is assumed to be a unit quaternion.
+    pub fn inverse(&self) -> Self {
+        let angular = self.angular().conjugate();
+        let linear = -(angular.clone() * self.linear());
+        SpatialTransform::new(angular, linear)
+    }
+}
+
+impl<T: TensorItem + Field + NativeType + ArrayElement> SpatialTransform<T> {
+    /// Re-expresses a force in the frame this transform maps into, using the dual of
+    /// [`Self::transform_motion`] so that the power of a motion and force pair is unchanged.
+    pub fn transform_force(&self, f: &SpatialForce<T>) -> SpatialForce<T> {
+        let force = self.angular() * f.force();
+        let torque = self.angular() * f.torque() - self.linear().cross(&force);
+        SpatialForce::from_vectors(torque, force)
+    }
+}
+
+impl<T: SpatialScalar> SpatialTransform<T> {
+    /// The SE(3) logarithm, returning the twist whose [`SpatialMotion::exp`] over a unit time
+    /// step reproduces this transform. Only valid for rotations of less than half a turn.
+    pub fn log(&self) -> SpatialMotion<T> {
+        let q = self.angular().0;
+        let qv: Vector<T, 3> = q.fixed_slice([0]);
+        let w = q.get(3);
+        // offsetting the norm keeps the divisions finite at the identity, where `qv` is zero
+        let qv_norm = (qv.norm_squared() + tiny::<T>()).sqrt();
+        let half_theta = qv_norm.atan2(&w);
+        let theta = half_theta.clone() + half_theta.clone();
+        let phi = qv * (theta.clone() / qv_norm);
+
+        let t = self.linear();
+        let phi_t = phi.cross(&t);
+        let cot = half_theta.cos() / half_theta.sin();
+        let c = (<T as Field>::one() - half_theta * cot) / (theta.clone() * theta);
+        let rho = t - phi_t.clone() * half::<T>() + phi.cross(&phi_t) * c;
+        SpatialMotion::new(phi, rho)
+    }
+
+    /// Converts the host value of a transform, as returned by `to_host`, back into an isometry.
+    /// The quaternion is renormalized to absorb any round-off error.
+    pub fn isometry_from_host(inner: &nalgebra::SVector<T, 7>) -> nalgebra::Isometry3<T> {
+        let rotation = nalgebra::Quaternion::new(inner[3], inner[0], inner[1], inner[2]);
+        let translation = nalgebra::Translation3::new(inner[4], inner[5], inner[6]);
+        nalgebra::Isometry3::from_parts(
+            translation,
+            nalgebra::UnitQuaternion::from_quaternion(rotation),
+        )
+    }
+}
+
+impl<T: SpatialScalar> From<nalgebra::Isometry3<T>> for SpatialTransform<T> {
+    fn from(isometry: nalgebra::Isometry3<T>) -> Self {
+        SpatialTransform::new(isometry.rotation.into_inner(), isometry.translation.vector)
+    }
+}
+
+impl SpatialTransform<f64> {
+    /// Samples a transform with a uniformly random rotation, and a translation drawn uniformly
+    /// from `[-translation_bound, translation_bound]` along each axis.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `translation_bound` is negative or not finite.
+    pub fn random(rng: &mut impl rand::Rng, translation_bound: f64) -> Self {
+        assert!(
+            translation_bound.is_finite() && translation_bound >= 0.0,
+            "random needs a finite, non-negative translation bound, found {translation_bound}"
+        );
+        // Shoemake's method, which maps three uniform samples to a uniform unit quaternion
+        let (u1, u2, u3): (f64, f64, f64) = rng.gen();
+        let (a, b) = ((1.0 - u1).sqrt(), u1.sqrt());
+        let (theta_a, theta_b) = (std::f64::consts::TAU * u2, std::f64::consts::TAU * u3);
+        let angular = nalgebra::Quaternion::new(
+            b * theta_b.cos(),
+            a * theta_a.sin(),
+            a * theta_a.cos(),
+            b * theta_b.sin(),
+        );
+        let mut coord = || rng.gen_range(-translation_bound..=translation_bound);
+        let linear = nalgebra::Vector3::new(coord(), coord(), coord());
+        SpatialTransform::new(angular, linear)
+    }
 }
 
 impl<T: TensorItem + ArrayElement + NativeType + Field> Mul for SpatialTransform<T> {
@@ -70,6 +221,20 @@ impl<T: TensorItem + Field + NativeType + ArrayElement> SpatialForce<T> {
         SpatialForce { inner }
     }
 
+    pub fn from_vectors(torque: Vector<T, 3>, force: Vector<T, 3>) -> Self {
+        SpatialForce {
+            inner: torque.concat(force),
+        }
+    }
+
+    /// Builds a force from dynamically sized tensors, checking that each has exactly 3 elements.
+    pub fn try_new(
+        torque: Tensor<T, nalgebra::Dyn>,
+        force: Tensor<T, nalgebra::Dyn>,
+    ) -> Result<Self, Error> {
+        Ok(Self::from_vectors(vector3(torque)?, vector3(force)?))
+    }
+
     pub fn from_linear(force: impl Into<Vector<T, 3>>) -> Self {
         let force = force.into();
         let zero = T::zero().broadcast::<Const<3>>();
@@ -84,12 +249,20 @@ impl<T: TensorItem + Field + NativeType + ArrayElement> SpatialForce<T> {
         SpatialForce { inner }
     }
 
+    /// The wrench of `force` applied at `point`, an offset from the origin of the force's frame,
+    /// which adds the torque `point × force`.
+    pub fn at_point(force: impl Into<Vector<T, 3>>, point: impl Into<Vector<T, 3>>) -> Self {
+        let force = force.into();
+        let torque = point.into().cross(&force);
+        SpatialForce::from_vectors(torque, force)
+    }
+
     pub fn torque(&self) -> Vector<T, 3> {
-        self.inner.fixed_slice([0])
+        Offset::<0>::slice(&self.inner)
     }
 
     pub fn force(&self) -> Vector<T, 3> {
-        self.inner.fixed_slice([3])
+        Offset::<3>::slice(&self.inner)
     }
 
     pub fn zero() -> Self {
@@ -109,6 +282,36 @@ impl<T: Field> Add for SpatialForce<T> {
     }
 }
 
+impl<T: Field> Sub for SpatialForce<T> {
+    type Output = SpatialForce<T>;
+
+    fn sub(self, rhs: SpatialForce<T>) -> Self::Output {
+        SpatialForce {
+            inner: self.inner - rhs.inner,
+        }
+    }
+}
+
+impl<T: Field> Mul<Scalar<T>> for SpatialForce<T> {
+    type Output = SpatialForce<T>;
+
+    fn mul(self, rhs: Scalar<T>) -> Self::Output {
+        SpatialForce {
+            inner: self.inner * rhs,
+        }
+    }
+}
+
+impl<T: Field> Div<Scalar<T>> for SpatialForce<T> {
+    type Output = SpatialForce<T>;
+
+    fn div(self, rhs: Scalar<T>) -> Self::Output {
+        SpatialForce {
+            inner: self.inner / rhs,
+        }
+    }
+}
+
 #[derive(FromBuilder, IntoOp, Clone, Debug, FromOp)]
 pub struct SpatialInertia<T> {
     pub inner: Vector<T, 7>,
@@ -137,14 +340,89 @@ impl<T: TensorItem + Field + NativeType + ArrayElement> SpatialInertia<T> {
     }
 
     pub fn inertia_diag(&self) -> Vector<T, 3> {
-        self.inner.fixed_slice([0])
+        Offset::<0>::slice(&self.inner)
     }
     pub fn momentum(&self) -> Vector<T, 3> {
-        self.inner.fixed_slice([3])
+        Offset::<3>::slice(&self.inner)
     }
     pub fn mass(&self) -> Scalar<T> {
-        self.inner.fixed_slice::<Const<1>>([6]).reshape()
+        self.mass_scalar()
+    }
+
+    /// Returns the mass, which is stored as the last element of `inner`.
+    pub fn mass_scalar(&self) -> Scalar<T> {
+        self.inner.get(6)
+    }
+
+    /// Like [`FullSpatialInertia::transform`], but any products of inertia the change of frame
+    /// introduces are dropped, so the result is only exact when the frame axes stay principal.
+    pub fn transform(&self, pose: &SpatialTransform<T>) -> SpatialInertia<T> {
+        let full = FullSpatialInertia::from(self.clone()).transform(pose);
+        SpatialInertia::new(full.inertia_diag(), full.momentum(), full.mass())
+    }
+
+    /// Solves `self * motion = f` for the acceleration `f` produces. Unlike `f / self`, which
+    /// divides elementwise, this accounts for the coupling the first moment of mass introduces,
+    /// see [`FullSpatialInertia::solve`].
+    pub fn solve(&self, f: &SpatialForce<T>) -> SpatialMotion<T> {
+        FullSpatialInertia::from(self.clone()).solve(f)
+    }
+}
+
+/// Combines two inertias expressed about the same origin, as for rigidly attached bodies.
+impl<T: Field> Add for SpatialInertia<T> {
+    type Output = SpatialInertia<T>;
+
+    fn add(self, rhs: SpatialInertia<T>) -> Self::Output {
+        SpatialInertia {
+            inner: self.inner + rhs.inner,
+        }
+    }
+}
+
+impl<T: SpatialScalar> SpatialInertia<T> {
+    /// Builds a spatial inertia from an inertia tensor about the center of mass, the mass, and
+    /// the center of mass offset. Fails with [`Error::OffDiagonalInertia`] if `inertia` has any
+    /// products of inertia beyond rounding error, since they can't be represented here; use
+    /// [`FullSpatialInertia::from_nalgebra`] for such tensors.
+    pub fn from_nalgebra(
+        inertia: nalgebra::Matrix3<T>,
+        mass: T,
+        com: nalgebra::Vector3<T>,
+    ) -> Result<Self, Error> {
+        let off_diagonal = off_diagonal(&inertia)?;
+        if off_diagonal.amax() > inertia_tolerance(&inertia) {
+            return Err(Error::OffDiagonalInertia);
+        }
+        let mass_scalar: Scalar<T> = mass.constant();
+        Ok(SpatialInertia::new(
+            inertia.diagonal(),
+            com * mass,
+            mass_scalar,
+        ))
+    }
+}
+
+/// Returns the `[xy, xz, yz]` products of inertia, averaged over both triangles of `inertia`.
+/// Fails with [`Error::AsymmetricInertia`] if the triangles differ by more than rounding error.
+fn off_diagonal<T: SpatialScalar>(
+    inertia: &nalgebra::Matrix3<T>,
+) -> Result<nalgebra::Vector3<T>, Error> {
+    if (inertia - inertia.transpose()).amax() > inertia_tolerance(inertia) {
+        return Err(Error::AsymmetricInertia);
     }
+    let sym = (inertia + inertia.transpose()) * nalgebra::convert::<f64, T>(0.5);
+    Ok(nalgebra::Vector3::new(
+        sym[(0, 1)],
+        sym[(0, 2)],
+        sym[(1, 2)],
+    ))
+}
+
+/// How far `inertia` may stray from an exact value before it counts as a different tensor,
+/// relative to its largest moment of inertia.
+fn inertia_tolerance<T: SpatialScalar>(inertia: &nalgebra::Matrix3<T>) -> T {
+    inertia.diagonal().amax() * T::default_epsilon().sqrt()
 }
 
 impl<T: TensorItem + Field + NativeType + ArrayElement> Div<SpatialInertia<T>> for SpatialForce<T> {
@@ -170,6 +448,242 @@ impl<T: TensorItem + ArrayElement + NativeType + Field> Mul<SpatialMotion<T>>
     }
 }
 
+/// A spatial inertia carrying the full symmetric rotational inertia, rather than only its
+/// diagonal like [`SpatialInertia`].
+///
+/// `inner` holds the inertia diagonal `[ixx, iyy, izz]`, the products of inertia
+/// `[ixy, ixz, iyz]`, the first moment of mass, and the mass, all expressed about the frame origin.
+#[derive(FromBuilder, IntoOp, Clone, Debug, FromOp)]
+pub struct FullSpatialInertia<T> {
+    pub inner: Vector<T, 10>,
+}
+
+impl<T: TensorItem + Field + NativeType + ArrayElement> FullSpatialInertia<T> {
+    pub fn new(
+        inertia_diag: impl Into<Vector<T, 3>>,
+        inertia_off_diag: impl Into<Vector<T, 3>>,
+        momentum: impl Into<Vector<T, 3>>,
+        mass: impl Into<Scalar<T>>,
+    ) -> Self {
+        let mass = mass.into().reshape::<Const<1>>();
+        let inner = inertia_diag
+            .into()
+            .concat(inertia_off_diag.into())
+            .concat(momentum.into())
+            .concat(mass);
+        FullSpatialInertia { inner }
+    }
+
+    /// Builds an inertia whose products of inertia are all zero.
+    pub fn from_diag(
+        inertia_diag: impl Into<Vector<T, 3>>,
+        momentum: impl Into<Vector<T, 3>>,
+        mass: impl Into<Scalar<T>>,
+    ) -> Self {
+        FullSpatialInertia::new(inertia_diag, Vector::zeros(), momentum, mass)
+    }
+
+    pub fn inertia_diag(&self) -> Vector<T, 3> {
+        Offset::<0>::slice(&self.inner)
+    }
+
+    /// Returns the products of inertia `[ixy, ixz, iyz]`.
+    pub fn inertia_off_diag(&self) -> Vector<T, 3> {
+        Offset::<3>::slice(&self.inner)
+    }
+
+    pub fn momentum(&self) -> Vector<T, 3> {
+        Offset::<6>::slice(&self.inner)
+    }
+
+    pub fn mass(&self) -> Scalar<T> {
+        self.inner.get(9)
+    }
+
+    /// Solves the full 6x6 system `self * motion = f`, as `f / self` does. When the products of
+    /// inertia and the first moment are all zero the elementwise divide is selected instead, so a
+    /// diagonal inertia gets the exact quotient rather than the round-off of the full solve.
+    pub fn solve(&self, f: &SpatialForce<T>) -> SpatialMotion<T> {
+        let full = f.clone() / self.clone();
+        let diag = SpatialMotion::new(f.torque() / self.inertia_diag(), f.force() / self.mass());
+        let coupling = (self.inertia_off_diag().abs() + self.momentum().abs()).sum_axis::<0>();
+        let is_diag = Vector::<T, 6>::zeros().eq(&coupling);
+        SpatialMotion {
+            inner: Tensor::select(&is_diag, diag.inner, full.inner),
+        }
+    }
+
+    /// Re-expresses an inertia given about the origin of a body frame about the origin of another
+    /// frame, where `pose` maps body coordinates into that frame.
+    pub fn transform(&self, pose: &SpatialTransform<T>) -> FullSpatialInertia<T> {
+        let q = pose.angular();
+        let r = pose.linear();
+        let mass = self.mass();
+
+        // the rotated inertia E I E^T, one column at a time
+        let [c0, c1, c2] = basis::<T>().map(|e| {
+            let body = symmetric_mul(
+                self.inertia_diag(),
+                self.inertia_off_diag(),
+                q.conjugate() * e,
+            );
+            q.clone() * body
+        });
+        let [xx, xy, xz] = c0.parts();
+        let [_, yy, yz] = c1.parts();
+        let [_, _, zz] = c2.parts();
+
+        // the parallel axis shift r g^T + g r^T - 2 (g . r) 1 + m (r r^T - |r|^2 1),
+        // where g is the rotated first moment
+        let g = q * self.momentum();
+        let [rx, ry, rz] = r.parts();
+        let [gx, gy, gz] = g.parts();
+        let rg = r.clone() * g.clone();
+        let gr = g.dot(&r);
+        let shift_diag = rg.clone() + rg - (gr.clone() + gr)
+            + (r.clone() * r.clone() - r.norm_squared()) * mass.clone();
+        let shift_off_diag = Vector::from_arr([
+            &rx * &gy + &gx * &ry,
+            &rx * &gz + &gx * &rz,
+            &ry * &gz + &gy * &rz,
+        ]) + Vector::from_arr([&rx * &ry, &rx * &rz, &ry * &rz])
+            * mass.clone();
+
+        FullSpatialInertia::new(
+            Vector::from_arr([xx, yy, zz]) - shift_diag,
+            Vector::from_arr([xy, xz, yz]) - shift_off_diag,
+            g + r * mass.clone(),
+            mass,
+        )
+    }
+}
+
+/// Combines two inertias expressed about the same origin, as for rigidly attached bodies.
+impl<T: Field> Add for FullSpatialInertia<T> {
+    type Output = FullSpatialInertia<T>;
+
+    fn add(self, rhs: FullSpatialInertia<T>) -> Self::Output {
+        FullSpatialInertia {
+            inner: self.inner + rhs.inner,
+        }
+    }
+}
+
+impl<T: SpatialScalar> FullSpatialInertia<T> {
+    /// Builds a spatial inertia from an inertia tensor about the center of mass, the mass, and
+    /// the center of mass offset, keeping its products of inertia. Fails with
+    /// [`Error::AsymmetricInertia`] if `inertia` isn't symmetric.
+    pub fn from_nalgebra(
+        inertia: nalgebra::Matrix3<T>,
+        mass: T,
+        com: nalgebra::Vector3<T>,
+    ) -> Result<Self, Error> {
+        let off_diagonal = off_diagonal(&inertia)?;
+        let mass_scalar: Scalar<T> = mass.constant();
+        Ok(FullSpatialInertia::new(
+            inertia.diagonal(),
+            off_diagonal,
+            com * mass,
+            mass_scalar,
+        ))
+    }
+}
+
+impl<T: TensorItem + Field + NativeType + ArrayElement> From<SpatialInertia<T>>
+    for FullSpatialInertia<T>
+{
+    fn from(inertia: SpatialInertia<T>) -> Self {
+        FullSpatialInertia::from_diag(inertia.inertia_diag(), inertia.momentum(), inertia.mass())
+    }
+}
+
+/// The unit vectors along the x, y, and z axes.
+fn basis<T: Field>() -> [Vector<T, 3>; 3] {
+    let one = T::one().reshape::<Const<1>>();
+    let zero = T::zero().reshape::<Const<1>>();
+    [
+        Vector::from_arr([one.clone(), zero.clone(), zero.clone()]),
+        Vector::from_arr([zero.clone(), one.clone(), zero.clone()]),
+        Vector::from_arr([zero.clone(), zero, one]),
+    ]
+}
+
+/// Multiplies `v` by the symmetric matrix with the given diagonal and `[xy, xz, yz]` off-diagonal.
+fn symmetric_mul<T: Field>(
+    diag: Vector<T, 3>,
+    off_diag: Vector<T, 3>,
+    v: Vector<T, 3>,
+) -> Vector<T, 3> {
+    let [xy, xz, yz] = off_diag.parts();
+    let [x, y, z] = v.parts();
+    let off = Vector::from_arr([
+        &xy * &y + &xz * &z,
+        &xy * &x + &yz * &z,
+        &xz * &x + &yz * &y,
+    ]);
+    diag * v + off
+}
+
+/// Solves `A x = b` for the symmetric matrix `A` with the given diagonal and `[xy, xz, yz]`
+/// off-diagonal, using the closed-form adjugate so that no host-side branching is needed.
+fn symmetric_solve<T: Field>(
+    diag: Vector<T, 3>,
+    off_diag: Vector<T, 3>,
+    b: Vector<T, 3>,
+) -> Vector<T, 3> {
+    let [xx, yy, zz] = diag.parts();
+    let [xy, xz, yz] = off_diag.parts();
+    let c00 = &yy * &zz - &yz * &yz;
+    let c11 = &xx * &zz - &xz * &xz;
+    let c22 = &xx * &yy - &xy * &xy;
+    let c01 = &xz * &yz - &xy * &zz;
+    let c02 = &xy * &yz - &yy * &xz;
+    let c12 = &xy * &xz - &xx * &yz;
+    let det = &xx * &c00 + &xy * &c01 + &xz * &c02;
+    symmetric_mul(
+        Vector::from_arr([c00, c11, c22]),
+        Vector::from_arr([c01, c02, c12]),
+        b,
+    ) / det
+}
+
+impl<T: TensorItem + ArrayElement + NativeType + Field> Mul<SpatialMotion<T>>
+    for FullSpatialInertia<T>
+{
+    type Output = SpatialForce<T>;
+
+    fn mul(self, rhs: SpatialMotion<T>) -> Self::Output {
+        let force: Vector<T, 3> =
+            self.mass() * rhs.linear() - self.momentum().cross(&rhs.angular());
+        let torque = symmetric_mul(self.inertia_diag(), self.inertia_off_diag(), rhs.angular())
+            + self.momentum().cross(&rhs.linear());
+        SpatialForce::new(torque, force)
+    }
+}
+
+impl<T: TensorItem + Field + NativeType + ArrayElement> Div<FullSpatialInertia<T>>
+    for SpatialForce<T>
+{
+    type Output = SpatialMotion<T>;
+
+    /// Solves the 6x6 system `inertia * motion = force`, by eliminating the linear part and
+    /// solving for the angular part against the rotational inertia about the center of mass.
+    fn div(self, rhs: FullSpatialInertia<T>) -> Self::Output {
+        let mass = rhs.mass();
+        let h = rhs.momentum();
+        let [hx, hy, hz] = h.parts();
+        // the rotational inertia about the center of mass, I + (h h^T - |h|^2 1) / m
+        let com_diag =
+            rhs.inertia_diag() + (h.clone() * h.clone() - h.norm_squared()) / mass.clone();
+        let com_off_diag = rhs.inertia_off_diag()
+            + Vector::from_arr([&hx * &hy, &hx * &hz, &hy * &hz]) / mass.clone();
+        let torque = self.torque() - h.cross(&self.force()) / mass.clone();
+        let angular = symmetric_solve(com_diag, com_off_diag, torque);
+        let linear = (self.force() + h.cross(&angular)) / mass;
+        SpatialMotion::new(angular, linear)
+    }
+}
+
 #[derive(FromBuilder, IntoOp, Clone, Debug, FromOp)]
 pub struct SpatialMotion<T> {
     pub inner: Vector<T, 6>,
@@ -183,6 +697,20 @@ impl<T: TensorItem + Field + NativeType + ArrayElement> SpatialMotion<T> {
         SpatialMotion { inner }
     }
 
+    pub fn from_vectors(angular: Vector<T, 3>, linear: Vector<T, 3>) -> Self {
+        SpatialMotion {
+            inner: angular.concat(linear),
+        }
+    }
+
+    /// Builds a motion from dynamically sized tensors, checking that each has exactly 3 elements.
+    pub fn try_new(
+        angular: Tensor<T, nalgebra::Dyn>,
+        linear: Tensor<T, nalgebra::Dyn>,
+    ) -> Result<Self, Error> {
+        Ok(Self::from_vectors(vector3(angular)?, vector3(linear)?))
+    }
+
     pub fn from_linear(linear: impl Into<Vector<T, 3>>) -> Self {
         let linear = linear.into();
         let zero = T::zero().broadcast::<Const<3>>();
@@ -198,11 +726,11 @@ impl<T: TensorItem + Field + NativeType + ArrayElement> SpatialMotion<T> {
     }
 
     pub fn angular(&self) -> Vector<T, 3> {
-        self.inner.fixed_slice([0])
+        Offset::<0>::slice(&self.inner)
     }
 
     pub fn linear(&self) -> Vector<T, 3> {
-        self.inner.fixed_slice([3])
+        Offset::<3>::slice(&self.inner)
     }
 
     pub fn offset(&self, pos: SpatialTransform<T>) -> Self {
@@ -223,31 +751,134 @@ impl<T: TensorItem + Field + NativeType + ArrayElement> SpatialMotion<T> {
         SpatialForce::new(torque, force)
     }
 
+    /// The instantaneous power of `f` acting along this motion, `angular . torque + linear . force`.
+    pub fn dot(&self, f: &SpatialForce<T>) -> Scalar<T> {
+        self.angular().dot(&f.torque()) + self.linear().dot(&f.force())
+    }
+
+    /// The magnitude of the angular velocity.
+    pub fn angular_norm(&self) -> Scalar<T> {
+        self.angular().norm()
+    }
+
+    /// The magnitude of the linear velocity.
+    pub fn linear_norm(&self) -> Scalar<T> {
+        self.linear().norm()
+    }
+
+    /// The norm of the whole 6-vector. The two parts have different units, so this is mostly
+    /// useful as a convergence measure rather than a physical speed.
+    pub fn norm(&self) -> Scalar<T> {
+        self.inner.norm()
+    }
+
     pub fn zero() -> Self {
         SpatialMotion {
             inner: Tensor::zeros(),
         }
     }
+
+    /// Scales the angular and linear parts down so their norms don't exceed the given limits,
+    /// preserving their direction. Parts that are already within their limit are left unchanged.
+    pub fn clamp_speeds(&self, max_angular: Scalar<T>, max_linear: Scalar<T>) -> Self {
+        let saturate = |v: Vector<T, 3>, limit: Scalar<T>| -> Vector<T, 3> {
+            let scale = limit.clone() / v.norm().max(&limit);
+            scale * v
+        };
+        SpatialMotion::new(
+            saturate(self.angular(), max_angular),
+            saturate(self.linear(), max_linear),
+        )
+    }
 }
 
-impl Mul<SpatialMotion<f64>> for f64 {
-    type Output = SpatialMotion<f64>;
-    fn mul(self, rhs: SpatialMotion<f64>) -> Self::Output {
+impl<T: SpatialScalar> Mul<T> for SpatialMotion<T> {
+    type Output = SpatialMotion<T>;
+    fn mul(self, rhs: T) -> Self::Output {
         SpatialMotion {
-            inner: self * rhs.inner,
+            inner: self.inner * rhs,
         }
     }
 }
 
-impl Mul<SpatialMotion<f32>> for f32 {
-    type Output = SpatialMotion<f32>;
-    fn mul(self, rhs: SpatialMotion<f32>) -> Self::Output {
+impl<T: SpatialScalar> SpatialMotion<T> {
+    /// The SE(3) exponential, integrating this twist over `dt` into the transform it traces out.
+    pub fn exp(self, dt: Scalar<T>) -> SpatialTransform<T> {
+        let phi = self.angular() * dt.clone();
+        let rho = self.linear() * dt;
+        // offsetting the angle keeps the divisions finite for a zero rotation, where `phi` is zero
+        let theta = (phi.norm_squared() + tiny::<T>()).sqrt();
+        let half_theta = theta.clone() * half::<T>();
+        let sinc_half = half_theta.sin() / theta.clone();
+        let angular = Quaternion(
+            (phi.clone() * sinc_half.clone()).concat(half_theta.cos().reshape::<Const<1>>()),
+        );
+
+        // (1 - cos) / theta^2 in its half angle form, which doesn't cancel for small angles
+        let a = sinc_half.clone() * sinc_half * (<T as Field>::one() + <T as Field>::one());
+        let b = (theta.clone() - theta.sin()) / (theta.clone() * theta.clone() * theta);
+        let phi_rho = phi.cross(&rho);
+        let linear = rho + phi_rho.clone() * a + phi.cross(&phi_rho) * b;
+        SpatialTransform::new(angular, linear)
+    }
+
+    /// The pose change of moving at this motion for `dt`, so that `pose * motion.integrate(dt)`
+    /// steps a pose forward.
+    ///
+    /// Unlike [`SpatialMotion::exp`] the two parts are integrated separately: the rotation is the
+    /// half angle quaternion of `angular * dt` and the translation is just `linear * dt`.
+    pub fn integrate(&self, dt: Scalar<T>) -> SpatialTransform<T> {
+        let phi = self.angular() * dt.clone();
+        let theta = (phi.norm_squared() + tiny::<T>()).sqrt();
+        let half_theta = theta.clone() * half::<T>();
+        let angular = Quaternion(
+            (phi * (half_theta.sin() / theta)).concat(half_theta.cos().reshape::<Const<1>>()),
+        );
+        SpatialTransform::new(angular, self.linear() * dt)
+    }
+}
+
+fn half<T: SpatialScalar>() -> Scalar<T> {
+    nalgebra::convert::<f64, T>(0.5).constant()
+}
+
+fn tiny<T: SpatialScalar>() -> Scalar<T> {
+    (T::default_epsilon() * T::default_epsilon()).constant()
+}
+
+// a blanket `impl<T> Mul<SpatialMotion<T>> for T` isn't allowed by the orphan rules,
+// so the scalar-on-the-left form is stamped out per type
+macro_rules! impl_scalar_mul {
+    ($($ty:ty),+) => {
+        $(
+            impl Mul<SpatialMotion<$ty>> for $ty {
+                type Output = SpatialMotion<$ty>;
+                fn mul(self, rhs: SpatialMotion<$ty>) -> Self::Output {
+                    rhs * self
+                }
+            }
+        )+
+    };
+}
+
+impl_scalar_mul!(f32, f64);
+
+impl<T: Field> Mul<Scalar<T>> for SpatialMotion<T> {
+    type Output = SpatialMotion<T>;
+    fn mul(self, rhs: Scalar<T>) -> Self::Output {
         SpatialMotion {
-            inner: self * rhs.inner,
+            inner: self.inner * rhs,
         }
     }
 }
 
+impl<T: Field> Mul<SpatialMotion<T>> for Scalar<T> {
+    type Output = SpatialMotion<T>;
+    fn mul(self, rhs: SpatialMotion<T>) -> Self::Output {
+        rhs * self
+    }
+}
+
 impl<T> Add<SpatialMotion<T>> for SpatialTransform<T>
 where
     T: ArrayElement + NativeType + Field,
@@ -278,6 +909,24 @@ impl<T: Field> Add<SpatialMotion<T>> for SpatialMotion<T> {
     }
 }
 
+impl<T: Field> Sub<SpatialMotion<T>> for SpatialMotion<T> {
+    type Output = SpatialMotion<T>;
+
+    fn sub(self, rhs: SpatialMotion<T>) -> Self::Output {
+        SpatialMotion {
+            inner: self.inner - rhs.inner,
+        }
+    }
+}
+
+impl<T: Field> Neg for SpatialMotion<T> {
+    type Output = SpatialMotion<T>;
+
+    fn neg(self) -> Self::Output {
+        SpatialMotion { inner: -self.inner }
+    }
+}
+
 impl<T: Field> Add<SpatialTransform<T>> for SpatialTransform<T> {
     type Output = SpatialTransform<T>;
 
@@ -288,9 +937,119 @@ impl<T: Field> Add<SpatialTransform<T>> for SpatialTransform<T> {
     }
 }
 
+fn vector3<T>(tensor: Tensor<T, nalgebra::Dyn>) -> Result<Vector<T, 3>, Error> {
+    let shape = tensor.inner.shape();
+    if shape.as_deref() != Some(&[3][..]) {
+        return Err(Error::DimensionMismatch {
+            expected: vec![3],
+            found: shape.map(|s| s.to_vec()),
+        });
+    }
+    Ok(tensor.reshape())
+}
+
+/// Scalars whose values can be read back from a constant's literal.
+trait LiteralValues: Sized + Copy {
+    fn literal_values(literal: &xla::Literal) -> Option<&[Self]>;
+}
+
+impl LiteralValues for f32 {
+    fn literal_values(literal: &xla::Literal) -> Option<&[Self]> {
+        literal.typed_buf().ok()
+    }
+}
+
+impl LiteralValues for f64 {
+    fn literal_values(literal: &xla::Literal) -> Option<&[Self]> {
+        literal.typed_buf().ok()
+    }
+}
+
+/// Reads the values of `expr` when it is built only from constants, such as a host value or a
+/// concatenation of them, without compiling or running anything.
+fn constant_values<T: LiteralValues>(expr: &Noxpr) -> Option<Vec<T>> {
+    match expr.deref() {
+        NoxprNode::Constant(c) => T::literal_values(&c.data).map(<[T]>::to_vec),
+        NoxprNode::Reshape(r) => constant_values(&r.expr),
+        NoxprNode::Concat(c) if expr.shape()?.len() == 1 => c
+            .nodes
+            .iter()
+            .map(constant_values)
+            .collect::<Option<Vec<Vec<T>>>>()
+            .map(|values| values.concat()),
+        _ => None,
+    }
+}
+
+/// Writes the labelled `parts` of a spatial value, falling back to the symbolic `inner` when it
+/// isn't a constant.
+fn fmt_parts<T: LiteralValues, const N: usize>(
+    f: &mut std::fmt::Formatter<'_>,
+    name: &str,
+    inner: &Vector<T, N>,
+    parts: &[(&str, usize)],
+) -> std::fmt::Result
+where
+    Vector<T, N>: std::fmt::Debug,
+{
+    let mut out = f.debug_struct(name);
+    let values = constant_values::<T>(&inner.inner).filter(|values| values.len() == N);
+    let Some(values) = values else {
+        return out.field("inner", inner).finish();
+    };
+    let mut offset = 0;
+    for (label, len) in parts {
+        out.field(label, &&values[offset..offset + len]);
+        offset += len;
+    }
+    out.finish()
+}
+
+macro_rules! impl_spatial_display {
+    ($($ty:ty),+) => {
+        $(
+            impl std::fmt::Display for SpatialTransform<$ty> {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    fmt_parts(
+                        f,
+                        "SpatialTransform",
+                        &self.inner,
+                        &[("angular", 4), ("linear", 3)],
+                    )
+                }
+            }
+
+            impl std::fmt::Display for SpatialForce<$ty> {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    fmt_parts(f, "SpatialForce", &self.inner, &[("torque", 3), ("force", 3)])
+                }
+            }
+
+            impl std::fmt::Display for SpatialInertia<$ty> {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    fmt_parts(
+                        f,
+                        "SpatialInertia",
+                        &self.inner,
+                        &[("inertia_diag", 3), ("momentum", 3), ("mass", 1)],
+                    )
+                }
+            }
+
+            impl std::fmt::Display for SpatialMotion<$ty> {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    fmt_parts(f, "SpatialMotion", &self.inner, &[("angular", 3), ("linear", 3)])
+                }
+            }
+        )+
+    };
+}
+
+impl_spatial_display!(f32, f64);
+
 #[cfg(test)]
 mod tests {
-    use crate::{CompFn, ToHost};
+    use crate::{Comp, CompFn, ConstantExt, Matrix, ToHost};
     use nalgebra::{vector, Vector3};
 
     use super::*;
@@ -328,6 +1087,537 @@ mod tests {
         )
     }
 
+    #[cfg(feature = "gpu")]
+    #[test]
+    fn test_spatial_transform_mul_gpu() {
+        let f = || -> Vector<f64, 7> {
+            let a = SpatialTransform::new(
+                nalgebra::UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 30f64.to_radians())
+                    .into_inner(),
+                nalgebra::Vector3::new(1.0, -3.0, 0.5),
+            );
+            let b = SpatialTransform::new(
+                nalgebra::UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 60f64.to_radians())
+                    .into_inner(),
+                nalgebra::Vector3::new(0.0, 2.0, 4.0),
+            );
+            (a * b).inner
+        };
+        let comp = f.build().unwrap();
+        let cpu = crate::Client::cpu().unwrap();
+        let expected = comp.compile(&cpu).unwrap().run(&cpu).unwrap().to_host();
+        let gpu = crate::Client::gpu(0).unwrap();
+        let res = comp.compile(&gpu).unwrap().run(&gpu).unwrap().to_host();
+        approx::assert_relative_eq!(res, expected, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_spatial_force_at_point() {
+        let f = || -> Vector<f64, 6> {
+            SpatialForce::at_point(vector![2.0, 0.0, 0.0], vector![0.0, 1.5, 0.0]).inner
+        };
+        let client = crate::Client::cpu().unwrap();
+        let comp = f.build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        // a +x force at a +y offset twists about -z
+        assert_eq!(res, vector![0.0, 0.0, -3.0, 2.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_spatial_motion_norms() {
+        let f = || -> Vector<f64, 3> {
+            let motion = SpatialMotion::new(vector![0.0, 3.0, 4.0], vector![1.0, 2.0, 2.0]);
+            Vector::from_arr([
+                motion.angular_norm().reshape(),
+                motion.linear_norm().reshape(),
+                motion.norm().reshape(),
+            ])
+        };
+        let client = crate::Client::cpu().unwrap();
+        let comp = f.build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(res, vector![5.0, 3.0, 34.0f64.sqrt()], epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_spatial_transform_random() {
+        use rand::SeedableRng;
+
+        let f = || -> Scalar<f64> {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+            let mut error: Scalar<f64> = 0.0f64.constant();
+            for _ in 0..1000 {
+                let transform = SpatialTransform::random(&mut rng, 10.0);
+                let norm_error = transform.angular().0.norm() - 1.0f64.constant();
+                error = error + norm_error.clone() * norm_error;
+            }
+            error
+        };
+        let client = crate::Client::cpu().unwrap();
+        let exec = f.build().unwrap().compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        assert!(res < 1e-20);
+    }
+
+    #[test]
+    fn test_spatial_transform_isometry_round_trip() {
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let mut sample =
+            || Vector3::<f64>::new(rng.gen(), rng.gen(), rng.gen()) * 4.0 - Vector3::repeat(2.0);
+        let isometry = nalgebra::Isometry3::new(sample(), sample());
+        let f = move || -> Vector<f64, 7> { SpatialTransform::from(isometry).inner };
+        let client = crate::Client::cpu().unwrap();
+        let exec = f.build().unwrap().compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        assert_eq!(res[3], isometry.rotation.w);
+        let round_trip = SpatialTransform::isometry_from_host(&res);
+        approx::assert_relative_eq!(round_trip, isometry, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_spatial_force_sum() {
+        let f = || -> Vector<f64, 6> {
+            let gravity = SpatialForce::from_linear(nalgebra::Vector3::new(0.0, 0.0, -9.81));
+            let drag = SpatialForce::new(
+                nalgebra::Vector3::new(0.1, 0.0, 0.0),
+                nalgebra::Vector3::new(-1.0, 0.5, 0.0),
+            );
+            let thrust = SpatialForce::from_torque(nalgebra::Vector3::new(0.0, 2.0, 0.0));
+            let total = SpatialForce::zero() + gravity + drag - thrust;
+            total.torque().concat(total.force())
+        };
+        let client = crate::Client::cpu().unwrap();
+        let exec = f.build().unwrap().compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(res, vector![0.1, -2.0, 0.0, -1.0, 0.5, -9.81]);
+    }
+
+    #[test]
+    fn test_spatial_force_div_scalar() {
+        let f = || -> Vector<f64, 6> {
+            let force = SpatialForce::new(
+                nalgebra::Vector3::new(2.0, -4.0, 1.0),
+                nalgebra::Vector3::new(0.5, 6.0, -3.0),
+            );
+            let half = force / 2.0f64.constant();
+            half.torque().concat(half.force())
+        };
+        let client = crate::Client::cpu().unwrap();
+        let exec = f.build().unwrap().compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(res, vector![1.0, -2.0, 0.5, 0.25, 3.0, -1.5]);
+
+        let f = || -> Vector<f64, 6> {
+            let force = SpatialForce::from_linear(nalgebra::Vector3::new(1.0, 2.0, 3.0));
+            (force * 3.0f64.constant()).inner
+        };
+        let exec = f.build().unwrap().compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(res, vector![0.0, 0.0, 0.0, 3.0, 6.0, 9.0]);
+    }
+
+    #[test]
+    fn test_spatial_motion_sub() {
+        let f = || -> Vector<f64, 6> {
+            let a = SpatialMotion::new(
+                nalgebra::Vector3::new(1.0, 2.0, 3.0),
+                nalgebra::Vector3::new(4.0, 5.0, 6.0),
+            );
+            let b = SpatialMotion::new(
+                nalgebra::Vector3::new(0.5, 0.5, 0.5),
+                nalgebra::Vector3::new(-1.0, 0.0, 1.0),
+            );
+            let relative = a.clone() - b.clone();
+            let negated = -(b - a);
+            (relative.angular() + negated.angular()).concat(relative.linear() + negated.linear())
+        };
+        let client = crate::Client::cpu().unwrap();
+        let exec = f.build().unwrap().compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(res, vector![1.0, 3.0, 5.0, 10.0, 10.0, 10.0]);
+    }
+
+    #[test]
+    fn test_spatial_motion_mul_scalar() {
+        fn scale<T: Field>(motion: SpatialMotion<T>, factor: Scalar<T>) -> SpatialMotion<T> {
+            factor.clone() * motion * factor
+        }
+
+        let f = || -> Vector<f64, 6> {
+            let motion = SpatialMotion::new(
+                nalgebra::Vector3::new(1.0, 2.0, 3.0),
+                nalgebra::Vector3::new(-4.0, 5.0, 0.5),
+            );
+            scale(motion, 2.0f64.constant()).inner
+        };
+        let client = crate::Client::cpu().unwrap();
+        let exec = f.build().unwrap().compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(res, vector![4.0, 8.0, 12.0, -16.0, 20.0, 2.0]);
+    }
+
+    #[test]
+    fn test_spatial_inertia_solve() {
+        fn force() -> SpatialForce<f64> {
+            SpatialForce::new(
+                nalgebra::Vector3::new(1.0, 2.0, -0.5),
+                nalgebra::Vector3::new(-2.0, 4.0, 1.0),
+            )
+        }
+        let client = crate::Client::cpu().unwrap();
+        let run = |f: fn() -> Vector<f64, 6>| {
+            let exec = f.build().unwrap().compile(&client).unwrap();
+            exec.run(&client).unwrap().to_host()
+        };
+
+        // a diagonal inertia takes the elementwise path, matching the divide exactly
+        fn diag() -> SpatialInertia<f64> {
+            SpatialInertia::new(
+                nalgebra::Vector3::new(1.0, 2.0, 3.0),
+                Vector::zeros(),
+                4.0f64.constant(),
+            )
+        }
+        let divided = run(|| (force() / diag()).inner);
+        let solved = run(|| diag().solve(&force()).inner);
+        assert_eq!(divided, solved);
+
+        // a first moment couples the two parts, which the solve inverts
+        let round_trip = run(|| {
+            let inertia = SpatialInertia::new(
+                nalgebra::Vector3::new(1.0, 2.0, 3.0),
+                nalgebra::Vector3::new(0.5, -1.0, 0.25),
+                4.0f64.constant(),
+            );
+            let motion = SpatialMotion::new(
+                nalgebra::Vector3::new(0.5, -1.0, 2.0),
+                nalgebra::Vector3::new(1.5, 0.25, -3.0),
+            );
+            inertia.solve(&(inertia.clone() * motion)).inner
+        });
+        approx::assert_relative_eq!(
+            round_trip,
+            vector![0.5, -1.0, 2.0, 1.5, 0.25, -3.0],
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_full_spatial_inertia() {
+        fn motion() -> SpatialMotion<f64> {
+            SpatialMotion::new(
+                nalgebra::Vector3::new(0.5, -1.0, 2.0),
+                nalgebra::Vector3::new(1.5, 0.25, -3.0),
+            )
+        }
+        fn force() -> SpatialForce<f64> {
+            SpatialForce::new(
+                nalgebra::Vector3::new(1.0, 2.0, -0.5),
+                nalgebra::Vector3::new(-2.0, 4.0, 1.0),
+            )
+        }
+        let client = crate::Client::cpu().unwrap();
+        let run = |f: fn() -> Vector<f64, 6>| {
+            let exec = f.build().unwrap().compile(&client).unwrap();
+            exec.run(&client).unwrap().to_host()
+        };
+
+        // for a diagonal tensor both representations must agree
+        let diag_mul = run(|| {
+            let inertia = SpatialInertia::new(
+                nalgebra::Vector3::new(1.0, 2.0, 3.0),
+                nalgebra::Vector3::new(0.5, -1.0, 0.25),
+                4.0f64.constant(),
+            );
+            (inertia * motion()).inner
+        });
+        let full_mul = run(|| {
+            let inertia = FullSpatialInertia::from_diag(
+                nalgebra::Vector3::new(1.0, 2.0, 3.0),
+                nalgebra::Vector3::new(0.5, -1.0, 0.25),
+                4.0f64.constant(),
+            );
+            (inertia * motion()).inner
+        });
+        approx::assert_relative_eq!(diag_mul, full_mul, epsilon = 1e-12);
+
+        let diag_div = run(|| {
+            let inertia = SpatialInertia::new(
+                nalgebra::Vector3::new(1.0, 2.0, 3.0),
+                Vector::zeros(),
+                4.0f64.constant(),
+            );
+            (force() / inertia).inner
+        });
+        let full_div = run(|| {
+            let inertia = SpatialInertia::new(
+                nalgebra::Vector3::new(1.0, 2.0, 3.0),
+                Vector::zeros(),
+                4.0f64.constant(),
+            );
+            (force() / FullSpatialInertia::from(inertia)).inner
+        });
+        approx::assert_relative_eq!(diag_div, full_div, epsilon = 1e-12);
+
+        // with products of inertia and a first moment, division undoes multiplication
+        let round_trip = run(|| {
+            let inertia = || {
+                FullSpatialInertia::new(
+                    nalgebra::Vector3::new(1.0, 2.0, 3.0),
+                    nalgebra::Vector3::new(0.1, -0.2, 0.3),
+                    nalgebra::Vector3::new(0.5, -1.0, 0.25),
+                    4.0f64.constant(),
+                )
+            };
+            ((inertia() * motion()) / inertia()).inner
+        });
+        approx::assert_relative_eq!(
+            round_trip,
+            vector![0.5, -1.0, 2.0, 1.5, 0.25, -3.0],
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_spatial_inertia_composition() {
+        let client = crate::Client::cpu().unwrap();
+        let f = || -> Vector<f64, 7> {
+            let point = || SpatialInertia::new(Vector::zeros(), Vector::zeros(), 1.0f64.constant());
+            let left = SpatialTransform::from_linear(nalgebra::Vector3::new(-1.0, 0.0, 0.0));
+            let right = SpatialTransform::from_linear(nalgebra::Vector3::new(1.0, 0.0, 0.0));
+            (point().transform(&left) + point().transform(&right)).inner
+        };
+        let exec = f.build().unwrap().compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(
+            res,
+            vector![0.0, 2.0, 2.0, 0.0, 0.0, 0.0, 2.0],
+            epsilon = 1e-12
+        );
+
+        // a quarter turn about z swaps the x and y moments before the shift along z
+        let f = || -> Vector<f64, 10> {
+            let body = FullSpatialInertia::from_diag(
+                nalgebra::Vector3::new(1.0, 2.0, 3.0),
+                Vector::zeros(),
+                1.0f64.constant(),
+            );
+            let q =
+                nalgebra::UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 90f64.to_radians())
+                    .into_inner();
+            let pose = SpatialTransform::new(q, nalgebra::Vector3::new(0.0, 0.0, 1.0));
+            body.transform(&pose).inner
+        };
+        let exec = f.build().unwrap().compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(
+            res,
+            vector![3.0, 2.0, 3.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0],
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_spatial_exp_log() {
+        let axis = nalgebra::Unit::new_normalize(Vector3::new(1.0, 2.0, 3.0));
+        let q = nalgebra::UnitQuaternion::from_axis_angle(&axis, 50f64.to_radians()).into_inner();
+        let transform = move || SpatialTransform::new(q, nalgebra::Vector3::new(0.3, -0.5, 1.2));
+        let client = crate::Client::cpu().unwrap();
+
+        let f = move || -> Vector<f64, 7> { transform().log().exp(1.0f64.constant()).inner };
+        let exec = f.build().unwrap().compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(
+            res,
+            vector![q.i, q.j, q.k, q.w, 0.3, -0.5, 1.2],
+            epsilon = 1e-12
+        );
+
+        let f = || -> Vector<f64, 6> { SpatialTransform::identity().log().inner };
+        let exec = f.build().unwrap().compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(res, nalgebra::Vector6::zeros(), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_spatial_motion_integrate() {
+        const STEPS: usize = 20;
+        let f = || -> Vector<f64, 7> {
+            let motion = SpatialMotion::new(
+                nalgebra::Vector3::new(0.0, 0.0, 0.3),
+                nalgebra::Vector3::zeros(),
+            );
+            let mut pose = SpatialTransform::identity();
+            for _ in 0..STEPS {
+                pose = pose * motion.integrate(0.1f64.constant());
+            }
+            pose.inner
+        };
+        let client = crate::Client::cpu().unwrap();
+        let exec = f.build().unwrap().compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        let q =
+            nalgebra::UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 0.3 * 0.1 * STEPS as f64)
+                .into_inner();
+        approx::assert_relative_eq!(
+            res,
+            vector![q.i, q.j, q.k, q.w, 0.0, 0.0, 0.0],
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_spatial_display() {
+        let q = nalgebra::UnitQuaternion::<f64>::identity().into_inner();
+        let transform = SpatialTransform::new(q, nalgebra::Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(
+            transform.to_string(),
+            "SpatialTransform { angular: [0.0, 0.0, 0.0, 1.0], linear: [1.0, 2.0, 3.0] }"
+        );
+
+        let force = SpatialForce::new(
+            nalgebra::Vector3::new(0.5f32, 0.0, 0.0),
+            nalgebra::Vector3::new(0.0, 0.0, -9.5),
+        );
+        let formatted = force.to_string();
+        assert!(formatted.contains("torque: [0.5, 0.0, 0.0]"), "{formatted}");
+        assert!(formatted.contains("force: [0.0, 0.0, -9.5]"), "{formatted}");
+
+        // anything computed from constants is shown symbolically rather than evaluated
+        let motion = SpatialMotion::new(
+            nalgebra::Vector3::new(1.0, 0.0, 0.0),
+            nalgebra::Vector3::new(0.0, 1.0, 0.0),
+        );
+        let sum = motion.clone() + motion;
+        assert!(sum.to_string().starts_with("SpatialMotion { inner: "));
+
+        // as are values that depend on a parameter
+        let f = |motion: SpatialMotion<f64>| -> Vector<f64, 6> {
+            assert!(motion.to_string().starts_with("SpatialMotion { inner: "));
+            assert!(format!("{motion:?}").contains("inner"));
+            motion.inner
+        };
+        f.build().unwrap();
+    }
+
+    #[test]
+    fn test_spatial_transform_motion() {
+        let q = nalgebra::UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 30f64.to_radians())
+            .into_inner();
+        let pose = move || SpatialTransform::new(q, nalgebra::Vector3::new(1.0, -2.0, 0.5));
+        let motion = || {
+            SpatialMotion::new(
+                nalgebra::Vector3::new(0.2, 0.4, -1.0),
+                nalgebra::Vector3::new(3.0, 0.0, 1.0),
+            )
+        };
+        let client = crate::Client::cpu().unwrap();
+
+        let f = move || -> Vector<f64, 6> { pose().transform_motion(&motion()).inner };
+        let exec = f.build().unwrap().compile(&client).unwrap();
+        let transformed = exec.run(&client).unwrap().to_host();
+
+        let f = move || -> Vector<f64, 6> { motion().offset(pose()).inner };
+        let exec = f.build().unwrap().compile(&client).unwrap();
+        let offset = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(transformed, offset, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_spatial_transform_force_power() {
+        let q = nalgebra::UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 70f64.to_radians())
+            .into_inner();
+        let f = move || -> Vector<f64, 2> {
+            let pose = SpatialTransform::new(q, nalgebra::Vector3::new(-1.0, 0.5, 2.0));
+            let motion = SpatialMotion::new(
+                nalgebra::Vector3::new(0.3, -0.7, 1.1),
+                nalgebra::Vector3::new(2.0, 1.0, -0.5),
+            );
+            let force = SpatialForce::new(
+                nalgebra::Vector3::new(-1.0, 0.25, 0.5),
+                nalgebra::Vector3::new(4.0, -3.0, 1.5),
+            );
+            let power = |m: &SpatialMotion<f64>, f: &SpatialForce<f64>| {
+                m.angular().dot(&f.torque()) + m.linear().dot(&f.force())
+            };
+            let before = power(&motion, &force);
+            let after = power(
+                &pose.transform_motion(&motion),
+                &pose.transform_force(&force),
+            );
+            before
+                .reshape::<Const<1>>()
+                .concat(after.reshape::<Const<1>>())
+        };
+        let client = crate::Client::cpu().unwrap();
+        let exec = f.build().unwrap().compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(res[0], res[1], epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_spatial_motion_dot() {
+        let f = || -> Scalar<f64> {
+            let motion = SpatialMotion::new(
+                nalgebra::Vector3::new(1.0, 2.0, 3.0),
+                nalgebra::Vector3::new(4.0, 5.0, 6.0),
+            );
+            let force = SpatialForce::new(
+                nalgebra::Vector3::new(0.5, -1.0, 2.0),
+                nalgebra::Vector3::new(1.0, 0.0, -2.0),
+            );
+            motion.dot(&force)
+        };
+        let client = crate::Client::cpu().unwrap();
+        let exec = f.build().unwrap().compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(res, -3.5, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_spatial_transform_identity() {
+        let q = nalgebra::UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 60f64.to_radians())
+            .into_inner();
+        let transform = move || SpatialTransform::new(q, nalgebra::Vector3::new(1.0, -2.0, 3.0));
+        let expected = vector![q.i, q.j, q.k, q.w, 1.0, -2.0, 3.0];
+        let client = crate::Client::cpu().unwrap();
+
+        let f = || -> Vector<f64, 7> { (SpatialTransform::identity() * transform()).inner };
+        let exec = f.build().unwrap().compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(res, expected, epsilon = 1e-12);
+
+        let f = || -> Vector<f64, 7> { (transform() * SpatialTransform::identity()).inner };
+        let exec = f.build().unwrap().compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(res, expected, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_spatial_transform_inverse() {
+        let f = || -> Vector<f64, 7> {
+            let a = SpatialTransform::new(
+                nalgebra::UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 30f64.to_radians())
+                    .into_inner(),
+                nalgebra::Vector3::new(1.0, -2.0, 3.0),
+            );
+            (a.clone() * a.inverse()).inner
+        };
+        let client = crate::Client::cpu().unwrap();
+        let comp = f.build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(
+            res,
+            vector![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0],
+            epsilon = 1e-12
+        );
+    }
+
     #[test]
     fn test_spatial_transform_add() {
         let f = || -> Vector<f64, 7> {
@@ -358,4 +1648,202 @@ mod tests {
             ]
         )
     }
+
+    #[test]
+    fn test_spatial_motion_clamp_speeds() {
+        let f = || -> Vector<f64, 6> {
+            let motion = SpatialMotion::new(
+                nalgebra::Vector3::new(0.0, 0.0, 1.0),
+                nalgebra::Vector3::new(3.0, 4.0, 0.0),
+            );
+            motion
+                .clamp_speeds(2.0f64.constant(), 2.0f64.constant())
+                .inner
+        };
+        let client = crate::Client::cpu().unwrap();
+        let comp = f.build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(
+            res,
+            vector![0.0, 0.0, 1.0, 1.2, 1.6, 0.0],
+            epsilon = 1.0e-12
+        );
+    }
+
+    #[test]
+    fn test_spatial_inertia_mass_scalar() {
+        let f = || -> Scalar<f64> {
+            SpatialInertia::new(
+                nalgebra::Vector3::new(1.0, 2.0, 3.0),
+                nalgebra::Vector3::new(4.0, 5.0, 6.0),
+                7.0f64.constant(),
+            )
+            .mass_scalar()
+        };
+        let client = crate::Client::cpu().unwrap();
+        let comp = f.build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        assert_eq!(res, 7.0);
+    }
+
+    #[test]
+    fn test_spatial_motion_try_new() {
+        let vec4: Vector<f64, 4> = nalgebra::Vector4::new(1.0, 2.0, 3.0, 4.0).constant();
+        let vec3: Vector<f64, 3> = nalgebra::Vector3::new(1.0, 2.0, 3.0).constant();
+        let angular = Tensor::<f64, nalgebra::Dyn>::from_op(vec4.inner);
+        let linear = Tensor::<f64, nalgebra::Dyn>::from_op(vec3.inner);
+        assert!(matches!(
+            SpatialMotion::try_new(angular.clone(), linear.clone()),
+            Err(Error::DimensionMismatch { .. })
+        ));
+        assert!(matches!(
+            SpatialForce::try_new(linear.clone(), angular),
+            Err(Error::DimensionMismatch { .. })
+        ));
+        assert!(SpatialMotion::try_new(linear.clone(), linear).is_ok());
+    }
+
+    #[test]
+    fn test_spatial_inertia_from_nalgebra() {
+        let f = || -> Vector<f64, 7> {
+            let (mass, radius) = (2.0, 0.5);
+            let inertia = nalgebra::Matrix3::identity() * (2.0 / 5.0 * mass * radius * radius);
+            let com = nalgebra::Vector3::new(0.0, 0.0, 1.0);
+            let inertia = SpatialInertia::from_nalgebra(inertia, mass, com).unwrap();
+            inertia
+                .inertia_diag()
+                .concat(inertia.momentum())
+                .concat(inertia.mass().reshape::<Const<1>>())
+        };
+        let client = crate::Client::cpu().unwrap();
+        let comp = f.build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(res, vector![0.2, 0.2, 0.2, 0.0, 0.0, 2.0, 2.0]);
+    }
+
+    #[test]
+    fn test_full_spatial_inertia_from_nalgebra() {
+        let inertia = nalgebra::Matrix3::new(1.0, 0.1, 0.2, 0.1, 2.0, 0.3, 0.2, 0.3, 3.0);
+        let com = nalgebra::Vector3::new(0.0, 0.0, 1.0);
+        assert!(matches!(
+            SpatialInertia::from_nalgebra(inertia, 2.0, com),
+            Err(Error::OffDiagonalInertia)
+        ));
+        let mut asymmetric = inertia;
+        asymmetric[(1, 0)] = 0.5;
+        assert!(matches!(
+            FullSpatialInertia::from_nalgebra(asymmetric, 2.0, com),
+            Err(Error::AsymmetricInertia)
+        ));
+        // products of inertia left over from rounding are dropped
+        let mut rounded = nalgebra::Matrix3::from_diagonal(&nalgebra::Vector3::new(1.0, 2.0, 3.0));
+        rounded[(0, 1)] = 1e-15;
+        rounded[(1, 0)] = 1e-15;
+        assert!(SpatialInertia::from_nalgebra(rounded, 2.0, com).is_ok());
+        let f = || -> Vector<f64, 10> {
+            FullSpatialInertia::from_nalgebra(inertia, 2.0, com)
+                .unwrap()
+                .inner
+        };
+        let client = crate::Client::cpu().unwrap();
+        let comp = f.build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(
+            res,
+            vector![1.0, 2.0, 3.0, 0.1, 0.2, 0.3, 0.0, 0.0, 2.0, 2.0]
+        );
+    }
+
+    #[test]
+    fn test_spatial_scalar_generic() {
+        fn step<T: SpatialScalar>(
+            pos: SpatialTransform<T>,
+            vel: SpatialMotion<T>,
+            dt: T,
+        ) -> Vector<T, 3> {
+            (pos + vel * dt).linear()
+        }
+
+        let client = crate::Client::cpu().unwrap();
+        let f = || -> Vector<f64, 3> {
+            let pos = SpatialTransform::from_linear(vector![1.0f64, 0.0, 0.0]);
+            let vel = 2.0f64 * SpatialMotion::from_linear(vector![0.0f64, 1.0, 0.0]);
+            step(pos, vel, 0.5)
+        };
+        let exec = f.build().unwrap().compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(res, vector![1.0, 1.0, 0.0]);
+
+        let f = || -> Vector<f32, 3> {
+            let pos = SpatialTransform::from_linear(vector![1.0f32, 0.0, 0.0]);
+            let vel = 2.0f32 * SpatialMotion::from_linear(vector![0.0f32, 1.0, 0.0]);
+            step(pos, vel, 0.5)
+        };
+        let exec = f.build().unwrap().compile(&client).unwrap();
+        let res = exec.run(&client).unwrap().to_host();
+        approx::assert_relative_eq!(res, vector![1.0f32, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_spatial_transform_mul_vmap() {
+        fn mul(a: Vector<f64, 7>, b: Vector<f64, 7>) -> Vector<f64, 7> {
+            (SpatialTransform { inner: a } * SpatialTransform { inner: b }).inner
+        }
+        fn pose(angle: f64, axis: Vector3<f64>, t: Vector3<f64>) -> nalgebra::SVector<f64, 7> {
+            let axis = nalgebra::Unit::new_normalize(axis);
+            let q = nalgebra::UnitQuaternion::from_axis_angle(&axis, angle);
+            nalgebra::SVector::from_iterator(q.coords.iter().chain(t.iter()).copied())
+        }
+        let lhs = [
+            pose(0.3, vector![1.0, 0.0, 0.0], vector![1.0, 2.0, 3.0]),
+            pose(-1.2, vector![0.0, 1.0, 1.0], vector![0.0, -1.0, 0.5]),
+            pose(2.0, vector![1.0, 1.0, 1.0], vector![4.0, 0.0, 0.0]),
+            pose(0.0, vector![0.0, 0.0, 1.0], vector![0.0, 0.0, 0.0]),
+        ];
+        let rhs = [
+            pose(0.7, vector![0.0, 0.0, 1.0], vector![-1.0, 0.0, 2.0]),
+            pose(1.5, vector![1.0, -1.0, 0.0], vector![0.5, 0.5, 0.5]),
+            pose(-0.4, vector![2.0, 0.0, 1.0], vector![0.0, 3.0, -2.0]),
+            pose(3.0, vector![0.0, 1.0, 0.0], vector![1.0, 1.0, 1.0]),
+        ];
+        let client = crate::Client::cpu().unwrap();
+
+        let batched = mul.vmap(4).unwrap();
+        type Batch = Matrix<f64, 4, 7>;
+        let exec = Comp::<(Batch, Batch), Batch>::from_expr(&batched, "mul_vmap")
+            .unwrap()
+            .compile(&client)
+            .unwrap();
+        let out = exec
+            .run(
+                &client,
+                nalgebra::SMatrix::<f64, 4, 7>::from_rows(&lhs.map(|p| p.transpose())),
+                nalgebra::SMatrix::<f64, 4, 7>::from_rows(&rhs.map(|p| p.transpose())),
+            )
+            .unwrap()
+            .to_host();
+
+        let exec = mul.build().unwrap().compile(&client).unwrap();
+        for (i, (a, b)) in lhs.into_iter().zip(rhs).enumerate() {
+            let expected = exec.run(&client, a, b).unwrap().to_host();
+            approx::assert_relative_eq!(out.row(i).transpose(), expected, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_spatial_transform_mul_dump() {
+        let mul =
+            |a: SpatialTransform<f64>, b: SpatialTransform<f64>| -> SpatialTransform<f64> { a * b };
+        let expr = mul.build_expr().unwrap();
+        let noxpr = expr.to_string();
+        assert_eq!(noxpr.matches("param(").count(), 2);
+        assert!(noxpr.contains(" * "));
+
+        let hlo = mul.build().unwrap().to_hlo_text().unwrap();
+        assert!(hlo.contains("multiply"));
+    }
 }
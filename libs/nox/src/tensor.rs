@@ -1,5 +1,6 @@
 use crate::{
-    AsBuffer, Buffer, Field, FromOp, IntoOp, Noxpr, NoxprScalarExt, Op, Param, Scalar, Vector,
+    ArrayTy, AsBuffer, Buffer, Error, Field, FromOp, IntoOp, Noxpr, NoxprScalarExt, Op, Param,
+    Scalar, SpatialScalar, Vector,
 };
 use nalgebra::{constraint::ShapeConstraint, ClosedMul, Const, Scalar as NalgebraScalar};
 use simba::scalar::ClosedNeg;
@@ -117,12 +118,338 @@ impl<T, D: TensorDim> Tensor<T, D, Op> {
     pub fn log(&self) -> Self {
         Self::from_op(self.inner.clone().log())
     }
+
+    pub fn sin(&self) -> Self {
+        Self::from_op(self.inner.clone().sin())
+    }
+
+    pub fn cos(&self) -> Self {
+        Self::from_op(self.inner.clone().cos())
+    }
+
+    pub fn exp(&self) -> Self
+    where
+        T: num_traits::Float,
+    {
+        Self::from_op(self.inner.clone().exp())
+    }
+
+    pub fn tanh(&self) -> Self
+    where
+        T: num_traits::Float,
+    {
+        Self::from_op(self.inner.clone().tanh())
+    }
+
+    pub fn abs(&self) -> Self {
+        Self::from_op(self.inner.clone().abs())
+    }
+
+    /// Limits each element to `[min, max]`, where the bounds are either the same shape as
+    /// `self` or scalars applied to every element.
+    pub fn clamp<D2: TensorDim>(
+        &self,
+        min: &Tensor<T, D2>,
+        max: &Tensor<T, D2>,
+    ) -> Tensor<T, BroadcastedDim<D, D2>>
+    where
+        ShapeConstraint: BroadcastDim<D, D2>,
+    {
+        let (inner, min) = <ShapeConstraint as BroadcastDim<D, D2>>::broadcast(
+            self.inner.clone(),
+            min.inner.clone(),
+        );
+        let (_, max) = <ShapeConstraint as BroadcastDim<D, D2>>::broadcast(
+            self.inner.clone(),
+            max.inner.clone(),
+        );
+        Tensor::from_op(inner.max(min).min(max))
+    }
+
+    /// The elementwise four-quadrant arctangent of `self / other`.
+    pub fn atan2(&self, other: &Self) -> Self {
+        Self::from_op(self.inner.clone().atan2(other.inner.clone()))
+    }
+
+    pub fn min(&self, other: &Self) -> Self {
+        Self::from_op(self.inner.clone().min(other.inner.clone()))
+    }
+
+    pub fn max(&self, other: &Self) -> Self {
+        Self::from_op(self.inner.clone().max(other.inner.clone()))
+    }
+
+    /// Elementwise `self < other`, where `other` is either the same shape as `self` or a scalar
+    /// compared against every element.
+    pub fn less<D2: TensorDim>(&self, other: &Tensor<T, D2>) -> Tensor<bool, BroadcastedDim<D, D2>>
+    where
+        ShapeConstraint: BroadcastDim<D, D2>,
+    {
+        self.compare(other, Noxpr::less)
+    }
+
+    pub fn less_or_equal<D2: TensorDim>(
+        &self,
+        other: &Tensor<T, D2>,
+    ) -> Tensor<bool, BroadcastedDim<D, D2>>
+    where
+        ShapeConstraint: BroadcastDim<D, D2>,
+    {
+        self.compare(other, Noxpr::less_or_equal)
+    }
+
+    pub fn greater<D2: TensorDim>(
+        &self,
+        other: &Tensor<T, D2>,
+    ) -> Tensor<bool, BroadcastedDim<D, D2>>
+    where
+        ShapeConstraint: BroadcastDim<D, D2>,
+    {
+        self.compare(other, |lhs, rhs| rhs.less(lhs))
+    }
+
+    pub fn greater_or_equal<D2: TensorDim>(
+        &self,
+        other: &Tensor<T, D2>,
+    ) -> Tensor<bool, BroadcastedDim<D, D2>>
+    where
+        ShapeConstraint: BroadcastDim<D, D2>,
+    {
+        self.compare(other, Noxpr::greater_or_equal)
+    }
+
+    pub fn eq<D2: TensorDim>(&self, other: &Tensor<T, D2>) -> Tensor<bool, BroadcastedDim<D, D2>>
+    where
+        ShapeConstraint: BroadcastDim<D, D2>,
+    {
+        self.compare(other, Noxpr::equal)
+    }
+
+    fn compare<D2: TensorDim>(
+        &self,
+        other: &Tensor<T, D2>,
+        op: impl FnOnce(Noxpr, Noxpr) -> Noxpr,
+    ) -> Tensor<bool, BroadcastedDim<D, D2>>
+    where
+        ShapeConstraint: BroadcastDim<D, D2>,
+    {
+        let (lhs, rhs) = <ShapeConstraint as BroadcastDim<D, D2>>::broadcast(
+            self.inner.clone(),
+            other.inner.clone(),
+        );
+        Tensor::from_op(op(lhs, rhs))
+    }
+
+    /// Picks each element from `on_true` where `mask` is set and from `on_false` elsewhere.
+    /// Both sides are always computed, since XLA can't branch on runtime values.
+    pub fn select(mask: &Tensor<bool, D>, on_true: Self, on_false: Self) -> Self {
+        Self::from_op(mask.inner.select(on_true.inner, on_false.inner))
+    }
+
+    pub fn sum_axis<const AXIS: usize>(&self) -> Tensor<T, D::Output>
+    where
+        D: RemoveAxis<AXIS>,
+    {
+        Tensor::from_op(self.inner.clone().reduce_sum(smallvec![AXIS as i64]))
+    }
+
+    pub fn max_axis<const AXIS: usize>(&self) -> Tensor<T, D::Output>
+    where
+        D: RemoveAxis<AXIS>,
+    {
+        Tensor::from_op(self.inner.clone().reduce_max(smallvec![AXIS as i64]))
+    }
+
+    /// Reorders the axes of the tensor, see [`Permutation`].
+    pub fn permute<P: Permutation<D>>(&self) -> Tensor<T, P::Output> {
+        let () = P::VALID;
+        Tensor::from_op(self.inner.clone().transpose(SmallVec::from_slice(P::AXES)))
+    }
+
+    pub fn mean_axis<const AXIS: usize>(&self) -> Tensor<T, D::Output>
+    where
+        D: RemoveAxis<AXIS> + XlaDim,
+        T: SpatialScalar,
+    {
+        let len = nalgebra::convert::<f64, T>(D::dims().as_ref()[AXIS] as f64);
+        Tensor::from_op(self.sum_axis::<AXIS>().inner / len.constant())
+    }
+
+    /// The p-norm of every slice along `AXIS`, `(sum |x|^p)^(1/p)`, so that for a matrix
+    /// `lp_norm::<1>` gives the norm of each row.
+    ///
+    /// `p` is a host value, so `1.0`, `2.0` and `f64::INFINITY` (the largest `|x|`) lower to
+    /// direct compositions, while any other `p` goes through `exp(p * log |x|)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `p` is not positive.
+    pub fn lp_norm<const AXIS: usize>(&self, p: f64) -> Tensor<T, D::Output>
+    where
+        D: RemoveAxis<AXIS>,
+        T: NativeType + ArrayElement + num_traits::FromPrimitive,
+    {
+        assert!(p > 0.0, "lp_norm needs a positive p, found {p}");
+        let abs = self.abs();
+        if p == f64::INFINITY {
+            return abs.max_axis::<AXIS>();
+        }
+        if p == 1.0 {
+            return abs.sum_axis::<AXIS>();
+        }
+        if p == 2.0 {
+            return Tensor::from_op(abs.inner.clone() * abs.inner)
+                .sum_axis::<AXIS>()
+                .sqrt();
+        }
+        let p = T::from_f64(p)
+            .expect("p must fit in the element type")
+            .constant();
+        let powers = Tensor::<T, D>::from_op((abs.inner.log() * p.clone()).exp());
+        Tensor::from_op((powers.sum_axis::<AXIS>().inner.log() / p).exp())
+    }
+
+    /// Exponentiates and normalizes along `AXIS`, so that every slice along it sums to one.
+    ///
+    /// The max of each slice is subtracted before exponentiating, which leaves the result
+    /// unchanged but keeps large logits from overflowing.
+    pub fn softmax<const AXIS: usize>(&self) -> Self
+    where
+        D: RemoveAxis<AXIS> + XlaDim,
+    {
+        let (shape, kept_dims) = Self::axis_broadcast_dims(AXIS);
+        let max = self
+            .max_axis::<AXIS>()
+            .inner
+            .broadcast_in_dim(shape.clone(), kept_dims.clone());
+        let exp = (self.inner.clone() - max).exp();
+        let sum = exp
+            .clone()
+            .reduce_sum(smallvec![AXIS as i64])
+            .broadcast_in_dim(shape, kept_dims);
+        Self::from_op(exp / sum)
+    }
+
+    /// The index of the largest element along `AXIS`, preferring the lowest index on ties.
+    ///
+    /// Fails with [`Error::InvalidAxisLen`] if `AXIS` is empty or dynamic, since there is then no
+    /// index to return.
+    pub fn argmax<const AXIS: usize>(&self) -> Result<Tensor<u32, D::Output>, Error>
+    where
+        D: RemoveAxis<AXIS> + XlaDim,
+    {
+        let (shape, kept_dims) = Self::axis_broadcast_dims(AXIS);
+        let len = shape[AXIS];
+        let last = u32::try_from(len - 1).map_err(|_| Error::InvalidAxisLen { axis: AXIS, len })?;
+        let max = self
+            .max_axis::<AXIS>()
+            .inner
+            .broadcast_in_dim(shape.clone(), kept_dims);
+        let is_max = self.inner.clone().equal(max);
+        // count indices down from the end of the axis, so that the max reduction picks the
+        // first maximal element rather than the last
+        let last = last.constant();
+        let reversed =
+            last.clone() - Noxpr::iota(ArrayTy::new(ElementType::U32, shape.clone()), AXIS);
+        let zeros = 0u32.constant().broadcast_in_dim(shape, smallvec![]);
+        let candidates = is_max.select(reversed, zeros);
+        Ok(Tensor::from_op(
+            last - candidates.reduce_max(smallvec![AXIS as i64]),
+        ))
+    }
+
+    /// The static shape of `D`, and the dimensions left once `axis` is removed, for broadcasting
+    /// a reduction along `axis` back out to the full shape.
+    fn axis_broadcast_dims(axis: usize) -> (SmallVec<[i64; 4]>, SmallVec<[i64; 4]>)
+    where
+        D: XlaDim,
+    {
+        let shape: SmallVec<[i64; 4]> = SmallVec::from_slice(D::dims().as_ref());
+        let kept_dims = (0..shape.len() as i64)
+            .filter(|dim| *dim != axis as i64)
+            .collect();
+        (shape, kept_dims)
+    }
+}
+
+impl<T, A: TensorDim, B: TensorDim> Tensor<T, (A, B), Op> {
+    /// Swaps the two axes of a rank 2 tensor.
+    pub fn transpose(&self) -> Tensor<T, (B, A)> {
+        self.permute::<(Axis<1>, Axis<0>)>()
+    }
 }
 
 impl<T: Field, D: TensorDim + XlaDim> Tensor<T, D, Op> {
     pub fn zeros() -> Self {
         T::zero().broadcast()
     }
+
+    /// The running sum along `AXIS`, so that each element is the sum of itself and every element
+    /// before it on that axis.
+    ///
+    /// XLA has no dedicated scan op, so this broadcasts the axis out into a lower-triangular
+    /// mask and reduces it, which costs `O(n^2)` in the axis length.
+    pub fn cumsum<const AXIS: usize>(&self) -> Self
+    where
+        D: RemoveAxis<AXIS>,
+    {
+        let shape: SmallVec<[i64; 4]> = D::dims().as_ref().iter().copied().collect();
+        let rank = shape.len();
+        // the extra trailing dim indexes the elements being summed into each output position
+        let mut wide_shape = shape.clone();
+        wide_shape.push(shape[AXIS]);
+        let dims = (0..rank as i64)
+            .map(|dim| if dim == AXIS as i64 { rank as i64 } else { dim })
+            .collect();
+        let wide = self
+            .inner
+            .clone()
+            .broadcast_in_dim(wide_shape.clone(), dims);
+        let iota = |dim| Noxpr::iota(ArrayTy::new(ElementType::S64, wide_shape.clone()), dim);
+        let mask = iota(rank).less_or_equal(iota(AXIS));
+        let zeros = T::zero()
+            .inner
+            .broadcast_in_dim(wide_shape.clone(), smallvec![]);
+        Self::from_op(mask.select(wide, zeros).reduce_sum(smallvec![rank as i64]))
+    }
+}
+
+impl<T, D> Tensor<T, D, Op>
+where
+    T: NativeType + ArrayElement + num_traits::FromPrimitive,
+    D: TensorDim + XlaDim,
+{
+    /// Samples uniformly from `[0, 1)` using XLA's stateless generator.
+    ///
+    /// The samples depend only on `key`, so they are identical across runs and devices.
+    pub fn random_uniform(key: u64) -> Self {
+        let shape = D::dims().as_ref().iter().copied().collect();
+        Self::from_op(Noxpr::rng_uniform([key, 0], ArrayTy::new(T::TY, shape)))
+    }
+
+    /// Samples from the standard normal distribution, keyed like [`Tensor::random_uniform`].
+    pub fn random_normal(key: u64) -> Self {
+        let dims: SmallVec<[i64; 4]> = D::dims().as_ref().iter().copied().collect();
+        // draw both Box-Muller inputs in one call so the two halves never share a counter
+        let mut pair_shape = smallvec![2];
+        pair_shape.extend_from_slice(&dims);
+        let pair = Noxpr::rng_uniform([key, 0], ArrayTy::new(T::TY, pair_shape.clone()));
+        let half = |i: i64| {
+            let mut start: SmallVec<[i64; 4]> = smallvec![0; pair_shape.len()];
+            let mut stop = pair_shape.clone();
+            start[0] = i;
+            stop[0] = i + 1;
+            let strides = smallvec![1; pair_shape.len()];
+            pair.clone()
+                .slice(start, stop, strides)
+                .reshape(dims.clone())
+        };
+        let constant = |x: f64| T::from_f64(x).expect("constant must fit in T").constant();
+        // 1 - u is in (0, 1], which keeps the log finite
+        let radius = ((constant(1.0) - half(0)).log() * constant(-2.0)).sqrt();
+        let angle = half(1) * constant(std::f64::consts::TAU);
+        Self::from_op(radius * angle.cos())
+    }
 }
 
 impl<T, D: TensorDim> IntoOp for Tensor<T, D, Op> {
@@ -248,6 +575,228 @@ impl_tensor_dim!(10; T1, T2, T3, T4, T5, T6, T7, T9, T10, T11);
 impl_tensor_dim!(11; T1, T2, T3, T4, T5, T6, T7, T9, T10, T11, T12);
 impl_tensor_dim!(12; T1, T2, T3, T4, T5, T6, T7, T9, T10, T11, T12, T13);
 
+/// The dimension of a tensor with axis `AXIS` replaced by the dimension `I` of an index tensor,
+/// as returned by [`Tensor::gather`]
+pub trait GatherDim<const AXIS: usize, I: TensorDim>: TensorDim {
+    type Output: TensorDim;
+}
+
+impl<const N: usize, I: TensorDim> GatherDim<0, I> for Const<N> {
+    type Output = I;
+}
+
+impl<A: TensorDim, B: TensorDim> GatherDim<0, ScalarDim> for (A, B) {
+    type Output = B;
+}
+
+impl<A: TensorDim, B: TensorDim> GatherDim<1, ScalarDim> for (A, B) {
+    type Output = A;
+}
+
+impl<A: TensorDim, B: TensorDim, const K: usize> GatherDim<0, Const<K>> for (A, B) {
+    type Output = (Const<K>, B);
+}
+
+impl<A: TensorDim, B: TensorDim, const K: usize> GatherDim<1, Const<K>> for (A, B) {
+    type Output = (A, Const<K>);
+}
+
+impl<A: TensorDim, B: TensorDim, I1: TensorDim, I2: TensorDim> GatherDim<0, (I1, I2)> for (A, B) {
+    type Output = (I1, I2, B);
+}
+
+impl<A: TensorDim, B: TensorDim, I1: TensorDim, I2: TensorDim> GatherDim<1, (I1, I2)> for (A, B) {
+    type Output = (A, I1, I2);
+}
+
+impl<A: TensorDim, B: TensorDim, C: TensorDim, const K: usize> GatherDim<0, Const<K>>
+    for (A, B, C)
+{
+    type Output = (Const<K>, B, C);
+}
+
+impl<A: TensorDim, B: TensorDim, C: TensorDim, const K: usize> GatherDim<1, Const<K>>
+    for (A, B, C)
+{
+    type Output = (A, Const<K>, C);
+}
+
+impl<A: TensorDim, B: TensorDim, C: TensorDim, const K: usize> GatherDim<2, Const<K>>
+    for (A, B, C)
+{
+    type Output = (A, B, Const<K>);
+}
+
+/// The dimension left behind once axis `AXIS` is reduced away
+pub trait RemoveAxis<const AXIS: usize>: TensorDim {
+    type Output: TensorDim;
+}
+
+impl<const N: usize> RemoveAxis<0> for Const<N> {
+    type Output = ScalarDim;
+}
+
+impl<A: TensorDim, B: TensorDim> RemoveAxis<0> for (A, B) {
+    type Output = B;
+}
+
+impl<A: TensorDim, B: TensorDim> RemoveAxis<1> for (A, B) {
+    type Output = A;
+}
+
+impl<A: TensorDim, B: TensorDim, C: TensorDim> RemoveAxis<0> for (A, B, C) {
+    type Output = (B, C);
+}
+
+impl<A: TensorDim, B: TensorDim, C: TensorDim> RemoveAxis<1> for (A, B, C) {
+    type Output = (A, C);
+}
+
+impl<A: TensorDim, B: TensorDim, C: TensorDim> RemoveAxis<2> for (A, B, C) {
+    type Output = (A, B);
+}
+
+/// The dimension with a new axis of length `N` inserted at position `AXIS`
+pub trait InsertAxis<const AXIS: usize, const N: usize>: TensorDim {
+    type Output: TensorDim;
+}
+
+impl<const N: usize> InsertAxis<0, N> for ScalarDim {
+    type Output = Const<N>;
+}
+
+impl<const M: usize, const N: usize> InsertAxis<0, N> for Const<M> {
+    type Output = (Const<N>, Const<M>);
+}
+
+impl<const M: usize, const N: usize> InsertAxis<1, N> for Const<M> {
+    type Output = (Const<M>, Const<N>);
+}
+
+impl<A: TensorDim, B: TensorDim, const N: usize> InsertAxis<0, N> for (A, B) {
+    type Output = (Const<N>, A, B);
+}
+
+impl<A: TensorDim, B: TensorDim, const N: usize> InsertAxis<1, N> for (A, B) {
+    type Output = (A, Const<N>, B);
+}
+
+impl<A: TensorDim, B: TensorDim, const N: usize> InsertAxis<2, N> for (A, B) {
+    type Output = (A, B, Const<N>);
+}
+
+/// The dimension with axis `AXIS` made `N` times longer
+pub trait RepeatAxis<const AXIS: usize, const N: usize>: TensorDim {
+    type Output: TensorDim;
+}
+
+impl<const M: usize, const N: usize> RepeatAxis<0, N> for Const<M>
+where
+    Const<M>: nalgebra::DimMul<Const<N>>,
+    MulDim<Const<M>, Const<N>>: TensorDim,
+{
+    type Output = MulDim<Const<M>, Const<N>>;
+}
+
+impl<A: TensorDim + nalgebra::DimMul<Const<N>>, B: TensorDim, const N: usize> RepeatAxis<0, N>
+    for (A, B)
+where
+    MulDim<A, Const<N>>: TensorDim,
+{
+    type Output = (MulDim<A, Const<N>>, B);
+}
+
+impl<A: TensorDim, B: TensorDim + nalgebra::DimMul<Const<N>>, const N: usize> RepeatAxis<1, N>
+    for (A, B)
+where
+    MulDim<B, Const<N>>: TensorDim,
+{
+    type Output = (A, MulDim<B, Const<N>>);
+}
+
+/// The dimension of axis `AXIS`
+pub trait AxisDim<const AXIS: usize>: TensorDim {
+    type Output: TensorDim;
+}
+
+impl<A: TensorDim, B: TensorDim> AxisDim<0> for (A, B) {
+    type Output = A;
+}
+
+impl<A: TensorDim, B: TensorDim> AxisDim<1> for (A, B) {
+    type Output = B;
+}
+
+impl<A: TensorDim, B: TensorDim, C: TensorDim> AxisDim<0> for (A, B, C) {
+    type Output = A;
+}
+
+impl<A: TensorDim, B: TensorDim, C: TensorDim> AxisDim<1> for (A, B, C) {
+    type Output = B;
+}
+
+impl<A: TensorDim, B: TensorDim, C: TensorDim> AxisDim<2> for (A, B, C) {
+    type Output = C;
+}
+
+/// Names an input axis in a [`Permutation`]
+pub struct Axis<const AXIS: usize>;
+
+/// A reordering of the axes of `D`, written as a tuple of [`Axis`] naming the input axis that
+/// ends up at each position, e.g. `(Axis<2>, Axis<0>, Axis<1>)` moves the last axis to the front.
+///
+/// Out of range axes fail to satisfy [`AxisDim`], and repeated axes fail when `VALID` is evaluated,
+/// so a bad permutation is rejected at compile time.
+pub trait Permutation<D: TensorDim> {
+    type Output: TensorDim;
+    const AXES: &'static [i64];
+    const VALID: ();
+}
+
+const fn is_permutation(axes: &[usize]) -> bool {
+    let mut i = 0;
+    while i < axes.len() {
+        let mut j = i + 1;
+        while j < axes.len() {
+            if axes[i] == axes[j] {
+                return false;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    true
+}
+
+impl<D, const I: usize, const J: usize> Permutation<D> for (Axis<I>, Axis<J>)
+where
+    D: AxisDim<I> + AxisDim<J>,
+    (<D as AxisDim<I>>::Output, <D as AxisDim<J>>::Output): TensorDim,
+{
+    type Output = (<D as AxisDim<I>>::Output, <D as AxisDim<J>>::Output);
+    const AXES: &'static [i64] = &[I as i64, J as i64];
+    const VALID: () = assert!(is_permutation(&[I, J]), "axes must be a permutation");
+}
+
+impl<D, const I: usize, const J: usize, const K: usize> Permutation<D>
+    for (Axis<I>, Axis<J>, Axis<K>)
+where
+    D: AxisDim<I> + AxisDim<J> + AxisDim<K>,
+    (
+        <D as AxisDim<I>>::Output,
+        <D as AxisDim<J>>::Output,
+        <D as AxisDim<K>>::Output,
+    ): TensorDim,
+{
+    type Output = (
+        <D as AxisDim<I>>::Output,
+        <D as AxisDim<J>>::Output,
+        <D as AxisDim<K>>::Output,
+    );
+    const AXES: &'static [i64] = &[I as i64, J as i64, K as i64];
+    const VALID: () = assert!(is_permutation(&[I, J, K]), "axes must be a permutation");
+}
+
 pub trait DimAdd<D1: TensorDim, D2: TensorDim> {}
 pub trait DimSub<D1: TensorDim, D2: TensorDim> {}
 
@@ -279,7 +828,8 @@ macro_rules! impl_op {
             type Output = Tensor<T, BroadcastedDim<D1, D2>>;
 
             fn $op_fn(self, rhs: Tensor<T, D2>) -> Self::Output {
-                Tensor::from_op(self.inner.clone() $inner rhs.inner.clone())
+                let (lhs, rhs) = <ShapeConstraint as BroadcastDim<D1, D2>>::broadcast(self.inner.clone(), rhs.inner.clone());
+                Tensor::from_op(lhs $inner rhs)
             }
         }
 
@@ -292,7 +842,8 @@ macro_rules! impl_op {
             type Output = Tensor<T, BroadcastedDim<D1, D2>>;
 
             fn $op_fn(self, rhs: &'a Tensor<T, D2>) -> Self::Output {
-                Tensor::from_op(self.inner.clone() $inner rhs.inner.clone())
+                let (lhs, rhs) = <ShapeConstraint as BroadcastDim<D1, D2>>::broadcast(self.inner.clone(), rhs.inner.clone());
+                Tensor::from_op(lhs $inner rhs)
             }
         }
 
@@ -305,7 +856,8 @@ macro_rules! impl_op {
             type Output = Tensor<T, BroadcastedDim<D1, D2>>;
 
             fn $op_fn(self, rhs: Tensor<T, D2>) -> Self::Output {
-                Tensor::from_op(self.inner.clone() $inner rhs.inner.clone())
+                let (lhs, rhs) = <ShapeConstraint as BroadcastDim<D1, D2>>::broadcast(self.inner.clone(), rhs.inner.clone());
+                Tensor::from_op(lhs $inner rhs)
             }
         }
 
@@ -319,7 +871,8 @@ macro_rules! impl_op {
             type Output = Tensor<T, BroadcastedDim<D1, D2>>;
 
             fn $op_fn(self, rhs: &'b Tensor<T, D2>) -> Self::Output {
-                Tensor::from_op((self.inner.clone() $inner rhs.inner.clone()))
+                let (lhs, rhs) = <ShapeConstraint as BroadcastDim<D1, D2>>::broadcast(self.inner.clone(), rhs.inner.clone());
+                Tensor::from_op(lhs $inner rhs)
             }
         }
 
@@ -361,10 +914,39 @@ impl<T, D: TensorDim + DimRank<R>, const R: usize> FixedSliceExt<T, D, R> for Te
                 .slice(offsets, new_offsets, smallvec![1i64; R]),
         )
     }
+
+    fn try_fixed_slice<ND: TensorDim + ConstDim<R>>(
+        &self,
+        offsets: [usize; R],
+    ) -> Result<Tensor<T, ND, Op>, Error>
+    where
+        D: ConstDim<R>,
+    {
+        let in_bounds = offsets
+            .iter()
+            .zip(ND::dims())
+            .zip(D::dims())
+            .all(|((offset, len), dim)| offset + len <= dim);
+        if !in_bounds {
+            return Err(Error::OutOfBoundsAccess);
+        }
+        Ok(self.fixed_slice(offsets))
+    }
 }
 
 pub trait FixedSliceExt<T, D: TensorDim, const R: usize> {
+    /// Slices out a tensor of shape `ND` starting at `offsets`, without checking that the slice
+    /// fits inside this tensor.
     fn fixed_slice<ND: TensorDim + ConstDim<R>>(&self, offsets: [usize; R]) -> Tensor<T, ND, Op>;
+
+    /// Like [`FixedSliceExt::fixed_slice`], but fails with [`Error::OutOfBoundsAccess`] when
+    /// the slice would extend past the end of this tensor.
+    fn try_fixed_slice<ND: TensorDim + ConstDim<R>>(
+        &self,
+        offsets: [usize; R],
+    ) -> Result<Tensor<T, ND, Op>, Error>
+    where
+        D: ConstDim<R>;
 }
 
 impl<T: NalgebraScalar + ClosedMul + NativeType + ArrayElement, D1: TensorDim> Mul<T>
@@ -584,9 +1166,23 @@ impl<T, D: TensorDim> Tensor<T, D> {
             phantom: PhantomData,
         }
     }
+
+    /// Broadcasts to `ND` following numpy's rules, so trailing dimensions line up and any of
+    /// size one are stretched, unlike [`Tensor::broadcast`] which only adds leading dimensions.
+    pub fn broadcast_to<ND>(self) -> Tensor<T, ND>
+    where
+        ND: TensorDim + XlaDim,
+        ND::Array: AsRef<[i64]>,
+    {
+        Tensor::from_op(
+            self.inner
+                .broadcast_to(SmallVec::from_slice(ND::dims().as_ref())),
+        )
+    }
 }
 
 type AddDim<A, B> = <A as nalgebra::DimAdd<B>>::Output;
+type MulDim<A, B> = <A as nalgebra::DimMul<B>>::Output;
 
 #[allow(clippy::type_complexity)]
 impl<T: TensorItem, D: TensorDim + DefaultMap> Tensor<T, D, crate::Op> {
@@ -635,8 +1231,61 @@ impl<T: TensorItem, D: TensorDim + DefaultMap> Tensor<T, D, crate::Op> {
     }
 }
 
+impl<T: TensorItem, D: TensorDim + XlaDim> Tensor<T, D, crate::Op> {
+    /// Concatenates `N` tensors of the same shape along `AXIS`, like a chain of
+    /// [`Tensor::concat_with_dim`] calls lowered to a single XLA concatenate.
+    pub fn concat_many<const AXIS: usize, const N: usize>(
+        tensors: [Self; N],
+    ) -> Tensor<T, <D as RepeatAxis<AXIS, N>>::Output>
+    where
+        D: RepeatAxis<AXIS, N>,
+    {
+        let nodes = tensors.into_iter().map(|t| t.inner).collect();
+        Tensor {
+            inner: Noxpr::concat_in_dim(nodes, AXIS),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Stacks `N` tensors of the same shape along a new axis of length `N`, inserted at `AXIS`.
+    pub fn stack<const AXIS: usize, const N: usize>(
+        tensors: [Self; N],
+    ) -> Tensor<T, <D as InsertAxis<AXIS, N>>::Output>
+    where
+        D: InsertAxis<AXIS, N>,
+    {
+        let mut shape: SmallVec<[i64; 4]> = SmallVec::from_slice(D::dims().as_ref());
+        shape.insert(AXIS, 1);
+        let nodes = tensors
+            .into_iter()
+            .map(|t| t.inner.reshape(shape.clone()))
+            .collect();
+        Tensor {
+            inner: Noxpr::concat_in_dim(nodes, AXIS),
+            phantom: PhantomData,
+        }
+    }
+}
+
 pub trait BroadcastDim<D1, D2> {
     type Output: TensorDim;
+
+    /// Broadcasts the operands of an elementwise op to `Output`, using only the static dims.
+    ///
+    /// XLA broadcasts scalars itself, and dynamic dims aren't known until the op is lowered, so
+    /// by default the operands are passed through untouched.
+    fn broadcast(lhs: Noxpr, rhs: Noxpr) -> (Noxpr, Noxpr) {
+        (lhs, rhs)
+    }
+}
+
+/// Broadcasts `expr`, whose dims are `From`, to `To`, lining up the trailing dims.
+fn broadcast_static<From: XlaDim, To: XlaDim>(expr: Noxpr) -> Noxpr {
+    let to: SmallVec<[i64; 4]> = SmallVec::from_slice(To::dims().as_ref());
+    let rank = From::dims().as_ref().len();
+    let offset = (to.len() - rank) as i64;
+    let broadcast_dims = (0..rank as i64).map(|dim| dim + offset).collect();
+    expr.broadcast_in_dim(to, broadcast_dims)
 }
 
 pub type BroadcastedDim<D1, D2> = <ShapeConstraint as BroadcastDim<D1, D2>>::Output;
@@ -645,12 +1294,20 @@ impl<D: TensorDim> BroadcastDim<D, D> for ShapeConstraint {
     type Output = D;
 }
 
-impl<D: TensorDim + NotConst1> BroadcastDim<D, Const<1>> for ShapeConstraint {
+impl<D: TensorDim + XlaDim + NotConst1> BroadcastDim<D, Const<1>> for ShapeConstraint {
     type Output = D;
+
+    fn broadcast(lhs: Noxpr, rhs: Noxpr) -> (Noxpr, Noxpr) {
+        (lhs, broadcast_static::<Const<1>, D>(rhs))
+    }
 }
 
-impl<D: TensorDim + NotConst1> BroadcastDim<Const<1>, D> for ShapeConstraint {
+impl<D: TensorDim + XlaDim + NotConst1> BroadcastDim<Const<1>, D> for ShapeConstraint {
     type Output = D;
+
+    fn broadcast(lhs: Noxpr, rhs: Noxpr) -> (Noxpr, Noxpr) {
+        (broadcast_static::<Const<1>, D>(lhs), rhs)
+    }
 }
 
 impl<D: TensorDim + NotConst1> BroadcastDim<D, nalgebra::Dyn> for ShapeConstraint {
@@ -669,6 +1326,38 @@ impl<D: TensorDim + NotConst1> BroadcastDim<ScalarDim, D> for ShapeConstraint {
     type Output = D;
 }
 
+impl<const N: usize, const M: usize> BroadcastDim<Const<N>, (Const<M>, Const<N>)>
+    for ShapeConstraint
+where
+    Const<N>: NotConst1,
+{
+    type Output = (Const<M>, Const<N>);
+
+    fn broadcast(lhs: Noxpr, rhs: Noxpr) -> (Noxpr, Noxpr) {
+        (broadcast_static::<Const<N>, Self::Output>(lhs), rhs)
+    }
+}
+
+impl<const N: usize, const M: usize> BroadcastDim<(Const<M>, Const<N>), Const<N>>
+    for ShapeConstraint
+where
+    Const<N>: NotConst1,
+{
+    type Output = (Const<M>, Const<N>);
+
+    fn broadcast(lhs: Noxpr, rhs: Noxpr) -> (Noxpr, Noxpr) {
+        (lhs, broadcast_static::<Const<N>, Self::Output>(rhs))
+    }
+}
+
+impl<A: TensorDim, B: TensorDim> BroadcastDim<(A, B), ScalarDim> for ShapeConstraint {
+    type Output = (A, B);
+}
+
+impl<A: TensorDim, B: TensorDim> BroadcastDim<ScalarDim, (A, B)> for ShapeConstraint {
+    type Output = (A, B);
+}
+
 pub trait NotConst1 {}
 
 seq_macro::seq!(N in 2..99 {
@@ -681,6 +1370,80 @@ impl<T: TensorItem, D: TensorDim> Tensor<T, D> {
     }
 }
 
+impl<T: TensorItem, D: TensorDim + XlaDim> Tensor<T, D> {
+    /// Gathers the slices along `AXIS` selected by `indices`, in order, like `numpy.take`: the
+    /// output has the dims of `self` with `AXIS` replaced by the dims of `indices`.
+    pub fn gather<const AXIS: usize, I: TensorDim + XlaDim>(
+        &self,
+        indices: Tensor<u32, I>,
+    ) -> Tensor<T, D::Output>
+    where
+        D: GatherDim<AXIS, I>,
+    {
+        let (indices, offset_dims, slice_sizes, index_vector_dim) =
+            take_dims::<D, I>(AXIS, indices.inner);
+        let inner = self.inner.clone().gather(
+            indices,
+            offset_dims,
+            smallvec![AXIS as i64],
+            smallvec![AXIS as i64],
+            slice_sizes,
+            index_vector_dim,
+        );
+        Tensor::from_op(inner)
+    }
+
+    /// Returns a copy of `self` with the slices along `AXIS` selected by `indices` replaced by
+    /// the matching slices of `updates`, the inverse of [`Tensor::gather`].
+    ///
+    /// If an index appears more than once, which of its updates is written is unspecified.
+    pub fn scatter<const AXIS: usize, I: TensorDim + XlaDim>(
+        &self,
+        indices: Tensor<u32, I>,
+        updates: Tensor<T, D::Output>,
+    ) -> Self
+    where
+        D: GatherDim<AXIS, I>,
+    {
+        let (indices, update_window_dims, _, index_vector_dim) =
+            take_dims::<D, I>(AXIS, indices.inner);
+        let inner = self.inner.clone().scatter(
+            indices,
+            updates.inner,
+            update_window_dims,
+            smallvec![AXIS as i64],
+            smallvec![AXIS as i64],
+            index_vector_dim,
+        );
+        Tensor::from_op(inner)
+    }
+}
+
+/// Lowers the indices of [`Tensor::gather`] and [`Tensor::scatter`] along `axis`, returning them
+/// with a trailing index vector dim, along with the offset dims, the slice sizes and the index
+/// vector dim.
+#[allow(clippy::type_complexity)]
+fn take_dims<D: XlaDim, I: XlaDim>(
+    axis: usize,
+    indices: Noxpr,
+) -> (Noxpr, SmallVec<[i64; 4]>, SmallVec<[i64; 4]>, i64) {
+    let operand = D::dims();
+    let operand = operand.as_ref();
+    let index_dims = I::dims();
+    let index_rank = index_dims.as_ref().len() as i64;
+    let mut index_shape: SmallVec<[i64; 4]> = SmallVec::from_slice(index_dims.as_ref());
+    index_shape.push(1);
+    let indices = indices.broadcast_in_dim(index_shape, (0..index_rank).collect());
+    // the dims of `self` before and after `axis` surround the dims of the indices
+    let (axis_dim, rank) = (axis as i64, operand.len() as i64);
+    let offset_dims = (0..axis_dim)
+        .chain(axis_dim + index_rank..index_rank + rank - 1)
+        .collect();
+    let mut slice_sizes = SmallVec::from_slice(operand);
+    slice_sizes[axis] = 1;
+    (indices, offset_dims, slice_sizes, index_rank)
+}
+
 pub trait TensorIndex<T, D: TensorDim> {
     type Output;
 
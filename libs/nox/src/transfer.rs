@@ -109,10 +109,14 @@ impl_buffer_form!(T1, T2, T3, T4, T5);
 impl_buffer_form!(T1, T2, T3, T4, T5, T6);
 impl_buffer_form!(T1, T2, T3, T4, T5, T6, T7);
 impl_buffer_form!(T1, T2, T3, T4, T5, T6, T7, T8);
-impl_buffer_form!(T1, T2, T3, T4, T5, T6, T7, T9, T10);
-impl_buffer_form!(T1, T2, T3, T4, T5, T6, T7, T9, T10, T11);
-impl_buffer_form!(T1, T2, T3, T4, T5, T6, T7, T9, T10, T11, T12);
-impl_buffer_form!(T1, T2, T3, T4, T5, T6, T7, T9, T10, T11, T12, T13);
+impl_buffer_form!(T1, T2, T3, T4, T5, T6, T7, T8, T9);
+impl_buffer_form!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
+impl_buffer_form!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+impl_buffer_form!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
+impl_buffer_form!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13);
+impl_buffer_form!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14);
+impl_buffer_form!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15);
+impl_buffer_form!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16);
 
 pub enum MaybeOwned<'a, T> {
     Owned(T),
@@ -1,4 +1,4 @@
-use crate::{ArrayTy, Field, FixedSliceExt};
+use crate::{ArrayTy, Field, FixedSliceExt, ScalarExt, SpatialScalar};
 use nalgebra::{ArrayStorage, Const, Scalar as NalgebraScalar};
 use num_traits::Zero;
 use smallvec::smallvec;
@@ -87,6 +87,15 @@ impl<T, const R: usize> Vector<T, R, Op> {
         self.dot(self).sqrt()
     }
 
+    /// Returns the element at `index` as a scalar, panicking if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> Scalar<T> {
+        assert!(
+            index < R,
+            "index {index} out of bounds for vector of length {R}"
+        );
+        self.fixed_slice::<Const<1>>([index]).reshape()
+    }
+
     pub fn parts(&self) -> [Vector<T, 1>; R] {
         let mut i = 0;
         [0; R].map(|_| {
@@ -97,7 +106,22 @@ impl<T, const R: usize> Vector<T, R, Op> {
     }
 }
 
+impl<T: SpatialScalar, const R: usize> Vector<T, R, Op> {
+    /// Scales the vector to unit length. Below a norm of `T::default_epsilon()` the direction is
+    /// meaningless, so zeros are returned instead.
+    pub fn normalize(&self) -> Self {
+        let norm = self.norm();
+        let epsilon = T::default_epsilon().constant();
+        // both sides of a select are computed, so the division must stay finite for zero too
+        let divisor = norm.select_less(&epsilon, <T as Field>::one(), norm.clone());
+        let unit = Self::from_op(self.inner.clone() / divisor.inner);
+        norm.select_less(&epsilon, Self::zeros(), unit)
+    }
+}
+
 impl<T: Field> Vector<T, 3, Op> {
+    /// The right-handed cross product `self × other`, e.g. the torque `r × F` of a force
+    /// applied at `r`.
     pub fn cross(&self, other: &Self) -> Self {
         let [ax, ay, az] = self.parts();
         let [bx, by, bz] = other.parts();
@@ -110,12 +134,34 @@ impl<T: Field> Vector<T, 3, Op> {
 
 #[cfg(test)]
 mod tests {
-    use nalgebra::vector;
+    use nalgebra::{matrix, vector};
 
-    use crate::CompFn;
+    use crate::{CompFn, Error, Matrix, ScalarExt};
 
     use super::*;
 
+    #[test]
+    fn test_try_fixed_slice() {
+        let client = Client::cpu().unwrap();
+        fn tail(v: Vector<f64, 3>) -> Vector<f64, 2> {
+            v.try_fixed_slice([1]).unwrap()
+        }
+        let comp = tail.build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec.run(&client, vector![1.0, 2.0, 3.0]).unwrap().to_host();
+        assert_eq!(out, vector![2.0, 3.0]);
+
+        let v: Vector<f64, 3> = vector![1.0, 2.0, 3.0].into();
+        assert!(matches!(
+            v.try_fixed_slice::<Const<2>>([2]),
+            Err(Error::OutOfBoundsAccess)
+        ));
+        assert!(matches!(
+            v.try_fixed_slice::<Const<4>>([0]),
+            Err(Error::OutOfBoundsAccess)
+        ));
+    }
+
     #[test]
     fn test_vector_from_arr() {
         let client = Client::cpu().unwrap();
@@ -143,6 +189,30 @@ mod tests {
         assert_eq!(out, vector![2.0, 4.0, 6.0]);
     }
 
+    #[test]
+    fn test_run_async() {
+        let client = Client::cpu().unwrap();
+        let comp = (|a: Vector<f32, 3>, b| a.dot(&b)).build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let first = exec
+            .run_async(&client, vector![1.0f32, 2.0, 3.0], vector![2.0, 3.0, 4.0])
+            .unwrap();
+        let second = exec
+            .run_async(&client, vector![-1.0f32, 0.0, 5.0], vector![1.0, 1.0, 1.0])
+            .unwrap();
+        let second_sync = exec
+            .run(&client, vector![-1.0f32, 0.0, 5.0], vector![1.0, 1.0, 1.0])
+            .unwrap()
+            .to_host();
+        let first_sync = exec
+            .run(&client, vector![1.0f32, 2.0, 3.0], vector![2.0, 3.0, 4.0])
+            .unwrap()
+            .to_host();
+        assert_eq!(second.wait(), second_sync);
+        assert_eq!(first.wait(), first_sync);
+        assert_eq!(first_sync, 20.0);
+    }
+
     #[test]
     fn test_vector_dot() {
         let client = Client::cpu().unwrap();
@@ -155,6 +225,82 @@ mod tests {
         assert_eq!(out, 20.0)
     }
 
+    #[test]
+    fn test_cross_unit_vectors() {
+        let client = Client::cpu().unwrap();
+        let comp = (|a: Vector<f64, 3>, b: Vector<f64, 3>| a.cross(&b))
+            .build()
+            .unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec
+            .run(&client, vector![1.0, 0.0, 0.0], vector![0.0, 1.0, 0.0])
+            .unwrap()
+            .to_host();
+        assert_eq!(out, vector![0.0, 0.0, 1.0]);
+        let out = exec
+            .run(&client, vector![0.0, 1.0, 0.0], vector![1.0, 0.0, 0.0])
+            .unwrap()
+            .to_host();
+        assert_eq!(out, vector![0.0, 0.0, -1.0]);
+    }
+
+    #[test]
+    fn test_stack_concat_many() {
+        let client = Client::cpu().unwrap();
+        fn stack(a: Vector<f64, 3>, b: Vector<f64, 3>, c: Vector<f64, 3>) -> Matrix<f64, 3, 3> {
+            Tensor::stack::<0, 3>([a, b, c])
+        }
+        let comp = stack.build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec
+            .run(
+                &client,
+                vector![1.0, 2.0, 3.0],
+                vector![4.0, 5.0, 6.0],
+                vector![7.0, 8.0, 9.0],
+            )
+            .unwrap()
+            .to_host();
+        assert_eq!(out, matrix![1.0, 2.0, 3.0; 4.0, 5.0, 6.0; 7.0, 8.0, 9.0]);
+
+        fn stack_columns(a: Vector<f64, 3>, b: Vector<f64, 3>) -> Matrix<f64, 3, 2> {
+            Tensor::stack::<1, 2>([a, b])
+        }
+        let comp = stack_columns.build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec
+            .run(&client, vector![1.0, 2.0, 3.0], vector![4.0, 5.0, 6.0])
+            .unwrap()
+            .to_host();
+        assert_eq!(out, matrix![1.0, 4.0; 2.0, 5.0; 3.0, 6.0]);
+
+        fn concat(a: Vector<f64, 2>, b: Vector<f64, 2>) -> Vector<f64, 4> {
+            Tensor::concat_many::<0, 2>([a, b])
+        }
+        let comp = concat.build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec
+            .run(&client, vector![1.0, 2.0], vector![3.0, 4.0])
+            .unwrap()
+            .to_host();
+        assert_eq!(out, vector![1.0, 2.0, 3.0, 4.0]);
+
+        fn concat_columns(a: Matrix<f64, 2, 2>, b: Matrix<f64, 2, 2>) -> Matrix<f64, 2, 4> {
+            Tensor::concat_many::<1, 2>([a, b])
+        }
+        let comp = concat_columns.build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec
+            .run(
+                &client,
+                matrix![1.0, 2.0; 3.0, 4.0],
+                matrix![5.0, 6.0; 7.0, 8.0],
+            )
+            .unwrap()
+            .to_host();
+        assert_eq!(out, matrix![1.0, 2.0, 5.0, 6.0; 3.0, 4.0, 7.0, 8.0]);
+    }
+
     #[test]
     fn test_norm_squared() {
         let client = Client::cpu().unwrap();
@@ -179,6 +325,102 @@ mod tests {
         assert_eq!(out, 14.0f32.sqrt())
     }
 
+    #[test]
+    fn test_normalize() {
+        let client = Client::cpu().unwrap();
+        let comp = (|a: Vector<f64, 3>| a.normalize()).build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec.run(&client, vector![3.0, 0.0, 4.0]).unwrap().to_host();
+        approx::assert_relative_eq!(out, vector![0.6, 0.0, 0.8], epsilon = 1e-12);
+        approx::assert_relative_eq!(out.norm(), 1.0, epsilon = 1e-12);
+
+        let out = exec.run(&client, vector![0.0, 0.0, 0.0]).unwrap().to_host();
+        assert_eq!(out, vector![0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_softmax_argmax() {
+        let client = Client::cpu().unwrap();
+        let logits = vector![1.0, 3.0, 0.5, 2.0];
+
+        let comp = (|a: Vector<f64, 4>| a.softmax::<0>()).build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec.run(&client, logits).unwrap().to_host();
+        let exp = logits.map(f64::exp);
+        approx::assert_relative_eq!(out, exp / exp.sum(), epsilon = 1e-12);
+        approx::assert_relative_eq!(out.sum(), 1.0, epsilon = 1e-12);
+
+        let comp = (|a: Vector<f64, 4>| a.argmax::<0>().unwrap())
+            .build()
+            .unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out: u32 = exec.run(&client, logits).unwrap().to_host();
+        assert_eq!(out, 1);
+        let out: u32 = exec
+            .run(&client, vector![2.0, 0.0, 2.0, 1.0])
+            .unwrap()
+            .to_host();
+        assert_eq!(out, 0);
+
+        let empty = Tensor::<f64, Const<0>>::zeros();
+        assert!(matches!(
+            empty.argmax::<0>(),
+            Err(Error::InvalidAxisLen { axis: 0, len: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_lp_norm() {
+        let client = Client::cpu().unwrap();
+        let norm = |p: f64| -> f64 {
+            let comp = (move |a: Vector<f64, 3>| a.lp_norm::<0>(p))
+                .build()
+                .unwrap();
+            let exec = comp.compile(&client).unwrap();
+            exec.run(&client, vector![3.0, -4.0, 12.0])
+                .unwrap()
+                .to_host()
+        };
+        assert_eq!(norm(1.0), 19.0);
+        assert_eq!(norm(2.0), 13.0);
+        assert_eq!(norm(f64::INFINITY), 12.0);
+        approx::assert_relative_eq!(norm(3.0), 1819f64.cbrt(), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_run_profiled() {
+        let client = Client::cpu().unwrap();
+        let comp = (|a: Vector<f64, 3>, b: Vector<f64, 3>| a + b)
+            .build()
+            .unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let (out, profile) = exec
+            .run_profiled(&client, vector![1.0, 2.0, 3.0], vector![4.0, 5.0, 6.0])
+            .unwrap();
+        assert_eq!(out.to_host(), vector![5.0, 7.0, 9.0]);
+        assert!(profile.wall_time > std::time::Duration::ZERO);
+        assert_eq!(profile.input_bytes, 2 * 3 * 8);
+        assert_eq!(profile.output_bytes, 3 * 8);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("trace.json");
+        profile.write_chrome_trace(&path).unwrap();
+        let trace = std::fs::read_to_string(&path).unwrap();
+        assert!(trace.contains(r#""output_bytes":24"#));
+    }
+
+    #[test]
+    fn test_cumsum() {
+        let client = Client::cpu().unwrap();
+        let comp = (|a: Vector<f64, 4>| a.cumsum::<0>()).build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec
+            .run(&client, vector![1.0, 2.0, 3.0, 4.0])
+            .unwrap()
+            .to_host();
+        assert_eq!(out, vector![1.0, 3.0, 6.0, 10.0]);
+    }
+
     #[test]
     fn test_vector_mul() {
         let client = Client::cpu().unwrap();
@@ -204,4 +446,118 @@ mod tests {
             .to_host();
         assert_eq!(out, vector![2.0, 1.0, 2.0, 1.0])
     }
+
+    #[test]
+    fn test_transcendental() {
+        let client = Client::cpu().unwrap();
+        let input = vector![-1.0, 0.0, 0.5, 2.0];
+        let comp = (|a: Vector<f64, 4>| a.sin()).build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec.run(&client, input).unwrap().to_host();
+        approx::assert_relative_eq!(out, input.map(f64::sin), epsilon = 1e-12);
+
+        let comp = (|a: Vector<f64, 4>| a.exp().tanh()).build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec.run(&client, input).unwrap().to_host();
+        approx::assert_relative_eq!(out, input.map(|x| x.exp().tanh()), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_random_keyed() {
+        let client = Client::cpu().unwrap();
+        let uniform = |key: u64| {
+            let comp = (move || Vector::<f64, 64>::random_uniform(key))
+                .build()
+                .unwrap();
+            let exec = comp.compile(&client).unwrap();
+            exec.run(&client).unwrap().to_host()
+        };
+        let normal = |key: u64| {
+            let comp = (move || Vector::<f64, 64>::random_normal(key))
+                .build()
+                .unwrap();
+            let exec = comp.compile(&client).unwrap();
+            exec.run(&client).unwrap().to_host()
+        };
+        let same_bits = |a: &nalgebra::SVector<f64, 64>, b: &nalgebra::SVector<f64, 64>| {
+            a.iter()
+                .zip(b.iter())
+                .all(|(a, b)| a.to_bits() == b.to_bits())
+        };
+
+        let u = uniform(7);
+        assert!(same_bits(&u, &uniform(7)));
+        assert!(!same_bits(&u, &uniform(8)));
+        assert!(u.iter().all(|u| (0.0..1.0).contains(u)));
+
+        let z = normal(7);
+        assert!(same_bits(&z, &normal(7)));
+        assert!(!same_bits(&z, &normal(8)));
+        assert!(z.iter().all(|z| z.is_finite()));
+    }
+
+    #[test]
+    fn test_select_relu() {
+        let client = Client::cpu().unwrap();
+        let comp = (|a: Vector<f64, 4>| {
+            let zeros = Vector::zeros();
+            Tensor::select(&a.greater(&zeros), a, zeros)
+        })
+        .build()
+        .unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec
+            .run(&client, vector![-1.5, 0.0, 2.0, -0.25])
+            .unwrap()
+            .to_host();
+        assert_eq!(out, vector![0.0, 0.0, 2.0, 0.0])
+    }
+
+    #[test]
+    fn test_clamp_abs() {
+        let client = Client::cpu().unwrap();
+        let comp = (|a: Vector<f64, 5>| {
+            let min: Scalar<f64> = (-1.0).constant();
+            let max: Scalar<f64> = 1.0.constant();
+            a.clamp(&min, &max)
+        })
+        .build()
+        .unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec
+            .run(&client, vector![-3.0, -0.5, 0.25, 1.0, 4.0])
+            .unwrap()
+            .to_host();
+        assert_eq!(out, vector![-1.0, -0.5, 0.25, 1.0, 1.0]);
+
+        let comp = (|a: Vector<f64, 4>| a.abs()).build().unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec
+            .run(&client, vector![-2.5, 0.0, 3.0, -0.125])
+            .unwrap()
+            .to_host();
+        assert_eq!(out, vector![2.5, 0.0, 3.0, 0.125]);
+    }
+
+    #[test]
+    fn test_compare_scalar_clamp() {
+        let client = Client::cpu().unwrap();
+        let comp = (|a: Vector<f64, 5>| {
+            let max: Scalar<f64> = 1.0.constant();
+            let min: Scalar<f64> = (-1.0).constant();
+            let upper = Tensor::select(&a.greater(&max), max.clone().broadcast(), a);
+            let clamped = Tensor::select(&upper.less(&min), min.broadcast(), upper);
+            // mark the elements that were clamped to the upper bound
+            let on_bound = clamped.eq(&max);
+            Tensor::select(&on_bound, Vector::zeros(), clamped)
+        })
+        .build()
+        .unwrap();
+        let exec = comp.compile(&client).unwrap();
+        let out = exec
+            .run(&client, vector![-3.0, -0.5, 0.25, 1.0, 4.0])
+            .unwrap()
+            .to_host();
+        assert_eq!(out, vector![-1.0, -0.5, 0.25, 0.0, 0.0])
+    }
 }
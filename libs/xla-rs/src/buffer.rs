@@ -38,6 +38,34 @@ impl PjRtBuffer {
         out_status.to_result()
     }
 
+    /// Blocks until the computation producing this buffer has finished writing it.
+    pub fn block_until_ready(&self) -> Result<()> {
+        let out_status: Pin<&mut Status> = std::pin::pin!(Status::ok());
+        unsafe {
+            cpp!([self as "std::unique_ptr<PjRtBuffer>*", out_status as "Status*"] {
+                *out_status = (*self)->GetReadyFuture().Await();
+            });
+        }
+        out_status.to_result()
+    }
+
+    pub fn on_device_size_in_bytes(&self) -> Result<usize> {
+        let out_status: Pin<&mut Status> = std::pin::pin!(Status::ok());
+        let size = unsafe {
+            cpp!([self as "std::unique_ptr<PjRtBuffer>*", out_status as "Status*"] -> usize as "size_t" {
+                auto status = (*self)->GetOnDeviceSizeInBytes();
+                if (status.ok()) {
+                    return status.value();
+                }else{
+                    *out_status = Status(status.status());
+                    return 0;
+                }
+            })
+        };
+        out_status.to_result()?;
+        Ok(size)
+    }
+
     pub fn to_literal_sync(&self) -> Result<Literal> {
         let out_status: Pin<&mut Status> = std::pin::pin!(Status::ok());
         let lit = unsafe {
@@ -2,6 +2,7 @@ use crate::{
     ArrayElement, Error, Literal, PjRtBuffer, PjRtLoadedExecutable, Result, Status, XlaComputation,
 };
 use cpp::{cpp, cpp_class};
+use cxx::{CxxString, UniquePtr};
 use std::pin::Pin;
 
 cpp! {{
@@ -42,13 +43,47 @@ impl PjRtClient {
         Ok(client)
     }
 
-    pub fn gpu(memory_fraction: f64, preallocate: bool) -> Result<Self> {
+    /// Creates a CPU client exposing `device_count` devices, which lets replicated executables be
+    /// run without any accelerators.
+    pub fn cpu_with_device_count(device_count: usize) -> Result<Self> {
         let out_status: Pin<&mut Status> = std::pin::pin!(Status::ok());
+        let device_count = device_count as i32;
         let client = unsafe {
-            cpp!([out_status as "Status*", memory_fraction as "double", preallocate as "bool"] -> PjRtClient as "std::shared_ptr<PjRtClient>" {
+            cpp!([out_status as "Status*", device_count as "int32_t"] -> PjRtClient as "std::shared_ptr<PjRtClient>" {
+                auto status = xla::GetTfrtCpuClient(false, device_count);
+                if (status.ok()) {
+                    return std::shared_ptr(std::move(status.value()));
+                }else{
+                    *out_status = Status(status.status());
+                    return std::shared_ptr<PjRtClient>();
+                }
+            })
+        };
+        out_status.to_result()?;
+        if client.is_null() {
+            let backtrace = std::backtrace::Backtrace::capture().to_string();
+            return Err(Error::XlaError {
+                msg: "Unexpected null pointer".to_string(),
+                backtrace,
+            });
+        }
+        Ok(client)
+    }
+
+    /// Creates a GPU client. When `ordinal` is set, only that device is made addressable.
+    pub fn gpu(memory_fraction: f64, preallocate: bool, ordinal: Option<usize>) -> Result<Self> {
+        let out_status: Pin<&mut Status> = std::pin::pin!(Status::ok());
+        let has_ordinal = ordinal.is_some();
+        let ordinal = ordinal.unwrap_or_default() as i32;
+        let client = unsafe {
+            cpp!([out_status as "Status*", memory_fraction as "double", preallocate as "bool", has_ordinal as "bool", ordinal as "int32_t"] -> PjRtClient as "std::shared_ptr<PjRtClient>" {
                 GpuAllocatorConfig allocator = {.memory_fraction = memory_fraction,
                                        .preallocate = preallocate};
-                auto status = GetStreamExecutorGpuClient(false, allocator, 0, 0);
+                std::optional<std::set<int>> allowed_devices;
+                if (has_ordinal) {
+                    allowed_devices = std::set<int>{ordinal};
+                }
+                auto status = GetStreamExecutorGpuClient(false, allocator, 0, 0, allowed_devices);
                 if (status.ok()) {
                     return std::shared_ptr(std::move(status.value()));
                 }else{
@@ -120,6 +155,22 @@ impl PjRtClient {
         buf: &[u8],
         dims: &[usize],
     ) -> Result<PjRtBuffer> {
+        self.copy_raw_host_buffer_to_device(ty, buf, dims, 0)
+    }
+
+    /// Like [`Self::copy_raw_host_buffer`], but places the buffer on the addressable device with
+    /// index `device` rather than the first one.
+    pub fn copy_raw_host_buffer_to_device(
+        &self,
+        ty: super::ElementType,
+        buf: &[u8],
+        dims: &[usize],
+        device: usize,
+    ) -> Result<PjRtBuffer> {
+        let count = self.device_count();
+        if device >= count {
+            Err(Error::DeviceOutOfRange { device, count })?
+        }
         let element_count: usize = dims.iter().product();
         let element_size_in_bytes = ty.element_size_in_bytes();
         if element_count * element_size_in_bytes != buf.len() {
@@ -134,14 +185,14 @@ impl PjRtClient {
         let prim_type = ty.primitive_type() as i32;
         let out_status: Pin<&mut Status> = std::pin::pin!(Status::ok());
         let buffer = unsafe {
-            cpp!([self as "std::shared_ptr<PjRtClient>*", buf_ptr as "const uint8_t*", out_status as "Status*", dims_ptr as "const int64_t*", dims_len as "size_t", prim_type as "int32_t"] -> PjRtBuffer as "std::unique_ptr<PjRtBuffer>" {
+            cpp!([self as "std::shared_ptr<PjRtClient>*", buf_ptr as "const uint8_t*", out_status as "Status*", dims_ptr as "const int64_t*", dims_len as "size_t", prim_type as "int32_t", device as "size_t"] -> PjRtBuffer as "std::unique_ptr<PjRtBuffer>" {
                 auto client = *self;
-                auto device = client->devices()[0];
+                auto device_ptr = client->addressable_devices()[device];
                 auto status = client->BufferFromHostBuffer(
                     buf_ptr,
                     (PrimitiveType)prim_type,
                     absl::Span(dims_ptr, dims_len), {},
-                    PjRtClient::HostBufferSemantics::kImmutableOnlyDuringCall, []() {}, device
+                    PjRtClient::HostBufferSemantics::kImmutableOnlyDuringCall, []() {}, device_ptr
                 );
                 if (status.ok()) {
                     return std::unique_ptr(std::move(status.value()));
@@ -189,11 +240,23 @@ impl PjRtClient {
     }
 
     pub fn compile(&self, comp: &XlaComputation) -> Result<PjRtLoadedExecutable> {
+        self.compile_replicated(comp, 1)
+    }
+
+    /// Compiles `comp` to run as `num_replicas` copies, one per device, each executing the same
+    /// program on its own arguments. See [`PjRtLoadedExecutable::execute_replicated`].
+    pub fn compile_replicated(
+        &self,
+        comp: &XlaComputation,
+        num_replicas: usize,
+    ) -> Result<PjRtLoadedExecutable> {
         let out_status: Pin<&mut Status> = std::pin::pin!(Status::ok());
+        let num_replicas = num_replicas as i32;
         let exec = unsafe {
-            cpp!([self as "std::shared_ptr<PjRtClient>*", comp as "const XlaComputation*", out_status as "Status*"] -> PjRtLoadedExecutable as "std::shared_ptr<PjRtLoadedExecutable>" {
+            cpp!([self as "std::shared_ptr<PjRtClient>*", comp as "const XlaComputation*", out_status as "Status*", num_replicas as "int32_t"] -> PjRtLoadedExecutable as "std::shared_ptr<PjRtLoadedExecutable>" {
                 auto client = *self;
                 CompileOptions options;
+                options.executable_build_options.set_num_replicas(num_replicas);
                 auto status = client->Compile(*comp, options);
                 if (status.ok()) {
                     return std::shared_ptr(std::move(status.value()));
@@ -214,6 +277,63 @@ impl PjRtClient {
         Ok(exec)
     }
 
+    /// Loads an executable previously written by [`PjRtLoadedExecutable::serialize`].
+    ///
+    /// The bytes must come from a client with the same platform, or XLA rejects them.
+    pub fn deserialize_executable(&self, bytes: &[u8]) -> Result<PjRtLoadedExecutable> {
+        let out_status: Pin<&mut Status> = std::pin::pin!(Status::ok());
+        let bytes_ptr = bytes.as_ptr();
+        let bytes_len = bytes.len();
+        let exec = unsafe {
+            cpp!([self as "std::shared_ptr<PjRtClient>*", bytes_ptr as "const char*", bytes_len as "size_t", out_status as "Status*"] -> PjRtLoadedExecutable as "std::shared_ptr<PjRtLoadedExecutable>" {
+                auto client = *self;
+                auto status = client->DeserializeExecutable(std::string_view(bytes_ptr, bytes_len), std::nullopt);
+                if (status.ok()) {
+                    return std::shared_ptr(std::move(status.value()));
+                }else{
+                    *out_status = Status(status.status());
+                    return std::shared_ptr<PjRtLoadedExecutable>();
+                }
+            })
+        };
+        out_status.to_result()?;
+        if exec.is_null() {
+            let backtrace = std::backtrace::Backtrace::capture().to_string();
+            return Err(Error::XlaError {
+                msg: "Unexpected null pointer".to_string(),
+                backtrace,
+            });
+        }
+        Ok(exec)
+    }
+
+    /// The name of the backend platform, such as `cpu` or `cuda`.
+    pub fn platform_name(&self) -> String {
+        let name = unsafe {
+            cpp!([self as "const std::shared_ptr<PjRtClient>*"] -> UniquePtr<CxxString> as "std::unique_ptr<std::string>" {
+                return std::make_unique<std::string>((*self)->platform_name());
+            })
+        };
+        name.to_string_lossy().into_owned()
+    }
+
+    pub fn device_count(&self) -> usize {
+        unsafe {
+            cpp!([self as "const std::shared_ptr<PjRtClient>*"] -> usize as "size_t" {
+                return (*self)->addressable_device_count();
+            })
+        }
+    }
+
+    /// The address of the underlying client, which is shared by every clone of this handle.
+    pub fn id(&self) -> usize {
+        unsafe {
+            cpp!([self as "const std::shared_ptr<PjRtClient>*"] -> usize as "size_t" {
+                return (size_t)self->get();
+            })
+        }
+    }
+
     pub(crate) fn is_null(&self) -> bool {
         unsafe {
             cpp!([self as "const std::shared_ptr<PjRtClient>*"] -> bool as "bool" {
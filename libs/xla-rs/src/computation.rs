@@ -1,6 +1,6 @@
 use std::pin::Pin;
 
-use crate::{Error, HloModuleProto, Status, XlaOp, XlaOpRaw};
+use crate::{Error, HloModuleProto, RawShape, Shape, Status, XlaOp, XlaOpRaw};
 use cpp::{cpp, cpp_class};
 use cxx::{CxxString, UniquePtr};
 cpp! {{
@@ -73,6 +73,32 @@ impl XlaComputation {
         Ok(cxx_string.to_string_lossy().into_owned())
     }
 
+    /// Returns the shapes of the computation's parameters, in parameter order.
+    pub fn parameter_shapes(&self) -> Result<Vec<Shape>, Error> {
+        let out_status: Pin<&mut Status> = std::pin::pin!(Status::ok());
+        let count = unsafe {
+            cpp!([self as "const XlaComputation*", out_status as "Status*"] -> usize as "size_t" {
+                auto shape = self->GetProgramShape();
+                if (!shape.ok()) {
+                    *out_status = Status(shape.status());
+                    return 0;
+                }
+                return shape->parameters_size();
+            })
+        };
+        out_status.to_result()?;
+        (0..count)
+            .map(|i| {
+                let raw = unsafe {
+                    cpp!([self as "const XlaComputation*", i as "size_t"] -> RawShape as "Shape" {
+                        return self->GetProgramShape()->parameters(i);
+                    })
+                };
+                raw.shape()
+            })
+            .collect()
+    }
+
     pub fn to_hlo_module(&self) -> HloModuleProto {
         unsafe {
             cpp!([self as "const XlaComputation*"] -> HloModuleProto as "HloModuleProto" {
@@ -78,6 +78,9 @@ pub enum Error {
     #[error("index out of bounds {index}, rank {rank}")]
     IndexOutOfBounds { index: i64, rank: usize },
 
+    #[error("device {device} out of range for a client with {count} devices")]
+    DeviceOutOfRange { device: usize, count: usize },
+
     #[error("npy/npz error {0}")]
     Npy(String),
 
@@ -1,6 +1,7 @@
-use crate::{BufferArgs, PjRtBuffer, Result, Status};
+use crate::{BufferArgs, BufferArgsInnerRaw, PjRtBuffer, Result, Status};
 
 use cpp::{cpp, cpp_class};
+use cxx::{CxxString, UniquePtr};
 
 use std::pin::Pin;
 
@@ -30,6 +31,25 @@ impl PjRtLoadedExecutable {
         }
     }
 
+    /// Serializes the compiled executable, so it can be reloaded with
+    /// [`crate::PjRtClient::deserialize_executable`] without compiling again.
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        let out_status: Pin<&mut Status> = std::pin::pin!(Status::ok());
+        let bytes = unsafe {
+            cpp!([self as "const std::shared_ptr<PjRtLoadedExecutable>*", out_status as "Status*"] -> UniquePtr<CxxString> as "std::unique_ptr<std::string>" {
+                auto status = (*self)->SerializeExecutable();
+                if (status.ok()) {
+                    return std::make_unique<std::string>(std::move(status.value()));
+                }else{
+                    *out_status = Status(status.status());
+                    return std::make_unique<std::string>();
+                }
+            })
+        };
+        out_status.to_result()?;
+        Ok(bytes.as_bytes().to_vec())
+    }
+
     pub fn execute_buffers(&self, buffers: impl BufferArgs) -> Result<Vec<PjRtBuffer>> {
         let out_status: Pin<&mut Status> = std::pin::pin!(Status::ok());
         let untuple_result = buffers.untuple_result();
@@ -64,4 +84,48 @@ impl PjRtLoadedExecutable {
         out_status.to_result()?;
         Ok(out)
     }
+
+    /// Runs an executable compiled by [`crate::PjRtClient::compile_replicated`], passing
+    /// `replicas[i]` to the replica on device `i` and returning each replica's outputs in turn.
+    pub fn execute_replicated<B: BufferArgs>(&self, replicas: &[B]) -> Result<Vec<Vec<PjRtBuffer>>> {
+        let out_status: Pin<&mut Status> = std::pin::pin!(Status::ok());
+        let untuple_result = replicas.first().is_some_and(|b| b.untuple_result());
+        let args: Vec<*const BufferArgsInnerRaw> =
+            replicas.iter().map(|b| b.get() as *const _).collect();
+        let args_ptr = args.as_ptr();
+        let args_len = args.len();
+        let mut out: Vec<Vec<PjRtBuffer>> = (0..args_len).map(|_| vec![]).collect();
+        {
+            let out_ptr = &mut out;
+            unsafe {
+                cpp!([self as "const std::shared_ptr<PjRtLoadedExecutable>*", args_ptr as "const std::vector<PjRtBuffer*>* const*", args_len as "size_t", out_status as "Status*", out_ptr as "void*", untuple_result as "bool"] {
+                    ExecuteOptions options;
+                    options.untuple_result = untuple_result;
+                    std::vector<std::vector<PjRtBuffer*>> args;
+                    for (size_t i = 0; i < args_len; i++) {
+                        args.push_back(*args_ptr[i]);
+                    }
+                    auto status = (*self)->Execute(absl::Span(args), options);
+                    if (status.ok()) {
+                        std::vector<std::vector<std::unique_ptr<PjRtBuffer>>> bufs = std::move(status).value();
+                        for (size_t replica = 0; replica < bufs.size(); replica++) {
+                             for (auto& buf : bufs[replica]) {
+                                 auto out_buf_ptr = rust!(push_out_buf_replicated [out_ptr : &mut Vec<Vec<PjRtBuffer>> as "void*", replica : usize as "size_t"] -> *mut PjRtBuffer as "std::unique_ptr<PjRtBuffer>*" {
+                                     let out = &mut out_ptr[replica];
+                                     out.push(std::mem::transmute(std::ptr::null::<()>()));
+                                     let i = out.len() - 1;
+                                     &mut out[i] as *mut PjRtBuffer
+                                 });
+                                 *out_buf_ptr = std::move(buf);
+                             }
+                        }
+                    }else{
+                        *out_status = Status(status.status());
+                    }
+                })
+            };
+        }
+        out_status.to_result()?;
+        Ok(out)
+    }
 }
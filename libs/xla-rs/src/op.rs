@@ -758,6 +758,21 @@ impl XlaOp {
         self.wrap(raw)
     }
 
+    pub fn min_value_like(&self) -> Self {
+        let op = &self.raw;
+        let raw = unsafe {
+            cpp!([op as "const XlaOp*"] -> XlaOpRaw as "XlaOp" {
+                try {
+                    const Shape *shape = op->builder()->GetShapePtr(*op).value();
+                    return XlaOp(MinValue(op->builder(), shape->element_type()));
+                }catch(std::exception& e) {
+                    return XlaOp(op->builder()->ReportError(tsl::errors::Internal(e.what())));
+                }
+            })
+        };
+        self.wrap(raw)
+    }
+
     pub fn reshape(&self, ds: &[i64]) -> Self {
         let op = &self.raw;
         let ds_ptr = ds.as_ptr();
@@ -892,6 +907,28 @@ impl XlaOp {
         self.wrap(raw)
     }
 
+    /// Stateless random bits using the three-fry algorithm, with `self` as the `u64[2]` initial state.
+    ///
+    /// Returns a tuple of the updated state and the generated bits.
+    pub fn rng_bit_generator(&self, shape: &ArrayShape) -> Self {
+        let op = &self.raw;
+        let dims = shape.dims();
+        let dims_ptr = dims.as_ptr();
+        let dims_len = dims.len();
+        let prim_type = shape.primitive_type() as i32;
+        let raw = unsafe {
+            cpp!([op as "const XlaOp*", dims_ptr as "const int64_t*", dims_len as "size_t", prim_type as "int32_t"] -> XlaOpRaw as "XlaOp" {
+                try {
+                    auto shape = ShapeUtil::MakeShape((PrimitiveType)prim_type, absl::Span(dims_ptr, dims_len));
+                    return XlaOp(RngBitGenerator(RandomAlgorithm::RNG_THREE_FRY, *op, shape));
+                }catch(std::exception& e) {
+                    return XlaOp(op->builder()->ReportError(tsl::errors::Internal(e.what())));
+                }
+            })
+        };
+        self.wrap(raw)
+    }
+
     pub fn slice(&self, start_indices: &[i64], limit_indices: &[i64], strides: &[i64]) -> Self {
         let op = &self.raw;
         let start_indices_ptr = start_indices.as_ptr();